@@ -6,7 +6,12 @@ Executes MONAI model inference for SwarmPool jobs.
 Called by the Rust CLI via subprocess.
 
 Usage:
-    python runner.py --model queenbee-spine --input /path/to/input.nii.gz --output /tmp/output.json
+    python runner.py --model queenbee-spine --input-file /path/to/input \
+        --params '{"confidence_threshold": 0.8, "output_format": "json"}'
+
+`prove` stages the job's input locally and passes its path as --input-file
+(a file, or a directory for a DICOM series) and the job's params as JSON in
+--params. A non-zero exit or "status": "error" fails the job.
 
 Output (JSON to stdout):
     {
@@ -190,10 +195,11 @@ def run_segmentation_inference(input_path: str, model_config: Dict) -> Dict[str,
     }
 
 
-def run_inference(model_name: str, input_path: str) -> Dict[str, Any]:
+def run_inference(model_name: str, input_path: str, params: Dict[str, Any]) -> Dict[str, Any]:
     """
     Main inference entry point.
 
+    `params` are the job's params (confidence_threshold, output_format, ...).
     Returns standardized output regardless of model type.
     """
     start_time = time.time()
@@ -207,11 +213,13 @@ def run_inference(model_name: str, input_path: str) -> Dict[str, Any]:
             "inference_seconds": 0,
         }
 
-    # Validate input exists (in production: fetch from IPFS if CID)
-    if not input_path.startswith("Qm") and not Path(input_path).exists():
-        # For CIDs, we'd fetch from IPFS
-        # For now, proceed with simulated inference
-        pass
+    # The CLI has already fetched the input; a CID here is a caller bug
+    if not Path(input_path).exists():
+        return {
+            "status": "error",
+            "error": f"Input file not found: {input_path}",
+            "inference_seconds": 0,
+        }
 
     # Run model-specific inference
     output_type = model_config.get("output_type", "classification")
@@ -241,6 +249,7 @@ def run_inference(model_name: str, input_path: str) -> Dict[str, Any]:
         "confidence": result.get("confidence", 0.0),
         "inference_seconds": round(inference_seconds, 3),
         "model_version": f"{model_name}-v{model_config['version']}",
+        "confidence_threshold": params.get("confidence_threshold"),
         "gpu": check_gpu(),
     }
 
@@ -248,7 +257,8 @@ def run_inference(model_name: str, input_path: str) -> Dict[str, Any]:
 def main():
     parser = argparse.ArgumentParser(description="SwarmPool Inference Runner")
     parser.add_argument("--model", required=True, help="Model name (e.g., queenbee-spine)")
-    parser.add_argument("--input", required=True, help="Input file path or IPFS CID")
+    parser.add_argument("--input-file", required=True, help="Path to the staged input file or series directory")
+    parser.add_argument("--params", default="{}", help="Job params as JSON")
     parser.add_argument("--output", help="Output file path (default: stdout)")
     parser.add_argument("--list-models", action="store_true", help="List available models")
 
@@ -259,8 +269,14 @@ def main():
             print(f"{name}: {config['description']} (VRAM: {config['vram_gb']}GB)")
         return
 
+    try:
+        params = json.loads(args.params)
+    except json.JSONDecodeError as e:
+        print(f"Invalid --params JSON: {e}", file=sys.stderr)
+        sys.exit(2)
+
     # Run inference
-    result = run_inference(args.model, args.input)
+    result = run_inference(args.model, args.input_file, params)
 
     # Output
     output_json = json.dumps(result, indent=2)
@@ -0,0 +1,257 @@
+//! Local snapshot cache for offline reads
+//!
+//! Immutable snapshots (jobs, claims, proofs, sealed epochs) never change once
+//! published, so content fetched by CID is persisted to `<config_dir>/cache/`
+//! once it's checked against that CID (see `IpfsClient::fetch_json`), and a
+//! CID already there is never fetched again. With `--offline` read
+//! commands are served exclusively from this cache; `--no-cache` bypasses it.
+//!
+//! Mutable state (`index/state.json`) is cached too, but only ever shown
+//! offline with a staleness warning, never as authoritative.
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Global `--offline` switch
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Serve reads exclusively from the local cache
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Global `--no-cache` switch
+static NO_CACHE: AtomicBool = AtomicBool::new(false);
+
+/// Neither read from nor write to the local cache
+pub fn set_no_cache(no_cache: bool) {
+    NO_CACHE.store(no_cache, Ordering::Relaxed);
+}
+
+/// On-disk snapshot cache rooted at a directory
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    /// Cache under the CLI config directory
+    pub fn open() -> Result<Self> {
+        let proj_dirs = ProjectDirs::from("eth", "swarmpool", "swarm-cli")
+            .context("Failed to determine config directory")?;
+        Ok(Self::at(proj_dirs.config_dir().join("cache")))
+    }
+
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// File backing a cache key (a CID or an MFS path)
+    ///
+    /// Letters, digits, `-` and `.` are kept and every other byte becomes
+    /// `_` and two hex digits, so two keys never share a file: a CID maps to
+    /// itself and `/swarmpool/epochs/epoch-047.json` to
+    /// `_2fswarmpool_2fepochs_2fepoch-047.json.json`.
+    fn entry_path(&self, key: &str) -> PathBuf {
+        let mut name = String::with_capacity(key.len() + 5);
+        for byte in key.bytes() {
+            if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'.' {
+                name.push(byte as char);
+            } else {
+                name.push_str(&format!("_{:02x}", byte));
+            }
+        }
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// Cached value for `key`, if present
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let Some(bytes) = self.get_bytes(key)? else {
+            return Ok(None);
+        };
+        let data = serde_json::from_slice(&bytes)
+            .with_context(|| format!("Corrupt cache entry {}", self.entry_path(key).display()))?;
+        Ok(Some(data))
+    }
+
+    /// Bytes cached for `key` exactly as they were stored, if present
+    pub fn get_bytes(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.entry_path(key);
+        match std::fs::read(&path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to read cache entry {}", path.display()))
+            }
+        }
+    }
+
+    /// Cached value for `key`, or a clear offline cache-miss error
+    pub fn require<T: DeserializeOwned>(&self, key: &str) -> Result<T> {
+        self.get(key)?.with_context(|| self.miss(key))
+    }
+
+    /// Cached bytes for `key`, or the same error as `require`
+    pub fn require_bytes(&self, key: &str) -> Result<Vec<u8>> {
+        self.get_bytes(key)?.with_context(|| self.miss(key))
+    }
+
+    fn miss(&self, key: &str) -> String {
+        format!(
+            "Offline: {} is not in the local cache ({}). Fetch it once while online.",
+            key,
+            self.dir.display()
+        )
+    }
+
+    pub fn put<T: Serialize>(&self, key: &str, data: &T) -> Result<()> {
+        self.put_bytes(key, &serde_json::to_vec(data)?)
+    }
+
+    /// Store `bytes` as they are, so content fetched by CID can be rehashed
+    /// when it's read back
+    pub fn put_bytes(&self, key: &str, bytes: &[u8]) -> Result<()> {
+        std::fs::create_dir_all(&self.dir).context("Failed to create cache directory")?;
+        let path = self.entry_path(key);
+        std::fs::write(&path, bytes)
+            .with_context(|| format!("Failed to write cache entry {}", path.display()))
+    }
+
+    /// Delete every entry; returns how many were removed
+    pub fn clear(&self) -> Result<usize> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e).context("Failed to read cache directory"),
+        };
+
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                std::fs::remove_file(&path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// Whether the cache may be read and written (not under `--no-cache`)
+pub fn enabled() -> bool {
+    !NO_CACHE.load(Ordering::Relaxed)
+}
+
+/// Best-effort write-through after a successful fetch; cache errors never
+/// fail the read itself
+pub fn store<T: Serialize>(key: &str, data: &T) {
+    if !enabled() {
+        return;
+    }
+    if let Err(e) = Cache::open().and_then(|cache| cache.put(key, data)) {
+        tracing::debug!("Cache write for {} skipped: {}", key, e);
+    }
+}
+
+/// Read `key` from the local cache while offline
+pub fn load_offline<T: DeserializeOwned>(key: &str) -> Result<T> {
+    Cache::open()?.require(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Payment;
+    use crate::test_support;
+
+    #[test]
+    fn test_offline_cache_hit() {
+        let dir = test_support::temp_dir();
+        let cache = Cache::at(dir.path());
+        let payment = Payment {
+            amount: "0.10".to_string(),
+            token: "USDC".to_string(),
+        };
+
+        cache
+            .put("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o", &payment)
+            .unwrap();
+        let cached: Payment = cache
+            .require("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o")
+            .unwrap();
+        assert_eq!(cached.amount, "0.10");
+
+        // MFS paths map to flat file names
+        cache
+            .put("/swarmpool/epochs/epoch-047.json", &payment)
+            .unwrap();
+        assert!(dir
+            .path()
+            .join("_2fswarmpool_2fepochs_2fepoch-047.json.json")
+            .exists());
+    }
+
+    #[test]
+    fn test_distinct_keys_never_share_an_entry() {
+        let dir = test_support::temp_dir();
+        let cache = Cache::at(dir.path());
+
+        let keys = [
+            "/swarmpool/jobs/a_b.json",
+            "/swarmpool/jobs/a/b.json",
+            "/swarmpool/jobs_a/b.json",
+            "/swarmpool/jobs/a_b",
+            "swarmpool/jobs/a_b.json",
+        ];
+        for key in keys {
+            cache.put(key, &key).unwrap();
+        }
+        for key in keys {
+            assert_eq!(cache.get::<String>(key).unwrap().as_deref(), Some(key));
+        }
+        assert_eq!(cache.clear().unwrap(), keys.len());
+    }
+
+    #[test]
+    fn test_offline_cache_miss() {
+        let dir = test_support::temp_dir();
+        let cache = Cache::at(dir.path());
+
+        let err = cache
+            .require::<Payment>("QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH")
+            .unwrap_err();
+        assert!(err.to_string().contains("not in the local cache"));
+        assert!(cache.get::<Payment>("anything").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_clear_removes_every_entry() {
+        let dir = test_support::temp_dir();
+        let cache = Cache::at(dir.path());
+        assert_eq!(cache.clear().unwrap(), 0);
+
+        let payment = Payment {
+            amount: "0.10".to_string(),
+            token: "USDC".to_string(),
+        };
+        cache.put("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o", &payment).unwrap();
+        cache.put("/swarmpool/epochs/epoch-047.json", &payment).unwrap();
+
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert!(cache
+            .get::<Payment>("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o")
+            .unwrap()
+            .is_none());
+    }
+}
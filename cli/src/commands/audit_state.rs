@@ -0,0 +1,306 @@
+//! Audit-state command - verify the pool-state hash chain
+//!
+//! Each seal records a `StateLink` in the epoch: the hash of the state it
+//! sealed from, the hash of the state it wrote, and cumulative totals.
+//! Walking sealed epochs oldest-first must reproduce an unbroken chain whose
+//! head is the current `state.json`; anything else means history was rewritten.
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::ipfs;
+use crate::storage::Storage;
+use crate::models::{to_microunits, EpochSnapshot, PoolState};
+use crate::ui;
+
+/// First point where published history and the current state disagree
+#[derive(Debug, PartialEq)]
+pub struct Divergence {
+    pub epoch_id: String,
+    pub reason: String,
+}
+
+impl Divergence {
+    fn new(epoch_id: &str, reason: impl Into<String>) -> Self {
+        Self {
+            epoch_id: epoch_id.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+pub async fn execute(store: &dyn Storage, pool: &str) -> Result<()> {
+    println!("{}", "Auditing Pool State".cyan().bold());
+    println!();
+
+    let pb = ui::spinner("Fetching epochs and pool state...");
+
+    let epochs = ipfs::fetch_epochs(store, pool, &ipfs::EpochFilter::default()).await?;
+    let state = ipfs::fetch_pool_state(store, pool).await?;
+
+    pb.done(format!("{} Fetched {} epochs", "✓".green(), epochs.len()));
+
+    let unlinked = epochs
+        .iter()
+        .filter(|e| e.status == "sealed" && e.state_link.is_none())
+        .count();
+
+    println!();
+    println!("  {} {}", "State hash:".bright_black(), state.canonical_hash()?);
+    println!(
+        "  {} {}",
+        "Prev hash:".bright_black(),
+        state.prev_state_hash.as_deref().unwrap_or("(none)")
+    );
+    if unlinked > 0 {
+        println!(
+            "  {} {} sealed epoch(s) predate the state chain",
+            "Note:".bright_black(),
+            unlinked
+        );
+    }
+    println!();
+
+    match audit_chain(&epochs, &state) {
+        Ok(linked) => {
+            println!("{}", "✅ STATE CHAIN INTACT".green().bold());
+            println!();
+            println!(
+                "  {}",
+                format!("{} linked epoch(s) verified; totals match current state.", linked)
+                    .bright_black()
+            );
+            Ok(())
+        }
+        Err(divergence) => {
+            println!("{}", "❌ STATE CHAIN BROKEN".red().bold());
+            println!();
+            println!(
+                "  {} {}",
+                "First divergent epoch:".bright_black(),
+                divergence.epoch_id.yellow()
+            );
+            println!("  {} {}", "Reason:".bright_black(), divergence.reason);
+            anyhow::bail!("Pool state audit failed at {}", divergence.epoch_id)
+        }
+    }
+}
+
+/// Walk sealed epochs oldest-first and check the hash chain and totals.
+///
+/// Returns the number of linked epochs verified. Sealed epochs without a
+/// `state_link` predate the chain and are skipped; the first linked epoch
+/// establishes the baseline totals.
+pub fn audit_chain(epochs: &[EpochSnapshot], state: &PoolState) -> Result<usize, Divergence> {
+    let mut linked: Vec<&EpochSnapshot> = epochs
+        .iter()
+        .filter(|e| e.status == "sealed" && e.state_link.is_some())
+        .collect();
+    linked.sort_by_key(|e| e.started_at);
+
+    let mut prev: Option<&EpochSnapshot> = None;
+
+    for epoch in &linked {
+        let link = epoch.state_link.as_ref().expect("filtered on state_link");
+
+        if let Some(prev_epoch) = prev {
+            let prev_link = prev_epoch.state_link.as_ref().expect("filtered on state_link");
+
+            if link.prev_state_hash != prev_link.state_hash {
+                return Err(Divergence::new(
+                    &epoch.epoch_id,
+                    format!(
+                        "prev_state_hash {} does not match {}'s state_hash {}",
+                        link.prev_state_hash, prev_epoch.epoch_id, prev_link.state_hash
+                    ),
+                ));
+            }
+
+            let expected_jobs = prev_link.total_jobs + epoch.jobs_count;
+            if link.total_jobs != expected_jobs {
+                return Err(Divergence::new(
+                    &epoch.epoch_id,
+                    format!(
+                        "cumulative jobs {} != {} + {} jobs sealed",
+                        link.total_jobs, prev_link.total_jobs, epoch.jobs_count
+                    ),
+                ));
+            }
+
+            let expected_volume =
+                to_microunits(prev_link.total_volume_usdc) + epoch.volume_microunits();
+            if to_microunits(link.total_volume_usdc) != expected_volume {
+                return Err(Divergence::new(
+                    &epoch.epoch_id,
+                    format!(
+                        "cumulative volume {} != {} + {} sealed",
+                        link.total_volume_usdc, prev_link.total_volume_usdc, epoch.total_volume_usdc
+                    ),
+                ));
+            }
+        }
+
+        prev = Some(epoch);
+    }
+
+    // The current state must be exactly what the last seal wrote
+    if let Some(head) = prev {
+        let link = head.state_link.as_ref().expect("filtered on state_link");
+
+        let state_hash = state
+            .canonical_hash()
+            .map_err(|e| Divergence::new(&head.epoch_id, format!("{:#}", e)))?;
+        if state_hash != link.state_hash {
+            let detail = if state.total_jobs != link.total_jobs {
+                format!(
+                    "current state total_jobs {} != {} sealed",
+                    state.total_jobs, link.total_jobs
+                )
+            } else if to_microunits(state.total_volume_usdc) != to_microunits(link.total_volume_usdc) {
+                format!(
+                    "current state volume {} != {} sealed",
+                    state.total_volume_usdc, link.total_volume_usdc
+                )
+            } else {
+                "current state was modified after this seal".to_string()
+            };
+            return Err(Divergence::new(&head.epoch_id, detail));
+        }
+    }
+
+    Ok(linked.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProviderInfo, StateLink, UsdcAmount};
+    use std::collections::HashMap;
+
+    fn genesis_state() -> PoolState {
+        let mut providers = HashMap::new();
+        providers.insert(
+            "miner.alice.eth".to_string(),
+            ProviderInfo {
+                ens: "miner.alice.eth".to_string(),
+                wallet: "0x1234567890123456789012345678901234567890".to_string(),
+                status: "online".to_string(),
+                registered_at: 1704067200,
+                last_heartbeat: 1704067200,
+                gpus: vec!["RTX 5090".to_string()],
+                models: vec!["queenbee-spine".to_string()],
+                jobs_completed: 0,
+                total_earnings: 0.0,
+                available_balance: 0.0,
+                reputation: None,
+            },
+        );
+
+        PoolState {
+            pool_id: "swarmpool.eth".to_string(),
+            version: "1.0.0".to_string(),
+            total_jobs: 0,
+            total_proofs: 0,
+            total_volume_usdc: 0.0,
+            current_epoch: Some("epoch-001".to_string()),
+            epoch_jobs: 0,
+            epoch_volume: 0.0,
+            pending_jobs: vec![],
+            active_providers: providers,
+            last_updated: 1704067200,
+            prev_state_hash: None,
+        }
+    }
+
+    /// Seal `n` epochs of 10 jobs / $1.00 each, returning epochs and head state
+    fn synthetic_chain(n: usize) -> (Vec<EpochSnapshot>, PoolState) {
+        let mut state = genesis_state();
+        let mut epochs = Vec::new();
+
+        for i in 1..=n {
+            let mut next = state.clone();
+            next.total_jobs += 10;
+            next.total_volume_usdc += 1.0;
+            next.last_updated += 3600;
+            next.prev_state_hash = Some(state.canonical_hash().unwrap());
+            if let Some(p) = next.active_providers.get_mut("miner.alice.eth") {
+                p.total_earnings += 0.75;
+                p.available_balance += 0.75;
+            }
+
+            epochs.push(EpochSnapshot {
+                snapshot_type: "epoch".to_string(),
+                version: "1.0.0".to_string(),
+                epoch_id: format!("epoch-{:03}", i),
+                name: "Alpha".to_string(),
+                status: "sealed".to_string(),
+                started_at: 1704067200 + (i as i64) * 3600,
+                duration_secs: Some(3600),
+                ended_at: Some(1704067200 + (i as i64 + 1) * 3600),
+                jobs_count: 10,
+                proofs_count: 10,
+                proofs: vec![],
+                total_volume_usdc: UsdcAmount::from_microunits(1_000_000),
+                merkle_root: None,
+                inclusion_paths: Default::default(),
+                settlements: None,
+                splits: None,
+                controller: "merlin.swarmos.eth".to_string(),
+                timestamp: 1704067200 + (i as i64 + 1) * 3600,
+                state_link: Some(StateLink {
+                    prev_state_hash: state.canonical_hash().unwrap(),
+                    state_hash: next.canonical_hash().unwrap(),
+                    total_jobs: next.total_jobs,
+                    total_volume_usdc: next.total_volume_usdc,
+                }),
+                processed_withdrawals: vec![],
+                sig: None,
+            });
+
+            state = next;
+        }
+
+        (epochs, state)
+    }
+
+    #[test]
+    fn test_intact_chain() {
+        let (epochs, state) = synthetic_chain(3);
+        assert_eq!(audit_chain(&epochs, &state), Ok(3));
+    }
+
+    #[test]
+    fn test_tampered_state_detected() {
+        let (epochs, mut state) = synthetic_chain(3);
+
+        // Zero out a provider's earnings without touching totals
+        let alice = state.active_providers.get_mut("miner.alice.eth").unwrap();
+        alice.total_earnings = 0.0;
+        alice.available_balance = 0.0;
+
+        let divergence = audit_chain(&epochs, &state).unwrap_err();
+        assert_eq!(divergence.epoch_id, "epoch-003");
+        assert!(divergence.reason.contains("modified after this seal"));
+    }
+
+    #[test]
+    fn test_rewritten_epoch_detected() {
+        let (mut epochs, state) = synthetic_chain(4);
+
+        // Rewrite history in the second epoch
+        epochs[1].jobs_count = 7;
+
+        let divergence = audit_chain(&epochs, &state).unwrap_err();
+        assert_eq!(divergence.epoch_id, "epoch-002");
+        assert!(divergence.reason.contains("cumulative jobs"));
+    }
+
+    #[test]
+    fn test_broken_link_detected() {
+        let (mut epochs, state) = synthetic_chain(3);
+        epochs[2].state_link.as_mut().unwrap().prev_state_hash = "0xdeadbeef".to_string();
+
+        let divergence = audit_chain(&epochs, &state).unwrap_err();
+        assert_eq!(divergence.epoch_id, "epoch-003");
+    }
+}
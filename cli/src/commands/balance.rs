@@ -0,0 +1,266 @@
+//! Balance command - a provider's earnings from published history
+//!
+//! Sealed earnings are summed from the `settlements.providers` of every
+//! epoch the controller signed as sealed, so they don't depend on the
+//! `active_providers` cached in pool state. Withdrawals are checked against
+//! the provider's genesis wallet, and one only counts as paid once a sealed
+//! epoch lists it in `processed_withdrawals`; the request's own `status` is
+//! ignored. Proofs not yet in a sealed epoch are shown apart as an estimate:
+//! the miners' 75% of each job's payment, which a PPL job shares with its
+//! other miners.
+
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::config;
+use crate::ipfs;
+use crate::models::{to_microunits, EpochSnapshot, JobSnapshot, UsdcAmount, HIVE_BPS};
+use crate::money;
+use crate::say;
+use crate::storage::Storage;
+use crate::ui;
+
+/// A provider's balance, in USDC microunits
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Balance {
+    pub provider: String,
+    /// Sealed epochs that paid the provider
+    pub sealed_epochs: usize,
+    /// Earned across all sealed epochs
+    pub earned: u64,
+    /// Paid out by withdrawals a sealed epoch processed
+    pub withdrawn: u64,
+    /// Requested in withdrawals not processed yet
+    pub pending_withdrawals: u64,
+    /// `earned - withdrawn - pending_withdrawals`
+    pub claimable: u64,
+    /// Jobs with a completed proof not settled in any sealed epoch
+    pub unsealed_jobs: usize,
+    /// Upper bound on what those jobs will pay once sealed
+    pub unsealed_estimate: u64,
+}
+
+pub async fn execute(provider: String, json: bool, store: &dyn Storage, _pool: &str) -> Result<()> {
+    let controller = config::controller_address(None)?;
+    let pb = ui::spinner("Reading sealed epochs...");
+
+    let epochs = ipfs::fetch_sealed_epochs(store, &controller).await?;
+    let (processed, pending) = ipfs::split_withdrawals(store, Some(&provider), &epochs).await?;
+    let micros = |withdrawals: &[ipfs::VerifiedWithdrawal]| -> Vec<u64> {
+        withdrawals.iter().map(|w| w.amount.microunits()).collect()
+    };
+
+    pb.set_message("Scanning unsealed proofs...");
+    let unsealed = unsealed_rewards(store, &provider, &epochs).await?;
+    pb.clear();
+
+    let balance = compute_balance(
+        &provider,
+        &epochs,
+        &micros(&processed),
+        &micros(&pending),
+        &unsealed,
+    );
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&balance)?);
+        return Ok(());
+    }
+
+    let usdc = money::format_usdc_display;
+    say!("{}", "Provider Balance".cyan().bold());
+    say!();
+    say!("  {} {}", "Provider:".bright_black(), provider.green());
+    say!();
+    say!("  {}", "Sealed".bright_black());
+    say!("  {}", "━".repeat(40).bright_black());
+    say!(
+        "    {} {} ({} epochs)",
+        "Earned:".bright_black(),
+        usdc(balance.earned),
+        balance.sealed_epochs
+    );
+    say!(
+        "    {} -{}",
+        "Withdrawn:".bright_black(),
+        usdc(balance.withdrawn)
+    );
+    say!(
+        "    {} -{}",
+        "Pending withdrawals:".bright_black(),
+        usdc(balance.pending_withdrawals)
+    );
+    say!(
+        "    {} {}",
+        "Claimable:".bright_black(),
+        usdc(balance.claimable).green().bold()
+    );
+    say!();
+    say!("  {}", "Pending (unsealed)".bright_black());
+    say!("  {}", "━".repeat(40).bright_black());
+    say!(
+        "    {} up to {} ({} jobs)",
+        "Estimate:".bright_black(),
+        usdc(balance.unsealed_estimate).yellow(),
+        balance.unsealed_jobs
+    );
+    say!();
+
+    Ok(())
+}
+
+/// The balance from sealed `epochs`, the amounts of the provider's
+/// `withdrawn` (processed) and `pending` withdrawals, and the estimated
+/// rewards of its unsealed jobs
+pub fn compute_balance(
+    provider: &str,
+    epochs: &[EpochSnapshot],
+    withdrawn: &[u64],
+    pending: &[u64],
+    unsealed: &[u64],
+) -> Balance {
+    let paid: Vec<u64> = epochs
+        .iter()
+        .filter(|e| e.status == "sealed")
+        .filter_map(|e| e.settlements.as_ref()?.providers.get(provider))
+        .map(|amount| to_microunits(*amount))
+        .collect();
+
+    let earned: u64 = paid.iter().sum();
+    let withdrawn: u64 = withdrawn.iter().sum();
+    let pending_withdrawals: u64 = pending.iter().sum();
+    Balance {
+        provider: provider.to_string(),
+        sealed_epochs: paid.len(),
+        earned,
+        withdrawn,
+        pending_withdrawals,
+        claimable: earned.saturating_sub(withdrawn + pending_withdrawals),
+        unsealed_jobs: unsealed.len(),
+        unsealed_estimate: unsealed.iter().map(|reward| miners_share(*reward)).sum(),
+    }
+}
+
+/// The miners' part of a job reward, rounded down as `seal` rounds the hive
+/// cut
+fn miners_share(reward_micro: u64) -> u64 {
+    reward_micro - reward_micro * HIVE_BPS as u64 / 10_000
+}
+
+/// Payment, in microunits, of each job the provider has a completed proof
+/// for that no sealed epoch settled. Cancelled or refunded jobs, and jobs
+/// that can't be read, are skipped.
+async fn unsealed_rewards(
+    store: &dyn Storage,
+    provider: &str,
+    epochs: &[EpochSnapshot],
+) -> Result<Vec<u64>> {
+    let sealed: HashSet<&str> = epochs
+        .iter()
+        .filter(|e| e.status == "sealed")
+        .flat_map(|e| e.proofs.iter().map(String::as_str))
+        .collect();
+    let withdrawn: HashSet<String> = ipfs::withdrawn_jobs(store)
+        .await?
+        .into_iter()
+        .map(|w| w.job_id)
+        .collect();
+
+    let mut jobs = HashSet::new();
+    let mut rewards = Vec::new();
+    for (cid, proof) in ipfs::proofs_with_cids(store, None).await? {
+        if proof.provider != provider
+            || proof.status != "completed"
+            || sealed.contains(cid.as_str())
+            || !jobs.insert(proof.job_cid.clone())
+        {
+            continue;
+        }
+        let job = match read_job(store, &proof.job_cid).await {
+            Ok(job) => job,
+            Err(e) => {
+                tracing::warn!("Skipping proof {}: job unreadable: {:#}", cid, e);
+                continue;
+            }
+        };
+        if withdrawn.contains(&job.job_id) {
+            continue;
+        }
+        match job.payment.amount.parse::<UsdcAmount>() {
+            Ok(amount) => rewards.push(amount.microunits()),
+            Err(e) => tracing::warn!("Skipping job {} with bad payment: {}", job.job_id, e),
+        }
+    }
+    Ok(rewards)
+}
+
+async fn read_job(store: &dyn Storage, job_cid: &str) -> Result<JobSnapshot> {
+    Ok(serde_json::from_value(store.get_json(job_cid).await?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SettlementMicros;
+
+    fn epoch(epoch_id: &str, status: &str, providers: &[(&str, u64)]) -> EpochSnapshot {
+        let settlements = SettlementMicros {
+            providers: providers
+                .iter()
+                .map(|(ens, micro)| (ens.to_string(), *micro))
+                .collect(),
+            ..Default::default()
+        }
+        .to_settlements(vec![]);
+        let mut epoch: EpochSnapshot = serde_json::from_value(serde_json::json!({
+            "type": "epoch",
+            "version": "1.0.0",
+            "epoch_id": epoch_id,
+            "name": "Alpha",
+            "status": status,
+            "started_at": 1704067200,
+            "jobs_count": 1,
+            "total_volume_usdc": "0.100000",
+            "controller": "merlin.swarmos.eth",
+            "timestamp": 1704070800
+        }))
+        .unwrap();
+        epoch.settlements = Some(settlements);
+        epoch
+    }
+
+    #[test]
+    fn test_balance_splits_sealed_and_unsealed() {
+        let epochs = [
+            epoch(
+                "epoch-001",
+                "sealed",
+                &[("a.eth", 75_000), ("b.eth", 50_000)],
+            ),
+            epoch("epoch-002", "sealed", &[("a.eth", 150_000)]),
+            // Not sealed yet: its settlements aren't paid
+            epoch("epoch-003", "active", &[("a.eth", 999_999)]),
+        ];
+        // a.eth's processed and pending withdrawals
+        let balance = compute_balance("a.eth", &epochs, &[50_000], &[25_000], &[100_000, 250_000]);
+        assert_eq!(
+            balance,
+            Balance {
+                provider: "a.eth".to_string(),
+                sealed_epochs: 2,
+                earned: 225_000,
+                withdrawn: 50_000,
+                pending_withdrawals: 25_000,
+                claimable: 150_000,
+                unsealed_jobs: 2,
+                unsealed_estimate: 262_500,
+            }
+        );
+
+        // Nothing sealed and more requested than earned: never negative
+        let none = compute_balance("c.eth", &epochs, &[], &[1_000_000], &[]);
+        assert_eq!(none.claimable, 0);
+    }
+}
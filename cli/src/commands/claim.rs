@@ -3,22 +3,43 @@
 //! Miners choose execution mode:
 //! - SOLO: Winner takes full job reward (first valid proof wins)
 //! - PPL: Pay-Per-Load, proportional payout based on compute_seconds
+//!
+//! `--estimate` prints the expected payout under each mode without publishing.
+//!
+//! Before signing, the job's existing claims are checked. A SOLO claim is
+//! refused when another provider already holds a SOLO claim on the job (only
+//! the first valid proof is paid), unless `--force`; a PPL claim goes ahead
+//! and reports how many other providers are already working on it. Claims
+//! past their `ttl_seconds` no longer hold the job and are ignored; a
+//! provider's heartbeats keep its claims alive up to `MAX_CLAIM_TTL_SECS`.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Duration;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use tracing::{info_span, Instrument};
 
 use crate::config;
 use crate::crypto;
 use crate::ipfs;
-use crate::models::{ClaimSnapshot, ExecutionMode, JobSnapshot};
+use crate::storage::Storage;
+use crate::models::{
+    to_microunits, ClaimSnapshot, ExecutionMode, JobSnapshot, DEFAULT_CLAIM_TTL_SECS, MINERS_PCT,
+};
+use crate::money;
+use crate::nonces;
+use crate::say;
+use crate::schema::{self, SchemaType};
+use crate::ui;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn execute(
     job_cid: String,
     mode: String,
     provider_override: Option<String>,
     key: Option<String>,
+    estimate: bool,
+    force: bool,
+    store: &dyn Storage,
     pool: &str,
 ) -> Result<()> {
     // Load config
@@ -28,39 +49,75 @@ pub async fn execute(
         .or(config.provider_ens)
         .context("Provider ENS required. Run 'swarm init' first or use --provider")?;
 
-    let private_key = key
-        .or_else(|| std::env::var("SWARM_PRIVATE_KEY").ok())
-        .context("Private key required. Use --key or set SWARM_PRIVATE_KEY")?;
-
     // Parse execution mode
     let exec_mode: ExecutionMode = mode.parse()
         .map_err(|e: String| anyhow::anyhow!(e))?;
 
-    println!("{}", "Claiming Job".cyan().bold());
-    println!();
-    println!("  {} {}", "Job CID:".bright_black(), job_cid.cyan());
-    println!("  {} {}", "Provider:".bright_black(), provider_ens.green());
-    println!("  {} {}", "Mode:".bright_black(), format_mode(&exec_mode));
-    println!();
+    say!("{}", "Claiming Job".cyan().bold());
+    say!();
+    say!("  {} {}", "Job CID:".bright_black(), job_cid.cyan());
+    say!("  {} {}", "Provider:".bright_black(), provider_ens.green());
+    say!("  {} {}", "Mode:".bright_black(), format_mode(&exec_mode));
+    say!();
 
     // Fetch job details
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Fetching job from IPFS...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Fetching job from IPFS...");
 
-    let job: JobSnapshot = ipfs::fetch_json(&job_cid).await?;
-    pb.finish_with_message(format!("{} Job fetched", "✓".green()));
+    let job: JobSnapshot = store
+        .get(&job_cid)
+        .instrument(info_span!("fetch", cid = %job_cid))
+        .await
+        .with_context(|| format!("Job not found: {}", job_cid))?;
+    pb.done(format!("{} Job fetched", "✓".green()));
 
     // Show job details
-    println!("  {} {}", "Model:".bright_black(), job.model.green());
-    println!("  {} {}", "Client:".bright_black(), job.client);
-    println!("  {} {}", "Payment:".bright_black(), format!("{} {}", job.payment.amount, job.payment.token).yellow());
-    println!();
+    say!("  {} {}", "Model:".bright_black(), job.model.green());
+    say!("  {} {}", "Client:".bright_black(), job.client);
+    say!("  {} {}", "Payment:".bright_black(), format!("{} {}", job.payment.amount, job.payment.token).yellow());
+    say!();
+
+    if estimate {
+        return show_estimate(store, &job, &job_cid, &provider_ens).await;
+    }
+
+    let claims = ipfs::verified_claims_for_job(store, &job_cid).await?;
+    let heartbeats = ipfs::last_heartbeats(store, claims.iter().map(|c| c.provider.as_str())).await;
+    let existing = existing_claims(
+        &claims,
+        &provider_ens,
+        chrono::Utc::now().timestamp(),
+        &heartbeats,
+    );
+    match exec_mode {
+        ExecutionMode::Solo if !existing.solo.is_empty() => {
+            let holders = existing.solo.iter().cloned().collect::<Vec<_>>().join(", ");
+            if !force {
+                bail!(
+                    "Job {} already has a SOLO claim by {}; only the first valid proof is paid. \
+                     Claim it as PPL, or use --force to claim SOLO anyway",
+                    job.job_id,
+                    holders
+                );
+            }
+            println!(
+                "  {} Already claimed SOLO by {} (--force: claiming anyway)",
+                "⚠".yellow(),
+                holders
+            );
+            say!();
+        }
+        ExecutionMode::Ppl if existing.count() > 0 => {
+            say!(
+                "  {} {} other provider(s) already claiming; the reward is split by compute_seconds",
+                "ℹ".blue(),
+                existing.count()
+            );
+            say!();
+        }
+        _ => {}
+    }
+
+    let private_key = crypto::resolve_signer(key)?;
 
     // Create claim snapshot
     let timestamp = chrono::Utc::now().timestamp();
@@ -80,47 +137,38 @@ pub async fn execute(
         provider: provider_ens.clone(),
         mode: exec_mode,
         timestamp,
+        ttl_seconds: Some(DEFAULT_CLAIM_TTL_SECS),
         nonce,
         sig: None,
     };
 
     // Sign claim
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Signing claim...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Signing claim...");
 
-    claim.sig = Some(crypto::sign_snapshot(&claim, &private_key).await?);
-    pb.finish_with_message(format!("{} Claim signed", "✓".green()));
+    claim.sig = Some(
+        crypto::sign_snapshot(&claim, &private_key)
+            .instrument(info_span!("sign", job_id = %job.job_id))
+            .await?,
+    );
+    pb.done(format!("{} Claim signed", "✓".green()));
+    schema::check_before_publish(&claim, SchemaType::Claim)?;
+    nonces::check(&provider_ens, &claim.nonce)?;
 
     // Publish claim to IPFS
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Publishing claim...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Publishing claim...");
 
-    let claim_cid = ipfs::write_claim(&claim_id, &claim).await?;
-    pb.finish_with_message(format!("{} Published: {}", "✓".green(), claim_cid.cyan()));
+    let publish = info_span!("publish", job_id = %job.job_id, cid = tracing::field::Empty);
+    let claim_cid = ipfs::write_claim(store, &claim_id, &claim)
+        .instrument(publish.clone())
+        .await?;
+    nonces::record_published(store, &provider_ens, &claim.nonce, timestamp);
+    publish.record("cid", claim_cid.as_str());
+    pb.done(format!("{} Published: {}", "✓".green(), claim_cid.cyan()));
 
     // Announce claim to pool
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Announcing claim to pool...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Announcing claim to pool...");
 
-    ipfs::pubsub_publish(
+    store.announce(
         &format!("/{}/claims", pool),
         &serde_json::json!({
             "claim_cid": claim_cid,
@@ -132,36 +180,198 @@ pub async fn execute(
             "timestamp": timestamp
         }),
     )
+    .instrument(publish)
     .await?;
 
-    pb.finish_with_message(format!("{} Claim announced", "✓".green()));
+    pb.done(format!("{} Claim announced", "✓".green()));
 
     // Summary
-    println!();
-    println!("{}", "Job Claimed".green().bold());
-    println!();
+    say!();
+    say!("{}", "Job Claimed".green().bold());
+    say!();
     println!("  {} {}", "Claim ID:".bright_black(), claim_id.cyan());
     println!("  {} {}", "Claim CID:".bright_black(), claim_cid);
-    println!("  {} {}", "Job ID:".bright_black(), job.job_id);
-    println!("  {} {}", "Mode:".bright_black(), format_mode(&exec_mode));
-    println!();
+    say!("  {} {}", "Job ID:".bright_black(), job.job_id);
+    say!("  {} {}", "Mode:".bright_black(), format_mode(&exec_mode));
+    say!();
 
     // Mode-specific messaging
     match exec_mode {
         ExecutionMode::Solo => {
-            println!("  {}", "SOLO: First valid proof wins the full reward".yellow());
+            say!("  {}", "SOLO: First valid proof wins the full reward".yellow());
         }
         ExecutionMode::Ppl => {
-            println!("  {}", "PPL: Reward proportional to compute_seconds contributed".yellow());
+            say!("  {}", "PPL: Reward proportional to compute_seconds contributed".yellow());
         }
     }
 
-    println!();
-    println!("  {}", "Next:".bright_black());
-    println!(
+    say!();
+    say!("  {}", "Next:".bright_black());
+    say!(
         "    {}",
         format!("swarm prove --job {} --claim {}", job_cid, claim_cid).cyan()
     );
+    say!();
+
+    Ok(())
+}
+
+/// Other providers' claims on a job, by mode
+#[derive(Debug, Default, PartialEq)]
+struct ExistingClaims {
+    solo: BTreeSet<String>,
+    ppl: BTreeSet<String>,
+}
+
+impl ExistingClaims {
+    /// Distinct providers claiming under either mode
+    fn count(&self) -> usize {
+        self.solo.union(&self.ppl).count()
+    }
+}
+
+/// Group a job's claims still unexpired at `now`, given each provider's last
+/// heartbeat, by mode, leaving out `provider_ens`'s own
+fn existing_claims(
+    claims: &[ClaimSnapshot],
+    provider_ens: &str,
+    now: i64,
+    heartbeats: &HashMap<String, i64>,
+) -> ExistingClaims {
+    let mut existing = ExistingClaims::default();
+    for claim in claims.iter().filter(|c| {
+        c.provider != provider_ens && !c.is_expired(now, heartbeats.get(&c.provider).copied())
+    }) {
+        let holders = match claim.mode {
+            ExecutionMode::Solo => &mut existing.solo,
+            ExecutionMode::Ppl => &mut existing.ppl,
+        };
+        holders.insert(claim.provider.clone());
+    }
+    existing
+}
+
+/// Competing activity on a job, excluding our own provider
+#[derive(Debug, Default)]
+struct Competition {
+    /// Other providers holding SOLO claims
+    solo_claimants: usize,
+    /// Other providers contributing (claims or proofs) under PPL
+    ppl_contributors: usize,
+    /// A proof already exists, so a SOLO claim can no longer win
+    solo_already_proven: bool,
+}
+
+/// Expected payout (USDC) for this miner under each mode
+#[derive(Debug)]
+struct Estimate {
+    solo: f64,
+    solo_win_probability: f64,
+    ppl: f64,
+    ppl_share: f64,
+}
+
+/// Predict earnings: SOLO = R * miners_pct * P(win), PPL = R * miners_pct * share.
+///
+/// Without compute history, P(win) and the PPL share both assume every
+/// competitor is equally fast.
+fn estimate_earnings(reward: f64, competition: &Competition) -> Estimate {
+    let miner_pool = reward * MINERS_PCT;
+
+    let solo_win_probability = if competition.solo_already_proven {
+        0.0
+    } else {
+        1.0 / (competition.solo_claimants as f64 + 1.0)
+    };
+    let ppl_share = 1.0 / (competition.ppl_contributors as f64 + 1.0);
+
+    Estimate {
+        solo: miner_pool * solo_win_probability,
+        solo_win_probability,
+        ppl: miner_pool * ppl_share,
+        ppl_share,
+    }
+}
+
+/// Print expected SOLO vs PPL payout for a job without publishing a claim
+async fn show_estimate(
+    store: &dyn Storage,
+    job: &JobSnapshot,
+    job_cid: &str,
+    provider_ens: &str,
+) -> Result<()> {
+    let reward: f64 = job
+        .payment
+        .amount
+        .parse()
+        .context(format!("Invalid job payment amount: {}", job.payment.amount))?;
+
+    let pb = ui::spinner("Counting competing claims and proofs...");
+
+    let claims = ipfs::verified_claims_for_job(store, job_cid).await?;
+    let proofs = ipfs::proofs_for_job(store, job_cid).await?;
+    let heartbeats = ipfs::last_heartbeats(store, claims.iter().map(|c| c.provider.as_str())).await;
+
+    let now = chrono::Utc::now().timestamp();
+    let mut solo_claimants = HashSet::new();
+    let mut ppl_contributors = HashSet::new();
+    for claim in claims.iter().filter(|c| {
+        c.provider != provider_ens && !c.is_expired(now, heartbeats.get(&c.provider).copied())
+    }) {
+        match claim.mode {
+            ExecutionMode::Solo => solo_claimants.insert(claim.provider.as_str()),
+            ExecutionMode::Ppl => ppl_contributors.insert(claim.provider.as_str()),
+        };
+    }
+    // Proofs from providers without a SOLO claim count as PPL contributors
+    for proof in proofs.iter().filter(|p| p.provider != provider_ens) {
+        if !solo_claimants.contains(proof.provider.as_str()) {
+            ppl_contributors.insert(proof.provider.as_str());
+        }
+    }
+
+    let competition = Competition {
+        solo_claimants: solo_claimants.len(),
+        ppl_contributors: ppl_contributors.len(),
+        solo_already_proven: !proofs.is_empty(),
+    };
+
+    pb.done(format!(
+        "{} {} claims, {} proofs",
+        "✓".green(),
+        claims.len(),
+        proofs.len()
+    ));
+
+    let estimate = estimate_earnings(reward, &competition);
+
+    println!();
+    println!("{}", "Earnings Estimate".cyan().bold());
+    println!(
+        "  {} {} other SOLO claimant(s), {} other PPL contributor(s)",
+        "Competition:".bright_black(),
+        competition.solo_claimants,
+        competition.ppl_contributors
+    );
+    println!();
+    println!(
+        "  {} {} {}",
+        format_mode(&ExecutionMode::Solo),
+        format!("${}", money::format_usdc(to_microunits(estimate.solo), 6)).green(),
+        format!("({:.0}% win probability)", estimate.solo_win_probability * 100.0).bright_black()
+    );
+    println!(
+        "  {} {} {}",
+        format_mode(&ExecutionMode::Ppl),
+        format!("${}", money::format_usdc(to_microunits(estimate.ppl), 6)).green(),
+        format!("({:.0}% compute share)", estimate.ppl_share * 100.0).bright_black()
+    );
+    if competition.solo_already_proven {
+        println!();
+        println!("  {}", "A proof already exists: SOLO can no longer win this job".yellow());
+    }
+    println!();
+    println!("  {}", "Estimate only - no claim was published.".bright_black());
     println!();
 
     Ok(())
@@ -173,3 +383,75 @@ fn format_mode(mode: &ExecutionMode) -> colored::ColoredString {
         ExecutionMode::Ppl => "PPL".blue().bold(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_uncontested() {
+        let estimate = estimate_earnings(0.10, &Competition::default());
+        assert!((estimate.solo - 0.075).abs() < 1e-9);
+        assert!((estimate.ppl - 0.075).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_with_competition() {
+        let competition = Competition {
+            solo_claimants: 1,
+            ppl_contributors: 3,
+            solo_already_proven: false,
+        };
+        let estimate = estimate_earnings(0.10, &competition);
+        assert!((estimate.solo - 0.0375).abs() < 1e-9);
+        assert!((estimate.ppl - 0.01875).abs() < 1e-9);
+
+        let proven = Competition { solo_already_proven: true, ..competition };
+        assert_eq!(estimate_earnings(0.10, &proven).solo, 0.0);
+    }
+
+    #[test]
+    fn test_existing_claims_exclude_own_and_dedupe() {
+        let claim = |provider: &str, mode: ExecutionMode| ClaimSnapshot {
+            snapshot_type: "claim".to_string(),
+            version: "1.0.0".to_string(),
+            claim_id: format!("claim-{}", provider),
+            job_id: "job-001".to_string(),
+            job_cid: "QmJob".to_string(),
+            provider: provider.to_string(),
+            mode,
+            timestamp: 1704067200,
+            ttl_seconds: None,
+            nonce: "abcdef1234567890".to_string(),
+            sig: None,
+        };
+
+        let claims = vec![
+            claim("alpha.swarmbee.eth", ExecutionMode::Solo),
+            claim("alpha.swarmbee.eth", ExecutionMode::Solo),
+            claim("beta.swarmbee.eth", ExecutionMode::Ppl),
+            claim("gamma.swarmbee.eth", ExecutionMode::Ppl),
+            claim("me.swarmbee.eth", ExecutionMode::Solo),
+        ];
+        let none = HashMap::new();
+        let existing = existing_claims(&claims, "me.swarmbee.eth", 1704067200 + 60, &none);
+        assert_eq!(existing.solo.iter().collect::<Vec<_>>(), ["alpha.swarmbee.eth"]);
+        assert_eq!(existing.ppl.len(), 2);
+        assert_eq!(existing.count(), 3);
+
+        // Our own earlier claim never blocks us
+        let own = existing_claims(&claims[4..], "me.swarmbee.eth", 1704067200 + 60, &none);
+        assert_eq!(own, ExistingClaims::default());
+
+        // Expired claims don't hold the job
+        let later = 1704067200 + DEFAULT_CLAIM_TTL_SECS as i64;
+        let lapsed = existing_claims(&claims, "me.swarmbee.eth", later, &none);
+        assert_eq!(lapsed, ExistingClaims::default());
+
+        // unless their provider is still heartbeating
+        let heartbeats = HashMap::from([("beta.swarmbee.eth".to_string(), later - 60)]);
+        let alive = existing_claims(&claims, "me.swarmbee.eth", later, &heartbeats);
+        assert_eq!(alive.ppl.iter().collect::<Vec<_>>(), ["beta.swarmbee.eth"]);
+        assert!(alive.solo.is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+//! claims command - list published claims
+//!
+//! Reads every `ClaimSnapshot` under `/swarmpool/claims/`, optionally only
+//! those for one job, oldest first.
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::ipfs;
+use crate::models::{self, ClaimSnapshot};
+use crate::storage::Storage;
+
+pub async fn execute(job: Option<String>, json: bool, store: &dyn Storage) -> Result<()> {
+    let mut claims: Vec<ClaimSnapshot> = ipfs::read_all(store, ipfs::paths::CLAIMS).await?;
+    if let Some(job_cid) = &job {
+        claims.retain(|c| &c.job_cid == job_cid);
+    }
+    claims.sort_by(|a, b| {
+        a.timestamp
+            .cmp(&b.timestamp)
+            .then_with(|| a.claim_id.cmp(&b.claim_id))
+    });
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&claims)?);
+        return Ok(());
+    }
+
+    println!("{}", "Claims".cyan().bold());
+    if let Some(job_cid) = &job {
+        println!("  {} {}", "Job:".bright_black(), job_cid);
+    }
+    println!();
+
+    if claims.is_empty() {
+        println!("  {}", "No claims found".bright_black());
+        println!();
+        return Ok(());
+    }
+
+    println!(
+        "  {:<20} {:<28} {:<6} {:<46} {}",
+        "Claimed".bright_black(),
+        "Provider".bright_black(),
+        "Mode".bright_black(),
+        "Job CID".bright_black(),
+        "Claim ID".bright_black()
+    );
+    println!("  {}", "━".repeat(60).bright_black());
+    for claim in &claims {
+        println!(
+            "  {:<20} {:<28} {:<6} {:<46} {}",
+            models::format_timestamp(claim.timestamp),
+            claim.provider.cyan(),
+            claim.mode.to_string(),
+            claim.job_cid,
+            claim.claim_id.bright_black()
+        );
+    }
+    println!();
+    println!("  {} {}", "Claims:".bright_black(), claims.len());
+    println!();
+
+    Ok(())
+}
@@ -0,0 +1,441 @@
+//! diff-epoch command - published seal vs an independent recomputation
+//!
+//! For dispute resolution: re-fetches the epoch's proofs, settles them again
+//! and reports every difference in microunits: totals, provider payouts, hive
+//! recipients, slashes and flagged jobs. Unlike `import-epoch`, which
+//! accepts or rejects, this produces a delta report (`--json` for machines)
+//! and exits non-zero only when a difference exceeds the rounding tolerance.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::commands::import_epoch::{fetch_epoch_proofs, recompute_settlements};
+use crate::commands::seal::proofs_merkle_root;
+use crate::ipfs;
+use crate::models::{to_microunits, EpochSnapshot, Settlements};
+use crate::storage::Storage;
+use crate::ui;
+
+/// Structured difference between a published and a recomputed seal
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EpochDiff {
+    pub epoch_id: String,
+    pub tolerance_micro: u64,
+    pub merkle_root: RootCheck,
+    /// total_volume, miner_pool, hive_ops and dust_to_hive
+    pub totals: Vec<AmountDelta>,
+    /// Providers in both settlements
+    pub providers: Vec<AmountDelta>,
+    /// Providers paid in the published seal but owed nothing on recomputation
+    pub only_published: Vec<String>,
+    /// Providers owed on recomputation but missing from the published seal
+    pub only_recomputed: Vec<String>,
+    /// Hive cut per recipient
+    pub hive_recipients: Vec<AmountDelta>,
+    /// Slash applied per `provider/proof_cid`
+    pub slashes: Vec<AmountDelta>,
+    /// Reward held per flagged job
+    pub flagged: Vec<AmountDelta>,
+    /// Hive recipients, slashes or flagged jobs present on one side only,
+    /// as `<kind> <name>`; the missing side shows 0 in its delta
+    pub unmatched: Vec<String>,
+    /// True when nothing differs beyond the tolerance
+    pub matches: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RootCheck {
+    pub published: Option<String>,
+    pub recomputed: String,
+    pub matches: bool,
+}
+
+/// One amount on both sides, in USDC microunits
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AmountDelta {
+    pub name: String,
+    pub published: u64,
+    pub recomputed: u64,
+    /// recomputed - published
+    pub delta: i64,
+}
+
+impl AmountDelta {
+    fn new(name: &str, published: f64, recomputed: f64) -> Self {
+        let (published, recomputed) = (to_microunits(published), to_microunits(recomputed));
+        Self {
+            name: name.to_string(),
+            published,
+            recomputed,
+            delta: recomputed as i64 - published as i64,
+        }
+    }
+
+    fn exceeds(&self, tolerance_micro: u64) -> bool {
+        self.delta.unsigned_abs() > tolerance_micro
+    }
+}
+
+pub async fn execute(
+    epoch_id: String,
+    tolerance: u64,
+    json: bool,
+    store: &dyn Storage,
+    _pool: &str,
+) -> Result<()> {
+    let pb = ui::spinner(format!("Fetching {}...", epoch_id));
+
+    let path = format!("{}/{}.json", ipfs::paths::EPOCHS, epoch_id);
+    let epoch: EpochSnapshot = store
+        .read(&path)
+        .await
+        .with_context(|| format!("Failed to read {}", path))?;
+    let published = epoch
+        .settlements
+        .as_ref()
+        .with_context(|| format!("Epoch {} has no published settlements", epoch_id))?;
+
+    pb.set_message(format!("Re-fetching {} proofs...", epoch.proofs.len()));
+    let proofs = fetch_epoch_proofs(store, &epoch.proofs).await?;
+    pb.clear();
+
+    let recomputed = recompute_settlements(&epoch, &proofs);
+    let recomputed_root = proofs_merkle_root(&proofs)?;
+    let diff = diff_epoch(
+        &epoch_id,
+        epoch.merkle_root.as_deref(),
+        published,
+        &recomputed_root,
+        &recomputed,
+        tolerance,
+    );
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print_diff(&diff);
+    }
+
+    if !diff.matches {
+        anyhow::bail!(
+            "Epoch {} differs from its recomputation beyond {} microunit(s)",
+            epoch_id,
+            tolerance
+        );
+    }
+    Ok(())
+}
+
+/// Compare a published seal with a recomputation of it
+pub fn diff_epoch(
+    epoch_id: &str,
+    published_root: Option<&str>,
+    published: &Settlements,
+    recomputed_root: &str,
+    recomputed: &Settlements,
+    tolerance_micro: u64,
+) -> EpochDiff {
+    let merkle_root = RootCheck {
+        published: published_root.map(str::to_string),
+        recomputed: recomputed_root.to_string(),
+        matches: published_root == Some(recomputed_root),
+    };
+
+    let totals = vec![
+        AmountDelta::new(
+            "total_volume",
+            published.total_volume,
+            recomputed.total_volume,
+        ),
+        AmountDelta::new("miner_pool", published.miner_pool, recomputed.miner_pool),
+        AmountDelta::new("hive_ops", published.hive_ops, recomputed.hive_ops),
+        AmountDelta::new(
+            "dust_to_hive",
+            published.dust_to_hive,
+            recomputed.dust_to_hive,
+        ),
+    ];
+
+    let names: BTreeSet<&String> = published
+        .providers
+        .keys()
+        .chain(recomputed.providers.keys())
+        .collect();
+    let mut providers = Vec::new();
+    let mut only_published = Vec::new();
+    let mut only_recomputed = Vec::new();
+    for name in names {
+        match (
+            published.providers.get(name),
+            recomputed.providers.get(name),
+        ) {
+            (Some(a), Some(b)) => providers.push(AmountDelta::new(name, *a, *b)),
+            (Some(_), None) => only_published.push(name.clone()),
+            (None, Some(_)) => only_recomputed.push(name.clone()),
+            (None, None) => {}
+        }
+    }
+
+    let mut unmatched = Vec::new();
+    let hive_recipients = keyed_deltas(
+        "hive recipient",
+        &published.hive_recipients,
+        &recomputed.hive_recipients,
+        &mut unmatched,
+    );
+    let slashes = keyed_deltas(
+        "slash",
+        &slash_amounts(published),
+        &slash_amounts(recomputed),
+        &mut unmatched,
+    );
+    let flagged = keyed_deltas(
+        "flagged job",
+        &held_amounts(published),
+        &held_amounts(recomputed),
+        &mut unmatched,
+    );
+
+    let matches = merkle_root.matches
+        && only_published.is_empty()
+        && only_recomputed.is_empty()
+        && unmatched.is_empty()
+        && !totals
+            .iter()
+            .chain(&providers)
+            .chain(&hive_recipients)
+            .chain(&slashes)
+            .chain(&flagged)
+            .any(|d| d.exceeds(tolerance_micro));
+
+    EpochDiff {
+        epoch_id: epoch_id.to_string(),
+        tolerance_micro,
+        merkle_root,
+        totals,
+        providers,
+        only_published,
+        only_recomputed,
+        hive_recipients,
+        slashes,
+        flagged,
+        unmatched,
+        matches,
+    }
+}
+
+/// Deltas for every name on either side, a missing side counting as 0;
+/// names found on one side only are added to `unmatched` as `<kind> <name>`
+fn keyed_deltas(
+    kind: &str,
+    published: &BTreeMap<String, f64>,
+    recomputed: &BTreeMap<String, f64>,
+    unmatched: &mut Vec<String>,
+) -> Vec<AmountDelta> {
+    let names: BTreeSet<&String> = published.keys().chain(recomputed.keys()).collect();
+    names
+        .into_iter()
+        .map(|name| {
+            let (a, b) = (published.get(name), recomputed.get(name));
+            if a.is_none() || b.is_none() {
+                unmatched.push(format!("{} {}", kind, name));
+            }
+            AmountDelta::new(name, a.copied().unwrap_or(0.0), b.copied().unwrap_or(0.0))
+        })
+        .collect()
+}
+
+/// Applied penalty per `provider/proof_cid`
+fn slash_amounts(settlements: &Settlements) -> BTreeMap<String, f64> {
+    settlements
+        .slashes
+        .iter()
+        .map(|s| (format!("{}/{}", s.provider, s.proof_cid), s.applied))
+        .collect()
+}
+
+/// Held reward per flagged job id
+fn held_amounts(settlements: &Settlements) -> BTreeMap<String, f64> {
+    settlements
+        .flagged
+        .iter()
+        .map(|f| (f.job_id.clone(), f.held))
+        .collect()
+}
+
+fn print_diff(diff: &EpochDiff) {
+    println!("{}", format!("Epoch Diff: {}", diff.epoch_id).cyan().bold());
+    println!();
+
+    let root = &diff.merkle_root;
+    if root.matches {
+        println!("  {} Merkle root matches", "✓".green());
+    } else {
+        println!(
+            "  {} Merkle root: published {}, recomputed {}",
+            "✗".red(),
+            root.published.as_deref().unwrap_or("(none)"),
+            root.recomputed
+        );
+    }
+    println!();
+
+    println!(
+        "  {:<28} {:>12} {:>12} {:>8}",
+        "Amount (microunits)".bright_black(),
+        "Published".bright_black(),
+        "Recomputed".bright_black(),
+        "Delta".bright_black()
+    );
+    println!("  {}", "━".repeat(64).bright_black());
+    let rows = diff
+        .totals
+        .iter()
+        .chain(&diff.providers)
+        .map(|d| ("", d))
+        .chain(diff.hive_recipients.iter().map(|d| ("hive ", d)))
+        .chain(diff.slashes.iter().map(|d| ("slash ", d)))
+        .chain(diff.flagged.iter().map(|d| ("held ", d)));
+    for (prefix, delta) in rows {
+        let line = format!(
+            "  {:<28} {:>12} {:>12} {:>+8}",
+            format!("{}{}", prefix, delta.name),
+            delta.published,
+            delta.recomputed,
+            delta.delta
+        );
+        if delta.exceeds(diff.tolerance_micro) {
+            println!("{}", line.red());
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    for name in &diff.only_published {
+        println!("  {} {} paid but has no proofs", "✗".red(), name);
+    }
+    for name in &diff.only_recomputed {
+        println!("  {} {} owed but missing from the seal", "✗".red(), name);
+    }
+    for entry in &diff.unmatched {
+        println!("  {} {} on one side only", "✗".red(), entry);
+    }
+    println!();
+
+    if diff.matches {
+        println!("{}", "✅ SEAL RECONCILES".green().bold());
+    } else {
+        println!("{}", "❌ SEAL DIFFERS".red().bold());
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{FlaggedJob, Slash};
+    use std::collections::HashMap;
+
+    fn settlements(providers: &[(&str, f64)]) -> Settlements {
+        let providers: HashMap<String, f64> =
+            providers.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        let miner_pool = providers.values().sum();
+
+        Settlements {
+            total_volume: 0.20,
+            miner_pool,
+            hive_ops: 0.05,
+            providers,
+            dust_to_hive: 0.0,
+            slashes: vec![],
+            flagged: vec![],
+            hive_recipients: Default::default(),
+            compute_overhead_cap: None,
+            micro: None,
+        }
+    }
+
+    #[test]
+    fn test_rounding_within_tolerance_matches() {
+        let root = format!("0x{}", "1".repeat(64));
+        let published = settlements(&[("a.eth", 0.075), ("b.eth", 0.075)]);
+        let recomputed = settlements(&[("a.eth", 0.075001), ("b.eth", 0.075)]);
+
+        let diff = diff_epoch("epoch-001", Some(&root), &published, &root, &recomputed, 1);
+        assert!(diff.matches);
+        assert_eq!(diff.providers[0].delta, 1);
+
+        let strict = diff_epoch("epoch-001", Some(&root), &published, &root, &recomputed, 0);
+        assert!(!strict.matches);
+    }
+
+    #[test]
+    fn test_structured_deltas() {
+        let published = settlements(&[("a.eth", 0.10), ("c.eth", 0.05)]);
+        let recomputed = settlements(&[("a.eth", 0.075), ("b.eth", 0.075)]);
+        let root = format!("0x{}", "2".repeat(64));
+
+        let diff = diff_epoch("epoch-001", None, &published, &root, &recomputed, 1);
+        assert!(!diff.matches);
+        assert!(!diff.merkle_root.matches);
+        assert_eq!(diff.only_published, ["c.eth"]);
+        assert_eq!(diff.only_recomputed, ["b.eth"]);
+        assert_eq!(
+            diff.providers,
+            [AmountDelta {
+                name: "a.eth".to_string(),
+                published: 100_000,
+                recomputed: 75_000,
+                delta: -25_000,
+            }]
+        );
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["totals"][1]["name"], "miner_pool");
+        assert_eq!(json["totals"][1]["delta"], 0);
+        assert_eq!(json["merkle_root"]["published"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_hive_slash_and_flagged_entries_diffed() {
+        let root = format!("0x{}", "3".repeat(64));
+        let mut published = settlements(&[("a.eth", 0.075)]);
+        published.hive_recipients =
+            BTreeMap::from([("ops.eth".to_string(), 0.03), ("dev.eth".to_string(), 0.02)]);
+        published.slashes = vec![Slash {
+            provider: "a.eth".to_string(),
+            proof_cid: "bafyp1".to_string(),
+            reason: "upheld".to_string(),
+            slash_bps: 1000,
+            penalty: 0.01,
+            applied: 0.01,
+        }];
+        let mut recomputed = published.clone();
+        assert!(diff_epoch("epoch-001", Some(&root), &published, &root, &recomputed, 1).matches);
+
+        // Same totals and payouts, but the hive cut, the slash and the
+        // held rewards disagree
+        recomputed.hive_recipients =
+            BTreeMap::from([("ops.eth".to_string(), 0.02), ("dev.eth".to_string(), 0.03)]);
+        recomputed.slashes.clear();
+        recomputed.flagged = vec![FlaggedJob {
+            job_id: "job-003".to_string(),
+            reason: "no completed proof".to_string(),
+            held: 0.10,
+        }];
+
+        let diff = diff_epoch("epoch-001", Some(&root), &published, &root, &recomputed, 1);
+        assert!(!diff.matches);
+        assert_eq!(diff.hive_recipients[0].name, "dev.eth");
+        assert_eq!(diff.hive_recipients[0].delta, 10_000);
+        assert_eq!(diff.slashes[0].name, "a.eth/bafyp1");
+        assert_eq!(diff.slashes[0].delta, -10_000);
+        assert_eq!(diff.flagged[0].recomputed, 100_000);
+        assert_eq!(
+            diff.unmatched,
+            ["slash a.eth/bafyp1", "flagged job job-003"]
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! doctor command - check this machine's setup
+//!
+//! Runs the checks whose failures otherwise surface deep inside another
+//! command: the IPFS daemon, the MFS layout, the config file, the signing key,
+//! GPU detection and, when `eth_rpc` is set, ENS resolution. Each failure
+//! comes with a hint. Any critical failure makes the command exit non-zero,
+//! so it can gate CI jobs and startup scripts.
+
+use anyhow::Result;
+use colored::Colorize;
+use ethers::signers::{LocalWallet, Signer};
+use std::path::Path;
+
+use crate::config::{self, Config};
+use crate::crypto;
+use crate::ipfs;
+use crate::keystore::{self, Keystore};
+use crate::provider::{self, GpuInfo};
+use crate::storage::Storage;
+
+/// Outcome of one check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass(String),
+    Fail(String),
+    /// Not applicable to this setup
+    Skip(String),
+}
+
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    /// A failure makes `doctor` exit non-zero
+    pub critical: bool,
+    pub outcome: Outcome,
+    /// What to do about a failure
+    pub hint: &'static str,
+}
+
+impl Check {
+    fn failed(&self) -> bool {
+        matches!(self.outcome, Outcome::Fail(_))
+    }
+}
+
+pub async fn execute(key: Option<String>, store: &dyn Storage) -> Result<()> {
+    println!("{}", "SwarmPool Doctor".cyan().bold());
+    println!();
+
+    let config_path = config::get_config_path()?;
+    let config = config::load_config().ok();
+
+    let checks = vec![
+        check_ipfs(store).await,
+        check_directories(store).await,
+        check_config(&config_path),
+        check_key(
+            key.or_else(|| std::env::var(crypto::PRIVATE_KEY_ENV).ok()),
+            config.as_ref().and_then(|c| c.keystore.as_deref()),
+            &Keystore::open()?,
+            std::env::var(keystore::PASSWORD_ENV).ok(),
+        ),
+        check_gpus(&provider::detect_gpus()),
+        check_eth_rpc(config.as_ref()).await,
+    ];
+
+    for check in &checks {
+        let (mark, detail) = match &check.outcome {
+            Outcome::Pass(detail) => ("✓".green(), detail.normal()),
+            Outcome::Fail(detail) if check.critical => ("✗".red(), detail.red()),
+            Outcome::Fail(detail) => ("⚠".yellow(), detail.yellow()),
+            Outcome::Skip(detail) => ("-".bright_black(), detail.bright_black()),
+        };
+        println!("  {} {:<16} {}", mark, check.name, detail);
+        if check.failed() {
+            println!("    {} {}", "→".bright_black(), check.hint.bright_black());
+        }
+    }
+    println!();
+
+    let critical = checks.iter().filter(|c| c.critical && c.failed()).count();
+    let warnings = checks.iter().filter(|c| !c.critical && c.failed()).count();
+    if critical > 0 {
+        anyhow::bail!("{} critical check(s) failed", critical);
+    }
+    if warnings > 0 {
+        println!(
+            "{} All critical checks passed ({} warning(s))",
+            "✓".green(),
+            warnings
+        );
+    } else {
+        println!("{} All checks passed", "✓".green());
+    }
+    println!();
+
+    Ok(())
+}
+
+pub async fn check_ipfs(store: &dyn Storage) -> Check {
+    Check {
+        name: "IPFS daemon",
+        critical: true,
+        outcome: match store.check_connection().await {
+            Ok(()) => Outcome::Pass("reachable".to_string()),
+            Err(e) => Outcome::Fail(format!("{:#}", e)),
+        },
+        hint: "Start it with `ipfs daemon`, or point ipfs_api in config at a running node",
+    }
+}
+
+pub async fn check_directories(store: &dyn Storage) -> Check {
+    let mut missing = Vec::new();
+    let mut error = None;
+    for dir in ipfs::paths::DIRECTORIES {
+        match store.dir_exists(dir).await {
+            Ok(true) => {}
+            Ok(false) => missing.push(dir),
+            Err(e) => {
+                error = Some(format!("{:#}", e));
+                break;
+            }
+        }
+    }
+
+    let outcome = match error {
+        Some(e) => Outcome::Fail(e),
+        None if missing.is_empty() => Outcome::Pass(format!(
+            "{} directories present",
+            ipfs::paths::DIRECTORIES.len()
+        )),
+        None => Outcome::Fail(format!("missing {}", missing.join(", "))),
+    };
+    Check {
+        name: "MFS directories",
+        critical: true,
+        outcome,
+        hint: "Create them with `ipfs files mkdir -p <dir>` for each missing directory",
+    }
+}
+
+pub fn check_config(path: &Path) -> Check {
+    let outcome = if !path.exists() {
+        Outcome::Fail(format!("no config at {}", path.display()))
+    } else {
+        match std::fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(toml::from_str::<Config>(&content)?))
+        {
+            Ok(_) => Outcome::Pass(path.display().to_string()),
+            Err(e) => Outcome::Fail(format!("{} doesn't parse: {:#}", path.display(), e)),
+        }
+    };
+    Check {
+        name: "Config file",
+        critical: true,
+        outcome,
+        hint: "Run `swarm init` to create it, or fix the file shown by `swarm config --path`",
+    }
+}
+
+/// Resolves the key the way `crypto::resolve_signer` does, but never prompts:
+/// a keystore entry is only decrypted when `password` is given
+pub fn check_key(
+    key: Option<String>,
+    label: Option<&str>,
+    keystore: &Keystore,
+    password: Option<String>,
+) -> Check {
+    let outcome = match (key, label) {
+        (Some(key), _) => match crypto::normalize_key(&key)
+            .map_err(anyhow::Error::from)
+            .and_then(|key| Ok(key.parse::<LocalWallet>()?))
+        {
+            Ok(wallet) => Outcome::Pass(format!("{:?}", wallet.address())),
+            Err(e) => Outcome::Fail(format!("{:#}", e)),
+        },
+        (None, None) => Outcome::Fail("no --key, SWARM_PRIVATE_KEY or keystore entry".to_string()),
+        (None, Some(label)) if !keystore.contains(label) => {
+            Outcome::Fail(format!("keystore entry '{}' not found", label))
+        }
+        (None, Some(label)) => match password {
+            Some(password) => match keystore.load(label, &password) {
+                Ok(wallet) => {
+                    Outcome::Pass(format!("{:?} (keystore '{}')", wallet.address(), label))
+                }
+                Err(e) => Outcome::Fail(format!("{:#}", e)),
+            },
+            None => Outcome::Pass(format!(
+                "keystore '{}' (set {} to unlock it here)",
+                label,
+                keystore::PASSWORD_ENV
+            )),
+        },
+    };
+    Check {
+        name: "Signing key",
+        critical: true,
+        outcome,
+        hint: "Pass --key, set SWARM_PRIVATE_KEY, or import one with `swarm migrate-keys`",
+    }
+}
+
+/// No GPU is only a warning: clients and controllers don't need one
+pub fn check_gpus(gpus: &[GpuInfo]) -> Check {
+    let outcome = if gpus.is_empty() {
+        Outcome::Fail("no GPU detected (nvidia-smi missing or reported no devices)".to_string())
+    } else {
+        let names: Vec<String> = gpus
+            .iter()
+            .map(|g| format!("{} ({:.0} GB)", g.name, g.vram_gb()))
+            .collect();
+        Outcome::Pass(names.join(", "))
+    };
+    Check {
+        name: "GPUs",
+        critical: false,
+        outcome,
+        hint: "Install the NVIDIA driver so `nvidia-smi` lists your cards",
+    }
+}
+
+/// Resolves the configured provider ENS through `eth_rpc`; skipped when
+/// either isn't set
+pub async fn check_eth_rpc(config: Option<&Config>) -> Check {
+    let rpc = config.and_then(|c| c.eth_rpc.as_deref());
+    let ens = config.and_then(|c| c.provider_ens.as_deref());
+    let outcome = match (rpc, ens) {
+        (None, _) => Outcome::Skip("eth_rpc not configured".to_string()),
+        (Some(_), None) => Outcome::Skip("no provider_ens in config to resolve".to_string()),
+        (Some(rpc), Some(ens)) => match crypto::resolve_ens(ens, rpc).await {
+            Ok(address) => Outcome::Pass(format!("{} → {:?}", ens, address)),
+            Err(e) => Outcome::Fail(format!("{:#}", e)),
+        },
+    };
+    Check {
+        name: "Ethereum RPC",
+        critical: true,
+        outcome,
+        hint: "Check eth_rpc in config points at a reachable mainnet JSON-RPC endpoint",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, TEST_ADDRESS, TEST_KEY};
+
+    #[tokio::test]
+    async fn test_doctor_checks() {
+        let (root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        assert_eq!(
+            check_ipfs(store).await.outcome,
+            Outcome::Pass("reachable".to_string())
+        );
+
+        let dirs = check_directories(store).await;
+        assert!(dirs.critical && dirs.failed());
+        std::fs::create_dir_all(root.path().join("swarmpool/jobs")).unwrap();
+        assert!(check_directories(store).await.failed());
+        for dir in ipfs::paths::DIRECTORIES {
+            std::fs::create_dir_all(root.path().join(dir.trim_start_matches('/'))).unwrap();
+        }
+        assert!(!check_directories(store).await.failed());
+
+        let config_path = root.path().join("config.toml");
+        assert!(check_config(&config_path).failed());
+        std::fs::write(&config_path, "pool = [\"not a string\"]\n").unwrap();
+        assert!(check_config(&config_path).failed());
+        let config = toml::to_string_pretty(&Config::new()).unwrap();
+        std::fs::write(&config_path, config).unwrap();
+        assert!(!check_config(&config_path).failed());
+
+        let keystore = Keystore::at(root.path().join("keystore"));
+        let key = check_key(Some(TEST_KEY.to_string()), None, &keystore, None);
+        assert_eq!(key.outcome, Outcome::Pass(TEST_ADDRESS.to_string()));
+        assert!(check_key(Some("0xnot-a-key".to_string()), None, &keystore, None).failed());
+        assert!(check_key(None, None, &keystore, None).failed());
+
+        // A keystore entry is reported without prompting for its password
+        assert!(check_key(None, Some("main"), &keystore, None).failed());
+        let wallet: LocalWallet = TEST_KEY.parse().unwrap();
+        keystore.save("main", &wallet, "hunter2").unwrap();
+        let locked = check_key(None, Some("main"), &keystore, None);
+        assert!(matches!(&locked.outcome, Outcome::Pass(d) if d.contains("to unlock")));
+        let unlocked = check_key(None, Some("main"), &keystore, Some("hunter2".to_string()));
+        assert!(matches!(&unlocked.outcome, Outcome::Pass(d) if d.starts_with(TEST_ADDRESS)));
+        assert!(check_key(None, Some("main"), &keystore, Some("wrong".to_string())).failed());
+
+        let gpus = check_gpus(&[]);
+        assert!(gpus.failed() && !gpus.critical);
+        assert!(matches!(
+            check_eth_rpc(None).await.outcome,
+            Outcome::Skip(_)
+        ));
+    }
+}
@@ -1,23 +1,352 @@
 //! Epochs command - view epoch history
+//!
+//! `--export-all` writes every published epoch to one file (a JSON array, or
+//! NDJSON) for archival or pool migration. Epochs are exported byte-for-byte
+//! as published so their signatures still verify in the backup.
+//!
+//! The list is newest first by `started_at`. `--before`/`--after` (unix
+//! seconds or RFC3339) bound when an epoch ended, or when it started if it
+//! has no `ended_at`; `--offset` pages past the first `--limit` epochs.
+//!
+//! `--format json|csv` prints the list or one epoch for scripts and
+//! spreadsheets instead of the colored table.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
+use futures::stream::{self, StreamExt};
+use indicatif::ProgressBar;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::time::Duration;
 
-use crate::ipfs;
+use crate::commands::verify_sig;
+use crate::crypto;
+use crate::ipfs::{self, EpochFilter};
+use crate::models::{to_microunits, EpochSnapshot, ProofSnapshot};
+use crate::money;
+use crate::schema::{self, SchemaType};
+use crate::storage::Storage;
+use crate::ui;
 
-pub async fn execute(id: Option<String>, limit: u32, pool: &str) -> Result<()> {
-    if let Some(epoch_id) = id {
-        show_epoch_detail(&epoch_id, pool).await
-    } else {
-        show_epoch_list(limit, pool).await
+/// Concurrent epoch fetches during an export
+const EXPORT_CONCURRENCY: usize = 8;
+
+/// Options for `epochs --export-all`
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub out: String,
+    pub ndjson: bool,
+    /// Check each epoch's signature and merkle root
+    pub verify: bool,
+    /// Address every epoch must be signed by (with `verify`)
+    pub controller: Option<String>,
+}
+
+/// How `epochs` prints the list or an epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(format!("Unknown format: {}. Use table, json, or csv", s)),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    id: Option<String>,
+    limit: u32,
+    offset: u32,
+    before: Option<String>,
+    after: Option<String>,
+    format: String,
+    export: Option<ExportOptions>,
+    store: &dyn Storage,
+    pool: &str,
+) -> Result<()> {
+    let format: OutputFormat = format.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+    let filter = EpochFilter {
+        before: before.as_deref().map(parse_time).transpose()?,
+        after: after.as_deref().map(parse_time).transpose()?,
+        offset: offset as usize,
+        limit: Some(limit as usize),
+    };
+
+    if let Some(options) = export {
+        return export_all(&options, store).await;
+    }
+
+    match (id, format) {
+        (Some(epoch_id), OutputFormat::Table) => show_epoch_detail(&epoch_id, store, pool).await,
+        (None, OutputFormat::Table) => show_epoch_list(&filter, store, pool).await,
+        (Some(epoch_id), format) => {
+            let epoch = ipfs::fetch_epoch(store, pool, &epoch_id).await?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&epoch)?),
+                _ => print!("{}", render_payouts_csv(&epoch)),
+            }
+            Ok(())
+        }
+        (None, format) => {
+            let epochs = ipfs::fetch_epochs(store, pool, &filter).await?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&epochs)?),
+                _ => print!("{}", render_list_csv(&epochs)),
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Unix time for a `--before`/`--after` value: unix seconds or RFC3339
+pub fn parse_time(value: &str) -> Result<i64> {
+    if let Ok(secs) = value.parse::<i64>() {
+        return Ok(secs);
+    }
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.timestamp())
+        .with_context(|| {
+            format!(
+                "Invalid time: {}. Use unix seconds or RFC3339 (2025-01-01T00:00:00Z)",
+                value
+            )
+        })
+}
+
+/// One CSV record, quoting fields that need it, with the `\r\n` ending
+/// spreadsheet imports expect
+pub(crate) fn csv_row(fields: &[&str]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            if f.contains([',', '"', '\r', '\n']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.to_string()
+            }
+        })
+        .collect();
+    fields.join(",") + "\r\n"
+}
+
+/// `epoch_id,name,status,jobs_count,total_volume_usdc`, one row per epoch
+pub fn render_list_csv(epochs: &[EpochSnapshot]) -> String {
+    let mut out = csv_row(&["epoch_id", "name", "status", "jobs_count", "total_volume_usdc"]);
+    for epoch in epochs {
+        out.push_str(&csv_row(&[
+            &epoch.epoch_id,
+            &epoch.name,
+            &epoch.status,
+            &epoch.jobs_count.to_string(),
+            &epoch.total_volume_usdc.to_string(),
+        ]));
+    }
+    out
+}
+
+/// `provider,amount` for each payout in a sealed epoch, largest first.
+/// An epoch without settlements has only the header.
+pub fn render_payouts_csv(epoch: &EpochSnapshot) -> String {
+    let mut out = csv_row(&["provider", "amount"]);
+    if let Some(settlements) = &epoch.settlements {
+        let mut providers: Vec<(&String, u64)> = settlements
+            .providers
+            .iter()
+            .map(|(ens, amount)| (ens, to_microunits(*amount)))
+            .collect();
+        providers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+        for (ens, micro) in providers {
+            out.push_str(&csv_row(&[ens, &money::format_usdc(micro, 6)]));
+        }
+    }
+    out
+}
+
+async fn export_all(options: &ExportOptions, store: &dyn Storage) -> Result<()> {
+    println!("{}", "Exporting Epochs".cyan().bold());
+    println!();
+
+    let names = store.list(ipfs::paths::EPOCHS).await?;
+
+    let pb = ui::progress_bar(names.len() as u64, "Fetching epochs");
+    pb.enable_steady_tick(Duration::from_millis(100));
+
+    let export = export_epochs(
+        store,
+        names,
+        options.verify,
+        options.controller.as_deref(),
+        &pb,
+    )
+    .await;
+    pb.finish_and_clear();
+
+    std::fs::write(&options.out, render_export(&export.epochs, options.ndjson)?)
+        .with_context(|| format!("Failed to write {}", options.out))?;
+
+    println!(
+        "{} Exported {} epochs to {} ({})",
+        "✓".green(),
+        export.epochs.len(),
+        options.out.cyan(),
+        if options.ndjson { "NDJSON" } else { "JSON" }
+    );
+    if options.verify {
+        println!("  {} signatures and merkle roots checked", "✓".green());
+        if options.controller.is_none() {
+            for signer in &export.signers {
+                println!("  {} {}", "Signer:".bright_black(), signer);
+            }
+            println!(
+                "  {}",
+                "Signer not pinned; pass --controller <address> to enforce it".yellow()
+            );
+        }
+    }
+
+    if !export.failures.is_empty() {
+        println!();
+        println!("  {}", "Failed (not exported):".yellow());
+        for (name, error) in &export.failures {
+            println!("    {} {} ({})", "✗".red(), name, error);
+        }
+        println!();
+        anyhow::bail!(
+            "{} of {} epochs failed; the export is incomplete",
+            export.failures.len(),
+            export.failures.len() + export.epochs.len()
+        );
+    }
+
+    println!();
+    Ok(())
+}
+
+/// Epochs gathered by an export
+#[derive(Debug, Default)]
+pub struct EpochExport {
+    /// Published epoch JSON, ordered by epoch ID
+    pub epochs: Vec<Value>,
+    /// Distinct recovered signers (with `verify`)
+    pub signers: BTreeSet<String>,
+    /// Entries that failed to fetch, validate or verify, with the reason
+    pub failures: Vec<(String, String)>,
+}
+
+/// Fetch and validate every named epoch under `/swarmpool/epochs/`
+/// concurrently, collecting failures rather than stopping at the first
+pub async fn export_epochs(
+    store: &dyn Storage,
+    names: Vec<String>,
+    verify: bool,
+    controller: Option<&str>,
+    pb: &ProgressBar,
+) -> EpochExport {
+    let mut results = stream::iter(names)
+        .map(|name| async move {
+            let fetched = fetch_checked(store, &name, verify, controller).await;
+            (name, fetched)
+        })
+        .buffer_unordered(EXPORT_CONCURRENCY);
+
+    let mut export = EpochExport::default();
+    let mut epochs = Vec::new();
+    while let Some((name, fetched)) = results.next().await {
+        pb.inc(1);
+        match fetched {
+            Ok((epoch_id, data, signer)) => {
+                epochs.push((epoch_id, data));
+                export.signers.extend(signer);
+            }
+            Err(e) => export.failures.push((name, format!("{:#}", e))),
+        }
+    }
+
+    epochs.sort_by(|a, b| a.0.cmp(&b.0));
+    export.epochs = epochs.into_iter().map(|(_, data)| data).collect();
+    export.failures.sort();
+    export
+}
+
+/// One epoch as published: schema-checked, and with `verify` its signer
+/// recovered (and matched to `controller`) and merkle root recomputed
+async fn fetch_checked(
+    store: &dyn Storage,
+    name: &str,
+    verify: bool,
+    controller: Option<&str>,
+) -> Result<(String, Value, Option<String>)> {
+    let path = format!("{}/{}", ipfs::paths::EPOCHS, name);
+    let data: Value = store.read(&path).await?;
+
+    schema::validate(&data, SchemaType::Epoch)?;
+    let epoch: EpochSnapshot =
+        serde_json::from_value(data.clone()).context("Failed to parse epoch snapshot")?;
+
+    if !verify {
+        return Ok((epoch.epoch_id, data, None));
+    }
+
+    // Recover over the published JSON, not a re-serialization of it
+    let signer = verify_sig::recover(&data)?.signer;
+    if let Some(expected) = controller {
+        if expected.to_lowercase() != signer.to_lowercase() {
+            anyhow::bail!("signed by {}, expected controller {}", signer, expected);
+        }
+    }
+
+    if let Some(ref published) = epoch.merkle_root {
+        let mut leaves = Vec::with_capacity(epoch.proofs.len());
+        for cid in &epoch.proofs {
+            let proof: ProofSnapshot = store
+                .get(cid)
+                .await
+                .with_context(|| format!("Failed to fetch proof {}", cid))?;
+            leaves.push(proof.proof_hash);
+        }
+        let recomputed = crypto::merkle_root(&leaves)?;
+        if *published != recomputed {
+            anyhow::bail!(
+                "merkle root mismatch: published {}, recomputed {}",
+                published,
+                recomputed
+            );
+        }
+    }
+
+    Ok((epoch.epoch_id, data, Some(signer)))
+}
+
+/// Serialize exported epochs as one JSON array, or one object per line
+pub fn render_export(epochs: &[Value], ndjson: bool) -> Result<String> {
+    if !ndjson {
+        return Ok(serde_json::to_string_pretty(epochs)? + "\n");
+    }
+    let mut out = String::new();
+    for epoch in epochs {
+        out.push_str(&serde_json::to_string(epoch)?);
+        out.push('\n');
     }
+    Ok(out)
 }
 
-async fn show_epoch_list(limit: u32, pool: &str) -> Result<()> {
+async fn show_epoch_list(filter: &EpochFilter, store: &dyn Storage, pool: &str) -> Result<()> {
     println!("{}", "Epoch History".cyan().bold());
     println!();
 
-    let epochs = ipfs::fetch_epochs(pool, limit).await?;
+    let epochs = ipfs::fetch_epochs(store, pool, filter).await?;
 
     if epochs.is_empty() {
         println!("  {}", "No epochs found".bright_black());
@@ -35,6 +364,7 @@ async fn show_epoch_list(limit: u32, pool: &str) -> Result<()> {
     );
     println!("  {}", "━".repeat(60).bright_black());
 
+    let shown = epochs.len();
     for epoch in epochs {
         let status = match epoch.status.as_str() {
             "active" => "🟢 Active".green().to_string(),
@@ -47,7 +377,7 @@ async fn show_epoch_list(limit: u32, pool: &str) -> Result<()> {
             epoch.epoch_id.cyan(),
             epoch.name,
             epoch.jobs_count,
-            format!("${}", epoch.total_volume_usdc).green(),
+            money::format_usdc_display(epoch.total_volume_usdc.microunits()).green(),
             status
         );
     }
@@ -57,12 +387,18 @@ async fn show_epoch_list(limit: u32, pool: &str) -> Result<()> {
         "  {}",
         format!("View details: swarm epochs --id <epoch_id>").bright_black()
     );
+    if filter.limit == Some(shown) {
+        println!(
+            "  {}",
+            format!("Older epochs: swarm epochs --offset {}", filter.offset + shown).bright_black()
+        );
+    }
 
     Ok(())
 }
 
-async fn show_epoch_detail(epoch_id: &str, pool: &str) -> Result<()> {
-    let epoch = ipfs::fetch_epoch(pool, epoch_id).await?;
+async fn show_epoch_detail(epoch_id: &str, store: &dyn Storage, pool: &str) -> Result<()> {
+    let epoch = ipfs::fetch_epoch(store, pool, epoch_id).await?;
 
     println!("{}", format!("Epoch: {}", epoch_id).cyan().bold());
     println!();
@@ -115,7 +451,7 @@ async fn show_epoch_detail(epoch_id: &str, pool: &str) -> Result<()> {
     println!(
         "    {} {}",
         "Volume:".bright_black(),
-        format!("${}", epoch.total_volume_usdc).green()
+        money::format_usdc_display(epoch.total_volume_usdc.microunits()).green()
     );
 
     if let Some(merkle_root) = &epoch.merkle_root {
@@ -134,12 +470,12 @@ async fn show_epoch_detail(epoch_id: &str, pool: &str) -> Result<()> {
         println!(
             "    {} {}",
             "Miner Pool (75%):".bright_black(),
-            format!("${:.2}", settlements.miner_pool).green()
+            money::format_usdc_display(to_microunits(settlements.miner_pool)).green()
         );
         println!(
             "    {} {}",
             "Hive Ops (25%):".bright_black(),
-            format!("${:.2}", settlements.hive_ops)
+            money::format_usdc_display(to_microunits(settlements.hive_ops))
         );
         println!();
 
@@ -154,7 +490,7 @@ async fn show_epoch_detail(epoch_id: &str, pool: &str) -> Result<()> {
                 println!(
                     "    {} {}",
                     truncate_ens(ens, 28),
-                    format!("${:.2}", amount).green()
+                    money::format_usdc_display(to_microunits(*amount)).green()
                 );
             }
         }
@@ -170,3 +506,173 @@ fn truncate_ens(ens: &str, max_len: usize) -> String {
         format!("{}...{}", &ens[..10], &ens[ens.len() - 10..])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, TEST_ADDRESS, TEST_KEY};
+    use serde_json::json;
+
+    async fn sealed_epoch(epoch_id: &str, proofs: Vec<String>, merkle_root: String) -> Value {
+        let unsigned = json!({
+            "type": "epoch",
+            "version": "1.0.0",
+            "epoch_id": epoch_id,
+            "name": "Golf",
+            "status": "sealed",
+            "started_at": 1704067200,
+            "jobs_count": proofs.len(),
+            "proofs": proofs,
+            "total_volume_usdc": "0.100000",
+            "merkle_root": merkle_root,
+            "controller": "merlin.swarmos.eth",
+            "timestamp": 1704070800
+        });
+        crypto::sign_json(&unsigned, TEST_KEY).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_export_verifies_and_reports_failures() {
+        let (_root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        let proof_hash = crypto::keccak256_hash(b"job-001:alpha.swarmbee.eth");
+        let proof_cid = store
+            .put(&json!({
+                "type": "proof",
+                "version": "1.0.0",
+                "proof_id": "proof-job-001",
+                "job_id": "job-001",
+                "job_cid": "QmJob1",
+                "status": "completed",
+                "output_cid": "QmOutput",
+                "metrics": {
+                    "inference_seconds": 10.0,
+                    "compute_seconds": 10.0,
+                    "confidence": 0.9,
+                    "model_version": "queenbee-spine-v1.0"
+                },
+                "provider": "alpha.swarmbee.eth",
+                "timestamp": 1704068000,
+                "proof_hash": proof_hash
+            }))
+            .await
+            .unwrap();
+
+        let good = sealed_epoch("epoch-002", vec![proof_cid.clone()], proof_hash.clone()).await;
+        let older = sealed_epoch("epoch-001", vec![proof_cid.clone()], proof_hash).await;
+        let bad_root = sealed_epoch(
+            "epoch-003",
+            vec![proof_cid],
+            format!("0x{}", "0".repeat(64)),
+        )
+        .await;
+        let mut unsigned = good.clone();
+        unsigned["epoch_id"] = json!("epoch-004");
+        unsigned.as_object_mut().unwrap().remove("sig");
+
+        for (id, epoch) in [
+            ("epoch-002", &good),
+            ("epoch-001", &older),
+            ("epoch-003", &bad_root),
+            ("epoch-004", &unsigned),
+        ] {
+            ipfs::write_epoch(store, id, epoch).await.unwrap();
+        }
+        let names = store.list(ipfs::paths::EPOCHS).await.unwrap();
+        let pb = ProgressBar::hidden();
+
+        // Schema alone passes the bad merkle root
+        let plain = export_epochs(store, names.clone(), false, None, &pb).await;
+        assert_eq!(plain.epochs.len(), 3);
+        assert_eq!(plain.failures.len(), 1);
+        assert_eq!(plain.failures[0].0, "epoch-004.json");
+
+        let verified = export_epochs(store, names.clone(), true, None, &pb).await;
+        let ids: Vec<&str> = verified
+            .epochs
+            .iter()
+            .map(|e| e["epoch_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, ["epoch-001", "epoch-002"]);
+        assert_eq!(verified.epochs[1], good);
+        assert!(verified.failures[0].1.contains("merkle root mismatch"));
+        assert_eq!(
+            verified
+                .signers
+                .iter()
+                .map(|s| s.to_lowercase())
+                .collect::<Vec<_>>(),
+            [TEST_ADDRESS]
+        );
+
+        let pinned = export_epochs(
+            store,
+            names,
+            true,
+            Some("0x0000000000000000000000000000000000000001"),
+            &pb,
+        )
+        .await;
+        assert!(pinned.epochs.is_empty());
+
+        let ndjson = render_export(&verified.epochs, true).unwrap();
+        assert_eq!(ndjson.lines().count(), 2);
+        let array: Vec<Value> =
+            serde_json::from_str(&render_export(&verified.epochs, false).unwrap()).unwrap();
+        assert_eq!(array, verified.epochs);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("1704067200").unwrap(), 1_704_067_200);
+        assert_eq!(parse_time("2024-01-01T00:00:00Z").unwrap(), 1_704_067_200);
+        assert_eq!(parse_time("2024-01-01T01:00:00+01:00").unwrap(), 1_704_067_200);
+        assert!(parse_time("2024-01-01").is_err());
+    }
+
+    #[test]
+    fn test_csv_is_plain_and_quoted() {
+        let epoch: EpochSnapshot = serde_json::from_value(json!({
+            "type": "epoch",
+            "version": "1.0.0",
+            "epoch_id": "epoch-007",
+            "name": "Golf, \"late\"",
+            "status": "sealed",
+            "started_at": 1704067200,
+            "jobs_count": 3,
+            "proofs": [],
+            "total_volume_usdc": "1.50",
+            "merkle_root": "0x00",
+            "controller": "merlin.swarmos.eth",
+            "timestamp": 1704070800,
+            "settlements": {
+                "total_volume": 1.5,
+                "miner_pool": 1.125,
+                "hive_ops": 0.375,
+                "providers": { "alpha.swarmbee.eth": 0.375, "beta.swarmbee.eth": 0.75 },
+                "dust_to_hive": 0.0
+            }
+        }))
+        .unwrap();
+
+        let list = render_list_csv(std::slice::from_ref(&epoch));
+        let payouts = render_payouts_csv(&epoch);
+
+        assert_eq!(
+            list,
+            "epoch_id,name,status,jobs_count,total_volume_usdc\r\n\
+             epoch-007,\"Golf, \"\"late\"\"\",sealed,3,1.50\r\n"
+        );
+        assert_eq!(
+            payouts,
+            "provider,amount\r\n\
+             beta.swarmbee.eth,0.750000\r\n\
+             alpha.swarmbee.eth,0.375000\r\n"
+        );
+        assert!(!list.contains('\x1b') && !payouts.contains('\x1b'));
+
+        assert_eq!("CSV".parse::<OutputFormat>(), Ok(OutputFormat::Csv));
+        assert!("xlsx".parse::<OutputFormat>().is_err());
+    }
+}
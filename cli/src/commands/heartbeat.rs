@@ -0,0 +1,51 @@
+//! heartbeat command - send one signed liveness ping
+//!
+//! Publishes the same record `watch` sends every 30 seconds, through the same
+//! `provider::publish_heartbeat` path: signed, checked against the HEARTBEAT
+//! schema, written to the provider's `status.json` and announced on
+//! `/{pool}/heartbeats`.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::config;
+use crate::crypto;
+use crate::provider;
+use crate::storage::Storage;
+
+pub async fn execute(
+    provider_override: Option<String>,
+    status: String,
+    models: Option<String>,
+    key: Option<String>,
+    store: &dyn Storage,
+    pool: &str,
+) -> Result<()> {
+    println!("{}", "Sending Heartbeat".cyan().bold());
+    println!();
+
+    let private_key = crypto::resolve_signer(key)?;
+    let config = config::load_config()?;
+
+    let provider_ens = provider_override
+        .or(config.provider_ens)
+        .context("Provider ENS required. Run 'swarm init' first or use --provider")?;
+    let model_list: Vec<String> = models
+        .map(|m| m.split(',').map(|s| s.trim().to_string()).collect())
+        .unwrap_or_else(|| config.models.clone());
+    let gpus = provider::gpu_names(&provider::detect_gpus());
+
+    let heartbeat = provider::build_heartbeat(&provider_ens, &status, &model_list, &gpus);
+    let cid = provider::publish_heartbeat(store, pool, heartbeat, &private_key).await?;
+
+    println!("  {} {}", "Provider:".bright_black(), provider_ens);
+    println!("  {} {}", "Status:".bright_black(), status.green());
+    println!("  {} {}", "Models:".bright_black(), model_list.join(", "));
+    if !gpus.is_empty() {
+        println!("  {} {}", "GPUs:".bright_black(), gpus.join(", "));
+    }
+    println!("  {} {}", "CID:".bright_black(), cid.cyan());
+    println!();
+
+    Ok(())
+}
@@ -0,0 +1,365 @@
+//! Import-epoch command - replay and verify an externally-provided epoch
+//!
+//! Auditors and mirror operators receive sealed epochs out of band. Before
+//! trusting one we check, in order:
+//! 1. The EPOCH schema
+//! 2. The controller signature, against the pinned controller address
+//! 3. The merkle root, recomputed from the referenced proofs
+//! 4. The settlements, recomputed from those same proofs
+//!
+//! Only a fully verified epoch can be written to `/swarmpool/epochs/`.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::commands::seal::{proofs_merkle_root, settle_epoch, EpochProof, Offense};
+use crate::config;
+use crate::ipfs;
+use crate::storage::Storage;
+use crate::money;
+use crate::models::{
+    to_microunits, ClaimSnapshot, EpochSnapshot, ExecutionMode, JobSnapshot, ProofSnapshot,
+    SettlementConfig, Settlements, DEFAULT_COMPUTE_OVERHEAD_CAP, DEFAULT_SLASH_BPS,
+};
+use crate::schema::{self, SchemaType};
+use crate::ui;
+
+/// Largest per-provider difference accepted as rounding (1 microunit)
+pub const ROUNDING_TOLERANCE_MICRO: u64 = 1;
+
+pub async fn execute(
+    file: String,
+    controller: Option<String>,
+    write: bool,
+    store: &dyn Storage,
+    _pool: &str,
+) -> Result<()> {
+    println!("{}", "Importing Epoch".cyan().bold());
+    println!();
+    println!("  {} {}", "File:".bright_black(), file);
+    println!();
+
+    // The epoch's signer is only meaningful against a pinned controller
+    let controller = config::controller_address(controller)?;
+
+    // 1. Schema
+    let content = std::fs::read_to_string(&file).context("Failed to read file")?;
+    let data: serde_json::Value = serde_json::from_str(&content).context("Failed to parse JSON")?;
+    schema::validate(&data, SchemaType::Epoch)?;
+
+    // 2. Signature, over the document as received, before anything in it is trusted
+    let epoch = ipfs::verified_epoch(data.clone(), &controller).context("Epoch rejected")?;
+    if epoch.status != "sealed" {
+        anyhow::bail!(
+            "Epoch {} is {}, only sealed epochs can be imported",
+            epoch.epoch_id,
+            epoch.status
+        );
+    }
+    println!("  {} Schema valid: {}", "✓".green(), epoch.epoch_id.cyan());
+    println!("  {} Signed by controller {}", "✓".green(), controller);
+
+    // 3. Re-fetch proofs
+    let pb = ui::spinner(format!("Re-fetching {} proofs...", epoch.proofs.len()));
+
+    let proofs = fetch_epoch_proofs(store, &epoch.proofs).await?;
+
+    pb.done(format!("{} Fetched {} proofs", "✓".green(), proofs.len()));
+
+    // 4. Merkle root and settlements
+    let mut problems = Vec::new();
+
+    let merkle_root = proofs_merkle_root(&proofs)?;
+    match epoch.merkle_root {
+        Some(ref published) if *published == merkle_root => {
+            println!(
+                "  {} Merkle root matches: {}...",
+                "✓".green(),
+                &merkle_root[..18]
+            );
+        }
+        ref published => problems.push(format!(
+            "merkle root mismatch: published {}, recomputed {}",
+            published.as_deref().unwrap_or("(none)"),
+            merkle_root
+        )),
+    }
+
+    let recomputed = recompute_settlements(&epoch, &proofs);
+
+    match epoch.settlements {
+        Some(ref published) => {
+            let diffs = reconcile_settlements(published, &recomputed);
+            if diffs.is_empty() {
+                println!(
+                    "  {} Settlements reconcile across {} providers",
+                    "✓".green(),
+                    recomputed.providers.len()
+                );
+            }
+            problems.extend(diffs);
+        }
+        None => problems.push("sealed epoch has no settlements".to_string()),
+    }
+
+    println!();
+
+    if !problems.is_empty() {
+        println!("{}", "❌ EPOCH REJECTED".red().bold());
+        println!();
+        for problem in &problems {
+            println!("    {} {}", "•".red(), problem);
+        }
+        anyhow::bail!("Epoch {} failed verification", epoch.epoch_id);
+    }
+
+    println!("{}", "✅ EPOCH VERIFIED".green().bold());
+
+    if write {
+        let pb = ui::spinner("Writing epoch to local MFS...");
+
+        // Written as received, so the controller's signature still verifies
+        let cid = ipfs::write_epoch(store, &epoch.epoch_id, &data).await?;
+        pb.done(format!(
+            "{} Mirrored to {}/{}.json ({})",
+            "✓".green(),
+            ipfs::paths::EPOCHS,
+            epoch.epoch_id,
+            cid
+        ));
+    } else {
+        println!();
+        println!(
+            "  {}",
+            "Re-run with --write to mirror it under /swarmpool/epochs/".bright_black()
+        );
+    }
+
+    Ok(())
+}
+
+/// Settle `proofs` as the controller should have for `epoch`
+///
+/// The published slashes are re-applied (at their recorded rate) so honest
+/// proofs reconcile exactly; whether those disputes were upheld is checked
+/// separately. Compute is capped at the epoch's recorded overhead cap.
+pub fn recompute_settlements(epoch: &EpochSnapshot, proofs: &[EpochProof]) -> Settlements {
+    let published_slashes = epoch
+        .settlements
+        .as_ref()
+        .map(|s| s.slashes.clone())
+        .unwrap_or_default();
+    let offenses: Vec<Offense> = published_slashes
+        .iter()
+        .map(|s| Offense {
+            proof_cid: s.proof_cid.clone(),
+            reason: s.reason.clone(),
+        })
+        .collect();
+    let config = SettlementConfig {
+        slash_bps: published_slashes
+            .first()
+            .map(|s| s.slash_bps)
+            .unwrap_or(DEFAULT_SLASH_BPS),
+        compute_overhead_cap: epoch
+            .settlements
+            .as_ref()
+            .and_then(|s| s.compute_overhead_cap)
+            .unwrap_or(DEFAULT_COMPUTE_OVERHEAD_CAP),
+        ..Default::default()
+    };
+    settle_epoch(proofs, epoch.total_volume_usdc.as_f64(), &offenses, &config)
+}
+
+/// Fetch each proof with its job (reward) and claim (mode)
+///
+/// Proofs without a claim are settled as SOLO, matching what `prove` publishes
+/// when run without `--claim`. `proof_cids` is the epoch's list, which is in
+/// the order the controller collected the proofs, so each proof's position is
+/// its `observed` order and replays resolve as they were sealed.
+pub async fn fetch_epoch_proofs(store: &dyn Storage, proof_cids: &[String]) -> Result<Vec<EpochProof>> {
+    let mut jobs: HashMap<String, JobSnapshot> = HashMap::new();
+    let mut proofs = Vec::with_capacity(proof_cids.len());
+
+    for (observed, cid) in proof_cids.iter().enumerate() {
+        let proof: ProofSnapshot = store.get(cid)
+            .await
+            .with_context(|| format!("Failed to fetch proof {}", cid))?;
+
+        if !jobs.contains_key(&proof.job_cid) {
+            let job: JobSnapshot = store.get(&proof.job_cid)
+                .await
+                .with_context(|| format!("Failed to fetch job {}", proof.job_cid))?;
+            jobs.insert(proof.job_cid.clone(), job);
+        }
+        let reward: f64 = jobs[&proof.job_cid]
+            .payment
+            .amount
+            .parse()
+            .with_context(|| format!("Invalid payment amount on job {}", proof.job_cid))?;
+
+        let mode = match proof.claim_cid {
+            Some(ref claim_cid) => {
+                let claim: ClaimSnapshot = store.get(claim_cid)
+                    .await
+                    .with_context(|| format!("Failed to fetch claim {}", claim_cid))?;
+                claim.mode
+            }
+            None => ExecutionMode::Solo,
+        };
+
+        proofs.push(EpochProof {
+            observed: observed as u64,
+            ..EpochProof::from_snapshot(cid, &proof, reward, mode)
+        });
+    }
+
+    Ok(proofs)
+}
+
+/// Compare published settlements against a recomputation, in microunits
+///
+/// Returns one line per discrepancy; empty means the settlements reconcile.
+pub fn reconcile_settlements(published: &Settlements, recomputed: &Settlements) -> Vec<String> {
+    let mut diffs: Vec<String> = [
+        (
+            "total_volume",
+            published.total_volume,
+            recomputed.total_volume,
+        ),
+        ("miner_pool", published.miner_pool, recomputed.miner_pool),
+        ("hive_ops", published.hive_ops, recomputed.hive_ops),
+    ]
+    .into_iter()
+    .filter_map(|(label, a, b)| amount_delta(label, a, b))
+    .collect();
+
+    let providers: BTreeSet<&String> = published
+        .providers
+        .keys()
+        .chain(recomputed.providers.keys())
+        .collect();
+
+    for provider in providers {
+        match (
+            published.providers.get(provider),
+            recomputed.providers.get(provider),
+        ) {
+            (Some(a), Some(b)) => diffs.extend(amount_delta(provider, *a, *b)),
+            (Some(a), None) => diffs.push(format!(
+                "{}: paid {} but has no proofs in this epoch",
+                provider,
+                money::format_usdc(to_microunits(*a), 6)
+            )),
+            (None, Some(b)) => diffs.push(format!(
+                "{}: owed {} but missing from published settlements",
+                provider,
+                money::format_usdc(to_microunits(*b), 6)
+            )),
+            (None, None) => {}
+        }
+    }
+
+    let flagged = |s: &Settlements| -> BTreeSet<String> {
+        s.flagged.iter().map(|f| f.job_id.clone()).collect()
+    };
+    let (published_flagged, recomputed_flagged) = (flagged(published), flagged(recomputed));
+    for job_id in recomputed_flagged.difference(&published_flagged) {
+        diffs.push(format!("{}: conflicting claim modes but settled anyway", job_id));
+    }
+    for job_id in published_flagged.difference(&recomputed_flagged) {
+        diffs.push(format!("{}: flagged but its claims agree", job_id));
+    }
+
+    diffs
+}
+
+/// Describe a published/recomputed mismatch beyond rounding tolerance
+fn amount_delta(label: &str, published: f64, recomputed: f64) -> Option<String> {
+    let (a, b) = (to_microunits(published), to_microunits(recomputed));
+    (a.abs_diff(b) > ROUNDING_TOLERANCE_MICRO).then(|| {
+        format!(
+            "{}: published {}, recomputed {}",
+            label,
+            money::format_usdc(a, 6),
+            money::format_usdc(b, 6)
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto;
+    use crate::test_support::{OTHER_KEY, TEST_ADDRESS, TEST_KEY};
+
+    fn settlements(providers: &[(&str, f64)]) -> Settlements {
+        let providers: HashMap<String, f64> =
+            providers.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        let miner_pool = providers.values().sum();
+
+        Settlements {
+            total_volume: 0.20,
+            miner_pool,
+            hive_ops: 0.05,
+            providers,
+            dust_to_hive: 0.0,
+            slashes: vec![],
+            flagged: vec![],
+            hive_recipients: Default::default(),
+            compute_overhead_cap: None,
+            micro: None,
+        }
+    }
+
+    #[test]
+    fn test_reconcile_matching_settlements() {
+        let a = settlements(&[("a.eth", 0.075), ("b.eth", 0.075)]);
+        let b = settlements(&[("b.eth", 0.075), ("a.eth", 0.075000001)]);
+        assert!(reconcile_settlements(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn test_reconcile_reports_deltas_and_missing_providers() {
+        let published = settlements(&[("a.eth", 0.10), ("c.eth", 0.05)]);
+        let recomputed = settlements(&[("a.eth", 0.075), ("b.eth", 0.075)]);
+
+        let diffs = reconcile_settlements(&published, &recomputed);
+        assert!(diffs
+            .iter()
+            .any(|d| d.starts_with("a.eth: published 0.100000")));
+        assert!(diffs.iter().any(|d| d.starts_with("b.eth: owed")));
+        assert!(diffs.iter().any(|d| d.starts_with("c.eth: paid")));
+        assert!(!diffs.iter().any(|d| d.starts_with("miner_pool")));
+    }
+
+    #[tokio::test]
+    async fn test_epoch_must_be_signed_by_controller() {
+        let mut epoch = serde_json::json!({
+            "type": "epoch",
+            "version": "1.0.0",
+            "epoch_id": "epoch-048",
+            "name": "Golf",
+            "status": "sealed",
+            "started_at": 1704067200,
+            "jobs_count": 1,
+            "proofs": ["bafyproof001alpha"],
+            "total_volume_usdc": "0.100000",
+            "merkle_root": "0xabc",
+            "controller": "merlin.swarmos.eth",
+            "timestamp": 1704070800
+        });
+        let signed = crypto::sign_json(&epoch, TEST_KEY).await.unwrap();
+        assert!(ipfs::verified_epoch(signed.clone(), TEST_ADDRESS).is_ok());
+
+        // Signed, but not by the pinned controller
+        let other = crypto::sign_json(&epoch, OTHER_KEY).await.unwrap();
+        assert!(ipfs::verified_epoch(other, TEST_ADDRESS).is_err());
+
+        // A merkle root swapped in after signing no longer verifies
+        epoch["merkle_root"] = "0xdef".into();
+        epoch["sig"] = signed["sig"].clone();
+        assert!(ipfs::verified_epoch(epoch, TEST_ADDRESS).is_err());
+    }
+}
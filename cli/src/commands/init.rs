@@ -1,46 +1,107 @@
 //! Init command - Initialize provider and register with pool
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Duration;
 
 use crate::config::{self, Config};
 use crate::crypto;
 use crate::ipfs;
-use crate::models::ProviderRegistration;
+use crate::storage::Storage;
+use crate::models::{self, ProviderRegistration};
+use crate::nonces;
 use crate::provider;
+use crate::say;
+use crate::schema::{self, SchemaType};
+use crate::ui;
 
 pub async fn execute(
     provider: String,
     wallet: String,
     gpus: Option<String>,
+    models: Option<String>,
     key: Option<String>,
+    store: &dyn Storage,
     pool: &str,
 ) -> Result<()> {
-    println!("{}", "Initializing SwarmPool Provider".cyan().bold());
-    println!();
+    say!("{}", "Initializing SwarmPool Provider".cyan().bold());
+    say!();
+
+    // A mistyped payout address would otherwise go unnoticed until withdrawal
+    if !crypto::is_valid_checksum_address(&wallet) {
+        anyhow::bail!(
+            "Wallet {} is not a valid address: it must be 0x + 40 hex characters, and a \
+             mixed-case address must match its EIP-55 checksum (check for a mistyped character)",
+            wallet
+        );
+    }
+    if wallet[2..].chars().any(|c| c.is_ascii_lowercase()) && wallet == wallet.to_lowercase() {
+        println!(
+            "  {} Wallet has no checksum; consider the checksummed form {}",
+            "⚠".yellow(),
+            crypto::checksum_address(&wallet)?
+        );
+    }
 
     // Get private key
-    let private_key = key
-        .or_else(|| std::env::var("SWARM_PRIVATE_KEY").ok())
-        .context("Private key required. Use --key or set SWARM_PRIVATE_KEY")?;
+    let private_key = crypto::resolve_signer(key)?;
 
     // Detect or parse GPUs
+    say!("  {} Detecting GPUs...", "⚡".yellow());
+    let detected = provider::detect_gpus();
+    if detected.is_empty() {
+        say!(
+            "  {} No GPU detected (nvidia-smi missing or reported no devices)",
+            "⚠".yellow()
+        );
+    }
     let gpu_list: Vec<String> = if let Some(gpus) = gpus {
         gpus.split(',').map(|s| s.trim().to_string()).collect()
     } else {
-        println!("  {} Detecting GPUs...", "⚡".yellow());
-        let detected = provider::detect_gpus();
         detected.iter().map(|g| g.name.clone()).collect()
     };
+    if gpu_list.is_empty() {
+        anyhow::bail!("No GPUs to register. Use --gpus to list them explicitly");
+    }
+
+    // Explicit --models wins; otherwise register everything the hardware can run
+    let model_list: Vec<String> = match models {
+        Some(models) => {
+            let list: Vec<String> = models.split(',').map(|s| s.trim().to_string()).collect();
+            if let Some(unknown) = list.iter().find(|m| models::model_spec(m).is_none()) {
+                anyhow::bail!("Unknown model: {}. Run 'swarm models' for the catalog", unknown);
+            }
+            list
+        }
+        None => {
+            say!("  {} Models this hardware can serve:", "⚡".yellow());
+            for (model, eligibility) in provider::model_eligibility(&detected) {
+                let reason = match eligibility {
+                    provider::Eligibility::Eligible => eligibility.to_string().green(),
+                    provider::Eligibility::TooLarge => format!(
+                        "{} (needs {} GB)",
+                        eligibility, model.vram_gb
+                    )
+                    .red(),
+                    provider::Eligibility::Beta => {
+                        format!("{} (pass --models to serve it)", eligibility).yellow()
+                    }
+                };
+                say!("    {:<18} {}", model.name, reason);
+            }
+            provider::eligible_models(&detected)
+        }
+    };
+    if model_list.is_empty() {
+        anyhow::bail!("No models fit the detected GPUs. Use --models to choose explicitly");
+    }
 
     // Show init details
-    println!("  {} {}", "Provider:".bright_black(), provider.green());
-    println!("  {} {}", "Wallet:".bright_black(), wallet);
-    println!("  {} {:?}", "GPUs:".bright_black(), gpu_list);
-    println!("  {} {}", "Pool:".bright_black(), pool);
-    println!();
+    say!("  {} {}", "Provider:".bright_black(), provider.green());
+    say!("  {} {}", "Wallet:".bright_black(), wallet);
+    say!("  {} {:?}", "GPUs:".bright_black(), gpu_list);
+    say!("  {} {:?}", "Models:".bright_black(), model_list);
+    say!("  {} {}", "Pool:".bright_black(), pool);
+    say!();
 
     // Create registration snapshot
     let timestamp = chrono::Utc::now().timestamp();
@@ -51,49 +112,31 @@ pub async fn execute(
         provider: provider.clone(),
         wallet: wallet.clone(),
         gpus: gpu_list.clone(),
-        models: vec!["queenbee-spine".to_string()],
+        models: model_list.clone(),
         timestamp,
         nonce,
         sig: None,
     };
 
     // Sign registration
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Signing init snapshot...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Signing init snapshot...");
 
     registration.sig = Some(crypto::sign_snapshot(&registration, &private_key).await?);
-    pb.finish_with_message(format!("{} Snapshot signed", "✓".green()));
+    pb.done(format!("{} Snapshot signed", "✓".green()));
+    schema::check_before_publish(&registration, SchemaType::Genesis)?;
+    nonces::check(&provider, &registration.nonce)?;
 
     // Write genesis to canonical IPFS path: /swarmpool/genesis/{provider}.json
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Publishing genesis to IPFS...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Publishing genesis to IPFS...");
 
-    let init_cid = ipfs::write_genesis(&provider, &registration).await?;
-    pb.finish_with_message(format!("{} Published: {}", "✓".green(), init_cid.cyan()));
+    let init_cid = ipfs::write_genesis(store, &provider, &registration).await?;
+    nonces::record_published(store, &provider, &registration.nonce, timestamp);
+    pb.done(format!("{} Published: {}", "✓".green(), init_cid.cyan()));
 
     // Announce to pool
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Announcing to pool...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Announcing to pool...");
 
-    ipfs::pubsub_publish(
+    store.announce(
         &format!("/{}/providers/init", pool),
         &serde_json::json!({
             "cid": init_cid,
@@ -104,35 +147,35 @@ pub async fn execute(
     )
     .await?;
 
-    pb.finish_with_message(format!("{} Announced to {}", "✓".green(), pool));
+    pb.done(format!("{} Announced to {}", "✓".green(), pool));
 
     // Save config
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Saving configuration...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Saving configuration...");
 
     let config = Config {
         provider_ens: Some(provider.clone()),
         wallet: Some(wallet.clone()),
         gpus: gpu_list,
-        models: vec!["queenbee-spine".to_string()],
+        models: model_list,
         pool: pool.to_string(),
         ipfs_api: "http://localhost:5001".to_string(),
+        ipfs_gateway: "https://ipfs.io/ipfs".to_string(),
+        eth_rpc: None,
+        controller: None,
+        settlement: Default::default(),
+        pricing: Default::default(),
+        sandbox: false,
+        keystore: None,
     };
     config::save_config(&config)?;
 
-    pb.finish_with_message(format!("{} Config saved", "✓".green()));
+    pb.done(format!("{} Config saved", "✓".green()));
 
     // Summary
-    println!();
-    println!("{}", "Provider Initialized".green().bold());
-    println!();
-    println!(
+    say!();
+    say!("{}", "Provider Initialized".green().bold());
+    say!();
+    say!(
         "  {}",
         format!("ENS: {}", provider.cyan())
     );
@@ -140,11 +183,11 @@ pub async fn execute(
         "  {}",
         format!("CID: {}", init_cid.bright_black())
     );
-    println!();
-    println!("  {}", "Next:".yellow());
-    println!("    {}", "swarm watch     # Watch for jobs".bright_black());
-    println!("    {}", "swarm status    # Check status".bright_black());
-    println!();
+    say!();
+    say!("  {}", "Next:".yellow());
+    say!("    {}", "swarm watch     # Watch for jobs".bright_black());
+    say!("    {}", "swarm status    # Check status".bright_black());
+    say!();
 
     Ok(())
 }
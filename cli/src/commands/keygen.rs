@@ -0,0 +1,91 @@
+//! Keygen command - create a new signing identity
+//!
+//! Generates a fresh secp256k1 key. The key is only shown with
+//! `--unsafe-print`; otherwise it is either saved to the encrypted keystore
+//! (`--save`) or discarded, and only the address is printed.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use ethers::signers::{LocalWallet, Signer};
+
+use crate::config;
+use crate::keystore::{self, Keystore};
+
+pub async fn execute(
+    save: bool,
+    label: String,
+    set_wallet: bool,
+    unsafe_print: bool,
+    _pool: &str,
+) -> Result<()> {
+    println!("{}", "Generate Signing Key".cyan().bold());
+    println!();
+
+    if set_wallet && !save {
+        bail!("--set-wallet requires --save (the key would otherwise be lost)");
+    }
+
+    let keystore = Keystore::open()?;
+    if save && keystore.contains(&label) {
+        bail!("Keystore entry '{}' already exists. Choose another --label.", label);
+    }
+
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let address = format!("{:?}", wallet.address());
+
+    println!("  {} {}", "Address:".bright_black(), address.green());
+
+    if save {
+        let password = keystore::read_password(true)?;
+        let path = keystore.save(&label, &wallet, &password)?;
+
+        // Make sure the file we just wrote actually opens to the same key
+        let reloaded = keystore.load(&label, &password)?;
+        if reloaded.address() != wallet.address() {
+            bail!("Keystore verification failed for '{}'", label);
+        }
+
+        println!("  {} {}", "Keystore:".bright_black(), path.display());
+
+        if set_wallet {
+            let mut cfg = config::load_config()?;
+            cfg.wallet = Some(address.clone());
+            config::save_config(&cfg)?;
+            println!("  {} {}", "Config:".bright_black(), "wallet updated".green());
+        }
+    }
+
+    if unsafe_print {
+        println!(
+            "  {} 0x{}",
+            "Private key:".bright_black(),
+            hex::encode(wallet.signer().to_bytes())
+        );
+    }
+
+    println!();
+    println!(
+        "{}",
+        "⚠ This key controls any funds paid to its address.".yellow().bold()
+    );
+    if unsafe_print {
+        println!(
+            "  {}",
+            "The private key was printed above. Clear your terminal history and never share it."
+                .yellow()
+        );
+    } else if save {
+        println!(
+            "  {}",
+            "Back up the keystore file and its password; losing either loses the key."
+                .bright_black()
+        );
+    } else {
+        println!(
+            "  {}",
+            "The key was not saved. Re-run with --save to keep it.".yellow()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! Ledger command - list snapshots this machine has published
+//!
+//! Reads the local append-only ledger (see `crate::ledger`), so CIDs can be
+//! recovered even after the IPFS node's MFS has been reset.
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, TimeZone, Utc};
+use colored::Colorize;
+
+use crate::ledger::{Ledger, LedgerFilter};
+
+pub async fn execute(
+    snapshot_type: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    json: bool,
+) -> Result<()> {
+    let filter = LedgerFilter {
+        snapshot_type,
+        since: since
+            .as_deref()
+            .map(|s| parse_bound(s, false))
+            .transpose()?,
+        until: until.as_deref().map(|s| parse_bound(s, true)).transpose()?,
+    };
+
+    let ledger = Ledger::open()?;
+    let (entries, skipped) = ledger.query(&filter)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    println!("{}", "Publish Ledger".cyan().bold());
+    println!("  {}", ledger.path().display().to_string().bright_black());
+    println!();
+
+    if entries.is_empty() {
+        println!("  {}", "No matching entries".bright_black());
+    } else {
+        println!(
+            "  {:<20} {:<8} {:<46} {}",
+            "Published".bright_black(),
+            "Type".bright_black(),
+            "CID".bright_black(),
+            "Path".bright_black()
+        );
+        println!("  {}", "━".repeat(40).bright_black());
+        for entry in &entries {
+            let when = Utc
+                .timestamp_opt(entry.timestamp, 0)
+                .single()
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| entry.timestamp.to_string());
+            println!(
+                "  {:<20} {:<8} {:<46} {}",
+                when,
+                entry.snapshot_type,
+                entry.cid.cyan(),
+                entry.path
+            );
+        }
+        println!();
+        println!("  {} {}", "Entries:".bright_black(), entries.len());
+    }
+
+    if skipped > 0 {
+        println!("  {} {} unreadable line(s) skipped", "⚠".yellow(), skipped);
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Unix time for a `--since`/`--until` value: unix seconds, or a
+/// `YYYY-MM-DD` date (UTC). A date as `--until` is its last second, so the
+/// whole day is included.
+pub fn parse_bound(value: &str, end: bool) -> Result<i64> {
+    if let Ok(secs) = value.parse::<i64>() {
+        return Ok(secs);
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date: {}. Use YYYY-MM-DD or unix seconds", value))?;
+    let date = if end {
+        date.succ_opt().context("Date out of range")?
+    } else {
+        date
+    };
+    let midnight = date
+        .and_hms_opt(0, 0, 0)
+        .context("Date out of range")?
+        .and_utc()
+        .timestamp();
+    Ok(if end { midnight - 1 } else { midnight })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bound() {
+        assert_eq!(parse_bound("2025-01-01", false).unwrap(), 1_735_689_600);
+        // --until covers the whole day
+        assert_eq!(parse_bound("2025-01-01", true).unwrap(), 1_735_775_999);
+        assert_eq!(parse_bound("1704067200", true).unwrap(), 1_704_067_200);
+        assert!(parse_bound("01/02/2025", false).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+//! migrate-keys command - move a raw signing key into the encrypted keystore
+//!
+//! Takes the key `--key`/`SWARM_PRIVATE_KEY` currently resolves to, encrypts
+//! it under a keystore label and points config at that label. The raw key may
+//! be the only copy, so nothing changes in config until the stored entry has
+//! been decrypted and proven to sign as the expected address.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::Address;
+use std::path::PathBuf;
+
+use crate::config;
+use crate::crypto;
+use crate::keystore::{self, Keystore};
+
+pub async fn execute(
+    key: Option<String>,
+    label: String,
+    address: Option<String>,
+    _pool: &str,
+) -> Result<()> {
+    println!("{}", "Migrate Signing Key".cyan().bold());
+    println!();
+
+    let mut cfg = config::load_config()?;
+    if key.is_none() && std::env::var(crypto::PRIVATE_KEY_ENV).is_err() {
+        bail!(
+            "No raw key to migrate. Use --key or set {}",
+            crypto::PRIVATE_KEY_ENV
+        );
+    }
+    let private_key = crypto::resolve_signer(key)?;
+
+    let expected = address
+        .or_else(|| cfg.wallet.clone())
+        .context("Expected address required. Use --address or set wallet in config")?;
+    let expected: Address = expected
+        .parse()
+        .with_context(|| format!("Invalid address: {}", expected))?;
+
+    let keystore = Keystore::open()?;
+    if keystore.contains(&label) {
+        bail!(
+            "Keystore entry '{}' already exists. Choose another --label.",
+            label
+        );
+    }
+
+    println!("  {} {:?}", "Address:".bright_black(), expected);
+    println!("  {} {}", "Label:".bright_black(), label);
+    println!();
+
+    let password = keystore::read_password(true)?;
+    let path = migrate(&keystore, &label, &private_key, &password, expected).await?;
+    println!("  {} Round-trip verified", "✓".green());
+    println!("  {} {}", "Keystore:".bright_black(), path.display());
+
+    cfg.keystore = Some(label.clone());
+    config::save_config(&cfg)?;
+    println!(
+        "  {} {}",
+        "Config:".bright_black(),
+        format!("keystore = \"{}\"", label).green()
+    );
+
+    println!();
+    println!(
+        "  {}",
+        format!(
+            "Commands now sign with this entry when --key and {} are unset.",
+            crypto::PRIVATE_KEY_ENV
+        )
+        .bright_black()
+    );
+    println!(
+        "  {}",
+        "Remove the raw key from your shell profile and scripts once you have backed up the keystore."
+            .yellow()
+    );
+
+    Ok(())
+}
+
+/// Encrypt `private_key` under `label` and confirm the stored entry.
+///
+/// The key must derive `expected` before anything is written; if the stored
+/// entry then fails the round-trip, it is removed again.
+pub async fn migrate(
+    keystore: &Keystore,
+    label: &str,
+    private_key: &str,
+    password: &str,
+    expected: Address,
+) -> Result<PathBuf> {
+    let wallet: LocalWallet = crypto::normalize_key(private_key)?
+        .parse()
+        .context("Invalid private key")?;
+    if wallet.address() != expected {
+        bail!(
+            "Key derives {:?}, expected {:?}; refusing to migrate",
+            wallet.address(),
+            expected
+        );
+    }
+
+    let path = keystore.save(label, &wallet, password)?;
+    if let Err(e) = confirm_roundtrip(keystore, label, password, expected).await {
+        let _ = std::fs::remove_file(&path);
+        return Err(e.context(format!(
+            "Keystore entry '{}' failed verification and was removed; the raw key is unchanged",
+            label
+        )));
+    }
+    Ok(path)
+}
+
+/// Decrypt `label`, sign a probe with it and check the signature recovers
+/// to `expected`
+pub async fn confirm_roundtrip(
+    keystore: &Keystore,
+    label: &str,
+    password: &str,
+    expected: Address,
+) -> Result<()> {
+    let wallet = keystore.load(label, password)?;
+    let key = hex::encode(wallet.signer().to_bytes());
+
+    let probe = serde_json::json!({ "type": "keystore-probe", "label": label });
+    let sig = crypto::sign_snapshot(&probe, &key).await?;
+    let signer: Address = crypto::recover_snapshot_signer(&probe, &sig)?
+        .parse()
+        .context("Recovered signer is not an address")?;
+
+    if signer != expected {
+        bail!(
+            "Keystore entry '{}' signs as {:?}, expected {:?}",
+            label,
+            signer,
+            expected
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, OTHER_ADDRESS, TEST_ADDRESS, TEST_KEY};
+
+    #[tokio::test]
+    async fn test_roundtrip_guard() {
+        let dir = test_support::temp_dir();
+        let keystore = Keystore::at(dir.path());
+        let expected: Address = TEST_ADDRESS.parse().unwrap();
+        let other: Address = OTHER_ADDRESS.parse().unwrap();
+
+        // A key that doesn't derive the expected address is never written
+        let err = migrate(&keystore, "provider", TEST_KEY, "hunter2", other)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("refusing to migrate"), "{}", err);
+        assert!(!keystore.contains("provider"));
+
+        let path = migrate(&keystore, "provider", TEST_KEY, "hunter2", expected)
+            .await
+            .unwrap();
+        assert!(path.exists());
+
+        // The guard itself: wrong password or wrong address both fail
+        assert!(
+            confirm_roundtrip(&keystore, "provider", "hunter2", expected)
+                .await
+                .is_ok()
+        );
+        assert!(confirm_roundtrip(&keystore, "provider", "wrong", expected)
+            .await
+            .is_err());
+        assert!(confirm_roundtrip(&keystore, "provider", "hunter2", other)
+            .await
+            .is_err());
+
+        // An existing entry is never overwritten
+        assert!(
+            migrate(&keystore, "provider", TEST_KEY, "hunter2", expected)
+                .await
+                .is_err()
+        );
+    }
+}
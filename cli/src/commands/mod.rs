@@ -5,13 +5,32 @@
 //! - If signing fails → nothing is published
 //! - If IPFS fails → nothing is published
 
+pub mod audit_state;
+pub mod balance;
 pub mod claim;
+pub mod claims;
+pub mod diff_epoch;
+pub mod doctor;
 pub mod epochs;
+pub mod heartbeat;
+pub mod import_epoch;
 pub mod init;
+pub mod keygen;
+pub mod ledger;
+pub mod migrate_keys;
+pub mod payout_manifest;
+pub mod proofs;
 pub mod prove;
+pub mod provider;
+pub mod publish;
+pub mod recover;
+pub mod reputation;
+pub mod resubmit;
 pub mod seal;
 pub mod status;
 pub mod submit;
 pub mod validate;
+pub mod verify_proof;
+pub mod verify_sig;
 pub mod watch;
 pub mod withdraw;
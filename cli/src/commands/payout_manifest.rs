@@ -0,0 +1,387 @@
+//! payout-manifest command - the transfers that pay out a sealed epoch
+//!
+//! Settlements name providers by ENS, but USDC goes to wallets. Only an
+//! epoch signed by the controller is paid out. Each provider is resolved
+//! through its verified genesis, or through `eth_rpc` when it has published
+//! none; amounts owed to the same wallet are summed, and the manifest is
+//! signed with the operator's key. Nothing is emitted unless every provider
+//! resolves.
+//!
+//! `--format disperse` writes the same transfers as a
+//! `token_address,receiver,amount` CSV for Safe's CSV Airdrop or
+//! Disperse.app, after checking the rows add up to the epoch's miner pool.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::commands::epochs::csv_row;
+use crate::config;
+use crate::crypto;
+use crate::ipfs;
+use crate::models::{to_microunits, PayoutEntry, PayoutManifest, Settlements};
+use crate::money;
+use crate::say;
+use crate::storage::Storage;
+use crate::ui;
+
+/// USDC on Ethereum mainnet, the default `--token-address`
+pub const USDC_MAINNET: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+/// What `payout-manifest` writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    /// The signed manifest
+    Json,
+    /// `token_address,receiver,amount` rows for batch transfer tools
+    Disperse,
+}
+
+impl std::str::FromStr for ManifestFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ManifestFormat::Json),
+            "disperse" => Ok(ManifestFormat::Disperse),
+            _ => Err(format!("Unknown format: {}. Use json or disperse", s)),
+        }
+    }
+}
+
+pub async fn execute(
+    epoch_id: String,
+    out: Option<String>,
+    format: String,
+    token_address: String,
+    key: Option<String>,
+    controller: Option<String>,
+    store: &dyn Storage,
+    _pool: &str,
+) -> Result<()> {
+    let format: ManifestFormat = format.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+    let controller = config::controller_address(controller)?;
+    let token_address = crypto::checksum_address(&token_address)
+        .context("Invalid --token-address")?;
+    let private_key = match format {
+        ManifestFormat::Json => Some(crypto::resolve_signer(key)?),
+        ManifestFormat::Disperse => None,
+    };
+
+    let pb = ui::spinner(format!("Fetching {}...", epoch_id));
+
+    let epoch = ipfs::fetch_verified_epoch(store, &epoch_id, &controller).await?;
+    let settlements = epoch
+        .settlements
+        .as_ref()
+        .with_context(|| format!("Epoch {} has no published settlements", epoch_id))?;
+
+    pb.set_message(format!(
+        "Resolving {} provider wallets...",
+        settlements.providers.len()
+    ));
+    let eth_rpc = config::load_config()?.eth_rpc;
+    let mut wallets = HashMap::new();
+    for provider in settlements.providers.keys() {
+        if let Some(wallet) = resolve_wallet(store, provider, eth_rpc.as_deref()).await? {
+            wallets.insert(provider.clone(), wallet);
+        }
+    }
+    pb.clear();
+
+    let timestamp = chrono::Utc::now().timestamp();
+    let mut manifest = build_manifest(&epoch_id, settlements, &wallets, timestamp)?;
+    let rendered = match &private_key {
+        Some(private_key) => {
+            manifest.sig = Some(crypto::sign_snapshot(&manifest, private_key).await?);
+            serde_json::to_string_pretty(&manifest)? + "\n"
+        }
+        None => {
+            let miner_pool = reconcile(&manifest, settlements)?;
+            // stderr, so the CSV on stdout stays importable
+            eprintln!(
+                "{} Rows sum to {} USDC = epoch miner_pool {} USDC",
+                "✓".green(),
+                money::format_usdc(manifest.total_microunits, 6),
+                money::format_usdc(miner_pool, 6)
+            );
+            render_disperse_csv(&manifest, &token_address)
+        }
+    };
+
+    let Some(out) = out else {
+        print!("{}", rendered);
+        return Ok(());
+    };
+    std::fs::write(&out, rendered).with_context(|| format!("Failed to write {}", out))?;
+
+    say!("{}", "Payout Manifest".cyan().bold());
+    say!();
+    for entry in &manifest.entries {
+        say!(
+            "  {} {} ({})",
+            entry.wallet.green(),
+            money::format_usdc_display(entry.amount_microunits),
+            entry.providers.join(", ")
+        );
+    }
+    say!();
+    println!(
+        "{} {} transfers, {} total, written to {}",
+        "✓".green(),
+        manifest.entries.len(),
+        money::format_usdc_display(manifest.total_microunits),
+        out
+    );
+
+    Ok(())
+}
+
+/// `provider`'s wallet: from its verified genesis, else, only when it has
+/// published no genesis at all, its ENS name through `eth_rpc`. `None` when
+/// neither exists; a genesis that is there but fails verification, or can't
+/// be read, is an error rather than a reason to look elsewhere.
+async fn resolve_wallet(
+    store: &dyn Storage,
+    provider: &str,
+    eth_rpc: Option<&str>,
+) -> Result<Option<String>> {
+    let published = store.path_cid(&ipfs::genesis_path(provider)).await?;
+    if published.is_some() {
+        let genesis = ipfs::fetch_verified_genesis(store, provider).await?;
+        return crypto::checksum_address(&genesis.wallet).map(Some);
+    }
+    let Some(eth_rpc) = eth_rpc else {
+        return Ok(None);
+    };
+    let address = match crypto::resolve_ens(provider, eth_rpc).await {
+        Ok(address) => address,
+        Err(e) => {
+            tracing::debug!("{}: {:#}", provider, e);
+            return Ok(None);
+        }
+    };
+    crypto::checksum_address(&format!("{:?}", address)).map(Some)
+}
+
+/// Check the manifest pays out exactly the epoch's miner pool, returning it
+/// in microunits
+pub fn reconcile(manifest: &PayoutManifest, settlements: &Settlements) -> Result<u64> {
+    let miner_pool = to_microunits(settlements.miner_pool);
+    if manifest.total_microunits != miner_pool {
+        bail!(
+            "Payout rows sum to {} USDC but the epoch's miner_pool is {} USDC; refusing to export",
+            money::format_usdc(manifest.total_microunits, 6),
+            money::format_usdc(miner_pool, 6)
+        );
+    }
+    Ok(miner_pool)
+}
+
+/// `token_address,receiver,amount` rows, amounts in whole USDC with six
+/// decimals, as Safe's CSV Airdrop and Disperse.app import them
+pub fn render_disperse_csv(manifest: &PayoutManifest, token_address: &str) -> String {
+    let mut out = csv_row(&["token_address", "receiver", "amount"]);
+    for entry in &manifest.entries {
+        out.push_str(&csv_row(&[
+            token_address,
+            &entry.wallet,
+            &money::format_usdc(entry.amount_microunits, 6),
+        ]));
+    }
+    out
+}
+
+/// The unsigned manifest for `settlements`, with provider wallets from
+/// `wallets`. Providers owed nothing (slashed to zero) are left out; any
+/// other provider missing from `wallets` is an error naming all of them.
+pub fn build_manifest(
+    epoch_id: &str,
+    settlements: &Settlements,
+    wallets: &HashMap<String, String>,
+    timestamp: i64,
+) -> Result<PayoutManifest> {
+    let owed: Vec<(&String, u64)> = settlements
+        .providers
+        .iter()
+        .map(|(provider, amount)| (provider, to_microunits(*amount)))
+        .filter(|(_, micro)| *micro > 0)
+        .collect();
+
+    let mut unresolved: Vec<&str> = owed
+        .iter()
+        .filter(|(provider, _)| !wallets.contains_key(*provider))
+        .map(|(provider, _)| provider.as_str())
+        .collect();
+    if !unresolved.is_empty() {
+        unresolved.sort();
+        bail!(
+            "No wallet for {} provider(s): {}. Nothing was emitted.",
+            unresolved.len(),
+            unresolved.join(", ")
+        );
+    }
+
+    let mut by_wallet: BTreeMap<&str, PayoutEntry> = BTreeMap::new();
+    for (provider, micro) in owed {
+        let wallet = wallets[provider].as_str();
+        let entry = by_wallet.entry(wallet).or_insert_with(|| PayoutEntry {
+            wallet: wallet.to_string(),
+            amount_microunits: 0,
+            providers: vec![],
+        });
+        entry.amount_microunits += micro;
+        entry.providers.push(provider.clone());
+    }
+    let entries: Vec<PayoutEntry> = by_wallet
+        .into_values()
+        .map(|mut entry| {
+            entry.providers.sort();
+            entry
+        })
+        .collect();
+
+    Ok(PayoutManifest {
+        snapshot_type: "payout_manifest".to_string(),
+        version: "1.0.0".to_string(),
+        epoch_id: epoch_id.to_string(),
+        total_microunits: entries.iter().map(|e| e.amount_microunits).sum(),
+        entries,
+        timestamp,
+        sig: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SettlementMicros;
+    use crate::test_support::{self, OTHER_KEY, TEST_KEY};
+
+    // Well-known Hardhat test accounts #0 and #1 (never hold real funds)
+    const WALLET_A: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    const WALLET_B: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+    fn settlements(providers: &[(&str, u64)]) -> Settlements {
+        SettlementMicros {
+            providers: providers
+                .iter()
+                .map(|(ens, micro)| (ens.to_string(), *micro))
+                .collect(),
+            ..Default::default()
+        }
+        .to_settlements(vec![])
+    }
+
+    #[test]
+    fn test_manifest_sums_per_wallet() {
+        let settlements = settlements(&[
+            ("a.eth", 75_000),
+            ("b.eth", 50_000),
+            ("a2.eth", 25_000),
+            ("slashed.eth", 0),
+        ]);
+        let wallets: HashMap<String, String> = [
+            ("a.eth", WALLET_A),
+            ("a2.eth", WALLET_A),
+            ("b.eth", WALLET_B),
+        ]
+        .iter()
+        .map(|(ens, wallet)| (ens.to_string(), wallet.to_string()))
+        .collect();
+
+        let manifest = build_manifest("epoch-001", &settlements, &wallets, 1704067200).unwrap();
+        assert_eq!(
+            manifest.entries,
+            [
+                PayoutEntry {
+                    wallet: WALLET_B.to_string(),
+                    amount_microunits: 50_000,
+                    providers: vec!["b.eth".to_string()],
+                },
+                PayoutEntry {
+                    wallet: WALLET_A.to_string(),
+                    amount_microunits: 100_000,
+                    providers: vec!["a.eth".to_string(), "a2.eth".to_string()],
+                },
+            ]
+        );
+        assert_eq!(manifest.total_microunits, 150_000);
+    }
+
+    #[test]
+    fn test_disperse_csv_reconciles_to_miner_pool() {
+        let settlements = settlements(&[("a.eth", 1_250_000), ("b.eth", 5)]);
+        let wallets: HashMap<String, String> = [("a.eth", WALLET_A), ("b.eth", WALLET_B)]
+            .iter()
+            .map(|(ens, wallet)| (ens.to_string(), wallet.to_string()))
+            .collect();
+        let manifest = build_manifest("epoch-001", &settlements, &wallets, 1704067200).unwrap();
+
+        let mut settled = settlements.clone();
+        settled.miner_pool = 1.250005;
+        assert_eq!(reconcile(&manifest, &settled).unwrap(), 1_250_005);
+        settled.miner_pool = 1.25;
+        assert!(reconcile(&manifest, &settled).is_err());
+
+        assert_eq!(
+            render_disperse_csv(&manifest, USDC_MAINNET),
+            format!(
+                "token_address,receiver,amount\r\n\
+                 {usdc},{b},0.000005\r\n\
+                 {usdc},{a},1.250000\r\n",
+                usdc = USDC_MAINNET,
+                a = WALLET_A,
+                b = WALLET_B
+            )
+        );
+    }
+
+    #[test]
+    fn test_manifest_refused_with_unresolved_providers() {
+        let settlements = settlements(&[("a.eth", 75_000), ("c.eth", 10), ("b.eth", 5)]);
+        let wallets: HashMap<String, String> =
+            [("a.eth".to_string(), WALLET_A.to_string())].into_iter().collect();
+
+        let err = build_manifest("epoch-001", &settlements, &wallets, 1704067200).unwrap_err();
+        assert!(
+            err.to_string().contains("2 provider(s): b.eth, c.eth"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wallet_falls_back_only_without_genesis() {
+        let (_root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        let genesis = |provider: &str| {
+            serde_json::json!({
+                "type": "provider-init",
+                "provider": provider,
+                "wallet": WALLET_A,
+                "gpus": ["RTX 5090"],
+                "models": ["queenbee-spine"],
+                "timestamp": 1704067200,
+                "nonce": "abcdef1234567890"
+            })
+        };
+        let signed = genesis("a.eth");
+        let signed = crypto::sign_json(&signed, TEST_KEY).await.unwrap();
+        ipfs::write_genesis(store, "a.eth", &signed).await.unwrap();
+        let forged = genesis("b.eth");
+        let forged = crypto::sign_json(&forged, OTHER_KEY).await.unwrap();
+        ipfs::write_genesis(store, "b.eth", &forged).await.unwrap();
+
+        assert_eq!(
+            resolve_wallet(store, "a.eth", None).await.unwrap(),
+            Some(WALLET_A.to_string())
+        );
+        // A forged genesis is an error, not a reason to try ENS
+        assert!(resolve_wallet(store, "b.eth", Some("http://127.0.0.1:9"))
+            .await
+            .is_err());
+        assert_eq!(resolve_wallet(store, "c.eth", None).await.unwrap(), None);
+    }
+}
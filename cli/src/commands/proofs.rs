@@ -0,0 +1,178 @@
+//! proofs command - list published proofs
+//!
+//! Reads every `ProofSnapshot` under `/swarmpool/proofs/`, optionally only
+//! those for one job, oldest first. For each job the proof that would win it
+//! if it settles SOLO is marked, by the same rule `seal` applies, so a miner
+//! can see whether they've been beaten: replays are dropped
+//! (`seal::drop_replays`), only completed proofs compete (`seal::is_paid`)
+//! and the first by `seal::solo_ranking` wins.
+
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::commands::seal::{self, EpochProof};
+use crate::ipfs;
+use crate::models::{self, ExecutionMode, ProofSnapshot};
+use crate::storage::Storage;
+
+/// One listed proof
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofRow {
+    pub cid: String,
+    /// Would win its job under SOLO
+    pub solo_winner: bool,
+    #[serde(flatten)]
+    pub proof: ProofSnapshot,
+}
+
+pub async fn execute(job: Option<String>, json: bool, store: &dyn Storage) -> Result<()> {
+    let rows = proof_rows(ipfs::proofs_with_cids(store, job.as_deref()).await?);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    println!("{}", "Proofs".cyan().bold());
+    if let Some(job_cid) = &job {
+        println!("  {} {}", "Job:".bright_black(), job_cid);
+    }
+    println!();
+
+    if rows.is_empty() {
+        println!("  {}", "No proofs found".bright_black());
+        println!();
+        return Ok(());
+    }
+
+    println!(
+        "  {:<20} {:<28} {:<10} {:<8} {:<15} {}",
+        "Proven".bright_black(),
+        "Provider".bright_black(),
+        "Confidence".bright_black(),
+        "Compute".bright_black(),
+        "Status".bright_black(),
+        "Job CID".bright_black()
+    );
+    println!("  {}", "━".repeat(60).bright_black());
+    for row in &rows {
+        let status = if row.solo_winner {
+            format!("{:<15}", "SOLO winner").green().to_string()
+        } else {
+            format!("{:<15}", row.proof.status)
+        };
+        println!(
+            "  {:<20} {:<28} {:<10.2} {:<8} {} {}",
+            models::format_timestamp(row.proof.timestamp),
+            row.proof.provider.cyan(),
+            row.proof.metrics.confidence,
+            format!("{:.1}s", row.proof.metrics.compute_seconds),
+            status,
+            row.proof.job_cid
+        );
+    }
+    println!();
+    println!("  {} {}", "Proofs:".bright_black(), rows.len());
+    println!();
+
+    Ok(())
+}
+
+/// Proofs oldest first, each marked with whether it would win its job under
+/// SOLO when `seal` settles it
+pub fn proof_rows(proofs: Vec<(String, ProofSnapshot)>) -> Vec<ProofRow> {
+    // Collected in listing order, as a seal collecting them now would, which
+    // decides only which copy of a replay is kept
+    let settled: Vec<EpochProof> = proofs
+        .iter()
+        .enumerate()
+        .map(|(i, (cid, p))| EpochProof {
+            observed: i as u64,
+            ..EpochProof::from_snapshot(cid, p, 0.0, ExecutionMode::Solo)
+        })
+        .collect();
+    let (settled, _) = seal::drop_replays(&settled);
+
+    let mut by_job: BTreeMap<&str, Vec<&EpochProof>> = BTreeMap::new();
+    for proof in settled.iter().filter(|p| seal::is_paid(p)) {
+        by_job.entry(proof.job_id.as_str()).or_default().push(proof);
+    }
+    let winners: HashSet<&str> = by_job
+        .values()
+        .filter_map(|job_proofs| seal::solo_ranking(job_proofs).first().copied())
+        .map(|p| p.proof_cid.as_str())
+        .collect();
+
+    let mut rows: Vec<ProofRow> = proofs
+        .iter()
+        .map(|(cid, proof)| ProofRow {
+            cid: cid.clone(),
+            solo_winner: winners.contains(cid.as_str()),
+            proof: proof.clone(),
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        a.proof
+            .timestamp
+            .cmp(&b.proof.timestamp)
+            .then_with(|| a.cid.cmp(&b.cid))
+    });
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto;
+    use serde_json::json;
+
+    fn proof(job_id: &str, provider: &str, status: &str, timestamp: i64) -> ProofSnapshot {
+        serde_json::from_value(json!({
+            "type": "proof",
+            "version": "1.0.0",
+            "proof_id": format!("proof-{}-{}", job_id, provider),
+            "job_id": job_id,
+            "job_cid": format!("Qm{}", job_id),
+            "status": status,
+            "output_cid": "QmOutput",
+            "metrics": {
+                "inference_seconds": 10.0,
+                "compute_seconds": 12.5,
+                "confidence": 0.91,
+                "model_version": "1.0.0"
+            },
+            "provider": provider,
+            "timestamp": timestamp,
+            "proof_hash": crypto::keccak256_hash(format!("{}:{}", job_id, provider).as_bytes())
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_earliest_completed_proof_marked_per_job() {
+        let rows = proof_rows(vec![
+            ("QmB".to_string(), proof("job-1", "beta.swarmbee.eth", "completed", 200)),
+            // Listed second, but earlier: wins
+            ("QmA".to_string(), proof("job-1", "alpha.swarmbee.eth", "completed", 150)),
+            // Earliest, but not completed: doesn't compete
+            ("QmL".to_string(), proof("job-1", "gamma.swarmbee.eth", "low_confidence", 100)),
+            ("QmC".to_string(), proof("job-2", "beta.swarmbee.eth", "completed", 300)),
+        ]);
+
+        let order: Vec<&str> = rows.iter().map(|r| r.cid.as_str()).collect();
+        assert_eq!(order, ["QmL", "QmA", "QmB", "QmC"]);
+        let winners: Vec<&str> = rows
+            .iter()
+            .filter(|r| r.solo_winner)
+            .map(|r| r.cid.as_str())
+            .collect();
+        assert_eq!(winners, ["QmA", "QmC"]);
+
+        let json = serde_json::to_value(&rows[1]).unwrap();
+        assert_eq!(json["cid"], "QmA");
+        assert_eq!(json["solo_winner"], true);
+        assert_eq!(json["metrics"]["compute_seconds"], "12.5");
+    }
+}
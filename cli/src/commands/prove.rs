@@ -2,14 +2,35 @@
 
 use anyhow::{Context, Result};
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
 use std::time::Duration;
+use tracing::{info_span, Instrument};
 
 use crate::config;
 use crate::crypto;
 use crate::ipfs;
-use crate::models::{JobSnapshot, ProofMetrics, ProofSnapshot};
+use crate::storage::Storage;
+use crate::models::{has_dicom_magic, JobSnapshot, ProofMetrics, ProofSnapshot};
+use crate::nonces;
+use crate::report::{self, ReportFormat, ReportInput};
+use crate::sandbox;
+use crate::say;
+use crate::schema::{self, SchemaType};
+use crate::ui;
+
+/// Proof status for a result below the job's `confidence_threshold`
+const LOW_CONFIDENCE: &str = "low_confidence";
+/// Proof status for a `--simulate` result; `seal` never pays one
+const SIMULATED: &str = "simulated";
+
+/// Where the Python inference runner is looked for, in order
+const RUNNER_PATHS: [&str; 3] = [
+    "./inference/runner.py",
+    "../cli/inference/runner.py",
+    "/usr/local/share/swarmpool/inference/runner.py",
+];
 
 /// Inference result from the Python runner
 #[derive(Debug, serde::Deserialize)]
@@ -21,96 +42,175 @@ struct InferenceResult {
     model_version: String,
     #[serde(default)]
     error: Option<String>,
+    /// Canned output from `--simulate`, never real inference
+    #[serde(skip)]
+    simulated: bool,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn execute(
     job_cid: String,
     claim_cid: Option<String>,
     provider_override: Option<String>,
     key: Option<String>,
+    client_wallet: Option<String>,
+    sandbox: bool,
+    allow_low_confidence: bool,
+    simulate: bool,
+    output_path: Option<String>,
+    store: &dyn Storage,
     pool: &str,
 ) -> Result<()> {
     // Load config
     let config = config::load_config()?;
 
+    // Resolve the sandbox up front so an unsupported host fails before any work
+    let sandbox_backend = if sandbox || config.sandbox {
+        Some(sandbox::detect()?)
+    } else {
+        None
+    };
+
     let provider_ens = provider_override
         .or(config.provider_ens)
         .context("Provider ENS required. Run 'swarm init' first or use --provider")?;
 
-    let private_key = key
-        .or_else(|| std::env::var("SWARM_PRIVATE_KEY").ok())
-        .context("Private key required. Use --key or set SWARM_PRIVATE_KEY")?;
+    let private_key = crypto::resolve_signer(key)?;
 
-    println!("{}", "Processing Job".cyan().bold());
-    println!();
-    println!("  {} {}", "Job CID:".bright_black(), job_cid.cyan());
+    say!("{}", "Processing Job".cyan().bold());
+    say!();
+    say!("  {} {}", "Job CID:".bright_black(), job_cid.cyan());
     if let Some(ref cid) = claim_cid {
-        println!("  {} {}", "Claim CID:".bright_black(), cid);
+        say!("  {} {}", "Claim CID:".bright_black(), cid);
     }
-    println!("  {} {}", "Provider:".bright_black(), provider_ens.green());
-    println!();
-
-    // Fetch job
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
+    say!("  {} {}", "Provider:".bright_black(), provider_ens.green());
+    if let Some(ref backend) = sandbox_backend {
+        say!("  {} {:?}", "Sandbox:".bright_black(), backend);
+    }
+    say!();
+
+    // Fetch job. The provider's time on the job starts here.
+    let job_start = std::time::Instant::now();
+    let pb = ui::spinner("Fetching job from IPFS...");
+
+    let job_json: serde_json::Value = store
+        .get_json(&job_cid)
+        .instrument(info_span!("fetch", cid = %job_cid))
+        .await?;
+
+    // Nobody gets GPU time for a job its client didn't sign. Without
+    // --client-wallet the client's ENS name is resolved when eth_rpc is set.
+    let (client_wallet, checked_against) = match (client_wallet, config.eth_rpc.as_deref()) {
+        (Some(wallet), _) => (Some(wallet), "matches --client-wallet"),
+        (None, Some(rpc_url)) => {
+            let client = job_json["client"].as_str().unwrap_or_default();
+            match crypto::resolve_ens(client, rpc_url).await {
+                Ok(address) => (Some(format!("{:?}", address)), "matches client ENS"),
+                Err(e) => {
+                    pb.done(format!("{} Client ENS unresolved", "✗".red()));
+                    return Err(e);
+                }
+            }
+        }
+        (None, None) => (None, "client wallet not checked"),
+    };
+    let signer = match check_job_signature(&job_json, client_wallet.as_deref()) {
+        Ok(signer) => signer,
+        Err(e) => {
+            pb.done(format!("{} Job signature rejected", "✗".red()));
+            return Err(e);
+        }
+    };
+    let job: JobSnapshot = serde_json::from_value(job_json)
+        .with_context(|| format!("Failed to parse {}", job_cid))?;
+    pb.done(format!("{} Job fetched: {}", "✓".green(), job.model));
+    say!(
+        "  {} {} ({})",
+        "Client sig:".bright_black(),
+        signer,
+        checked_against
     );
-    pb.set_message("Fetching job from IPFS...");
-    pb.enable_steady_tick(Duration::from_millis(100));
 
-    let job: JobSnapshot = ipfs::fetch_json(&job_cid).await?;
-    pb.finish_with_message(format!("{} Job fetched: {}", "✓".green(), job.model));
+    // Resolve the requested report format before spending GPU time
+    let report_format: ReportFormat = job
+        .params
+        .output_format
+        .parse()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
+    report_format.ensure_supported(&job.model)?;
 
     // Fetch input data
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Fetching input data...");
-    pb.enable_steady_tick(Duration::from_millis(100));
-
-    let _input_data: serde_json::Value = ipfs::fetch_json(&job.input_cid).await
-        .unwrap_or_else(|_| serde_json::json!({"status": "placeholder"}));
-    pb.finish_with_message(format!("{} Input fetched: {}", "✓".green(), job.input_cid));
+    let pb = ui::spinner("Fetching input data...");
+
+    // Private to this run and removed when it ends, however it ends
+    let staging = tempfile::Builder::new()
+        .prefix("swarm-input-")
+        .tempdir()
+        .context("Failed to create a staging directory")?;
+    let staging_dir = staging.path();
+    let input_path = match stage_input(store, &job, staging_dir)
+        .instrument(info_span!("fetch", job_id = %job.job_id, cid = %job.input_cid))
+        .await
+    {
+        Ok(path) => path,
+        Err(e) => {
+            pb.done(format!("{} Input fetch failed", "✗".red()));
+            return Err(e);
+        }
+    };
+    pb.done(format!("{} Input fetched: {}", "✓".green(), job.input_cid));
 
     // Run inference via Python runner
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message(format!("Running {} inference...", job.model));
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner(format!("Running {} inference...", job.model));
 
     let start = std::time::Instant::now();
 
     // Find inference runner script
-    let runner_paths = [
-        "./inference/runner.py",
-        "../cli/inference/runner.py",
-        "/usr/local/share/swarmpool/inference/runner.py",
-    ];
-
-    let runner_path = runner_paths
-        .iter()
-        .find(|p| std::path::Path::new(p).exists())
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| "inference/runner.py".to_string());
-
-    // Call inference runner
-    let inference_result = match Command::new("python3")
-        .arg(&runner_path)
-        .arg("--model")
-        .arg(&job.model)
-        .arg("--input")
-        .arg(&job.input_cid)
-        .output()
-    {
+    let runner_path = RUNNER_PATHS.iter().find(|p| Path::new(p).exists());
+
+    let spawned = match runner_path {
+        None => Err(format!(
+            "Inference runner not found (looked in {})",
+            RUNNER_PATHS.join(", ")
+        )),
+        Some(runner_path) => {
+            // Mounted read-only into the sandbox, so it must be absolute there
+            let runner = match sandbox_backend {
+                Some(_) => std::fs::canonicalize(runner_path)
+                    .unwrap_or_else(|_| PathBuf::from(runner_path)),
+                None => PathBuf::from(runner_path),
+            };
+            let runner_args = runner_args(&runner, &job, &input_path);
+
+            // Call inference runner, confined to the staging dir when sandboxed.
+            // Awaited rather than blocking so `watch --auto-prove` can overlap jobs.
+            let command = match &sandbox_backend {
+                Some(backend) => {
+                    let argv =
+                        sandbox::wrap(backend, staging_dir, &runner, "python3", &runner_args);
+                    sandbox::command(&argv, staging_dir)
+                }
+                None => {
+                    let mut command = Command::new("python3");
+                    command.args(&runner_args);
+                    command
+                }
+            };
+            match tokio::process::Command::from(command)
+                .output()
+                .instrument(info_span!("compute", job_id = %job.job_id, model = %job.model))
+                .await
+            {
+                Err(e) if sandbox_backend.is_some() => {
+                    pb.done(format!("{} Sandbox failed to start", "✗".red()));
+                    return Err(e).context("Failed to launch sandboxed runner");
+                }
+                spawned => spawned.map_err(|e| format!("Failed to launch inference runner: {}", e)),
+            }
+        }
+    };
+
+    let inference_result = match spawned {
         Ok(output) => {
             if output.status.success() {
                 let stdout = String::from_utf8_lossy(&output.stdout);
@@ -122,6 +222,7 @@ pub async fn execute(
                         inference_seconds: start.elapsed().as_secs_f64(),
                         model_version: format!("{}-v1.0", job.model),
                         error: Some(format!("Parse error: {}", e)),
+                        simulated: false,
                     })
             } else {
                 let stderr = String::from_utf8_lossy(&output.stderr);
@@ -132,36 +233,31 @@ pub async fn execute(
                     inference_seconds: start.elapsed().as_secs_f64(),
                     model_version: format!("{}-v1.0", job.model),
                     error: Some(format!("Runner failed: {}", stderr)),
+                    simulated: false,
                 }
             }
         }
-        Err(e) => {
-            // Fallback to simulated inference if runner not available
-            tracing::warn!("Inference runner not found, using simulation: {}", e);
-            tokio::time::sleep(Duration::from_secs(2)).await;
-            InferenceResult {
-                status: "completed".to_string(),
-                result: Some(serde_json::json!({
-                    "classification": "L4-L5 moderate stenosis",
-                    "confidence": 0.847,
-                    "findings": [
-                        {"level": "L4-L5", "grade": "moderate", "confidence": 0.89},
-                        {"level": "L5-S1", "grade": "mild", "confidence": 0.72}
-                    ]
-                })),
-                confidence: 0.847,
-                inference_seconds: start.elapsed().as_secs_f64(),
-                model_version: format!("{}-v1.0", job.model),
-                error: None,
+        // No runner, no proof, unless a simulation was asked for
+        Err(reason) => match simulate_or_fail(reason, &job.model, simulate) {
+            Ok(mut result) => {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                result.inference_seconds = start.elapsed().as_secs_f64();
+                result
             }
-        }
+            Err(e) => {
+                pb.done(format!("{} Inference runner unavailable", "✗".red()));
+                return Err(e);
+            }
+        },
     };
 
+    drop(staging);
+
     let inference_time = inference_result.inference_seconds;
     let confidence = inference_result.confidence;
 
     if inference_result.status == "error" {
-        pb.finish_with_message(format!(
+        pb.done(format!(
             "{} Inference failed: {}",
             "✗".red(),
             inference_result.error.unwrap_or_default()
@@ -169,36 +265,97 @@ pub async fn execute(
         anyhow::bail!("Inference failed");
     }
 
-    pb.finish_with_message(format!(
+    pb.done(format!(
         "{} Inference complete: {:.1}s, {:.0}% confidence",
         "✓".green(),
         inference_time,
         confidence * 100.0
     ));
+    if inference_result.simulated {
+        say!(
+            "  {} Runner unavailable; output is SIMULATED and the proof marked {}",
+            "⚠".yellow(),
+            SIMULATED
+        );
+    }
+
+    // A result below the client's threshold isn't what they are paying for,
+    // and a simulated one is never paid at all
+    let status = if inference_result.simulated {
+        SIMULATED
+    } else {
+        proof_status(
+            confidence,
+            job.params.confidence_threshold,
+            allow_low_confidence,
+        )?
+    };
+    if status == LOW_CONFIDENCE {
+        println!(
+            "  {} {:.0}% is below the job's {:.0}% threshold; proof marked {}",
+            "⚠".yellow(),
+            confidence * 100.0,
+            job.params.confidence_threshold * 100.0,
+            LOW_CONFIDENCE
+        );
+    }
 
     // Create output from inference result
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Uploading output...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Uploading output...");
 
-    let output = serde_json::json!({
+    let mut output = serde_json::json!({
         "job_id": job.job_id,
         "model": job.model,
         "result": inference_result.result,
         "model_version": inference_result.model_version,
         "inference_seconds": inference_time
     });
+    if inference_result.simulated {
+        output["simulated"] = serde_json::json!(true);
+    }
 
-    let output_cid = ipfs::upload_json(&output).await?;
-    pb.finish_with_message(format!("{} Output: {}", "✓".green(), output_cid.cyan()));
+    let output_cid = store.put(&output).await?;
+    pb.done(format!("{} Output: {}", "✓".green(), output_cid.cyan()));
 
-    // Create proof
+    // Render a report in the job's requested output format. A JSON job
+    // wants the output object itself, so there is no separate report. The
+    // report is a convenience: the proof publishes without one if it fails.
     let timestamp = chrono::Utc::now().timestamp();
+    let model_version = format!("{}-v1.0", job.model);
+
+    let report_cid = if report_format == ReportFormat::Json {
+        None
+    } else {
+        let pb = ui::spinner(format!("Rendering {} report...", report_format));
+
+        let input = ReportInput {
+            job_id: &job.job_id,
+            model: &job.model,
+            model_version: &model_version,
+            provider: &provider_ens,
+            result: inference_result.result.as_ref(),
+            confidence,
+            inference_seconds: inference_time,
+            timestamp,
+        };
+        match publish_report(store, report_format, &input).await {
+            Ok(report_cid) => {
+                pb.done(format!("{} Report: {}", "✓".green(), report_cid.cyan()));
+                Some(report_cid)
+            }
+            Err(e) => {
+                tracing::warn!("{} report for {} failed: {:#}", report_format, job.job_id, e);
+                pb.done(format!(
+                    "{} Report skipped ({}); publishing without report_cid",
+                    "⚠".yellow(),
+                    e
+                ));
+                None
+            }
+        }
+    };
+
+    // Create proof
 
     let proof_data = format!(
         "{}:{}:{}:{}:{}",
@@ -206,8 +363,11 @@ pub async fn execute(
     );
     let proof_hash = crypto::keccak256_hash(proof_data.as_bytes());
 
-    // compute_seconds = total time spent on this job (for PPL proportional payout)
-    let compute_seconds = inference_time;
+    let compute_seconds = compute_seconds(
+        job_start.elapsed().as_secs_f64(),
+        inference_time,
+        config.settlement.compute_overhead_cap,
+    );
     let proof_id = format!("proof-{}-{}", job.job_id, &crypto::random_hex(4));
 
     let mut proof = ProofSnapshot {
@@ -216,58 +376,54 @@ pub async fn execute(
         proof_id: proof_id.clone(),
         job_id: job.job_id.clone(),
         job_cid: job_cid.clone(),
-        status: "completed".to_string(),
+        claim_cid: claim_cid.clone(),
+        status: status.to_string(),
         output_cid: output_cid.clone(),
-        report_cid: None,
+        report_cid: report_cid.clone(),
         metrics: ProofMetrics {
             inference_seconds: inference_time,
-            compute_seconds,  // For PPL mode proportional rewards
+            compute_seconds,
             confidence,
-            model_version: format!("{}-v1.0", job.model),
+            model_version,
         },
         provider: provider_ens.clone(),
         timestamp,
         proof_hash,
+        nonce: Some(crypto::random_hex(16)),
         sig: None,
     };
 
     // Sign proof
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Signing proof...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Signing proof...");
 
-    proof.sig = Some(crypto::sign_snapshot(&proof, &private_key).await?);
-    pb.finish_with_message(format!("{} Proof signed", "✓".green()));
-
-    // Write proof to canonical IPFS path: /swarmpool/proofs/{job_id}.json
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
+    proof.sig = Some(
+        crypto::sign_snapshot(&proof, &private_key)
+            .instrument(info_span!("sign", job_id = %job.job_id))
+            .await?,
     );
-    pb.set_message("Publishing proof to IPFS...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    pb.done(format!("{} Proof signed", "✓".green()));
+    schema::check_before_publish(&proof, SchemaType::Proof)?;
+    if let Some(nonce) = &proof.nonce {
+        nonces::check(&provider_ens, nonce)?;
+    }
 
-    let proof_cid = ipfs::write_proof(&proof_id, &proof).await?;
-    pb.finish_with_message(format!("{} Proof: {}", "✓".green(), proof_cid.cyan()));
+    // Write proof to canonical IPFS path: /swarmpool/proofs/{job_id}.json
+    let pb = ui::spinner("Publishing proof to IPFS...");
+
+    let publish = info_span!("publish", job_id = %job.job_id, cid = tracing::field::Empty);
+    let proof_cid = ipfs::write_proof(store, &proof_id, &proof)
+        .instrument(publish.clone())
+        .await?;
+    if let Some(nonce) = &proof.nonce {
+        nonces::record_published(store, &provider_ens, nonce, timestamp);
+    }
+    publish.record("cid", proof_cid.as_str());
+    pb.done(format!("{} Proof: {}", "✓".green(), proof_cid.cyan()));
 
     // Announce proof to pool
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Announcing proof to pool...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Announcing proof to pool...");
 
-    ipfs::pubsub_publish(
+    store.announce(
         &format!("/{}/proofs", pool),
         &serde_json::json!({
             "proof_cid": proof_cid,
@@ -279,25 +435,540 @@ pub async fn execute(
             "timestamp": timestamp
         }),
     )
+    .instrument(publish)
     .await?;
 
-    pb.finish_with_message(format!("{} Proof announced", "✓".green()));
+    pb.done(format!("{} Proof announced", "✓".green()));
+
+    // Only a published proof leaves anything on disk. The proof is out by
+    // now, so failing to save a copy is only worth a warning.
+    let saved = match output_path {
+        Some(path) => match save_output(Path::new(&path), &job.job_id, &output, &proof) {
+            Ok(files) => Some(files),
+            Err(e) => {
+                tracing::warn!("Saving output for {} failed: {:#}", job.job_id, e);
+                say!("  {} Output not saved: {:#}", "⚠".yellow(), e);
+                None
+            }
+        },
+        None => None,
+    };
 
     // Summary
-    println!();
-    println!("{}", "Proof Submitted".green().bold());
-    println!();
+    say!();
+    say!("{}", "Proof Submitted".green().bold());
+    say!();
     println!("  {} {}", "Job ID:".bright_black(), job.job_id.cyan());
     println!("  {} {}", "Proof CID:".bright_black(), proof_cid.cyan());
     println!("  {} {}", "Output CID:".bright_black(), output_cid);
-    println!("  {} {:.0}%", "Confidence:".bright_black(), confidence * 100.0);
-    println!("  {} {:.2}s", "Inference:".bright_black(), inference_time);
-    println!();
-    println!(
+    match &report_cid {
+        Some(cid) => println!("  {} {} ({})", "Report CID:".bright_black(), cid, report_format),
+        None => println!("  {} none ({} output)", "Report CID:".bright_black(), report_format),
+    }
+    say!("  {} {:.0}%", "Confidence:".bright_black(), confidence * 100.0);
+    say!("  {} {:.2}s", "Inference:".bright_black(), inference_time);
+    if let Some(paths) = &saved {
+        for path in paths {
+            say!("  {} {}", "Saved:".bright_black(), path.display());
+        }
+    }
+    say!();
+    say!(
         "  {}",
         format!("Earnings: +${:.3} (pending epoch seal)", 0.075).yellow()
     );
-    println!();
+    say!();
 
     Ok(())
 }
+
+/// Fetch the job's input as raw bytes into `dir` and return the path for the
+/// runner: the file itself, or `dir` for a series.
+///
+/// Inputs are never parsed here, so binary DICOM passes through untouched
+/// and a failed fetch fails the job rather than running on a placeholder.
+/// A file input must hash to `input_cid`, or to `input_sha256` when it's too
+/// large to rehash (see `ipfs::fetch_verified`). When the job carries
+/// `input_sha256`, the fetched bytes must match it before anything is
+/// written, and a DICOM `input_type` must fetch DICOM.
+async fn stage_input(store: &dyn Storage, job: &JobSnapshot, dir: &Path) -> Result<PathBuf> {
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    // Series inputs are directories: fetch every file and check the count
+    let Some(expected) = job.input_files else {
+        let bytes = ipfs::fetch_verified(store, &job.input_cid, job.input_sha256.as_deref())
+            .await
+            .with_context(|| format!("Failed to fetch input {}", job.input_cid))?;
+        check_input_sha256(job, crypto::file_sha256(&bytes))?;
+        check_dicom_input(job, &job.input_cid, &bytes)?;
+        let path = dir.join("input");
+        std::fs::write(&path, bytes)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        return Ok(path);
+    };
+
+    let files = store
+        .get_dir(&job.input_cid)
+        .await
+        .with_context(|| format!("Failed to fetch series {}", job.input_cid))?;
+    if files.len() as u64 != expected {
+        anyhow::bail!(
+            "Series {} has {} files, job expects {}",
+            job.input_cid,
+            files.len(),
+            expected
+        );
+    }
+    check_input_sha256(job, crypto::series_sha256(&files))?;
+    for (name, bytes) in &files {
+        check_dicom_input(job, name, bytes)?;
+    }
+
+    for (name, bytes) in files {
+        let relative = Path::new(&name);
+        if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            anyhow::bail!("Invalid file name in series: {}", name);
+        }
+        let path = dir.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, bytes)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(dir.to_path_buf())
+}
+
+/// Render `input` as a `format` report and upload it, returning its CID
+async fn publish_report(
+    store: &dyn Storage,
+    format: ReportFormat,
+    input: &ReportInput<'_>,
+) -> Result<String> {
+    let bytes = report::render(format, input)?;
+    store.put_bytes(bytes, format.file_name()).await
+}
+
+/// Arguments for `python3`, following the runner's contract:
+///
+/// `runner.py --model <name> --input-file <path> --params <json>`
+///
+/// `<path>` is the staged input (a file, or a directory for a series) and
+/// `<json>` the job's `JobParams`. The runner prints an `InferenceResult`
+/// as JSON on stdout and exits non-zero on failure.
+fn runner_args(runner: &Path, job: &JobSnapshot, input_path: &Path) -> Vec<OsString> {
+    vec![
+        runner.into(),
+        "--model".into(),
+        job.model.clone().into(),
+        "--input-file".into(),
+        input_path.into(),
+        "--params".into(),
+        serde_json::json!(job.params).to_string().into(),
+    ]
+}
+
+/// What to run when the inference runner is unavailable for `reason`: an
+/// error, or with `simulate` a canned result marked `simulated`. A proof is
+/// never published over a fabricated result unless it was asked for, and
+/// then only with status `simulated`.
+fn simulate_or_fail(reason: String, model: &str, simulate: bool) -> Result<InferenceResult> {
+    if !simulate {
+        anyhow::bail!("{}; nothing published (use --simulate for a test run)", reason);
+    }
+    tracing::warn!("{}; publishing a SIMULATED result", reason);
+    Ok(InferenceResult {
+        status: "completed".to_string(),
+        result: Some(serde_json::json!({
+            "classification": "L4-L5 moderate stenosis",
+            "confidence": 0.847,
+            "findings": [
+                {"level": "L4-L5", "grade": "moderate", "confidence": 0.89},
+                {"level": "L5-S1", "grade": "mild", "confidence": 0.72}
+            ]
+        })),
+        confidence: 0.847,
+        inference_seconds: 0.0,
+        model_version: format!("{}-v1.0", model),
+        error: None,
+        simulated: true,
+    })
+}
+
+/// Write `output` (the object behind `output_cid`) to `path`. When `path` is
+/// a directory, it is saved there as `<job_id>.output.json` and the signed
+/// `proof` as `<job_id>.proof.json`; a `job_id` that isn't a plain file name
+/// (the client chose it) is refused rather than written outside `path`.
+/// Returns the files written.
+fn save_output(
+    path: &Path,
+    job_id: &str,
+    output: &serde_json::Value,
+    proof: &ProofSnapshot,
+) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        let mut components = Path::new(job_id).components();
+        let plain = matches!(components.next(), Some(Component::Normal(_)))
+            && components.next().is_none()
+            && !job_id.contains(['/', '\\']);
+        if !plain {
+            anyhow::bail!("Job id {:?} is not a plain file name", job_id);
+        }
+        files.push((
+            path.join(format!("{}.output.json", job_id)),
+            serde_json::to_string_pretty(output)?,
+        ));
+        files.push((
+            path.join(format!("{}.proof.json", job_id)),
+            serde_json::to_string_pretty(proof)?,
+        ));
+    } else {
+        files.push((path.to_path_buf(), serde_json::to_string_pretty(output)?));
+    }
+
+    for (file, json) in &files {
+        std::fs::write(file, format!("{}\n", json))
+            .with_context(|| format!("Failed to write {}", file.display()))?;
+    }
+    Ok(files.into_iter().map(|(file, _)| file).collect())
+}
+
+/// `compute_seconds` from the job's wall-clock so far (fetch, inference,
+/// uploads). Time outside inference is credited up to `overhead_cap` times
+/// the inference time, so a slow link doesn't earn a bigger PPL share.
+fn compute_seconds(wall_clock: f64, inference_seconds: f64, overhead_cap: f64) -> f64 {
+    let overhead = (wall_clock - inference_seconds).max(0.0);
+    inference_seconds + overhead.min(inference_seconds * overhead_cap.max(0.0))
+}
+
+/// Proof status for a result with `confidence` on a job demanding
+/// `threshold`. Below it, the proof is refused unless `allow_low` (then
+/// published as `low_confidence`).
+fn proof_status(confidence: f64, threshold: f64, allow_low: bool) -> Result<&'static str> {
+    if confidence >= threshold {
+        return Ok("completed");
+    }
+    if !allow_low {
+        anyhow::bail!(
+            "Inference confidence {:.3} is below the job's threshold {:.3}. \
+             Use --allow-low-confidence to submit it as {}",
+            confidence,
+            threshold,
+            LOW_CONFIDENCE
+        );
+    }
+    Ok(LOW_CONFIDENCE)
+}
+
+/// Recover the signer of a fetched job, checked against the client's wallet
+/// when one is given
+///
+/// Without `client_wallet` (no `--client-wallet` and no `eth_rpc` to resolve
+/// the client's ENS name) this only proves the signature is well-formed and
+/// recoverable.
+fn check_job_signature(job: &serde_json::Value, client_wallet: Option<&str>) -> Result<String> {
+    let client = job["client"].as_str().unwrap_or("unknown client");
+    crypto::verify_snapshot_json(job, client_wallet).with_context(|| {
+        format!(
+            "Job signature check failed for {}; refusing to run inference",
+            client
+        )
+    })
+}
+
+/// Fail closed when the fetched input doesn't hash to the job's `input_sha256`
+fn check_input_sha256(job: &JobSnapshot, actual: String) -> Result<()> {
+    match &job.input_sha256 {
+        Some(expected) if !expected.eq_ignore_ascii_case(&actual) => anyhow::bail!(
+            "Input {} has sha256 {}, job expects {}; refusing to run inference",
+            job.input_cid,
+            actual,
+            expected
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Fail closed when a job declared as DICOM fetched something else
+fn check_dicom_input(job: &JobSnapshot, name: &str, bytes: &[u8]) -> Result<()> {
+    match job.input_type {
+        Some(input_type) if input_type.is_dicom() && !has_dicom_magic(bytes) => anyhow::bail!(
+            "Input {} is not DICOM but the job declares {:?}; refusing to run inference",
+            name,
+            input_type
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::submit::build_job;
+    use crate::models::{InputType, JobParams};
+    use crate::storage::FsStorage;
+    use crate::test_support::{self, OTHER_ADDRESS, TEST_ADDRESS, TEST_KEY};
+
+    #[tokio::test]
+    async fn test_binary_input_staged_verbatim() {
+        let root = test_support::temp_dir();
+        let store = FsStorage::new(root.path().join("store"));
+        let store: &dyn Storage = &store;
+
+        // DICOM preamble: 128 zero bytes then "DICM" - not JSON
+        let mut dicom = vec![0u8; 128];
+        dicom.extend_from_slice(b"DICM\xff\xfe");
+        let cid = store.put_bytes(dicom.clone(), "scan.dcm").await.unwrap();
+
+        let mut job = build_job("job-1".to_string(), "queenbee-spine", cid, "a.eth", "0.10", 0);
+        job.input_type = Some(InputType::Dicom);
+        let path = stage_input(store, &job, &root.path().join("stage")).await.unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), dicom);
+
+        // Declared DICOM but fetched something else
+        let cid = store.put_bytes(b"not a scan".to_vec(), "scan.dcm").await.unwrap();
+        let mut fake = build_job("job-3".to_string(), "queenbee-spine", cid, "a.eth", "0.10", 0);
+        fake.input_type = Some(InputType::Dicom);
+        assert!(stage_input(store, &fake, &root.path().join("stage")).await.is_err());
+
+        // A missing input fails closed
+        let missing = build_job(
+            "job-2".to_string(),
+            "queenbee-spine",
+            "0xdead".to_string(),
+            "a.eth",
+            "0.10",
+            0,
+        );
+        assert!(stage_input(store, &missing, &root.path().join("stage")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_input_sha256_mismatch_rejected() {
+        let root = test_support::temp_dir();
+        let store = FsStorage::new(root.path().join("store"));
+        let store: &dyn Storage = &store;
+        let stage = root.path().join("stage");
+
+        let cid = store.put_bytes(b"scan".to_vec(), "scan.dcm").await.unwrap();
+        let mut job = build_job("job-1".to_string(), "queenbee-spine", cid, "a.eth", "0.10", 0);
+        job.input_sha256 = Some(crypto::file_sha256(b"scan"));
+        assert!(stage_input(store, &job, &stage).await.is_ok());
+
+        // Swapped input: nothing is staged for the runner
+        job.input_sha256 = Some(crypto::file_sha256(b"other scan"));
+        let err = stage_input(store, &job, &stage).await.unwrap_err();
+        assert!(err.to_string().contains("refusing to run inference"), "{}", err);
+        assert!(!stage.join("input").exists());
+
+        // Series: a file renamed inside the directory changes the digest
+        let files = vec![
+            ("a.dcm".to_string(), b"one".to_vec()),
+            ("b.dcm".to_string(), b"two".to_vec()),
+        ];
+        let renamed = vec![
+            ("a.dcm".to_string(), b"one".to_vec()),
+            ("c.dcm".to_string(), b"two".to_vec()),
+        ];
+        let cid = store.put_dir(renamed).await.unwrap();
+        let mut job = build_job("job-2".to_string(), "queenbee-spine", cid, "a.eth", "0.10", 0);
+        job.input_files = Some(2);
+        job.input_sha256 = Some(crypto::series_sha256(&files));
+        assert!(stage_input(store, &job, &stage).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tampered_job_signature_rejected() {
+        let mut job = build_job(
+            "job-1".to_string(),
+            "queenbee-spine",
+            "bafyinput".to_string(),
+            "a.eth",
+            "0.10",
+            0,
+        );
+        job.sig = Some(crypto::sign_snapshot(&job, TEST_KEY).await.unwrap());
+        let signed = serde_json::to_value(&job).unwrap();
+
+        let signer = check_job_signature(&signed, Some(TEST_ADDRESS)).unwrap();
+        assert!(signer.eq_ignore_ascii_case(TEST_ADDRESS));
+        assert!(check_job_signature(&signed, None).is_ok());
+        assert!(check_job_signature(&signed, Some(OTHER_ADDRESS)).is_err());
+
+        // A field changed after signing no longer recovers to the client
+        let mut tampered = signed.clone();
+        tampered["payment"]["amount"] = serde_json::json!("100.00");
+        let err = check_job_signature(&tampered, Some(TEST_ADDRESS)).unwrap_err();
+        assert!(format!("{:#}", err).contains("refusing to run inference"), "{:#}", err);
+
+        // Without a wallet, a malformed or missing sig still fails
+        let mut malformed = signed.clone();
+        malformed["sig"] = serde_json::json!("0x1234");
+        assert!(check_job_signature(&malformed, None).is_err());
+        let mut unsigned = signed;
+        unsigned.as_object_mut().unwrap().remove("sig");
+        assert!(check_job_signature(&unsigned, None).is_err());
+    }
+
+    #[test]
+    fn test_output_saved_with_proof_in_directory() {
+        let dir = test_support::temp_dir();
+
+        let output = serde_json::json!({
+            "job_id": "job-1",
+            "model": "queenbee-spine",
+            "result": {"classification": "L4-L5 moderate stenosis"},
+        });
+        let proof: ProofSnapshot = serde_json::from_value(serde_json::json!({
+            "type": "proof",
+            "version": "1.0.0",
+            "proof_id": "proof-job-1-abcd",
+            "job_id": "job-1",
+            "job_cid": "bafyjob",
+            "status": "completed",
+            "output_cid": "bafyoutput",
+            "metrics": {
+                "inference_seconds": 2.5,
+                "compute_seconds": 2.5,
+                "confidence": 0.9,
+                "model_version": "queenbee-spine-v1.0"
+            },
+            "provider": "a.eth",
+            "timestamp": 1704067200,
+            "proof_hash": "0x00",
+            "sig": "0x1234"
+        }))
+        .unwrap();
+
+        // A file path gets the output alone
+        let file = dir.path().join("result.json");
+        assert_eq!(save_output(&file, "job-1", &output, &proof).unwrap(), [file.clone()]);
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(saved, output);
+
+        // A directory also gets the signed proof
+        let written = save_output(dir.path(), "job-1", &output, &proof).unwrap();
+        assert_eq!(
+            written,
+            [
+                dir.path().join("job-1.output.json"),
+                dir.path().join("job-1.proof.json")
+            ]
+        );
+        let saved: ProofSnapshot =
+            serde_json::from_str(&std::fs::read_to_string(&written[1]).unwrap()).unwrap();
+        assert_eq!(saved.sig.as_deref(), Some("0x1234"));
+
+        // A job id can't steer the files out of the directory
+        let inner = dir.path().join("results");
+        std::fs::create_dir(&inner).unwrap();
+        for job_id in ["../escape", "..", "a/b", "a\\b", "/tmp/x", ""] {
+            let err = save_output(&inner, job_id, &output, &proof).unwrap_err();
+            assert!(
+                err.to_string().contains("not a plain file name"),
+                "{}",
+                job_id
+            );
+        }
+        assert!(!dir.path().join("escape.output.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_report_uploaded_or_skipped() {
+        let (_root, store) = test_support::fs_store();
+        let result = serde_json::json!({"classification": "L4-L5 moderate stenosis"});
+        let input = ReportInput {
+            job_id: "job-1",
+            model: "queenbee-spine",
+            model_version: "queenbee-spine-v1.0",
+            provider: "a.eth",
+            result: Some(&result),
+            confidence: 0.9,
+            inference_seconds: 2.5,
+            timestamp: 1704067200,
+        };
+
+        let cid = publish_report(&store, ReportFormat::Pdf, &input).await.unwrap();
+        let pdf = store.get_bytes(&cid).await.unwrap();
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+
+        // A renderer failure is an error for the caller to downgrade
+        assert!(publish_report(&store, ReportFormat::DicomSr, &input).await.is_err());
+    }
+
+    #[test]
+    fn test_runner_args_carry_input_file_and_params() {
+        let mut job = build_job(
+            "job-1".to_string(),
+            "queenbee-spine",
+            "bafyinput".to_string(),
+            "a.eth",
+            "0.10",
+            0,
+        );
+        job.params.confidence_threshold = 0.9;
+        job.params.output_format = "pdf".to_string();
+
+        let staged = Path::new("/tmp/swarm-input-job-1/input");
+        let args = runner_args(Path::new("inference/runner.py"), &job, staged);
+        let args: Vec<&str> = args.iter().map(|a| a.to_str().unwrap()).collect();
+        assert_eq!(
+            args[..6],
+            [
+                "inference/runner.py",
+                "--model",
+                "queenbee-spine",
+                "--input-file",
+                "/tmp/swarm-input-job-1/input",
+                "--params",
+            ]
+        );
+
+        // The runner gets the client's params, not a CID it can't read
+        let params: JobParams = serde_json::from_str(args[6]).unwrap();
+        assert_eq!(params.confidence_threshold, 0.9);
+        assert_eq!(params.output_format, "pdf");
+        assert!(!args.contains(&"bafyinput"));
+    }
+
+    #[test]
+    fn test_missing_runner_fails_without_simulate() {
+        let reason = || "Inference runner not found".to_string();
+
+        // Nothing to sign, so no proof is produced
+        let err = simulate_or_fail(reason(), "queenbee-spine", false).unwrap_err();
+        assert!(err.to_string().contains("nothing published"), "{}", err);
+
+        let simulated = simulate_or_fail(reason(), "queenbee-spine", true).unwrap();
+        assert!(simulated.simulated);
+        assert_eq!(simulated.status, "completed");
+        // The proof over it is published as simulated, which the schema knows
+        let status = serde_json::json!(SIMULATED);
+        assert!(schema::validate_field(SchemaType::Proof, "status", &status).is_ok());
+    }
+
+    #[test]
+    fn test_confidence_threshold_enforced() {
+        assert_eq!(proof_status(0.847, 0.6, false).unwrap(), "completed");
+        assert_eq!(proof_status(0.6, 0.6, false).unwrap(), "completed");
+
+        let err = proof_status(0.3, 0.6, false).unwrap_err();
+        assert!(err.to_string().contains("below the job's threshold"), "{}", err);
+        assert_eq!(proof_status(0.3, 0.6, true).unwrap(), LOW_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_compute_seconds_caps_network_time() {
+        // 2s fetch/upload around 10s of inference: all of it counts
+        assert_eq!(compute_seconds(12.0, 10.0, 0.5), 12.0);
+        // A slow link adds no more than half the inference time
+        assert_eq!(compute_seconds(60.0, 10.0, 0.5), 15.0);
+        assert_eq!(compute_seconds(60.0, 10.0, 0.0), 10.0);
+        // The runner's own timing can exceed the measured wall-clock
+        assert_eq!(compute_seconds(9.5, 10.0, 0.5), 10.0);
+    }
+}
@@ -0,0 +1,228 @@
+//! provider command - list registered providers
+//!
+//! Every `swarm init` writes a signed `ProviderRegistration` under
+//! `/swarmpool/genesis/`, so that directory, not the pool state's
+//! `active_providers`, is the full set of registered providers. Each listed
+//! document is checked itself: it must be signed by the wallet it names and
+//! its file must be its provider's canonical genesis path.
+
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::ipfs;
+use crate::models::ProviderRegistration;
+use crate::storage::Storage;
+
+/// A registration from the genesis directory and whether it checked out
+#[derive(Debug, Serialize)]
+pub struct ProviderEntry {
+    #[serde(flatten)]
+    pub registration: ProviderRegistration,
+    pub verified: bool,
+    /// Why verification failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem: Option<String>,
+}
+
+pub async fn list(model: Option<String>, json: bool, store: &dyn Storage) -> Result<()> {
+    let providers = list_providers(store, model.as_deref()).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&providers)?);
+        return Ok(());
+    }
+
+    println!("{}", "Providers".cyan().bold());
+    if let Some(model) = &model {
+        println!("  {} {}", "Model:".bright_black(), model);
+    }
+    println!();
+
+    if providers.is_empty() {
+        println!("  {}", "No providers found".bright_black());
+        println!();
+        return Ok(());
+    }
+
+    println!(
+        "  {:<28} {:<42} {:<14} {:<28} {}",
+        "Provider".bright_black(),
+        "Wallet".bright_black(),
+        "GPUs".bright_black(),
+        "Models".bright_black(),
+        "Genesis".bright_black()
+    );
+    println!("  {}", "━".repeat(60).bright_black());
+    for entry in &providers {
+        let reg = &entry.registration;
+        let status = if entry.verified {
+            "✓ verified".green()
+        } else {
+            "✗ unverified".red()
+        };
+        println!(
+            "  {:<28} {:<42} {:<14} {:<28} {}",
+            reg.provider.cyan(),
+            reg.wallet,
+            reg.gpus.join(", "),
+            reg.models.join(", "),
+            status
+        );
+    }
+    println!();
+
+    let unverified: Vec<&ProviderEntry> = providers.iter().filter(|p| !p.verified).collect();
+    println!("  {} {}", "Providers:".bright_black(), providers.len());
+    if !unverified.is_empty() {
+        println!(
+            "  {} {}",
+            "Unverified:".bright_black(),
+            unverified.len().to_string().red()
+        );
+        for entry in unverified {
+            if let Some(problem) = &entry.problem {
+                println!("    {} {}", "⚠".yellow(), problem);
+            }
+        }
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Registrations in the genesis directory, optionally only those advertising
+/// `model`, sorted by provider ENS
+pub async fn list_providers(
+    store: &dyn Storage,
+    model: Option<&str>,
+) -> Result<Vec<ProviderEntry>> {
+    let mut providers = Vec::new();
+    for name in store.list(ipfs::paths::GENESIS).await? {
+        let path = format!("{}/{}", ipfs::paths::GENESIS, name);
+        let data = match store.read_path(&path).await {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!("Skipping unreadable {}: {:#}", path, e);
+                continue;
+            }
+        };
+        let registration: ProviderRegistration = match serde_json::from_value(data.clone()) {
+            Ok(registration) => registration,
+            Err(e) => {
+                tracing::warn!("Skipping unreadable {}: {}", path, e);
+                continue;
+            }
+        };
+        if model.is_some_and(|model| !registration.models.iter().any(|m| m == model)) {
+            continue;
+        }
+
+        let verified = ipfs::verified_genesis(data, &path);
+        providers.push(ProviderEntry {
+            verified: verified.is_ok(),
+            problem: verified.err().map(|e| format!("{:#}", e)),
+            registration,
+        });
+    }
+    providers.sort_by(|a, b| a.registration.provider.cmp(&b.registration.provider));
+    Ok(providers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto;
+    use crate::test_support::{self, OTHER_ADDRESS, TEST_ADDRESS, TEST_KEY};
+
+    #[tokio::test]
+    async fn test_list_providers_verifies_and_filters() {
+        let (_root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        let genesis = |provider: &str, wallet: &str, models: &[&str]| {
+            serde_json::json!({
+                "type": "provider-init",
+                "provider": provider,
+                "wallet": wallet,
+                "gpus": ["RTX 5090"],
+                "models": models,
+                "timestamp": 1704067200,
+                "nonce": "abcdef1234567890"
+            })
+        };
+
+        let alice = crypto::sign_json(
+            &genesis(
+                "alice.swarmbee.eth",
+                TEST_ADDRESS,
+                &["queenbee-spine", "queenbee-brain"],
+            ),
+            TEST_KEY,
+        )
+        .await
+        .unwrap();
+        ipfs::write_genesis(store, "alice.swarmbee.eth", &alice)
+            .await
+            .unwrap();
+
+        // Names a wallet the signing key doesn't belong to
+        let bob = crypto::sign_json(
+            &genesis("bob.swarmbee.eth", OTHER_ADDRESS, &["queenbee-spine"]),
+            TEST_KEY,
+        )
+        .await
+        .unwrap();
+        ipfs::write_genesis(store, "bob.swarmbee.eth", &bob)
+            .await
+            .unwrap();
+
+        // Alice's genuine genesis copied under another file name
+        store
+            .write_path(&format!("{}/mallory.json", ipfs::paths::GENESIS), &alice)
+            .await
+            .unwrap();
+
+        let all = list_providers(store, None).await.unwrap();
+        let names: Vec<&str> = all
+            .iter()
+            .map(|p| p.registration.provider.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "alice.swarmbee.eth",
+                "alice.swarmbee.eth",
+                "bob.swarmbee.eth"
+            ]
+        );
+        let (canonical, copy) = if all[0].verified {
+            (&all[0], &all[1])
+        } else {
+            (&all[1], &all[0])
+        };
+        assert!(canonical.verified && canonical.problem.is_none());
+        assert!(!copy.verified);
+        assert!(copy
+            .problem
+            .as_deref()
+            .unwrap()
+            .contains("not the provider at that path"));
+        assert!(!all[2].verified);
+        assert!(all[2]
+            .problem
+            .as_deref()
+            .unwrap()
+            .contains("not signed by its wallet"));
+
+        let brain = list_providers(store, Some("queenbee-brain")).await.unwrap();
+        assert_eq!(brain.len(), 2);
+        assert!(brain
+            .iter()
+            .all(|p| p.registration.provider == "alice.swarmbee.eth"));
+        assert!(list_providers(store, Some("queenbee-mri"))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,714 @@
+//! publish command - publish a snapshot signed elsewhere
+//!
+//! The other half of `--sign-only`: a job, claim, proof or sealed epoch
+//! signed on an offline machine is checked against its schema, must be
+//! signed by the party entitled to write it, and is then written to its
+//! canonical path and announced exactly as the command that built it would
+//! have. The file is published as is; nothing in it is re-signed or
+//! rewritten, and nothing already at the canonical path is replaced. Like
+//! the command that built it, a snapshot whose nonce its signer already
+//! published with from this machine is refused, and the nonce is recorded
+//! once the snapshot is written.
+//!
+//! Whatever `--sign-only` staged next to the file (`<file>.staged/`) goes
+//! with it: staged content is uploaded first and must come back under the
+//! CID the snapshot refers to, and an epoch's staged pool state is written
+//! to `index/state.json` once it is checked to chain onto the current state.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use serde_json::Value;
+use std::path::Path;
+
+use crate::commands::recover;
+use crate::config;
+use crate::crypto;
+use crate::ipfs;
+use crate::models::{ClaimSnapshot, EpochSnapshot, JobSnapshot, PoolState, ProofSnapshot};
+use crate::nonces::NonceStore;
+use crate::schema::{self, SchemaType};
+use crate::storage::{SignOnlyStorage, Storage};
+
+pub async fn execute(
+    file: String,
+    schema: String,
+    controller: Option<String>,
+    store: &dyn Storage,
+    pool: &str,
+) -> Result<()> {
+    println!("{}", "Publishing Signed Snapshot".cyan().bold());
+    println!();
+
+    let schema_type = SchemaType::from_str(&schema).with_context(|| {
+        format!(
+            "Unknown schema type: {}. Use: job, claim, proof, epoch",
+            schema
+        )
+    })?;
+
+    let content = std::fs::read_to_string(&file).context("Failed to read file")?;
+    let data: Value = serde_json::from_str(&content).context("Failed to parse JSON")?;
+
+    println!("  {} {}", "File:".bright_black(), file);
+    println!("  {} {:?}", "Schema:".bright_black(), schema_type);
+
+    let authority = authority(&data, schema_type, controller, store).await?;
+    let staging = SignOnlyStorage::staging_dir(Path::new(&file));
+    let nonces = NonceStore::open()?;
+    let (cid, signer) = publish(
+        &data,
+        schema_type,
+        &authority,
+        &staging,
+        &nonces,
+        store,
+        pool,
+    )
+    .await?;
+
+    println!("  {} {}", "Signer:".bright_black(), signer);
+    println!();
+    println!("{} {}", "✓ Published:".green(), cid.cyan());
+    println!();
+
+    Ok(())
+}
+
+/// The address a `schema_type` snapshot must be signed by: for a job, the
+/// wallet of the client it names (its ENS name through `eth_rpc`, else the
+/// wallet in the client's verified genesis), the provider's genesis wallet
+/// for a claim or proof, and the pool controller for an epoch
+pub async fn authority(
+    data: &Value,
+    schema_type: SchemaType,
+    controller: Option<String>,
+    store: &dyn Storage,
+) -> Result<String> {
+    match schema_type {
+        SchemaType::Job => {
+            let client = data["client"].as_str().unwrap_or_default();
+            if let Some(rpc_url) = config::load_config()?.eth_rpc {
+                return Ok(format!(
+                    "{:?}",
+                    crypto::resolve_ens(client, &rpc_url).await?
+                ));
+            }
+            let genesis = ipfs::fetch_verified_genesis(store, client)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Can't check {}'s signature: set eth_rpc to resolve its ENS name",
+                        client
+                    )
+                })?;
+            Ok(genesis.wallet)
+        }
+        SchemaType::Claim | SchemaType::Proof => {
+            let provider = data["provider"].as_str().unwrap_or_default();
+            let genesis = ipfs::fetch_verified_genesis(store, provider)
+                .await
+                .with_context(|| format!("Can't check {}'s signature", provider))?;
+            Ok(genesis.wallet)
+        }
+        SchemaType::Epoch => config::controller_address(controller),
+        other => bail!(
+            "{:?} snapshots can't be published with swarm publish",
+            other
+        ),
+    }
+}
+
+/// Check `data` and publish it as a `schema_type` snapshot signed by
+/// `authority`, with what `--sign-only` staged in `staging`; returns its CID
+/// and the address that signed it. Canonical paths are append-only, so a
+/// snapshot already there is never replaced, and a nonce already in
+/// `nonces` is never published again.
+pub async fn publish(
+    data: &Value,
+    schema_type: SchemaType,
+    authority: &str,
+    staging: &Path,
+    nonces: &NonceStore,
+    store: &dyn Storage,
+    pool: &str,
+) -> Result<(String, String)> {
+    let snapshot_type = data["type"].as_str().unwrap_or("unknown");
+    if !recover::matches_schema(snapshot_type, schema_type) {
+        bail!(
+            "Snapshot type is {:?}, not {:?}",
+            snapshot_type,
+            schema_type
+        );
+    }
+    schema::validate(data, schema_type)?;
+    let signer = recover::recover(data)?.report.signer;
+    if !signer.eq_ignore_ascii_case(authority) {
+        bail!(
+            "{:?} snapshot is signed by {}, not {}; refusing to publish it",
+            schema_type,
+            signer,
+            authority
+        );
+    }
+
+    let path = canonical_path(data, schema_type)?;
+    if let Some(existing) = store.path_cid(&path).await? {
+        bail!(
+            "{} already exists ({}); snapshots are append-only",
+            path,
+            existing
+        );
+    }
+    let nonce = nonce_of(data, schema_type);
+    if let Some((owner, nonce, _)) = &nonce {
+        nonces.check(owner, nonce)?;
+    }
+    let next_state = match schema_type {
+        SchemaType::Epoch => Some(staged_pool_state(data, staging, store).await?),
+        _ => None,
+    };
+    upload_staged(staging, store).await?;
+    let cid = store.write_path(&path, data).await?;
+    if let Some((owner, nonce, timestamp)) = &nonce {
+        nonces.record_published(store, owner, nonce, *timestamp);
+    }
+    if let Some(state) = next_state {
+        ipfs::write_pool_state(store, &state).await?;
+    }
+
+    let (topic, message) = announcement(data, schema_type, &cid)?;
+    store
+        .announce(&format!("/{}/{}", pool, topic), &message)
+        .await?;
+
+    Ok((cid, signer))
+}
+
+/// Upload the content `--sign-only` staged, checking each comes back under
+/// the CID it was staged as (the one the snapshot refers to)
+async fn upload_staged(staging: &Path, store: &dyn Storage) -> Result<()> {
+    let blobs = staging.join("blobs");
+    if !blobs.is_dir() {
+        return Ok(());
+    }
+    for entry in
+        std::fs::read_dir(&blobs).with_context(|| format!("Failed to list {}", blobs.display()))?
+    {
+        let entry = entry?;
+        let staged = entry.file_name().to_string_lossy().into_owned();
+        let bytes = std::fs::read(entry.path())
+            .with_context(|| format!("Failed to read {}", entry.path().display()))?;
+        let cid = store.put_bytes(bytes, &staged).await?;
+        if cid != staged {
+            bail!(
+                "Staged content {} was stored as {}; the snapshot refers to it by {}",
+                staged,
+                cid,
+                staged
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The pool state `seal --sign-only` staged with an epoch, once it is
+/// checked to be the state the epoch's `state_link` names and to chain onto
+/// the pool's current state
+async fn staged_pool_state(data: &Value, staging: &Path, store: &dyn Storage) -> Result<PoolState> {
+    let epoch: EpochSnapshot = serde_json::from_value(data.clone())?;
+    let link = epoch
+        .state_link
+        .with_context(|| format!("Epoch {} has no state_link", epoch.epoch_id))?;
+
+    let state_path = format!("{}/state.json", ipfs::paths::INDEX);
+    let staged = SignOnlyStorage::staged_path(staging, &state_path)?;
+    let bytes = std::fs::read(&staged).with_context(|| {
+        format!(
+            "No staged pool state at {}; publish the epoch with the file seal --sign-only wrote",
+            staged.display()
+        )
+    })?;
+    let state: PoolState = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Failed to parse {}", staged.display()))?;
+    let state_hash = state.canonical_hash()?;
+    if state_hash != link.state_hash {
+        bail!(
+            "Staged pool state hashes to {}, but epoch {} names {}",
+            state_hash,
+            epoch.epoch_id,
+            link.state_hash
+        );
+    }
+
+    if store.path_cid(&state_path).await?.is_none() {
+        bail!(
+            "No pool state at {} for epoch {} to chain onto",
+            state_path,
+            epoch.epoch_id
+        );
+    }
+    let current: PoolState = store.read(&state_path).await?;
+    let current_hash = current.canonical_hash()?;
+    if current_hash != link.prev_state_hash {
+        bail!(
+            "Pool state has moved on since epoch {} was sealed (now {}, sealed from {}); \
+             seal it again",
+            epoch.epoch_id,
+            current_hash,
+            link.prev_state_hash
+        );
+    }
+    Ok(state)
+}
+
+/// Who a snapshot's nonce belongs to (its client, provider or controller),
+/// the nonce and the snapshot's timestamp; `None` for a snapshot without one
+fn nonce_of(data: &Value, schema_type: SchemaType) -> Option<(String, String, i64)> {
+    let owner_field = match schema_type {
+        SchemaType::Job => "client",
+        SchemaType::Claim | SchemaType::Proof => "provider",
+        SchemaType::Epoch => "controller",
+        _ => return None,
+    };
+    let owner = data[owner_field].as_str()?;
+    let nonce = data["nonce"].as_str()?;
+    Some((
+        owner.to_string(),
+        nonce.to_string(),
+        data["timestamp"].as_i64().unwrap_or_default(),
+    ))
+}
+
+/// Where the command that built a snapshot writes it: the type's directory
+/// and its id, as in `ipfs::write_job` and friends
+pub fn canonical_path(data: &Value, schema_type: SchemaType) -> Result<String> {
+    let (dir, id_field) = match schema_type {
+        SchemaType::Job => (ipfs::paths::JOBS, "job_id"),
+        SchemaType::Claim => (ipfs::paths::CLAIMS, "claim_id"),
+        SchemaType::Proof => (ipfs::paths::PROOFS, "proof_id"),
+        SchemaType::Epoch => (ipfs::paths::EPOCHS, "epoch_id"),
+        other => bail!(
+            "{:?} snapshots can't be published with swarm publish",
+            other
+        ),
+    };
+    let id = data[id_field]
+        .as_str()
+        .filter(|id| !id.is_empty() && !id.contains('/'))
+        .with_context(|| format!("Snapshot has no usable {}", id_field))?;
+    Ok(format!("{}/{}.json", dir, id))
+}
+
+/// Topic (under the pool) and message the publishing command announces
+fn announcement(data: &Value, schema_type: SchemaType, cid: &str) -> Result<(&'static str, Value)> {
+    Ok(match schema_type {
+        SchemaType::Job => {
+            let job: JobSnapshot = serde_json::from_value(data.clone())?;
+            (
+                "jobs",
+                serde_json::json!({
+                    "cid": cid,
+                    "client": job.client,
+                    "model": job.model,
+                    "timestamp": job.timestamp
+                }),
+            )
+        }
+        SchemaType::Claim => {
+            let claim: ClaimSnapshot = serde_json::from_value(data.clone())?;
+            (
+                "claims",
+                serde_json::json!({
+                    "claim_cid": cid,
+                    "claim_id": claim.claim_id,
+                    "job_cid": claim.job_cid,
+                    "job_id": claim.job_id,
+                    "provider": claim.provider,
+                    "mode": claim.mode.to_string(),
+                    "timestamp": claim.timestamp
+                }),
+            )
+        }
+        SchemaType::Proof => {
+            let proof: ProofSnapshot = serde_json::from_value(data.clone())?;
+            (
+                "proofs",
+                serde_json::json!({
+                    "proof_cid": cid,
+                    "job_cid": proof.job_cid,
+                    "job_id": proof.job_id,
+                    "output_cid": proof.output_cid,
+                    "provider": proof.provider,
+                    "confidence": proof.metrics.confidence,
+                    "timestamp": proof.timestamp
+                }),
+            )
+        }
+        SchemaType::Epoch => {
+            let epoch: EpochSnapshot = serde_json::from_value(data.clone())?;
+            let settlements = epoch.settlements.as_ref();
+            (
+                "epochs/sealed",
+                serde_json::json!({
+                    "epoch_cid": cid,
+                    "epoch_id": epoch.epoch_id,
+                    "merkle_root": epoch.merkle_root,
+                    "jobs_count": epoch.jobs_count,
+                    "proofs_count": epoch.proofs_count,
+                    "total_volume": settlements.map(|s| s.total_volume),
+                    "miner_pool": settlements.map(|s| s.miner_pool),
+                    "hive_ops": settlements.map(|s| s.hive_ops),
+                    "timestamp": epoch.timestamp
+                }),
+            )
+        }
+        other => bail!(
+            "{:?} snapshots can't be published with swarm publish",
+            other
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::submit::build_job;
+    use crate::models::StateLink;
+    use crate::test_support::{self, OTHER_ADDRESS, OTHER_KEY, TEST_ADDRESS, TEST_KEY};
+
+    const POOL: &str = "swarmpool.eth";
+
+    #[tokio::test]
+    async fn test_signed_job_published_and_announced() {
+        let (root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+        let nonces = NonceStore::at(root.path().join("nonces.jsonl"));
+        let staging = root.path().join("job.json.staged");
+
+        let mut job = build_job(
+            "job-20250101000000-abcd1234".to_string(),
+            "queenbee-spine",
+            "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+            "clinic.clientswarm.eth",
+            "0.25",
+            1704067200,
+        );
+        let unsigned = serde_json::to_value(&job).unwrap();
+        let job_type = SchemaType::Job;
+        assert!(publish(
+            &unsigned,
+            job_type,
+            TEST_ADDRESS,
+            &staging,
+            &nonces,
+            store,
+            POOL
+        )
+        .await
+        .is_err());
+
+        job.sig = Some(crypto::sign_snapshot(&job, TEST_KEY).await.unwrap());
+        let signed = serde_json::to_value(&job).unwrap();
+        let claim_type = SchemaType::Claim;
+        assert!(publish(
+            &signed,
+            claim_type,
+            TEST_ADDRESS,
+            &staging,
+            &nonces,
+            store,
+            POOL
+        )
+        .await
+        .is_err());
+
+        // Signed, but not by the client's wallet
+        let err = publish(
+            &signed,
+            job_type,
+            OTHER_ADDRESS,
+            &staging,
+            &nonces,
+            store,
+            POOL,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("refusing to publish"), "{}", err);
+
+        let (cid, signer) = publish(
+            &signed,
+            job_type,
+            TEST_ADDRESS,
+            &staging,
+            &nonces,
+            store,
+            POOL,
+        )
+        .await
+        .unwrap();
+        assert!(signer.eq_ignore_ascii_case(TEST_ADDRESS));
+        let stored: Value = store
+            .read_path(&format!("{}/{}.json", ipfs::paths::JOBS, job.job_id))
+            .await
+            .unwrap();
+        assert_eq!(stored, signed);
+
+        let announced =
+            std::fs::read_to_string(root.path().join("pubsub/swarmpool.eth_jobs.jsonl")).unwrap();
+        assert!(announced.contains(&cid), "{}", announced);
+
+        // Already there: canonical paths are never overwritten
+        let err = publish(
+            &signed,
+            job_type,
+            TEST_ADDRESS,
+            &staging,
+            &nonces,
+            store,
+            POOL,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("append-only"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_claim_must_be_signed_by_genesis_wallet() {
+        let (root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+        let nonces = NonceStore::at(root.path().join("nonces.jsonl"));
+        let staging = root.path().join("claim.json.staged");
+
+        let claim = serde_json::json!({
+            "type": "claim",
+            "version": "1.0.0",
+            "claim_id": "claim-20250101000000-abcd1234",
+            "job_id": "job-20250101000000-abcd1234",
+            "job_cid": "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o",
+            "provider": "alpha.swarmbee.eth",
+            "mode": "SOLO",
+            "timestamp": 1704067200,
+            "nonce": "abcdef1234567890"
+        });
+
+        // No genesis: nobody is entitled to claim as alpha
+        assert!(authority(&claim, SchemaType::Claim, None, store)
+            .await
+            .is_err());
+
+        let genesis = serde_json::json!({
+            "type": "provider-init",
+            "provider": "alpha.swarmbee.eth",
+            "wallet": TEST_ADDRESS,
+            "gpus": ["RTX 5090"],
+            "models": ["queenbee-spine"],
+            "timestamp": 1704067200,
+            "nonce": "abcdef1234567890"
+        });
+        let genesis = crypto::sign_json(&genesis, TEST_KEY).await.unwrap();
+        ipfs::write_genesis(store, "alpha.swarmbee.eth", &genesis)
+            .await
+            .unwrap();
+        let wallet = authority(&claim, SchemaType::Claim, None, store)
+            .await
+            .unwrap();
+        assert!(wallet.eq_ignore_ascii_case(TEST_ADDRESS));
+
+        let forged = crypto::sign_json(&claim, OTHER_KEY).await.unwrap();
+        assert!(publish(
+            &forged,
+            SchemaType::Claim,
+            &wallet,
+            &staging,
+            &nonces,
+            store,
+            POOL
+        )
+        .await
+        .is_err());
+        let signed = crypto::sign_json(&claim, TEST_KEY).await.unwrap();
+        publish(
+            &signed,
+            SchemaType::Claim,
+            &wallet,
+            &staging,
+            &nonces,
+            store,
+            POOL,
+        )
+        .await
+        .unwrap();
+
+        // The same claim again under another id reuses its nonce
+        let mut again = claim.clone();
+        again["claim_id"] = "claim-20250101000000-ffff0000".into();
+        let again = crypto::sign_json(&again, TEST_KEY).await.unwrap();
+        let err = publish(
+            &again,
+            SchemaType::Claim,
+            &wallet,
+            &staging,
+            &nonces,
+            store,
+            POOL,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("replayed"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_epoch_publishes_its_staged_pool_state() {
+        let (root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+        let nonces = NonceStore::at(root.path().join("nonces.jsonl"));
+        let state_path = format!("{}/state.json", ipfs::paths::INDEX);
+
+        let current: PoolState = serde_json::from_value(serde_json::json!({
+            "pool_id": "swarmpool.eth",
+            "version": "1.0.0",
+            "total_jobs": 10,
+            "total_proofs": 10,
+            "total_volume_usdc": 1.0,
+            "current_epoch": "epoch-002",
+            "epoch_jobs": 1,
+            "epoch_volume": 0.1,
+            "pending_jobs": [],
+            "active_providers": {},
+            "last_updated": 1704067200
+        }))
+        .unwrap();
+        ipfs::write_pool_state(store, &current).await.unwrap();
+        let mut next = current.clone();
+        next.total_jobs = 11;
+        next.prev_state_hash = Some(current.canonical_hash().unwrap());
+
+        // What seal --sign-only leaves behind: the epoch and its staged state
+        let out = root.path().join("epoch.json");
+        let (_offline_root, offline) = test_support::fs_store();
+        let sign_only = SignOnlyStorage::new(Box::new(offline), &out);
+        let sign_only: &dyn Storage = &sign_only;
+        sign_only.replace(&state_path, &next).await.unwrap();
+        let staging = SignOnlyStorage::staging_dir(&out);
+
+        let epoch = |state_hash: String| {
+            let link = StateLink {
+                prev_state_hash: current.canonical_hash().unwrap(),
+                state_hash,
+                total_jobs: 11,
+                total_volume_usdc: 1.1,
+            };
+            serde_json::json!({
+                "type": "epoch",
+                "version": "1.0.0",
+                "epoch_id": "epoch-002",
+                "name": "Bravo",
+                "status": "sealed",
+                "started_at": 1704067200,
+                "jobs_count": 1,
+                "total_volume_usdc": "0.100000",
+                "controller": "merlin.swarmos.eth",
+                "state_link": link,
+                "timestamp": 1704070800
+            })
+        };
+
+        // The staged state isn't the one the epoch was sealed with
+        let wrong = crypto::sign_json(&epoch(current.canonical_hash().unwrap()), TEST_KEY)
+            .await
+            .unwrap();
+        let err = publish(
+            &wrong,
+            SchemaType::Epoch,
+            TEST_ADDRESS,
+            &staging,
+            &nonces,
+            store,
+            POOL,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Staged pool state"), "{}", err);
+
+        let signed = crypto::sign_json(&epoch(next.canonical_hash().unwrap()), TEST_KEY)
+            .await
+            .unwrap();
+
+        // The pool moved on after the seal
+        let mut moved = current.clone();
+        moved.total_jobs = 12;
+        ipfs::write_pool_state(store, &moved).await.unwrap();
+        let err = publish(
+            &signed,
+            SchemaType::Epoch,
+            TEST_ADDRESS,
+            &staging,
+            &nonces,
+            store,
+            POOL,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("moved on"), "{}", err);
+
+        ipfs::write_pool_state(store, &current).await.unwrap();
+        publish(
+            &signed,
+            SchemaType::Epoch,
+            TEST_ADDRESS,
+            &staging,
+            &nonces,
+            store,
+            POOL,
+        )
+        .await
+        .unwrap();
+        let state: PoolState = store.read(&state_path).await.unwrap();
+        assert_eq!(
+            state.canonical_hash().unwrap(),
+            next.canonical_hash().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_epoch_without_state_link_refused() {
+        let (root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+        let nonces = NonceStore::at(root.path().join("nonces.jsonl"));
+        let staging = root.path().join("epoch.json.staged");
+
+        let epoch = serde_json::json!({
+            "type": "epoch",
+            "version": "1.0.0",
+            "epoch_id": "epoch-002",
+            "name": "Bravo",
+            "status": "sealed",
+            "started_at": 1704067200,
+            "jobs_count": 1,
+            "total_volume_usdc": "0.100000",
+            "controller": "merlin.swarmos.eth",
+            "timestamp": 1704070800
+        });
+        let signed = crypto::sign_json(&epoch, TEST_KEY).await.unwrap();
+
+        // No state_link, so no staged state can be checked against it
+        let err = publish(
+            &signed,
+            SchemaType::Epoch,
+            TEST_ADDRESS,
+            &staging,
+            &nonces,
+            store,
+            POOL,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("no state_link"), "{}", err);
+        assert!(store
+            .path_cid(&format!("{}/epoch-002.json", ipfs::paths::EPOCHS))
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,166 @@
+//! recover command - Show the address that signed a snapshot
+//!
+//! For debugging signature mismatches: the `sig` is stripped, the rest is
+//! hashed exactly as `crypto::sign_snapshot` hashes it (keccak256 over the
+//! canonical JSON, then the EIP-191 envelope) and the signer recovered. The
+//! digest is printed too, so it can be compared with another signer's.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::Value;
+
+use crate::commands::verify_sig::{self, SignatureReport};
+use crate::crypto;
+use crate::schema::SchemaType;
+
+/// The signer of a snapshot and what it signed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recovered {
+    /// keccak256 of the canonical JSON minus `sig`
+    pub digest: String,
+    /// Recovered signer and the name claiming to be it (see `verify_sig`)
+    pub report: SignatureReport,
+}
+
+pub async fn execute(file: String, schema: String) -> Result<()> {
+    println!("{}", "Recovering Signer".cyan().bold());
+    println!();
+
+    let schema_type = SchemaType::from_str(&schema).with_context(|| {
+        format!(
+            "Unknown schema type: {}. Use: genesis, job, claim, proof, epoch, heartbeat, withdrawal",
+            schema
+        )
+    })?;
+
+    println!("  {} {}", "File:".bright_black(), file);
+    println!("  {} {:?}", "Schema:".bright_black(), schema_type);
+
+    let content = std::fs::read_to_string(&file).context("Failed to read file")?;
+    let data: Value = serde_json::from_str(&content).context("Failed to parse JSON")?;
+
+    let snapshot_type = data["type"].as_str().unwrap_or("unknown");
+    if !matches_schema(snapshot_type, schema_type) {
+        anyhow::bail!(
+            "Snapshot type is {:?}, not {:?}; pass the --schema it was signed as",
+            snapshot_type,
+            schema_type
+        );
+    }
+
+    let recovered = recover(&data)?;
+
+    if let Some((field, claimed)) = &recovered.report.claimed {
+        println!(
+            "  {} {} ({})",
+            "Claimed:".bright_black(),
+            claimed.cyan(),
+            field
+        );
+    }
+    println!("  {} {}", "Digest:".bright_black(), recovered.digest);
+    println!(
+        "  {} {}",
+        "Signer:".bright_black(),
+        recovered.report.signer.green()
+    );
+    println!();
+
+    Ok(())
+}
+
+/// Recover the signer of `data`, reporting a missing or malformed `sig`
+pub fn recover(data: &Value) -> Result<Recovered> {
+    let object = data.as_object().context("Snapshot is not a JSON object")?;
+    let sig = match object.get("sig") {
+        None | Some(Value::Null) => anyhow::bail!("Snapshot has no sig field; it was never signed"),
+        Some(Value::String(sig)) => sig,
+        Some(other) => anyhow::bail!("Malformed sig field: expected a hex string, got {}", other),
+    };
+
+    let report =
+        verify_sig::recover(data).with_context(|| format!("Malformed sig field: {}", sig))?;
+
+    let mut unsigned = object.clone();
+    unsigned.remove("sig");
+    let canonical = crypto::canonical_json(&Value::Object(unsigned));
+
+    Ok(Recovered {
+        digest: crypto::keccak256_hash(canonical.as_bytes()),
+        report,
+    })
+}
+
+/// Whether a snapshot's `type` is the one `schema` describes
+pub fn matches_schema(snapshot_type: &str, schema: SchemaType) -> bool {
+    match schema {
+        SchemaType::Genesis => matches!(snapshot_type, "genesis" | "provider-init"),
+        SchemaType::Job => snapshot_type == "job",
+        SchemaType::Claim => snapshot_type == "claim",
+        SchemaType::Proof => snapshot_type == "proof",
+        SchemaType::Epoch => snapshot_type == "epoch",
+        SchemaType::Heartbeat => snapshot_type == "heartbeat",
+        SchemaType::Withdrawal => snapshot_type == "withdrawal",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, TEST_ADDRESS, TEST_KEY};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_recover_reports_missing_and_malformed_sig() {
+        let unsigned = json!({
+            "type": "claim",
+            "claim_id": "claim-001",
+            "provider": "alpha.swarmbee.eth",
+            "timestamp": 1704067200
+        });
+        let signed = crypto::sign_json(&unsigned, TEST_KEY).await.unwrap();
+
+        let recovered = recover(&signed).unwrap();
+        assert!(recovered.report.signer.eq_ignore_ascii_case(TEST_ADDRESS));
+        assert_eq!(
+            recovered.report.claimed,
+            Some(("provider", "alpha.swarmbee.eth".to_string()))
+        );
+        assert_eq!(
+            recovered.digest,
+            crypto::keccak256_hash(crypto::canonical_json(&unsigned).as_bytes())
+        );
+
+        let err = recover(&unsigned).unwrap_err();
+        assert!(err.to_string().contains("no sig field"), "{}", err);
+
+        for bad in [json!("0xzz"), json!("0x1234"), json!(42)] {
+            let mut malformed = signed.clone();
+            malformed["sig"] = bad;
+            let err = recover(&malformed).unwrap_err();
+            assert!(
+                err.to_string().starts_with("Malformed sig field"),
+                "{}",
+                err
+            );
+        }
+
+        assert!(matches_schema("provider-init", SchemaType::Genesis));
+        assert!(!matches_schema("job", SchemaType::Claim));
+    }
+
+    #[tokio::test]
+    async fn test_schema_mismatch_is_an_error() {
+        let dir = test_support::temp_dir();
+        let file = dir.path().join("claim.json");
+        let signed = crypto::sign_json(&json!({"type": "claim", "provider": "a.eth"}), TEST_KEY)
+            .await
+            .unwrap();
+        std::fs::write(&file, signed.to_string()).unwrap();
+        let file = file.display().to_string();
+
+        execute(file.clone(), "claim".to_string()).await.unwrap();
+        let err = execute(file, "job".to_string()).await.unwrap_err();
+        assert!(err.to_string().contains("not Job"), "{}", err);
+    }
+}
@@ -0,0 +1,64 @@
+//! Reputation command - score a provider from published history
+//!
+//! Recomputes the score from proofs, claims, disputes and sealed epochs, so
+//! the figure cached in pool state can be checked by anyone.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+
+use crate::reputation::{self, History};
+use crate::storage::Storage;
+use crate::ui;
+
+pub async fn execute(provider: String, json: bool, store: &dyn Storage, _pool: &str) -> Result<()> {
+    let pb = ui::spinner("Reading pool history...");
+
+    let history = History::load(store).await?;
+    pb.clear();
+
+    let Some(rep) = reputation::score(&history, &provider) else {
+        bail!("No proofs published by {}; nothing to score", provider);
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&rep)?);
+        return Ok(());
+    }
+
+    println!("{}", "Provider Reputation".cyan().bold());
+    println!();
+    println!("  {} {}", "Provider:".bright_black(), provider.green());
+    println!(
+        "  {} {}",
+        "Score:".bright_black(),
+        format!("{:.1}/100", rep.score).cyan().bold()
+    );
+    println!();
+    println!("  {}", "History".bright_black());
+    println!("  {}", "━".repeat(40).bright_black());
+    println!("    {} {}", "Proofs:".bright_black(), rep.proofs);
+    println!(
+        "    {} {} ({:.0}%)",
+        "Accepted:".bright_black(),
+        rep.accepted,
+        rep.acceptance_rate * 100.0
+    );
+    println!(
+        "    {} {} ({:.0}%)",
+        "Disputed:".bright_black(),
+        rep.disputes,
+        rep.dispute_rate * 100.0
+    );
+    println!(
+        "    {} {:.0}%",
+        "Avg Confidence:".bright_black(),
+        rep.avg_confidence * 100.0
+    );
+    match rep.avg_latency_secs {
+        Some(secs) => println!("    {} {:.0}s", "Avg Latency:".bright_black(), secs),
+        None => println!("    {} {}", "Avg Latency:".bright_black(), "n/a (no claims)".bright_black()),
+    }
+    println!();
+
+    Ok(())
+}
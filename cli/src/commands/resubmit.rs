@@ -0,0 +1,330 @@
+//! resubmit command - republish a job that no provider has proven
+//!
+//! When a provider claims a job and never produces a proof, the client can
+//! publish it again once `--timeout` has passed since the original was
+//! submitted. The new job has a fresh `job_id`, nonce and signature and the
+//! same input, params and payment. A job that already has a valid proof is
+//! never resubmitted.
+//!
+//! Only the key that signed the original may resubmit it, and the original
+//! is cancelled (`/swarmpool/cancellations/`) before its replacement is
+//! published, so the client never pays for both.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+
+use crate::commands::submit;
+use crate::config;
+use crate::crypto;
+use crate::ipfs;
+use crate::models::{JobSnapshot, ProofSnapshot, WithdrawnJob};
+use crate::storage::Storage;
+use crate::ui;
+
+/// Default wait for a proof before a job may be resubmitted (seconds)
+pub const DEFAULT_TIMEOUT_SECS: u64 = 3600;
+
+pub async fn execute(
+    job_cid: String,
+    timeout_secs: u64,
+    no_confirm: bool,
+    key: Option<String>,
+    store: &dyn Storage,
+    pool: &str,
+) -> Result<()> {
+    println!("{}", "Resubmitting job".cyan().bold());
+    println!();
+
+    let private_key = crypto::resolve_signer(key)?;
+
+    let pb = ui::spinner("Fetching job and proofs...");
+
+    let raw = store
+        .get_json(&job_cid)
+        .await
+        .with_context(|| format!("Failed to fetch job {}", job_cid))?;
+    let original: JobSnapshot =
+        serde_json::from_value(raw.clone()).with_context(|| format!("{} is not a job", job_cid))?;
+    let client_address = crypto::verify_snapshot_json(&raw, None)
+        .with_context(|| format!("Job {} has no valid signature", original.job_id))?;
+    let proven = find_valid_proof(store, &job_cid).await;
+    pb.clear();
+    let (proof_cid, invalid) = proven?;
+
+    println!("  {} {}", "Job ID:".bright_black(), original.job_id);
+    println!("  {} {}", "Input:".bright_black(), original.input_cid);
+    println!("  {} {}", "Client:".bright_black(), original.client);
+    println!();
+
+    if let Some(proof_cid) = proof_cid {
+        println!("  {} {}", "Proof:".bright_black(), proof_cid.green());
+        bail!(
+            "Job {} already has a valid proof ({}); not resubmitting",
+            original.job_id,
+            proof_cid
+        );
+    }
+    if invalid > 0 {
+        println!(
+            "  {} {} proof(s) reference this job but are not valid",
+            "⚠".yellow(),
+            invalid
+        );
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let age = now - original.timestamp;
+    if age < timeout_secs as i64 {
+        bail!(
+            "Job {} was submitted {}s ago; wait until {}s have passed (--timeout) before resubmitting",
+            original.job_id,
+            age,
+            timeout_secs
+        );
+    }
+
+    // Signing the cancellation proves the key is the original client's
+    let cancellation = cancellation(&original, &client_address, now, &private_key).await?;
+
+    // Another job for the same input (an earlier resubmission, say) would be
+    // paid for as well
+    let pricing = config::load_config()?.pricing;
+    if pricing.dedup_window_secs > 0 {
+        if let Some((existing_cid, existing)) = ipfs::find_recent_job(
+            store,
+            &original.input_cid,
+            &original.client,
+            pricing.dedup_window_secs,
+        )
+        .await?
+        {
+            if existing.job_id != original.job_id {
+                submit::confirm_duplicate(&existing_cid, &existing, no_confirm)?;
+            }
+        }
+    }
+
+    // Cancel first: if publishing the replacement fails, running resubmit
+    // again finds the original already cancelled and only publishes
+    let already_cancelled = ipfs::withdrawn_jobs(store)
+        .await?
+        .iter()
+        .any(|w| w.job_id == original.job_id);
+    if !already_cancelled {
+        let pb = ui::spinner("Cancelling original job...");
+        ipfs::write_cancellation(store, &original.job_id, &cancellation)
+            .await
+            .with_context(|| format!("Failed to cancel job {}", original.job_id))?;
+        pb.done(format!("{} Cancelled {}", "✓".green(), original.job_id));
+    }
+
+    let job_id = format!(
+        "job-{}-{}",
+        chrono::Utc::now().format("%Y%m%d%H%M%S"),
+        &crypto::random_hex(4)
+    );
+    let mut job = resubmission(&original, job_id.clone(), now);
+    let new_cid = submit::publish_job(&mut job, &private_key, store, pool)
+        .await
+        .with_context(|| {
+            format!(
+                "Job {} is cancelled but its replacement wasn't published; run resubmit again",
+                original.job_id
+            )
+        })?;
+
+    println!();
+    println!("{}", "Job Resubmitted".green().bold());
+    println!();
+    println!("  {} {}", "Job ID:".bright_black(), job_id.cyan());
+    println!("  {} {}", "CID:".bright_black(), new_cid);
+    println!("  {} {}", "Replaces:".bright_black(), job_cid);
+    println!();
+
+    Ok(())
+}
+
+/// `original`'s cancellation signed with `private_key`, refused unless that
+/// is the key of `client_address`, which signed the original
+async fn cancellation(
+    original: &JobSnapshot,
+    client_address: &str,
+    timestamp: i64,
+    private_key: &str,
+) -> Result<serde_json::Value> {
+    let record = WithdrawnJob {
+        job_id: original.job_id.clone(),
+        epoch_id: None,
+        amount: None,
+        timestamp,
+        sig: None,
+    };
+    let signed = crypto::sign_json(&serde_json::to_value(record)?, private_key).await?;
+    crypto::verify_snapshot_json(&signed, Some(client_address)).with_context(|| {
+        format!(
+            "Job {} can only be resubmitted by the key that signed it",
+            original.job_id
+        )
+    })?;
+    Ok(signed)
+}
+
+/// Fresh copy of `original` to publish again: new id, timestamp and nonce,
+/// unsigned, with the same input, params and payment
+pub fn resubmission(original: &JobSnapshot, job_id: String, timestamp: i64) -> JobSnapshot {
+    JobSnapshot {
+        job_id,
+        timestamp,
+        nonce: crypto::random_hex(16),
+        sig: None,
+        ..original.clone()
+    }
+}
+
+/// CID of a valid proof for `job_cid`, if any, and how many others
+/// reference the job without being valid
+async fn find_valid_proof(store: &dyn Storage, job_cid: &str) -> Result<(Option<String>, usize)> {
+    let mut invalid = 0;
+    for (cid, proof) in ipfs::proofs_for_job_with_cids(store, job_cid).await? {
+        if is_valid_proof(store, &proof).await {
+            return Ok((Some(cid), invalid));
+        }
+        invalid += 1;
+    }
+    Ok((None, invalid))
+}
+
+/// A completed proof signed by the wallet in its provider's verified genesis
+async fn is_valid_proof(store: &dyn Storage, proof: &ProofSnapshot) -> bool {
+    if proof.status != "completed" {
+        return false;
+    }
+    let Ok(genesis) = ipfs::fetch_verified_genesis(store, &proof.provider).await else {
+        return false;
+    };
+    serde_json::to_value(proof)
+        .ok()
+        .is_some_and(|data| crypto::verify_snapshot_json(&data, Some(&genesis.wallet)).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::submit::build_job;
+    use crate::test_support::{self, OTHER_KEY, TEST_ADDRESS, TEST_KEY};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_only_a_valid_proof_blocks_resubmission() {
+        let (_root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        let original = build_job(
+            "job-20250101000000-abcd1234".to_string(),
+            "queenbee-spine",
+            "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+            "clinic.clientswarm.eth",
+            "0.25",
+            1704067200,
+        );
+        let job_cid = ipfs::write_job(store, &original.job_id, &original)
+            .await
+            .unwrap();
+
+        let again = resubmission(
+            &original,
+            "job-20250101010000-ef567890".to_string(),
+            1704070800,
+        );
+        assert_eq!(again.input_cid, original.input_cid);
+        assert_eq!(again.payment.amount, "0.25");
+        assert_eq!(again.timestamp, 1704070800);
+        assert_ne!(again.nonce, original.nonce);
+        assert!(again.sig.is_none());
+
+        assert_eq!(find_valid_proof(store, &job_cid).await.unwrap(), (None, 0));
+
+        let genesis = crypto::sign_json(
+            &json!({
+                "type": "provider-init",
+                "provider": "alpha.swarmbee.eth",
+                "wallet": TEST_ADDRESS,
+                "gpus": ["RTX 5090"],
+                "models": ["queenbee-spine"],
+                "timestamp": 1704067200,
+                "nonce": "abcdef1234567890"
+            }),
+            TEST_KEY,
+        )
+        .await
+        .unwrap();
+        ipfs::write_genesis(store, "alpha.swarmbee.eth", &genesis)
+            .await
+            .unwrap();
+
+        let mut proof: ProofSnapshot = serde_json::from_value(json!({
+            "type": "proof",
+            "version": "1.0.0",
+            "proof_id": "proof-001",
+            "job_id": original.job_id,
+            "job_cid": job_cid,
+            "status": "completed",
+            "output_cid": "bafyoutput",
+            "metrics": {
+                "inference_seconds": 1.0,
+                "compute_seconds": 1.0,
+                "confidence": 0.9,
+                "model_version": "1.0.0"
+            },
+            "provider": "alpha.swarmbee.eth",
+            "timestamp": 1704067300,
+            "proof_hash": crypto::keccak256_hash(b"proof-001")
+        }))
+        .unwrap();
+
+        // Unsigned: doesn't count
+        ipfs::write_proof(store, "proof-001", &proof).await.unwrap();
+        assert_eq!(find_valid_proof(store, &job_cid).await.unwrap(), (None, 1));
+
+        proof.proof_id = "proof-002".to_string();
+        proof.sig = Some(crypto::sign_snapshot(&proof, TEST_KEY).await.unwrap());
+        let proof_cid = ipfs::write_proof(store, "proof-002", &proof).await.unwrap();
+        let (found, _) = find_valid_proof(store, &job_cid).await.unwrap();
+        assert_eq!(found, Some(proof_cid));
+    }
+
+    #[tokio::test]
+    async fn test_only_the_original_signer_cancels() {
+        let (_root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        let mut original = build_job(
+            "job-20250101000000-abcd1234".to_string(),
+            "queenbee-spine",
+            "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+            "clinic.clientswarm.eth",
+            "0.25",
+            1704067200,
+        );
+        original.sig = Some(crypto::sign_snapshot(&original, TEST_KEY).await.unwrap());
+        ipfs::write_job(store, &original.job_id, &original)
+            .await
+            .unwrap();
+
+        let err = cancellation(&original, TEST_ADDRESS, 1704070800, OTHER_KEY)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("key that signed it"), "{}", err);
+
+        // The client's own cancellation takes the original out of the pool
+        let signed = cancellation(&original, TEST_ADDRESS, 1704070800, TEST_KEY)
+            .await
+            .unwrap();
+        ipfs::write_cancellation(store, &original.job_id, &signed)
+            .await
+            .unwrap();
+        let withdrawn = ipfs::withdrawn_jobs(store).await.unwrap();
+        assert_eq!(withdrawn.len(), 1);
+        assert_eq!(withdrawn[0].job_id, original.job_id);
+    }
+}
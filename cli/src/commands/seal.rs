@@ -5,297 +5,929 @@
 //! - PPL: each miner gets R * 0.75 * (their_compute / total_compute)
 //! - Hive: always gets R * 0.25 (hive_pct)
 //! - Dust: remainder from rounding → hive ops
+//!
+//! Each seal also writes the next pool state, chained to the previous one by
+//! `prev_state_hash`, and records the link in the epoch (see `audit-state`).
+//!
+//! Collected proofs are checkpointed under the data dir until the seal is
+//! published, so `seal --resume` picks up where an interrupted run stopped.
 
 use anyhow::{Context, Result};
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
-use std::collections::HashMap;
-use std::time::Duration;
+use directories::ProjectDirs;
+use ethers::signers::{LocalWallet, Signer};
+use futures::stream::{self, StreamExt};
+use indicatif::ProgressBar;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::{info_span, Instrument};
 
-use crate::crypto;
+use crate::config;
+use crate::crypto::{self, merkle};
 use crate::ipfs;
+use crate::storage::Storage;
 use crate::models::{
-    EpochSnapshot, ExecutionMode, Settlements,
-    MINERS_PCT, HIVE_PCT, to_microunits, from_microunits,
+    ClaimSnapshot, DisputeSnapshot, EpochSnapshot, ExecutionMode, FlaggedJob, PoolState, ProofSnapshot,
+    SettlementConfig, SettlementMicros, Settlements, Slash,
+    Splits, StateLink, UsdcAmount, WithdrawnJob,
+    DEFAULT_EPOCH_DURATION_SECS, MINERS_PCT, HIVE_BPS, HIVE_PCT, to_microunits, from_microunits,
 };
+use crate::money;
+use crate::reputation;
+use crate::say;
+use crate::schema::{self, SchemaType};
+use crate::ui;
 
 pub async fn execute(
     epoch_id: Option<String>,
     key: Option<String>,
+    no_pin: bool,
+    inclusion_paths: bool,
+    resume: bool,
+    store: &dyn Storage,
     pool: &str,
 ) -> Result<()> {
-    println!("{}", "Sealing Epoch".cyan().bold());
-    println!("  {}", "(Merlin controller only)".bright_black());
-    println!();
+    say!("{}", "Sealing Epoch".cyan().bold());
+    say!("  {}", "(Merlin controller only)".bright_black());
+    say!();
 
     // Get private key (must be Merlin's key)
-    let private_key = key
-        .or_else(|| std::env::var("SWARM_PRIVATE_KEY").ok())
-        .context("Private key required. Use --key or set SWARM_PRIVATE_KEY")?;
+    let private_key = crypto::resolve_signer(key)?;
 
     // Fetch current pool state
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Fetching pool state...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Fetching pool state...");
 
-    let pool_state = ipfs::fetch_pool_state(pool).await?;
+    let pool_state = ipfs::fetch_pool_state(store, pool)
+        .instrument(info_span!("fetch", pool = %pool))
+        .await?;
 
     let target_epoch = epoch_id
         .or(pool_state.current_epoch.clone())
         .context("No active epoch to seal")?;
 
-    pb.finish_with_message(format!("{} Pool state fetched", "✓".green()));
+    pb.done(format!("{} Pool state fetched", "✓".green()));
 
-    println!("  {} {}", "Epoch:".bright_black(), target_epoch.cyan());
-    println!("  {} {}", "Jobs:".bright_black(), pool_state.epoch_jobs);
-    println!("  {} ${:.2}", "Volume:".bright_black(), pool_state.epoch_volume);
-    println!();
+    say!("  {} {}", "Epoch:".bright_black(), target_epoch.cyan());
+    say!("  {} {}", "Jobs:".bright_black(), pool_state.epoch_jobs);
+    say!("  {} {}", "Volume:".bright_black(), money::format_usdc_display(to_microunits(pool_state.epoch_volume)));
+    say!();
 
     // Collect proofs for epoch
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Collecting epoch proofs...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Collecting epoch proofs...");
+
+    // Proofs an interrupted run already collected are not fetched again
+    let checkpoint_path = SealCheckpoint::path(&target_epoch)?;
+    let resumed = if resume {
+        SealCheckpoint::load(&checkpoint_path, &target_epoch)?
+    } else {
+        None
+    };
+    if resume && resumed.is_none() {
+        tracing::warn!("No checkpoint for {}; collecting all proofs", target_epoch);
+    }
+    let mut checkpoint = resumed.unwrap_or_else(|| SealCheckpoint::new(&target_epoch));
+    let resumed_count = checkpoint.proofs.len();
+
+    // TODO: List and fetch actual proofs from IPFS /swarmpool/proofs/
+    // For now: mock data representing epoch activity, so the checkpoint only
+    // spares re-reading it
+    let epoch_proofs = generate_mock_epoch_proofs();
+    let listed: Vec<String> = epoch_proofs.iter().map(|p| p.proof_cid.clone()).collect();
+    let by_cid: HashMap<String, EpochProof> = epoch_proofs
+        .into_iter()
+        .map(|p| (p.proof_cid.clone(), p))
+        .collect();
+    collect_proofs(&mut checkpoint, &checkpoint_path, &listed, |cid| {
+        let proof = by_cid
+            .get(&cid)
+            .cloned()
+            .with_context(|| format!("Proof {} not found", cid));
+        async move { proof }
+    })
+    .await?;
+    let mock_proofs = settlement_order(checkpoint.proofs);
 
-    // TODO: Fetch actual proofs from IPFS /swarmpool/proofs/
-    // For now: mock data representing epoch activity
-    let mock_proofs = generate_mock_epoch_proofs();
+    // Cancelled and refunded jobs are neither settled nor counted in volume
+    let withdrawn = ipfs::withdrawn_jobs(store)
+        .instrument(info_span!("fetch", epoch_id = %target_epoch))
+        .await
+        .context("Failed to read cancellations and refunds; refusing to seal without them")?;
+    let excluded = exclude_withdrawn(
+        mock_proofs,
+        pool_state.epoch_volume,
+        &withdrawn,
+        &target_epoch,
+    );
+    // A proof republished under the same (provider, nonce) is counted once
+    let (mock_proofs, replays) = drop_replays(&excluded.proofs);
+    // Proofs published after their provider's claim lapsed aren't paid
+    let claims = ipfs::verified_claims(store)
+        .instrument(info_span!("fetch", epoch_id = %target_epoch))
+        .await
+        .context("Failed to read claims; refusing to seal without them")?;
+    let heartbeats = ipfs::last_heartbeats(store, claims.iter().map(|c| c.provider.as_str()))
+        .instrument(info_span!("fetch", epoch_id = %target_epoch))
+        .await;
+    let (mock_proofs, lapsed) = drop_lapsed_claims(mock_proofs, &claims, &heartbeats);
+    let epoch_volume = excluded.volume;
     let proof_count = mock_proofs.len();
 
-    pb.finish_with_message(format!("{} Collected {} proofs", "✓".green(), proof_count));
+    if resume && resumed_count > 0 {
+        pb.done(format!(
+            "{} Collected {} proofs ({} from checkpoint)",
+            "✓".green(),
+            proof_count,
+            resumed_count
+        ));
+    } else {
+        pb.done(format!("{} Collected {} proofs", "✓".green(), proof_count));
+    }
+
+    for orphan in &excluded.orphaned {
+        tracing::warn!(
+            "Orphaned proof {} for withdrawn job {} ({})",
+            orphan.proof_cid,
+            orphan.job_id,
+            orphan.provider
+        );
+    }
+    for replay in &replays {
+        tracing::warn!(
+            "Replayed proof {} ignored: {} already used nonce {}",
+            replay.proof_cid,
+            replay.provider,
+            replay.nonce
+        );
+    }
+    if !replays.is_empty() {
+        println!(
+            "  {} {} replayed proof(s) ignored",
+            "⚠".yellow(),
+            replays.len()
+        );
+    }
+    for late in &lapsed {
+        tracing::warn!(
+            "Proof {} ignored: {}'s claim on {} had expired",
+            late.proof_cid,
+            late.provider,
+            late.job_id
+        );
+    }
+    if !lapsed.is_empty() {
+        say!(
+            "  {} {} proof(s) published after their claim expired ignored",
+            "⚠".yellow(),
+            lapsed.len()
+        );
+    }
+    if !excluded.orphaned.is_empty() {
+        println!(
+            "  {} {} orphaned proof(s) for cancelled/refunded jobs excluded",
+            "⚠".yellow(),
+            excluded.orphaned.len()
+        );
+    }
+
+    // Upheld disputes exclude the proof and slash its provider, but only
+    // when the ruling is signed by this controller or a configured arbiter
+    let settlement_config = config::load_config()?.settlement;
+    settlement_config.validate()?;
+    let controller = format!("{:?}", private_key.parse::<LocalWallet>()?.address());
+    let disputes = ipfs::disputes_for_epoch(store, &target_epoch)
+        .instrument(info_span!("fetch", epoch_id = %target_epoch))
+        .await
+        .context("Failed to read disputes; refusing to seal without them")?;
+    let (offenses, unauthorized) =
+        upheld_offenses(&disputes, &controller, &settlement_config.arbiters);
+    for (dispute_id, reason) in &unauthorized {
+        tracing::warn!("Dispute {} not applied: {}", dispute_id, reason);
+    }
+    if !unauthorized.is_empty() {
+        say!(
+            "  {} {} upheld dispute(s) without a controller or arbiter signature ignored",
+            "⚠".yellow(),
+            unauthorized.len()
+        );
+    }
 
     // Calculate settlements
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Calculating settlements...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Calculating settlements...");
 
-    let settlements = calculate_settlements(&mock_proofs, pool_state.epoch_volume);
+    let settlements = info_span!("compute", epoch_id = %target_epoch).in_scope(|| {
+        settle_epoch(&mock_proofs, epoch_volume, &offenses, &settlement_config)
+    });
 
-    pb.finish_with_message(format!("{} Settlements calculated", "✓".green()));
+    pb.done(format!("{} Settlements calculated", "✓".green()));
 
     // Print settlement summary
-    println!();
-    println!("{}", "Settlement Summary".cyan().bold());
-    println!("  {} ${:.6}", "Total Volume:".bright_black(), settlements.total_volume);
-    println!("  {} ${:.6}", "Miner Pool (75%):".bright_black(), settlements.miner_pool);
-    println!("  {} ${:.6}", "Hive Ops (25%):".bright_black(), settlements.hive_ops);
-    println!("  {} ${:.6}", "Dust → Hive:".bright_black(), settlements.dust_to_hive);
-    println!();
-    println!("  {}", "Provider Earnings:".bright_black());
+    say!();
+    say!("{}", "Settlement Summary".cyan().bold());
+    say!("  {} ${}", "Total Volume:".bright_black(), money::format_usdc(to_microunits(settlements.total_volume), 6));
+    say!("  {} ${}", "Miner Pool (75%):".bright_black(), money::format_usdc(to_microunits(settlements.miner_pool), 6));
+    say!("  {} ${}", "Hive Ops (25%):".bright_black(), money::format_usdc(to_microunits(settlements.hive_ops), 6));
+    say!("  {} ${}", "Dust → Hive:".bright_black(), money::format_usdc(to_microunits(settlements.dust_to_hive), 6));
+    for (recipient, amount) in &settlements.hive_recipients {
+        say!("    {} ${}", recipient.cyan(), money::format_usdc(to_microunits(*amount), 6));
+    }
+    say!();
+    say!("  {}", "Provider Earnings:".bright_black());
     for (provider, amount) in &settlements.providers {
-        println!("    {} ${:.6}", provider.green(), amount);
+        say!("    {} ${}", provider.green(), money::format_usdc(to_microunits(*amount), 6));
+    }
+    if !settlements.flagged.is_empty() {
+        say!();
+        say!("  {}", "Flagged (held, not settled):".bright_black());
+        for job in &settlements.flagged {
+            say!(
+                "    {} ${} ({})",
+                job.job_id.yellow(),
+                money::format_usdc(to_microunits(job.held), 6),
+                job.reason
+            );
+        }
     }
-    println!();
+    if !settlements.slashes.is_empty() {
+        say!();
+        say!("  {}", "Slashed:".bright_black());
+        for slash in &settlements.slashes {
+            say!(
+                "    {} -${} ({})",
+                slash.provider.red(),
+                money::format_usdc(to_microunits(slash.applied), 6),
+                slash.reason
+            );
+        }
+    }
+    say!();
 
     // Build merkle root
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Building merkle tree...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Building merkle tree...");
 
-    // TODO: Actual merkle tree construction from proof CIDs
-    let merkle_root = crypto::keccak256_hash(
-        format!("{}:{}:{}", target_epoch, proof_count, chrono::Utc::now().timestamp()).as_bytes()
-    );
+    let merkle_root = info_span!("compute", epoch_id = %target_epoch)
+        .in_scope(|| proofs_merkle_root(&mock_proofs))?;
 
-    pb.finish_with_message(format!("{} Merkle root: {}...", "✓".green(), &merkle_root[..18]));
+    pb.done(format!("{} Merkle root: {}...", "✓".green(), &merkle_root[..18]));
 
     // Create sealed epoch snapshot
     let timestamp = chrono::Utc::now().timestamp();
 
-    let mut epoch = EpochSnapshot {
-        snapshot_type: "epoch-sealed".to_string(),
-        version: "1.0.0".to_string(),
-        epoch_id: target_epoch.clone(),
-        name: generate_epoch_name(&target_epoch),
-        status: "sealed".to_string(),
-        started_at: timestamp - 3600, // Placeholder
-        ended_at: Some(timestamp),
-        jobs_count: proof_count as u64,
-        total_volume_usdc: format!("{:.6}", settlements.total_volume),
-        merkle_root: Some(merkle_root.clone()),
-        settlements: Some(settlements.clone()),
-        controller: "merlin.swarmos.eth".to_string(),
+    // Roll pool state forward, chained to the state we sealed from
+    let jobs_count = distinct_jobs(&mock_proofs);
+    let mut next_state = advance_pool_state(
+        &pool_state,
+        &target_epoch,
+        jobs_count,
+        proof_count as u64,
+        &settlements,
         timestamp,
-        sig: None,
+    )?;
+
+    // Pay out the withdrawals providers' balances now cover
+    let pending_withdrawals = ipfs::fetch_pending_withdrawals(store, None, &controller)
+        .instrument(info_span!("fetch", epoch_id = %target_epoch))
+        .await
+        .context("Failed to read withdrawals")?;
+    let processed_withdrawals = process_withdrawals(&mut next_state, &pending_withdrawals);
+    if !processed_withdrawals.is_empty() {
+        say!(
+            "  {} {} withdrawal(s) processed",
+            "✓".green(),
+            processed_withdrawals.len()
+        );
+    }
+
+    // Refresh cached reputations, counting this epoch's slashes
+    let mut history = reputation::History::load(store).await.unwrap_or_default();
+    history.settlements.push(settlements.clone());
+    for (ens, info) in next_state.active_providers.iter_mut() {
+        info.reputation = reputation::score(&history, ens).map(|r| r.score);
+    }
+    let state_link = StateLink {
+        prev_state_hash: pool_state.canonical_hash()?,
+        state_hash: next_state.canonical_hash()?,
+        total_jobs: next_state.total_jobs,
+        total_volume_usdc: next_state.total_volume_usdc,
     };
 
-    // Sign epoch
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
+    let mut epoch = build_sealed_epoch(
+        &target_epoch,
+        &mock_proofs,
+        &settlements,
+        merkle_root.clone(),
+        state_link,
+        timestamp,
     );
-    pb.set_message("Signing epoch seal...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    epoch.processed_withdrawals = processed_withdrawals;
+    if inclusion_paths {
+        epoch.inclusion_paths = proofs_inclusion_paths(&mock_proofs)?;
+    }
 
-    epoch.sig = Some(crypto::sign_snapshot(&epoch, &private_key).await?);
-    pb.finish_with_message(format!("{} Epoch signed", "✓".green()));
+    // Sign epoch
+    let pb = ui::spinner("Signing epoch seal...");
 
-    // Publish sealed epoch
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
+    epoch.sig = Some(
+        crypto::sign_snapshot(&epoch, &private_key)
+            .instrument(info_span!("sign", epoch_id = %target_epoch))
+            .await?,
     );
-    pb.set_message("Publishing sealed epoch...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    pb.done(format!("{} Epoch signed", "✓".green()));
+    schema::check_before_publish(&epoch, SchemaType::Epoch)?;
 
-    let epoch_cid = ipfs::write_epoch(&target_epoch, &epoch).await?;
-    pb.finish_with_message(format!("{} Published: {}", "✓".green(), epoch_cid.cyan()));
+    // Publish sealed epoch
+    let pb = ui::spinner("Publishing sealed epoch...");
+
+    let publish = info_span!("publish", epoch_id = %target_epoch, cid = tracing::field::Empty);
+    let epoch_cid = ipfs::write_epoch(store, &target_epoch, &epoch)
+        .instrument(publish.clone())
+        .await?;
+    publish.record("cid", epoch_cid.as_str());
+    pb.done(format!("{} Published: {}", "✓".green(), epoch_cid.cyan()));
+
+    // Publish chained pool state
+    let pb = ui::spinner("Updating pool state...");
+
+    let state_cid = ipfs::write_pool_state(store, &next_state)
+        .instrument(publish.clone())
+        .await?;
+    pb.done(format!(
+        "{} State chained: {}...",
+        "✓".green(),
+        &next_state.canonical_hash()?[..18]
+    ));
 
     // Announce seal
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Announcing epoch seal...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Announcing epoch seal...");
 
-    ipfs::pubsub_publish(
+    store.announce(
         &format!("/{}/epochs/sealed", pool),
         &serde_json::json!({
             "epoch_cid": epoch_cid,
             "epoch_id": target_epoch,
             "merkle_root": merkle_root,
-            "jobs_count": proof_count,
+            "jobs_count": jobs_count,
+            "proofs_count": proof_count,
             "total_volume": settlements.total_volume,
             "miner_pool": settlements.miner_pool,
             "hive_ops": settlements.hive_ops,
             "timestamp": timestamp
         }),
     )
+    .instrument(publish)
     .await?;
 
-    pb.finish_with_message(format!("{} Seal announced", "✓".green()));
+    pb.done(format!("{} Seal announced", "✓".green()));
+
+    // Sealed: nothing left to resume
+    if let Err(e) = std::fs::remove_file(&checkpoint_path) {
+        tracing::debug!("Checkpoint {} not removed: {}", checkpoint_path.display(), e);
+    }
+
+    // Pin everything the seal references so history survives GC. The seal
+    // is already published, so failures are reported, not fatal.
+    let pin_failures = if no_pin {
+        vec![]
+    } else {
+        let mut cids: Vec<String> = mock_proofs.iter().map(|p| p.proof_cid.clone()).collect();
+        cids.push(epoch_cid.clone());
+        cids.push(state_cid);
+        cids.sort();
+        cids.dedup();
+
+        let total = cids.len();
+        let pb = ui::progress_bar(total as u64, "Pinning");
+        let failures =
+            pin_concurrently(cids, |cid| async move { store.pin(&cid).await }, &pb)
+                .instrument(info_span!("pin", epoch_id = %target_epoch, count = total))
+                .await;
+        pb.finish_and_clear();
+        println!("{} Pinned {}/{} CIDs", "✓".green(), total - failures.len(), total);
+        failures
+    };
 
     // Summary
-    println!();
-    println!("{}", "Epoch Sealed".green().bold());
-    println!();
+    say!();
+    say!("{}", "Epoch Sealed".green().bold());
+    say!();
     println!("  {} {}", "Epoch:".bright_black(), target_epoch.cyan());
     println!("  {} {}", "CID:".bright_black(), epoch_cid);
     println!("  {} {}...", "Merkle Root:".bright_black(), &merkle_root[..18]);
-    println!("  {} {}", "Jobs:".bright_black(), proof_count);
-    println!("  {} ${:.2}", "Volume:".bright_black(), settlements.total_volume);
-    println!("  {} ${:.2}", "Miner Pool:".bright_black(), settlements.miner_pool);
-    println!("  {} ${:.2}", "Hive Ops:".bright_black(), settlements.hive_ops + settlements.dust_to_hive);
-    println!();
-    println!("  {}", "Provider balances now claimable via 'swarm withdraw'".yellow());
-    println!();
+    say!("  {} {}", "Jobs:".bright_black(), jobs_count);
+    say!("  {} {}", "Proofs:".bright_black(), proof_count);
+    say!("  {} {}", "Volume:".bright_black(), money::format_usdc_display(to_microunits(settlements.total_volume)));
+    say!("  {} {}", "Miner Pool:".bright_black(), money::format_usdc_display(to_microunits(settlements.miner_pool)));
+    say!("  {} {}", "Hive Ops:".bright_black(), money::format_usdc_display(to_microunits(settlements.hive_ops + settlements.dust_to_hive)));
+    say!();
+    say!("  {}", "Provider balances now claimable via 'swarm withdraw'".yellow());
+    say!();
+
+    if !pin_failures.is_empty() {
+        println!("  {}", "Pin failures (re-run 'ipfs pin add' for these):".yellow());
+        for (cid, error) in &pin_failures {
+            println!("    {} {} ({})", "✗".red(), cid, error);
+        }
+        say!();
+    }
 
     Ok(())
 }
 
-/// Mock proof data for epoch (in production: fetch from IPFS)
-struct MockProof {
-    job_id: String,
-    provider: String,
-    compute_seconds: f64,
-    reward: f64,
-    mode: ExecutionMode,
+/// Concurrent pins in flight after a seal
+const PIN_CONCURRENCY: usize = 8;
+
+/// Pin `cids` concurrently, ticking `pb` as each finishes
+///
+/// Returns the CIDs that failed to pin, with their errors.
+async fn pin_concurrently<F, Fut>(
+    cids: Vec<String>,
+    pin: F,
+    pb: &ProgressBar,
+) -> Vec<(String, String)>
+where
+    F: Fn(String) -> Fut,
+    Fut: std::future::Future<Output = Result<()>>,
+{
+    let mut results = stream::iter(cids)
+        .map(|cid| {
+            let pinned = pin(cid.clone());
+            async move { (cid, pinned.await) }
+        })
+        .buffer_unordered(PIN_CONCURRENCY);
+
+    let mut failures = Vec::new();
+    while let Some((cid, result)) = results.next().await {
+        pb.inc(1);
+        if let Err(e) = result {
+            failures.push((cid, e.to_string()));
+        }
+    }
+    failures.sort();
+    failures
+}
+
+/// A proof as settled in an epoch: who did the work, for which job, how
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochProof {
+    pub proof_cid: String,
+    pub proof_hash: String,
+    pub job_id: String,
+    pub provider: String,
+    pub compute_seconds: f64,
+    /// Model run time alone; bounds what `compute_seconds` is paid on (see
+    /// `credited_seconds`)
+    pub inference_seconds: f64,
+    pub reward: f64,
+    /// When the proof was published; the earliest SOLO proof wins
+    pub timestamp: i64,
+    pub mode: ExecutionMode,
+    /// The proof snapshot's nonce; a `(provider, nonce)` pair seen twice
+    /// is a replay
+    pub nonce: String,
+    /// The proof snapshot's status; only "completed" proofs are paid
+    pub status: String,
+    /// Position in the order the sealing controller first collected the
+    /// proof (0 first). Kept in the checkpoint, so a resumed seal keeps the
+    /// same copy of a replay, and recorded as the order of the epoch's
+    /// `proofs`.
+    #[serde(default)]
+    pub observed: u64,
 }
 
-fn generate_mock_epoch_proofs() -> Vec<MockProof> {
+impl EpochProof {
+    /// Settlement view of a published proof; `reward` comes from the job's
+    /// payment and `mode` from the `ClaimSnapshot` the proof fulfils, never
+    /// from the proof itself
+    pub fn from_snapshot(
+        proof_cid: &str,
+        proof: &ProofSnapshot,
+        reward: f64,
+        mode: ExecutionMode,
+    ) -> Self {
+        Self {
+            proof_cid: proof_cid.to_string(),
+            proof_hash: proof.proof_hash.clone(),
+            job_id: proof.job_id.clone(),
+            provider: proof.provider.clone(),
+            compute_seconds: proof.metrics.compute_seconds,
+            inference_seconds: proof.metrics.inference_seconds,
+            reward,
+            timestamp: proof.timestamp,
+            mode,
+            // Proofs without a nonce are told apart by their hash
+            nonce: proof
+                .nonce
+                .clone()
+                .unwrap_or_else(|| proof.proof_hash.clone()),
+            status: proof.status.clone(),
+            // Set by whoever collects the proof (see `collect_proofs`)
+            observed: 0,
+        }
+    }
+}
+
+/// Split `proofs` into those settled and replays: later proofs reusing an
+/// earlier proof's `(provider, nonce)`. The copy the pool `observed` first
+/// is kept, whatever timestamp a copy carries; both lists stay in their
+/// original order.
+pub fn drop_replays(proofs: &[EpochProof]) -> (Vec<EpochProof>, Vec<EpochProof>) {
+    let mut order: Vec<usize> = (0..proofs.len()).collect();
+    order.sort_by(|&a, &b| {
+        proofs[a]
+            .observed
+            .cmp(&proofs[b].observed)
+            .then_with(|| proofs[a].proof_cid.cmp(&proofs[b].proof_cid))
+    });
+    let replayed: HashSet<usize> = ipfs::replayed_nonces(
+        order
+            .iter()
+            .map(|&i| (proofs[i].provider.as_str(), proofs[i].nonce.as_str())),
+    )
+    .into_iter()
+    .map(|k| order[k])
+    .collect();
+
+    let (replays, kept): (Vec<_>, Vec<_>) = proofs
+        .iter()
+        .enumerate()
+        .partition(|(i, _)| replayed.contains(i));
+    let strip = |v: Vec<(usize, &EpochProof)>| v.into_iter().map(|(_, p)| p.clone()).collect();
+    (strip(kept), strip(replays))
+}
+
+/// Proofs collected for an epoch that isn't sealed yet
+///
+/// Settlement needs all of a job's proofs at once (SOLO ranking, PPL shares),
+/// so the proofs themselves are checkpointed rather than partial sums:
+/// settling is cheap, fetching is what's slow. A resumed seal settles the
+/// same proof list as a single pass and so seals the same epoch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealCheckpoint {
+    pub epoch_id: String,
+    /// Every proof fetched so far; resuming skips their CIDs
+    pub proofs: Vec<EpochProof>,
+}
+
+impl SealCheckpoint {
+    pub fn new(epoch_id: &str) -> Self {
+        Self {
+            epoch_id: epoch_id.to_string(),
+            proofs: Vec::new(),
+        }
+    }
+
+    /// `<data_dir>/seal/<epoch_id>.json`
+    pub fn path(epoch_id: &str) -> Result<PathBuf> {
+        let proj_dirs = ProjectDirs::from("eth", "swarmpool", "swarm-cli")
+            .context("Failed to determine data directory")?;
+        Ok(proj_dirs.data_dir().join("seal").join(format!("{}.json", epoch_id)))
+    }
+
+    /// The checkpoint at `path`, if a previous run left one. A checkpoint
+    /// for any epoch but `epoch_id` is an error, never resumed.
+    pub fn load(path: &Path, epoch_id: &str) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let checkpoint: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse checkpoint {}", path.display()))?;
+        if checkpoint.epoch_id != epoch_id {
+            anyhow::bail!(
+                "Checkpoint {} is for {}, not {}; delete it to seal {} from scratch",
+                path.display(),
+                checkpoint.epoch_id,
+                epoch_id,
+                epoch_id
+            );
+        }
+        Ok(Some(checkpoint))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// New proofs fetched between checkpoint saves
+const CHECKPOINT_EVERY: usize = 25;
+
+/// Fetch each proof CID in `listed` that `checkpoint` doesn't hold yet
+///
+/// The checkpoint is saved to `path` after every `CHECKPOINT_EVERY` new
+/// proofs, when a fetch fails and at the end, so even a killed run loses at
+/// most that many fetches. Proofs are matched by CID, not timestamp: one published late
+/// with an early timestamp is still collected on resume. Each new proof's
+/// `observed` is its position in the checkpoint. Returns how many proofs
+/// were fetched.
+pub async fn collect_proofs<F, Fut>(
+    checkpoint: &mut SealCheckpoint,
+    path: &Path,
+    listed: &[String],
+    mut fetch: F,
+) -> Result<usize>
+where
+    F: FnMut(String) -> Fut,
+    Fut: std::future::Future<Output = Result<EpochProof>>,
+{
+    let mut seen: HashSet<String> = checkpoint
+        .proofs
+        .iter()
+        .map(|p| p.proof_cid.clone())
+        .collect();
+    let mut fetched = 0;
+    for cid in listed {
+        if !seen.insert(cid.clone()) {
+            continue;
+        }
+        match fetch(cid.clone()).await {
+            Ok(proof) => {
+                let observed = checkpoint.proofs.len() as u64;
+                checkpoint.proofs.push(EpochProof { observed, ..proof });
+            }
+            Err(e) => {
+                checkpoint.save(path)?;
+                return Err(e);
+            }
+        }
+        fetched += 1;
+        if fetched % CHECKPOINT_EVERY == 0 {
+            checkpoint.save(path)?;
+        }
+    }
+    checkpoint.save(path)?;
+    Ok(fetched)
+}
+
+/// `proofs` with each CID once, in the order they were `observed`. The
+/// epoch lists its proofs in this order, which is how verifiers recover it
+/// (see `import_epoch::fetch_epoch_proofs`).
+pub fn settlement_order(proofs: Vec<EpochProof>) -> Vec<EpochProof> {
+    let mut seen = HashSet::new();
+    let mut proofs: Vec<EpochProof> = proofs
+        .into_iter()
+        .filter(|p| seen.insert(p.proof_cid.clone()))
+        .collect();
+    proofs.sort_by(|a, b| {
+        a.observed
+            .cmp(&b.observed)
+            .then_with(|| a.proof_cid.cmp(&b.proof_cid))
+    });
+    proofs
+}
+
+/// Merkle root over the settled proofs' hashes
+pub fn proofs_merkle_root(proofs: &[EpochProof]) -> Result<String> {
+    let leaves: Vec<String> = proofs.iter().map(|p| p.proof_hash.clone()).collect();
+    crypto::merkle_root(&leaves)
+}
+
+/// Each proof's inclusion path under `proofs_merkle_root`, keyed by proof CID
+pub fn proofs_inclusion_paths(proofs: &[EpochProof]) -> Result<BTreeMap<String, Vec<String>>> {
+    let leaves: Vec<merkle::Hash> = proofs
+        .iter()
+        .map(|p| merkle::parse_hash(&p.proof_hash))
+        .collect::<Result<_>>()?;
+    let tree = merkle::MerkleTree::from_leaves(&leaves);
+
+    Ok(proofs
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let path = tree.proof(i).iter().map(merkle::to_hex).collect();
+            (p.proof_cid.clone(), path)
+        })
+        .collect())
+}
+
+/// Mock proof data for epoch (in production: fetch from IPFS)
+fn generate_mock_epoch_proofs() -> Vec<EpochProof> {
     vec![
         // Job 1: SOLO - miner A wins
-        MockProof {
+        EpochProof {
+            proof_cid: "bafyproof001alpha".to_string(),
+            proof_hash: crypto::keccak256_hash(b"job-001:alpha.swarmbee.eth"),
             job_id: "job-001".to_string(),
             provider: "alpha.swarmbee.eth".to_string(),
             compute_seconds: 12.5,
+            inference_seconds: 12.5,
             reward: 0.10,
+            timestamp: 1704067200,
             mode: ExecutionMode::Solo,
+            nonce: "a11ce00000000001".to_string(),
+            status: "completed".to_string(),
+            observed: 0,
         },
         // Job 2: PPL - multiple miners contribute
-        MockProof {
+        EpochProof {
+            proof_cid: "bafyproof002alpha".to_string(),
+            proof_hash: crypto::keccak256_hash(b"job-002:alpha.swarmbee.eth"),
             job_id: "job-002".to_string(),
             provider: "alpha.swarmbee.eth".to_string(),
             compute_seconds: 40.0,
+            inference_seconds: 40.0,
             reward: 0.10,
+            timestamp: 1704067200,
             mode: ExecutionMode::Ppl,
+            nonce: "a11ce00000000002".to_string(),
+            status: "completed".to_string(),
+            observed: 0,
         },
-        MockProof {
+        EpochProof {
+            proof_cid: "bafyproof002beta".to_string(),
+            proof_hash: crypto::keccak256_hash(b"job-002:beta.swarmbee.eth"),
             job_id: "job-002".to_string(),
             provider: "beta.swarmbee.eth".to_string(),
             compute_seconds: 35.0,
+            inference_seconds: 35.0,
             reward: 0.10,
+            timestamp: 1704067200,
             mode: ExecutionMode::Ppl,
+            nonce: "a11ce00000000003".to_string(),
+            status: "completed".to_string(),
+            observed: 0,
         },
-        MockProof {
+        EpochProof {
+            proof_cid: "bafyproof002gamma".to_string(),
+            proof_hash: crypto::keccak256_hash(b"job-002:gamma.swarmbee.eth"),
             job_id: "job-002".to_string(),
             provider: "gamma.swarmbee.eth".to_string(),
             compute_seconds: 25.0,
+            inference_seconds: 25.0,
             reward: 0.10,
+            timestamp: 1704067200,
             mode: ExecutionMode::Ppl,
+            nonce: "a11ce00000000004".to_string(),
+            status: "completed".to_string(),
+            observed: 0,
         },
         // Job 3: SOLO - miner B wins
-        MockProof {
+        EpochProof {
+            proof_cid: "bafyproof003beta".to_string(),
+            proof_hash: crypto::keccak256_hash(b"job-003:beta.swarmbee.eth"),
             job_id: "job-003".to_string(),
             provider: "beta.swarmbee.eth".to_string(),
             compute_seconds: 8.2,
+            inference_seconds: 8.2,
             reward: 0.10,
+            timestamp: 1704067200,
             mode: ExecutionMode::Solo,
+            nonce: "a11ce00000000005".to_string(),
+            status: "completed".to_string(),
+            observed: 0,
         },
     ]
 }
 
+/// Epoch proofs and volume once withdrawn jobs are taken out
+#[derive(Debug, Clone)]
+pub struct Exclusion {
+    pub proofs: Vec<EpochProof>,
+    pub volume: f64,
+    /// Proofs dropped because their job was cancelled or refunded
+    pub orphaned: Vec<EpochProof>,
+}
+
+/// Drop proofs for cancelled or refunded jobs and take their payments out of
+/// the epoch volume
+///
+/// A withdrawn job's payment is subtracted once: at its proofs' reward when it
+/// has proofs this epoch, otherwise at the recorded amount when the record
+/// names this epoch. Volume never goes below zero.
+pub fn exclude_withdrawn(
+    proofs: Vec<EpochProof>,
+    total_volume: f64,
+    withdrawn: &[WithdrawnJob],
+    epoch_id: &str,
+) -> Exclusion {
+    let withdrawn_ids: HashSet<&str> = withdrawn.iter().map(|w| w.job_id.as_str()).collect();
+
+    let (orphaned, proofs): (Vec<EpochProof>, Vec<EpochProof>) = proofs
+        .into_iter()
+        .partition(|p| withdrawn_ids.contains(p.job_id.as_str()));
+
+    // One reward per withdrawn job seen in this epoch's proofs
+    let mut refunded: HashMap<&str, u64> = HashMap::new();
+    for proof in &orphaned {
+        refunded.insert(proof.job_id.as_str(), to_microunits(proof.reward));
+    }
+    for job in withdrawn {
+        if refunded.contains_key(job.job_id.as_str()) || job.epoch_id.as_deref() != Some(epoch_id) {
+            continue;
+        }
+        if let Some(amount) = job.amount.as_deref().and_then(|a| a.parse::<f64>().ok()) {
+            refunded.insert(job.job_id.as_str(), to_microunits(amount));
+        }
+    }
+
+    let volume_micro = to_microunits(total_volume).saturating_sub(refunded.values().sum());
+
+    Exclusion {
+        proofs,
+        volume: from_microunits(volume_micro),
+        orphaned,
+    }
+}
+
+/// Split `proofs` into those their provider published while its claim on
+/// the job still held it, and late ones: every claim the provider has on
+/// the job had expired (see `ClaimSnapshot::expires_at`, given the
+/// provider's last heartbeat) by the proof's `timestamp`. A proof with no
+/// claim from its provider is kept; its mode is settled elsewhere.
+pub fn drop_lapsed_claims(
+    proofs: Vec<EpochProof>,
+    claims: &[ClaimSnapshot],
+    heartbeats: &HashMap<String, i64>,
+) -> (Vec<EpochProof>, Vec<EpochProof>) {
+    let mut expiry: HashMap<(&str, &str), i64> = HashMap::new();
+    for claim in claims {
+        let expires_at = claim.expires_at(heartbeats.get(&claim.provider).copied());
+        expiry
+            .entry((claim.provider.as_str(), claim.job_id.as_str()))
+            .and_modify(|latest| *latest = (*latest).max(expires_at))
+            .or_insert(expires_at);
+    }
+    proofs.into_iter().partition(|p| {
+        expiry
+            .get(&(p.provider.as_str(), p.job_id.as_str()))
+            .is_none_or(|&expires_at| p.timestamp < expires_at)
+    })
+}
+
 /// Calculate settlements for an epoch
 ///
 /// Math:
 /// - SOLO: winner gets R * 0.75
-/// - PPL: each miner gets R * 0.75 * (their_compute / total_compute)
+/// - PPL: each miner gets R * 0.75 * (their_compute / total_compute); with
+///   no compute reported at all, the R * 0.75 goes to hive ops
 /// - Hive: always gets R * 0.25
 /// - Dust: remainder → hive ops
-fn calculate_settlements(proofs: &[MockProof], total_volume: f64) -> Settlements {
+///
+/// Only "completed" proofs are paid; "low_confidence", "failed" and
+/// "simulated" ones earn nothing. A job with no completed proof, or whose
+/// completed proofs were claimed under different modes, is not settled: it
+/// is recorded in `flagged` and its reward held back from miners and hive.
+/// Replayed proofs (see `drop_replays`) are ignored.
+///
+/// All arithmetic is in microunits (kept in `micro`); the USDC fields are
+/// converted from them at the end.
+pub fn calculate_settlements(proofs: &[EpochProof], total_volume: f64) -> Settlements {
+    let (proofs, _) = drop_replays(proofs);
     let mut provider_earnings: HashMap<String, u64> = HashMap::new(); // microunits
     let mut total_hive_micro: u64 = 0;
+    let mut held_micro: u64 = 0;
+    let mut flagged = Vec::new();
 
     // Group proofs by job_id
-    let mut jobs: HashMap<String, Vec<&MockProof>> = HashMap::new();
-    for proof in proofs {
+    let mut jobs: HashMap<String, Vec<&EpochProof>> = HashMap::new();
+    for proof in &proofs {
         jobs.entry(proof.job_id.clone()).or_default().push(proof);
     }
 
     // Process each job
-    for (_job_id, job_proofs) in &jobs {
+    for (job_id, job_proofs) in &jobs {
         if job_proofs.is_empty() {
             continue;
         }
 
         let first = job_proofs[0];
         let reward_micro = to_microunits(first.reward);
-        let miner_pool_micro = (reward_micro as f64 * MINERS_PCT).floor() as u64;
-        let hive_cut_micro = reward_micro - miner_pool_micro;
+
+        let job_proofs: Vec<&EpochProof> =
+            job_proofs.iter().copied().filter(|p| is_paid(p)).collect();
+        let mode = if job_proofs.is_empty() {
+            Err("no completed proof".to_string())
+        } else {
+            job_mode(&job_proofs)
+        };
+        let mode = match mode {
+            Ok(mode) => mode,
+            Err(reason) => {
+                held_micro += reward_micro;
+                flagged.push(FlaggedJob {
+                    job_id: job_id.clone(),
+                    reason,
+                    held: from_microunits(reward_micro),
+                });
+                continue;
+            }
+        };
+
+        let hive_cut_micro = reward_micro * HIVE_BPS as u64 / 10_000;
+        let miner_pool_micro = reward_micro - hive_cut_micro;
 
         total_hive_micro += hive_cut_micro;
 
-        match first.mode {
+        match mode {
             ExecutionMode::Solo => {
-                // SOLO: First proof (winner) takes the miner pool
-                // In production: validate this is the first valid proof
-                let winner = &first.provider;
+                // SOLO: the earliest proof takes the miner pool
+                let winner = &solo_ranking(&job_proofs)[0].provider;
                 *provider_earnings.entry(winner.clone()).or_insert(0) += miner_pool_micro;
             }
             ExecutionMode::Ppl => {
@@ -307,43 +939,363 @@ fn calculate_settlements(proofs: &[MockProof], total_volume: f64) -> Settlements
 
                     for (i, proof) in job_proofs.iter().enumerate() {
                         let share = proof.compute_seconds / total_compute;
+                        let remaining = miner_pool_micro - distributed;
                         let payout_micro = if i == job_proofs.len() - 1 {
                             // Last miner gets remainder to avoid dust loss
-                            miner_pool_micro - distributed
+                            remaining
                         } else {
-                            (miner_pool_micro as f64 * share).floor() as u64
+                            // Shares can sum past 1.0 in floating point
+                            ((miner_pool_micro as f64 * share).floor() as u64).min(remaining)
                         };
 
                         *provider_earnings.entry(proof.provider.clone()).or_insert(0) += payout_micro;
                         distributed += payout_micro;
                     }
+                } else {
+                    // Nobody reported any work to weight by, so nobody
+                    // earns the miner pool; it goes to hive with the cut
+                    total_hive_micro += miner_pool_micro;
                 }
             }
         }
     }
 
-    // Convert back to USDC and calculate dust
-    let providers: HashMap<String, f64> = provider_earnings
+    flagged.sort_by(|a: &FlaggedJob, b| a.job_id.cmp(&b.job_id));
+
+    // Whatever no job accounts for (volume from jobs without proofs) is dust
+    let total_volume_micro = to_microunits(total_volume);
+    let miner_pool_micro: u64 = provider_earnings.values().sum();
+    let dust_micro = total_volume_micro.saturating_sub(held_micro + miner_pool_micro + total_hive_micro);
+
+    SettlementMicros {
+        total_volume: total_volume_micro,
+        miner_pool: miner_pool_micro,
+        hive_ops: total_hive_micro + dust_micro,
+        dust_to_hive: dust_micro,
+        held: held_micro,
+        providers: provider_earnings,
+    }
+    .to_settlements(flagged)
+}
+
+/// Whether a proof can earn anything: only "completed" ones can
+pub fn is_paid(proof: &EpochProof) -> bool {
+    proof.status == "completed"
+}
+
+/// A SOLO job's proofs in award order: by `timestamp`, ties broken by
+/// `proof_hash`, keeping only each provider's first proof. The first entry
+/// wins; the order never depends on how the proofs were listed or
+/// collected, so naming a proof file can't win a job.
+pub fn solo_ranking<'a>(job_proofs: &[&'a EpochProof]) -> Vec<&'a EpochProof> {
+    let mut ranked = job_proofs.to_vec();
+    ranked.sort_by(|a, b| {
+        a.timestamp
+            .cmp(&b.timestamp)
+            .then_with(|| a.proof_hash.cmp(&b.proof_hash))
+    });
+
+    let mut seen = HashSet::new();
+    ranked.retain(|p| seen.insert(p.provider.as_str()));
+    ranked
+}
+
+/// The execution mode a job settles under, taken from its claims.
+///
+/// Every contributing proof must have been claimed under the same mode;
+/// otherwise returns a description of the conflict.
+fn job_mode(job_proofs: &[&EpochProof]) -> Result<ExecutionMode, String> {
+    let mode = job_proofs[0].mode;
+    if job_proofs.iter().all(|p| p.mode == mode) {
+        return Ok(mode);
+    }
+
+    let claims: Vec<String> = job_proofs
         .iter()
-        .map(|(k, v)| (k.clone(), from_microunits(*v)))
+        .map(|p| format!("{} {}", p.provider, p.mode))
         .collect();
+    Err(format!("conflicting claim modes: {}", claims.join(", ")))
+}
+
+/// A proof excluded from settlement, with why
+#[derive(Debug, Clone)]
+pub struct Offense {
+    pub proof_cid: String,
+    pub reason: String,
+}
+
+/// Offenses from the upheld disputes signed by `controller` or one of the
+/// `arbiters`
+///
+/// Anyone can write to `/disputes/`, so an upheld ruling only counts with an
+/// authorised signature. The rest are returned as `(dispute_id, why)`.
+pub fn upheld_offenses(
+    disputes: &[DisputeSnapshot],
+    controller: &str,
+    arbiters: &[String],
+) -> (Vec<Offense>, Vec<(String, String)>) {
+    let mut offenses = Vec::new();
+    let mut unauthorized = Vec::new();
+    for dispute in disputes.iter().filter(|d| d.status == "upheld") {
+        let signer = match dispute.sig.as_deref() {
+            Some(sig) => {
+                let mut unsigned = dispute.clone();
+                unsigned.sig = None;
+                crypto::recover_snapshot_signer(&unsigned, sig)
+            }
+            None => Err(anyhow::anyhow!("unsigned")),
+        };
+        match signer {
+            Ok(signer)
+                if signer.eq_ignore_ascii_case(controller)
+                    || arbiters.iter().any(|a| a.eq_ignore_ascii_case(&signer)) =>
+            {
+                offenses.push(Offense {
+                    proof_cid: dispute.proof_cid.clone(),
+                    reason: format!("dispute {} upheld: {}", dispute.dispute_id, dispute.reason),
+                });
+            }
+            Ok(signer) => unauthorized.push((
+                dispute.dispute_id.clone(),
+                format!("signed by {}, not the controller or an arbiter", signer),
+            )),
+            Err(e) => unauthorized.push((dispute.dispute_id.clone(), format!("{:#}", e))),
+        }
+    }
+    (offenses, unauthorized)
+}
+
+/// Settle an epoch, excluding offending proofs and slashing their providers
+///
+/// Each offending proof costs its provider `slash_bps` of the job reward,
+/// deducted from their other earnings this epoch and routed to hive ops.
+/// Penalties are capped so no provider's net goes below zero. The final hive
+/// cut, dust and penalties included, is then split across the configured
+/// hive recipients. PPL shares are weighted by each proof's
+/// `credited_seconds` under `compute_overhead_cap`, which is recorded.
+pub fn settle_epoch(
+    proofs: &[EpochProof],
+    total_volume: f64,
+    offenses: &[Offense],
+    config: &SettlementConfig,
+) -> Settlements {
+    let offense_for = |p: &EpochProof| offenses.iter().find(|o| o.proof_cid == p.proof_cid);
+
+    let (slashed, honest): (Vec<EpochProof>, Vec<EpochProof>) = proofs
+        .iter()
+        .map(|p| EpochProof {
+            compute_seconds: credited_seconds(p, config.compute_overhead_cap),
+            ..p.clone()
+        })
+        .partition(|p| offense_for(p).is_some());
+
+    let mut settlements = calculate_settlements(&honest, total_volume);
+    settlements.compute_overhead_cap = Some(config.compute_overhead_cap);
+    if slashed.is_empty() {
+        settlements.hive_recipients = split_hive(settlements.hive_ops, &config.hive_recipients);
+        return settlements;
+    }
+
+    let mut micro = settlements.micro.take().unwrap_or_default();
 
-    let miner_pool = providers.values().sum::<f64>();
-    let hive_ops = from_microunits(total_hive_micro);
+    for proof in &slashed {
+        let penalty_micro = to_microunits(proof.reward) * config.slash_bps as u64 / 10_000;
+        let earned = micro.providers.entry(proof.provider.clone()).or_insert(0);
+        let applied_micro = penalty_micro.min(*earned);
 
-    // Dust = total_volume - miner_pool - hive_ops (should be ~0 or very small)
-    let dust_to_hive = total_volume - miner_pool - hive_ops;
+        *earned -= applied_micro;
+        micro.miner_pool -= applied_micro;
+        micro.hive_ops += applied_micro;
 
-    Settlements {
-        total_volume,
-        miner_pool,
-        hive_ops: hive_ops + dust_to_hive.max(0.0),
-        providers,
-        dust_to_hive: dust_to_hive.max(0.0),
+        settlements.slashes.push(Slash {
+            provider: proof.provider.clone(),
+            proof_cid: proof.proof_cid.clone(),
+            reason: offense_for(proof).map(|o| o.reason.clone()).unwrap_or_default(),
+            slash_bps: config.slash_bps,
+            penalty: from_microunits(penalty_micro),
+            applied: from_microunits(applied_micro),
+        });
     }
+
+    let mut settled = micro.to_settlements(settlements.flagged);
+    settled.slashes = settlements.slashes;
+    settled.hive_recipients = split_hive(settled.hive_ops, &config.hive_recipients);
+    settled.compute_overhead_cap = settlements.compute_overhead_cap;
+    settled
+}
+
+/// The part of a proof's `compute_seconds` that earns a PPL share: at most
+/// its inference time plus `overhead_cap` of it again, the most an honest
+/// `prove` records. A provider claiming more is paid as if it hit the cap.
+pub fn credited_seconds(proof: &EpochProof, overhead_cap: f64) -> f64 {
+    let limit = proof.inference_seconds.max(0.0) * (1.0 + overhead_cap.max(0.0));
+    proof.compute_seconds.min(limit)
+}
+
+/// Split the hive cut by recipient bps (out of `HIVE_BPS`)
+///
+/// Each recipient's share is floored to a microunit; the rounding remainder
+/// goes to the largest recipient (first by name on a tie) so the split sums
+/// exactly to `hive_ops`.
+pub fn split_hive(hive_ops: f64, recipients: &BTreeMap<String, u32>) -> BTreeMap<String, f64> {
+    let hive_micro = to_microunits(hive_ops);
+    let mut shares: BTreeMap<String, u64> = recipients
+        .iter()
+        .map(|(ens, bps)| {
+            let share = hive_micro as u128 * *bps as u128 / HIVE_BPS as u128;
+            (ens.clone(), share as u64)
+        })
+        .collect();
+
+    let mut largest: Option<(&String, u32)> = None;
+    for (ens, bps) in recipients {
+        if !matches!(largest, Some((_, max)) if max >= *bps) {
+            largest = Some((ens, *bps));
+        }
+    }
+    if let Some((ens, _)) = largest {
+        let remainder = hive_micro - shares.values().sum::<u64>();
+        *shares.get_mut(ens).unwrap() += remainder;
+    }
+
+    shares
+        .into_iter()
+        .map(|(ens, micro)| (ens, from_microunits(micro)))
+        .collect()
+}
+
+/// Number of distinct jobs among the settled proofs
+fn distinct_jobs(proofs: &[EpochProof]) -> u64 {
+    proofs
+        .iter()
+        .map(|p| p.job_id.as_str())
+        .collect::<std::collections::HashSet<_>>()
+        .len() as u64
+}
+
+/// Assemble the unsigned sealed-epoch snapshot
+///
+/// The type is always "epoch"; `status: "sealed"` carries the lifecycle.
+fn build_sealed_epoch(
+    epoch_id: &str,
+    proofs: &[EpochProof],
+    settlements: &Settlements,
+    merkle_root: String,
+    state_link: StateLink,
+    timestamp: i64,
+) -> EpochSnapshot {
+    EpochSnapshot {
+        snapshot_type: "epoch".to_string(),
+        version: "1.0.0".to_string(),
+        epoch_id: epoch_id.to_string(),
+        name: generate_epoch_name(epoch_id),
+        status: "sealed".to_string(),
+        started_at: timestamp - 3600, // Placeholder
+        duration_secs: Some(DEFAULT_EPOCH_DURATION_SECS),
+        ended_at: Some(timestamp),
+        jobs_count: distinct_jobs(proofs),
+        proofs_count: proofs.len() as u64,
+        proofs: proofs.iter().map(|p| p.proof_cid.clone()).collect(),
+        total_volume_usdc: UsdcAmount::from_microunits(to_microunits(settlements.total_volume)),
+        merkle_root: Some(merkle_root),
+        inclusion_paths: BTreeMap::new(),
+        settlements: Some(settlements.clone()),
+        splits: Some(Splits {
+            miners_pct: MINERS_PCT,
+            hive_pct: HIVE_PCT,
+        }),
+        controller: "merlin.swarmos.eth".to_string(),
+        timestamp,
+        state_link: Some(state_link),
+        processed_withdrawals: vec![],
+        sig: None,
+    }
+}
+
+/// Build the pool state that follows sealing `epoch_id`
+///
+/// Totals accumulate, sealed earnings are credited to known providers, and
+/// the epoch counters reset for the next epoch.
+fn advance_pool_state(
+    prev: &PoolState,
+    epoch_id: &str,
+    jobs_count: u64,
+    proofs_count: u64,
+    settlements: &Settlements,
+    timestamp: i64,
+) -> Result<PoolState> {
+    let mut next = prev.clone();
+
+    next.total_jobs += jobs_count;
+    next.total_proofs += proofs_count;
+    next.total_volume_usdc = from_microunits(
+        to_microunits(prev.total_volume_usdc) + to_microunits(settlements.total_volume),
+    );
+    next.current_epoch = Some(next_epoch_id(epoch_id));
+    next.epoch_jobs = 0;
+    next.epoch_volume = 0.0;
+    next.last_updated = timestamp;
+    next.prev_state_hash = Some(prev.canonical_hash()?);
+
+    for (ens, amount) in &settlements.providers {
+        if let Some(info) = next.active_providers.get_mut(ens) {
+            info.total_earnings += amount;
+            info.available_balance += amount;
+        }
+    }
+
+    Ok(next)
+}
+
+/// Debit `state` for each pending withdrawal its provider's balance covers,
+/// oldest first, returning the ids paid out. The rest stay pending.
+pub fn process_withdrawals(
+    state: &mut PoolState,
+    pending: &[ipfs::VerifiedWithdrawal],
+) -> Vec<String> {
+    let mut ordered: Vec<&ipfs::VerifiedWithdrawal> = pending.iter().collect();
+    ordered.sort_by(|a, b| {
+        a.withdrawal
+            .timestamp
+            .cmp(&b.withdrawal.timestamp)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    let mut processed = Vec::new();
+    for w in ordered {
+        let Some(info) = state.active_providers.get_mut(&w.withdrawal.provider) else {
+            tracing::warn!("Withdrawal {} is for unknown provider {}", w.id, w.withdrawal.provider);
+            continue;
+        };
+        let balance = to_microunits(info.available_balance);
+        let amount = w.amount.microunits();
+        if amount > balance {
+            tracing::warn!(
+                "Withdrawal {} of {} exceeds {}'s balance {}; left pending",
+                w.id,
+                money::format_usdc_display(amount),
+                w.withdrawal.provider,
+                money::format_usdc_display(balance)
+            );
+            continue;
+        }
+        info.available_balance = from_microunits(balance - amount);
+        processed.push(w.id.clone());
+    }
+    processed
+}
+
+/// Next sequential epoch id (e.g., "epoch-048" -> "epoch-049")
+fn next_epoch_id(epoch_id: &str) -> String {
+    let digits: String = epoch_id.chars().filter(|c| c.is_ascii_digit()).collect();
+    let num: u64 = digits.parse().unwrap_or(0);
+    format!("epoch-{:03}", num + 1)
 }
 
 /// Generate NATO phonetic alphabet name for epoch
+///
+/// The first 26 epochs get the bare word; after that the alphabet's cycle is
+/// appended ("Victor-2" for epoch-047) so no two epochs share a name.
 fn generate_epoch_name(epoch_id: &str) -> String {
     let nato = [
         "Alpha", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf",
@@ -360,23 +1312,35 @@ fn generate_epoch_name(epoch_id: &str) -> String {
         .parse()
         .unwrap_or(0);
 
-    nato[num % nato.len()].to_string()
+    let word = nato[num % nato.len()];
+    match num / nato.len() {
+        0 => word.to_string(),
+        cycle => format!("{}-{}", word, cycle + 1),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support;
 
     #[test]
     fn test_solo_payout() {
         // SOLO: $0.10 job, winner takes $0.075
         let proofs = vec![
-            MockProof {
+            EpochProof {
+                proof_cid: "bafyproof001miner".to_string(),
+                proof_hash: crypto::keccak256_hash(b"job-001:miner.eth"),
                 job_id: "job-001".to_string(),
                 provider: "miner.eth".to_string(),
                 compute_seconds: 10.0,
+                inference_seconds: 10.0,
                 reward: 0.10,
+                timestamp: 1704067200,
                 mode: ExecutionMode::Solo,
+                nonce: "bafyproof001miner".to_string(),
+                status: "completed".to_string(),
+                observed: 0,
             },
         ];
 
@@ -387,31 +1351,91 @@ mod tests {
         assert_eq!(settlements.providers.get("miner.eth"), Some(&0.075));
     }
 
+    #[test]
+    fn test_earliest_solo_proof_wins() {
+        let seen = |cid: &str, provider: &str, observed: u64, timestamp: i64| EpochProof {
+            observed,
+            timestamp,
+            ..proof(cid, "job-001", provider, ExecutionMode::Solo)
+        };
+        // Collection order (`observed`) disagrees with the timestamps and
+        // plays no part in who wins
+        let scrambled = [
+            seen("bafyp1", "c.eth", 0, 1704067300),
+            seen("bafyp2", "a.eth", 3, 1704067100),
+            seen("bafyp3", "b.eth", 1, 1704067200),
+            // a.eth again, later: dropped
+            seen("bafyp4", "a.eth", 2, 1704067400),
+            // Same timestamp as c.eth: the lower proof_hash goes first
+            EpochProof {
+                proof_hash: "0x00".to_string(),
+                ..seen("bafyp5", "d.eth", 4, 1704067300)
+            },
+        ];
+
+        for rotation in 0..scrambled.len() {
+            let mut proofs = scrambled.to_vec();
+            proofs.rotate_left(rotation);
+
+            let refs: Vec<&EpochProof> = proofs.iter().collect();
+            let ranked: Vec<&str> = solo_ranking(&refs)
+                .iter()
+                .map(|p| p.proof_cid.as_str())
+                .collect();
+            assert_eq!(ranked, ["bafyp2", "bafyp3", "bafyp5", "bafyp1"]);
+
+            let settlements = calculate_settlements(&proofs, 0.10);
+            assert_eq!(settlements.providers.len(), 1);
+            assert_eq!(settlements.providers.get("a.eth"), Some(&0.075));
+        }
+    }
+
     #[test]
     fn test_ppl_payout() {
         // PPL: $0.10 job, split by compute_seconds
         // A: 40s, B: 35s, C: 25s (total 100s)
         let proofs = vec![
-            MockProof {
+            EpochProof {
+                proof_cid: "bafyproof001a".to_string(),
+                proof_hash: crypto::keccak256_hash(b"job-001:a.eth"),
                 job_id: "job-001".to_string(),
                 provider: "a.eth".to_string(),
                 compute_seconds: 40.0,
+                inference_seconds: 40.0,
                 reward: 0.10,
+                timestamp: 1704067200,
                 mode: ExecutionMode::Ppl,
+                nonce: "bafyproof001a".to_string(),
+                status: "completed".to_string(),
+                observed: 0,
             },
-            MockProof {
+            EpochProof {
+                proof_cid: "bafyproof001b".to_string(),
+                proof_hash: crypto::keccak256_hash(b"job-001:b.eth"),
                 job_id: "job-001".to_string(),
                 provider: "b.eth".to_string(),
                 compute_seconds: 35.0,
+                inference_seconds: 35.0,
                 reward: 0.10,
+                timestamp: 1704067200,
                 mode: ExecutionMode::Ppl,
+                nonce: "bafyproof001b".to_string(),
+                status: "completed".to_string(),
+                observed: 0,
             },
-            MockProof {
+            EpochProof {
+                proof_cid: "bafyproof001c".to_string(),
+                proof_hash: crypto::keccak256_hash(b"job-001:c.eth"),
                 job_id: "job-001".to_string(),
                 provider: "c.eth".to_string(),
                 compute_seconds: 25.0,
+                inference_seconds: 25.0,
                 reward: 0.10,
+                timestamp: 1704067200,
                 mode: ExecutionMode::Ppl,
+                nonce: "bafyproof001c".to_string(),
+                status: "completed".to_string(),
+                observed: 0,
             },
         ];
 
@@ -428,4 +1452,729 @@ mod tests {
         assert!((b - 0.02625).abs() < 0.001);
         assert!((c - 0.01875).abs() < 0.001);
     }
+
+    #[test]
+    fn test_five_dollar_job_splits_75_25() {
+        let proofs = vec![EpochProof {
+            proof_cid: "bafyproof001miner".to_string(),
+            proof_hash: crypto::keccak256_hash(b"job-001:miner.eth"),
+            job_id: "job-001".to_string(),
+            provider: "miner.eth".to_string(),
+            compute_seconds: 10.0,
+            inference_seconds: 10.0,
+            reward: 5.00,
+            timestamp: 1704067200,
+            mode: ExecutionMode::Solo,
+            nonce: "bafyproof001miner".to_string(),
+            status: "completed".to_string(),
+            observed: 0,
+        }];
+
+        let settlements = calculate_settlements(&proofs, 5.00);
+
+        assert_eq!(settlements.providers.get("miner.eth"), Some(&3.75));
+        assert_eq!(settlements.miner_pool, 3.75);
+        assert_eq!(settlements.hive_ops, 1.25);
+        assert_eq!(settlements.dust_to_hive, 0.0);
+    }
+
+    #[test]
+    fn test_no_microunit_created_or_lost() {
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(2025);
+        for _ in 0..500 {
+            let mut proofs = Vec::new();
+            let mut volume_micro = 0;
+            for job in 0..rng.gen_range(1..6) {
+                let reward_micro: u64 = rng.gen_range(1..50_000_000);
+                volume_micro += reward_micro;
+                let mode = if rng.gen_bool(0.5) {
+                    ExecutionMode::Solo
+                } else {
+                    ExecutionMode::Ppl
+                };
+                for miner in 0..rng.gen_range(1..5) {
+                    // Sometimes nobody reports any work
+                    let compute_seconds = if rng.gen_bool(0.1) {
+                        0.0
+                    } else {
+                        rng.gen_range(0.0..500.0)
+                    };
+                    proofs.push(EpochProof {
+                        proof_cid: format!("bafy{}{}", job, miner),
+                        proof_hash: crypto::keccak256_hash(format!("{}:{}", job, miner).as_bytes()),
+                        job_id: format!("job-{}", job),
+                        provider: format!("m{}.eth", rng.gen_range(0..4)),
+                        compute_seconds,
+                        inference_seconds: compute_seconds,
+                        reward: from_microunits(reward_micro),
+                        timestamp: 1704067200,
+                        mode,
+                        nonce: format!("bafy{}{}", job, miner),
+                        status: "completed".to_string(),
+                        observed: 0,
+                    });
+                }
+            }
+
+            let settlements = calculate_settlements(&proofs, from_microunits(volume_micro));
+            let micro = settlements.micro.unwrap();
+            assert_eq!(micro.total_volume, volume_micro);
+            assert_eq!(micro.miner_pool, micro.providers.values().sum::<u64>());
+            assert_eq!(micro.miner_pool + micro.hive_ops + micro.held, volume_micro);
+            assert_eq!(micro.dust_to_hive, 0);
+
+            // Each job on its own splits exactly too
+            let mut jobs: Vec<&str> = proofs.iter().map(|p| p.job_id.as_str()).collect();
+            jobs.dedup();
+            for job in jobs {
+                let job_proofs: Vec<EpochProof> =
+                    proofs.iter().filter(|p| p.job_id == job).cloned().collect();
+                let reward_micro = to_microunits(job_proofs[0].reward);
+                let micro = calculate_settlements(&job_proofs, job_proofs[0].reward)
+                    .micro
+                    .unwrap();
+                assert_eq!(micro.providers.values().sum::<u64>() + micro.hive_ops, reward_micro);
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_compute_ppl_pool_goes_to_hive() {
+        let proofs: Vec<EpochProof> = ["a.eth", "b.eth", "c.eth"]
+            .iter()
+            .map(|provider| EpochProof {
+                proof_cid: format!("bafyproof001{}", provider),
+                proof_hash: crypto::keccak256_hash(provider.as_bytes()),
+                job_id: "job-001".to_string(),
+                provider: provider.to_string(),
+                compute_seconds: 0.0,
+                inference_seconds: 0.0,
+                reward: 0.10,
+                timestamp: 1704067200,
+                mode: ExecutionMode::Ppl,
+                nonce: format!("bafyproof001{}", provider),
+                status: "completed".to_string(),
+                observed: 0,
+            })
+            .collect();
+
+        let settlements = calculate_settlements(&proofs, 0.10);
+        let micro = settlements.micro.unwrap();
+
+        assert_eq!(micro.miner_pool, 0);
+        assert_eq!(micro.hive_ops, 100_000);
+        assert_eq!(micro.dust_to_hive, 0);
+        assert_eq!(micro.miner_pool + micro.hive_ops, micro.total_volume);
+    }
+
+    #[test]
+    fn test_sealed_epoch_validates() {
+        use crate::schema::{self, SchemaType};
+
+        let proofs = generate_mock_epoch_proofs();
+        let settlements = calculate_settlements(&proofs, 0.30);
+        let link = StateLink {
+            prev_state_hash: format!("0x{}", "1".repeat(64)),
+            state_hash: format!("0x{}", "2".repeat(64)),
+            total_jobs: 3,
+            total_volume_usdc: 0.30,
+        };
+
+        let mut epoch = build_sealed_epoch(
+            "epoch-048",
+            &proofs,
+            &settlements,
+            format!("0x{}", "3".repeat(64)),
+            link.clone(),
+            1704067200,
+        );
+        epoch.sig = Some(format!("0x{}", "a".repeat(130)));
+
+        let data = serde_json::to_value(&epoch).unwrap();
+        schema::validate(&data, SchemaType::Epoch).unwrap();
+
+        let parsed: EpochSnapshot = serde_json::from_value(data).unwrap();
+        assert_eq!(parsed.snapshot_type, "epoch");
+        assert_eq!(parsed.status, "sealed");
+        assert_eq!(parsed.jobs_count, 3);
+        assert_eq!(parsed.proofs_count, 5);
+        assert_eq!(parsed.state_link, Some(link));
+        assert_eq!(parsed.splits.map(|s| s.miners_pct), Some(MINERS_PCT));
+    }
+
+    #[test]
+    fn test_inclusion_paths_verify_against_root() {
+        let proofs = generate_mock_epoch_proofs();
+        let root = merkle::parse_hash(&proofs_merkle_root(&proofs).unwrap()).unwrap();
+        let paths = proofs_inclusion_paths(&proofs).unwrap();
+        assert_eq!(paths.len(), proofs.len());
+
+        for p in &proofs {
+            let leaf = merkle::parse_hash(&p.proof_hash).unwrap();
+            let path: Vec<merkle::Hash> = paths[&p.proof_cid]
+                .iter()
+                .map(|h| merkle::parse_hash(h).unwrap())
+                .collect();
+            assert!(merkle::verify(&leaf, &path, &root), "{}", p.proof_cid);
+        }
+    }
+
+    #[test]
+    fn test_replayed_proof_ignored() {
+        let original = proof("bafyp1", "job-001", "a.eth", ExecutionMode::Ppl);
+        // The same signed proof published again under another CID, seen
+        // later but claiming to be older
+        let replay = EpochProof {
+            proof_cid: "bafyp0".to_string(),
+            timestamp: original.timestamp - 60,
+            observed: 1,
+            ..original.clone()
+        };
+        let proofs = vec![
+            replay,
+            original,
+            EpochProof {
+                observed: 2,
+                ..proof("bafyp2", "job-001", "b.eth", ExecutionMode::Ppl)
+            },
+        ];
+
+        let (kept, replays) = drop_replays(&proofs);
+        let kept: Vec<&str> = kept.iter().map(|p| p.proof_cid.as_str()).collect();
+        assert_eq!(kept, ["bafyp1", "bafyp2"]);
+        assert_eq!(replays.len(), 1);
+        assert_eq!(replays[0].proof_cid, "bafyp0");
+
+        // a.eth and b.eth reported equal compute, so they split evenly
+        let settlements = calculate_settlements(&proofs, 0.10);
+        assert_eq!(settlements.providers["a.eth"], settlements.providers["b.eth"]);
+        assert_eq!(
+            settlements.providers.values().sum::<f64>(),
+            settlements.miner_pool
+        );
+    }
+
+    #[test]
+    fn test_only_completed_proofs_paid() {
+        let with_status = |p: EpochProof, status: &str| EpochProof {
+            status: status.to_string(),
+            ..p
+        };
+        let proofs = vec![
+            // PPL: b.eth's low-confidence result earns no share
+            proof("bafyp1", "job-001", "a.eth", ExecutionMode::Ppl),
+            with_status(
+                proof("bafyp2", "job-001", "b.eth", ExecutionMode::Ppl),
+                "low_confidence",
+            ),
+            // SOLO: the earlier proof failed, so the completed one wins
+            EpochProof {
+                timestamp: 1704067100,
+                ..with_status(
+                    proof("bafyp3", "job-002", "a.eth", ExecutionMode::Solo),
+                    "failed",
+                )
+            },
+            proof("bafyp4", "job-002", "b.eth", ExecutionMode::Solo),
+            // Nothing completed: held back, hive takes no cut either
+            with_status(
+                proof("bafyp5", "job-003", "a.eth", ExecutionMode::Solo),
+                "low_confidence",
+            ),
+        ];
+
+        let settlements = calculate_settlements(&proofs, 0.30);
+        assert_eq!(settlements.providers["a.eth"], 0.075);
+        assert_eq!(settlements.providers["b.eth"], 0.075);
+        assert_eq!(settlements.flagged.len(), 1);
+        assert_eq!(settlements.flagged[0].job_id, "job-003");
+        assert_eq!(settlements.flagged[0].reason, "no completed proof");
+        assert_eq!(settlements.flagged[0].held, 0.10);
+        assert_eq!(settlements.hive_ops, 0.05);
+    }
+
+    fn proof(cid: &str, job_id: &str, provider: &str, mode: ExecutionMode) -> EpochProof {
+        EpochProof {
+            proof_cid: cid.to_string(),
+            proof_hash: crypto::keccak256_hash(cid.as_bytes()),
+            job_id: job_id.to_string(),
+            provider: provider.to_string(),
+            compute_seconds: 10.0,
+            inference_seconds: 10.0,
+            reward: 0.10,
+            timestamp: 1704067200,
+            mode,
+            nonce: cid.to_string(),
+            status: "completed".to_string(),
+            observed: 0,
+        }
+    }
+
+    fn offense(cid: &str) -> Offense {
+        Offense {
+            proof_cid: cid.to_string(),
+            reason: "dispute upheld".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_epoch_names_unique_past_the_alphabet() {
+        assert_eq!(generate_epoch_name("epoch-000"), "Alpha");
+        assert_eq!(generate_epoch_name("epoch-021"), "Victor");
+        assert_eq!(generate_epoch_name("epoch-047"), "Victor-2");
+        assert_eq!(generate_epoch_name("epoch-073"), "Victor-3");
+
+        let names: HashSet<String> = (0..260)
+            .map(|n| generate_epoch_name(&format!("epoch-{:03}", n)))
+            .collect();
+        assert_eq!(names.len(), 260);
+    }
+
+    #[tokio::test]
+    async fn test_resumed_seal_matches_single_pass() {
+        let at = |cid: &str, job_id: &str, provider: &str, timestamp: i64| EpochProof {
+            timestamp,
+            ..proof(cid, job_id, provider, ExecutionMode::Ppl)
+        };
+        // Listed in publish order: bafyp0 arrived last but claims the
+        // earliest timestamp
+        let all = vec![
+            at("bafyp3", "job-002", "c.eth", 1704067300),
+            at("bafyp1", "job-001", "a.eth", 1704067100),
+            at("bafyp2", "job-001", "b.eth", 1704067200),
+            at("bafyp4", "job-002", "a.eth", 1704067200),
+            at("bafyp0", "job-002", "b.eth", 1704067000),
+        ];
+        let listed: Vec<String> = all.iter().map(|p| p.proof_cid.clone()).collect();
+        let by_cid: HashMap<String, EpochProof> = all
+            .iter()
+            .map(|p| (p.proof_cid.clone(), p.clone()))
+            .collect();
+        let dir = test_support::temp_dir();
+        let mut single = SealCheckpoint::new("epoch-001");
+        let single_path = dir.path().join("single.json");
+        collect_proofs(&mut single, &single_path, &listed, |cid| {
+            let proof = Ok(by_cid[&cid].clone());
+            async move { proof }
+        })
+        .await
+        .unwrap();
+        let single = settlement_order(single.proofs);
+
+        // Interrupted by a failed fetch of the third proof
+        let path = dir.path().join("epoch-001.json");
+        let mut first = SealCheckpoint::new("epoch-001");
+        let err = collect_proofs(&mut first, &path, &listed, |cid| {
+            let proof = match cid.as_str() {
+                "bafyp2" => Err(anyhow::anyhow!("gateway timeout")),
+                _ => Ok(by_cid[&cid].clone()),
+            };
+            async move { proof }
+        })
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("gateway timeout"));
+
+        // The resumed run fetches only the proofs the first one didn't get
+        let mut checkpoint = SealCheckpoint::load(&path, "epoch-001").unwrap().unwrap();
+        assert_eq!(checkpoint.proofs.len(), 2);
+        let mut requested = Vec::new();
+        let fetched = collect_proofs(&mut checkpoint, &path, &listed, |cid| {
+            requested.push(cid.clone());
+            let proof = Ok(by_cid[&cid].clone());
+            async move { proof }
+        })
+        .await
+        .unwrap();
+        assert_eq!(fetched, 3);
+        assert_eq!(requested, ["bafyp2", "bafyp4", "bafyp0"]);
+        let observed: Vec<(&str, u64)> = checkpoint
+            .proofs
+            .iter()
+            .map(|p| (p.proof_cid.as_str(), p.observed))
+            .collect();
+        assert_eq!(
+            observed,
+            [
+                ("bafyp3", 0),
+                ("bafyp1", 1),
+                ("bafyp2", 2),
+                ("bafyp4", 3),
+                ("bafyp0", 4)
+            ]
+        );
+        let resumed = settlement_order(checkpoint.proofs);
+
+        let cids = |proofs: &[EpochProof]| -> Vec<String> {
+            proofs.iter().map(|p| p.proof_cid.clone()).collect()
+        };
+        assert_eq!(cids(&resumed), cids(&single));
+        assert_eq!(
+            proofs_merkle_root(&resumed).unwrap(),
+            proofs_merkle_root(&single).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(calculate_settlements(&resumed, 0.20)).unwrap(),
+            serde_json::to_value(calculate_settlements(&single, 0.20)).unwrap()
+        );
+
+        // Another epoch's checkpoint is never resumed
+        let err = SealCheckpoint::load(&path, "epoch-002").unwrap_err();
+        assert!(err.to_string().contains("is for epoch-001"), "{}", err);
+        assert!(
+            SealCheckpoint::load(&dir.path().join("epoch-002.json"), "epoch-002")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_slash_applied() {
+        // a.eth wins two SOLO jobs honestly, but a third proof is disputed
+        let proofs = vec![
+            proof("bafyp1", "job-001", "a.eth", ExecutionMode::Solo),
+            proof("bafyp2", "job-002", "a.eth", ExecutionMode::Solo),
+            proof("bafyp3", "job-003", "a.eth", ExecutionMode::Solo),
+        ];
+        let config = SettlementConfig {
+            slash_bps: 2000,
+            ..Default::default()
+        };
+
+        let settlements = settle_epoch(&proofs, 0.30, &[offense("bafyp3")], &config);
+
+        // 2 x 0.075 earned, minus 20% of the $0.10 disputed reward
+        assert_eq!(settlements.providers.get("a.eth"), Some(&0.13));
+        assert_eq!(settlements.slashes.len(), 1);
+        assert_eq!(settlements.slashes[0].penalty, 0.02);
+        assert_eq!(settlements.slashes[0].applied, 0.02);
+        // Penalty and the excluded job's reward both end up with the hive
+        assert_eq!(
+            to_microunits(settlements.miner_pool) + to_microunits(settlements.hive_ops),
+            to_microunits(0.30)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_authorised_rulings_slash() {
+        use crate::test_support::{OTHER_ADDRESS, OTHER_KEY, TEST_ADDRESS, TEST_KEY};
+
+        let dispute = |id: &str, status: &str| DisputeSnapshot {
+            snapshot_type: "dispute".to_string(),
+            version: "1.0.0".to_string(),
+            dispute_id: id.to_string(),
+            epoch_id: "epoch-001".to_string(),
+            proof_cid: format!("bafy{}", id),
+            provider: "a.eth".to_string(),
+            status: status.to_string(),
+            reason: "wrong output".to_string(),
+            timestamp: 1704067200,
+            sig: None,
+        };
+        let signed = |mut d: DisputeSnapshot, key: &'static str| async move {
+            d.sig = Some(crypto::sign_snapshot(&d, key).await.unwrap());
+            d
+        };
+
+        let disputes = vec![
+            signed(dispute("d-1", "upheld"), TEST_KEY).await,
+            signed(dispute("d-2", "upheld"), OTHER_KEY).await,
+            dispute("d-3", "upheld"),
+            signed(dispute("d-4", "rejected"), TEST_KEY).await,
+        ];
+
+        let (offenses, unauthorized) = upheld_offenses(&disputes, TEST_ADDRESS, &[]);
+        let cids: Vec<&str> = offenses.iter().map(|o| o.proof_cid.as_str()).collect();
+        assert_eq!(cids, ["bafyd-1"]);
+        let ids: Vec<&str> = unauthorized.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["d-2", "d-3"]);
+
+        // An arbiter's ruling counts; tampering after signing doesn't
+        let (offenses, _) =
+            upheld_offenses(&disputes, TEST_ADDRESS, &[OTHER_ADDRESS.to_uppercase()]);
+        assert_eq!(offenses.len(), 2);
+        let mut forged = disputes[0].clone();
+        forged.proof_cid = "bafyother".to_string();
+        let (offenses, unauthorized) = upheld_offenses(&[forged], TEST_ADDRESS, &[]);
+        assert!(offenses.is_empty());
+        assert_eq!(unauthorized.len(), 1);
+    }
+
+    #[test]
+    fn test_withdrawals_paid_oldest_first_within_balance() {
+        use crate::models::{ProviderInfo, WithdrawalSnapshot};
+
+        let provider = |ens: &str, balance: f64| ProviderInfo {
+            ens: ens.to_string(),
+            wallet: "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string(),
+            status: "online".to_string(),
+            registered_at: 1704067200,
+            last_heartbeat: 1704067200,
+            gpus: vec![],
+            models: vec![],
+            jobs_completed: 0,
+            total_earnings: balance,
+            available_balance: balance,
+            reputation: None,
+        };
+        let mut state = PoolState {
+            pool_id: "swarmpool.eth".to_string(),
+            version: "1.0.0".to_string(),
+            total_jobs: 0,
+            total_proofs: 0,
+            total_volume_usdc: 0.0,
+            current_epoch: Some("epoch-002".to_string()),
+            epoch_jobs: 0,
+            epoch_volume: 0.0,
+            pending_jobs: vec![],
+            active_providers: [provider("a.eth", 1.0)]
+                .into_iter()
+                .map(|p| (p.ens.clone(), p))
+                .collect(),
+            last_updated: 1704067200,
+            prev_state_hash: None,
+        };
+        let withdrawal = |id: &str, provider: &str, amount: &str, timestamp: i64| {
+            ipfs::VerifiedWithdrawal {
+                id: id.to_string(),
+                amount: amount.parse().unwrap(),
+                withdrawal: WithdrawalSnapshot {
+                    snapshot_type: "withdrawal".to_string(),
+                    provider: provider.to_string(),
+                    amount: amount.to_string(),
+                    wallet: "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string(),
+                    status: "pending".to_string(),
+                    timestamp,
+                    nonce: "abcdef1234567890".to_string(),
+                    sig: None,
+                },
+            }
+        };
+
+        let pending = [
+            withdrawal("w-late", "a.eth", "0.5", 300),
+            withdrawal("w-early", "a.eth", "0.75", 100),
+            withdrawal("w-small", "a.eth", "0.25", 200),
+            withdrawal("w-unknown", "z.eth", "0.1", 100),
+        ];
+        let processed = process_withdrawals(&mut state, &pending);
+
+        // 0.75 then 0.25 use up the balance; 0.5 waits for more earnings
+        assert_eq!(processed, ["w-early", "w-small"]);
+        assert_eq!(to_microunits(state.active_providers["a.eth"].available_balance), 0);
+    }
+
+    #[test]
+    fn test_ppl_compute_overhead_capped() {
+        // b.eth claims 90s of fetching and uploading around 10s of inference
+        let proofs = vec![
+            proof("bafyp1", "job-001", "a.eth", ExecutionMode::Ppl),
+            EpochProof {
+                compute_seconds: 100.0,
+                ..proof("bafyp2", "job-001", "b.eth", ExecutionMode::Ppl)
+            },
+        ];
+        let config = SettlementConfig::default();
+
+        // Paid as 15s (10s + 50%) against a.eth's 10s
+        let settlements = settle_epoch(&proofs, 0.10, &[], &config);
+        assert_eq!(settlements.providers["a.eth"], 0.03);
+        assert_eq!(settlements.providers["b.eth"], 0.045);
+        assert_eq!(settlements.compute_overhead_cap, Some(0.5));
+
+        let uncapped = SettlementConfig {
+            compute_overhead_cap: 9.0,
+            ..Default::default()
+        };
+        let settlements = settle_epoch(&proofs, 0.10, &[], &uncapped);
+        assert_eq!(settlements.providers["b.eth"], 0.068182);
+    }
+
+    #[test]
+    fn test_slash_floors_at_zero() {
+        // b.eth earned only a small PPL share; the penalty exceeds it
+        let proofs = vec![
+            proof("bafyp1", "job-001", "a.eth", ExecutionMode::Ppl),
+            EpochProof {
+                compute_seconds: 1.0,
+                inference_seconds: 1.0,
+                ..proof("bafyp2", "job-001", "b.eth", ExecutionMode::Ppl)
+            },
+            proof("bafyp3", "job-002", "b.eth", ExecutionMode::Solo),
+        ];
+        let config = SettlementConfig {
+            slash_bps: 10_000,
+            ..Default::default()
+        };
+
+        let settlements = settle_epoch(&proofs, 0.20, &[offense("bafyp3")], &config);
+
+        let slash = &settlements.slashes[0];
+        assert_eq!(slash.penalty, 0.10);
+        assert!(slash.applied < slash.penalty);
+        assert_eq!(settlements.providers.get("b.eth"), Some(&0.0));
+        assert!(*settlements.providers.get("a.eth").unwrap() > 0.06);
+    }
+
+    #[test]
+    fn test_conflicting_modes_flagged() {
+        // Two claims on job-001 disagree on SOLO vs PPL
+        let proofs = vec![
+            proof("bafyp1", "job-001", "a.eth", ExecutionMode::Solo),
+            proof("bafyp2", "job-001", "b.eth", ExecutionMode::Ppl),
+            proof("bafyp3", "job-002", "a.eth", ExecutionMode::Solo),
+        ];
+
+        let settlements = calculate_settlements(&proofs, 0.20);
+
+        assert_eq!(settlements.flagged.len(), 1);
+        assert_eq!(settlements.flagged[0].job_id, "job-001");
+        assert_eq!(settlements.flagged[0].held, 0.10);
+        assert!(settlements.flagged[0].reason.contains("conflicting"));
+
+        // Only job-002 is paid; the held reward goes to neither miners nor hive
+        assert_eq!(settlements.providers.get("a.eth"), Some(&0.075));
+        assert!(settlements.providers.get("b.eth").is_none());
+        assert_eq!(to_microunits(settlements.hive_ops), to_microunits(0.025));
+        assert_eq!(to_microunits(settlements.dust_to_hive), 0);
+    }
+
+    #[test]
+    fn test_two_way_hive_split_with_dust() {
+        let config = SettlementConfig {
+            hive_recipients: BTreeMap::from([
+                ("insurance.swarmos.eth".to_string(), 1000),
+                ("ops.swarmos.eth".to_string(), 1500),
+            ]),
+            ..Default::default()
+        };
+        config.validate().unwrap();
+
+        // 3 microunits of volume beyond the one job's reward become dust
+        let proofs = vec![proof("bafyp1", "job-1", "a.eth", ExecutionMode::Solo)];
+        let settlements = settle_epoch(&proofs, 0.100003, &[], &config);
+        assert_eq!(to_microunits(settlements.dust_to_hive), 3);
+        assert_eq!(to_microunits(settlements.hive_ops), 25_003);
+
+        // 15001.8 and 10001.2 floor to 15001 + 10001; the spare 1 goes to ops
+        let split = &settlements.hive_recipients;
+        assert_eq!(to_microunits(split["ops.swarmos.eth"]), 15_002);
+        assert_eq!(to_microunits(split["insurance.swarmos.eth"]), 10_001);
+        assert_eq!(
+            split.values().map(|v| to_microunits(*v)).sum::<u64>(),
+            to_microunits(settlements.hive_ops)
+        );
+
+        let mut short = config.clone();
+        short.hive_recipients.insert("ops.swarmos.eth".to_string(), 1400);
+        assert!(short.validate().unwrap_err().to_string().contains("sum to 2400 bps"));
+        assert!(SettlementConfig::default().validate().is_ok());
+        assert!(calculate_settlements(&proofs, 0.10).hive_recipients.is_empty());
+    }
+
+    #[test]
+    fn test_cancelled_job_excluded_from_volume_and_settlement() {
+        // Mock epoch: job-001 SOLO, job-002 PPL x3, job-003 SOLO, $0.10 each
+        let cancelled = WithdrawnJob {
+            job_id: "job-003".to_string(),
+            epoch_id: None,
+            amount: Some("0.10".to_string()),
+            timestamp: 0,
+            sig: None,
+        };
+        // Refunded in an earlier epoch: already out of this epoch's volume
+        let earlier = WithdrawnJob {
+            job_id: "job-000".to_string(),
+            epoch_id: Some("epoch-001".to_string()),
+            amount: Some("0.10".to_string()),
+            timestamp: 0,
+            sig: None,
+        };
+
+        let excluded = exclude_withdrawn(
+            generate_mock_epoch_proofs(),
+            0.30,
+            &[cancelled, earlier],
+            "epoch-002",
+        );
+
+        assert_eq!(excluded.orphaned.len(), 1);
+        assert_eq!(excluded.orphaned[0].job_id, "job-003");
+        assert!(excluded.proofs.iter().all(|p| p.job_id != "job-003"));
+        assert_eq!(to_microunits(excluded.volume), 200_000);
+
+        let settlements = calculate_settlements(&excluded.proofs, excluded.volume);
+        assert_eq!(to_microunits(settlements.total_volume), 200_000);
+        assert_eq!(
+            to_microunits(settlements.miner_pool) + to_microunits(settlements.hive_ops),
+            200_000
+        );
+        // beta only keeps its PPL share of job-002, not the job-003 win
+        assert_eq!(settlements.providers.get("beta.swarmbee.eth"), Some(&0.02625));
+    }
+
+    #[test]
+    fn test_proofs_after_claim_expiry_dropped() {
+        let claim = |provider: &str, job_id: &str| ClaimSnapshot {
+            snapshot_type: "claim".to_string(),
+            version: "1.0.0".to_string(),
+            claim_id: format!("claim-{}-{}", provider, job_id),
+            job_id: job_id.to_string(),
+            job_cid: format!("Qm{}", job_id),
+            provider: provider.to_string(),
+            mode: ExecutionMode::Ppl,
+            timestamp: 1704067200 - 3600,
+            ttl_seconds: Some(1800),
+            nonce: "abcdef1234567890".to_string(),
+            sig: None,
+        };
+        // Every proof is timestamped an hour after its claim
+        let proofs = vec![
+            proof("bafyp1", "job-001", "a.eth", ExecutionMode::Ppl),
+            proof("bafyp2", "job-001", "b.eth", ExecutionMode::Ppl),
+            proof("bafyp3", "job-002", "a.eth", ExecutionMode::Ppl),
+        ];
+        let claims = vec![claim("a.eth", "job-001"), claim("b.eth", "job-001")];
+
+        let (kept, lapsed) = drop_lapsed_claims(proofs.clone(), &claims, &HashMap::new());
+        let cids = |v: &[EpochProof]| v.iter().map(|p| p.proof_cid.clone()).collect::<Vec<_>>();
+        // job-002 has no claim from a.eth to lapse
+        assert_eq!(cids(&kept), ["bafyp3"]);
+        assert_eq!(cids(&lapsed), ["bafyp1", "bafyp2"]);
+
+        // b.eth kept heartbeating while it worked
+        let heartbeats = HashMap::from([("b.eth".to_string(), 1704067200 - 600)]);
+        let (kept, lapsed) = drop_lapsed_claims(proofs, &claims, &heartbeats);
+        assert_eq!(cids(&kept), ["bafyp2", "bafyp3"]);
+        assert_eq!(cids(&lapsed), ["bafyp1"]);
+    }
+
+    #[tokio::test]
+    async fn test_pin_failures_reported_not_fatal() {
+        let cids: Vec<String> = (0..20).map(|i| format!("bafycid{:02}", i)).collect();
+        let pb = ProgressBar::hidden();
+
+        let failures = pin_concurrently(
+            cids,
+            |cid| async move {
+                if cid.ends_with('7') {
+                    anyhow::bail!("not found");
+                }
+                Ok(())
+            },
+            &pb,
+        )
+        .await;
+
+        assert_eq!(pb.position(), 20);
+        assert_eq!(
+            failures,
+            vec![
+                ("bafycid07".to_string(), "not found".to_string()),
+                ("bafycid17".to_string(), "not found".to_string()),
+            ]
+        );
+    }
 }
@@ -1,30 +1,62 @@
 //! Status command - check network or provider status
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use colored::Colorize;
 
+use crate::crypto;
 use crate::ipfs;
-use crate::models::{NetworkStats, PoolState, ProviderInfo};
+use crate::storage::Storage;
+use crate::models::{
+    format_time_remaining, to_microunits, EpochSnapshot, HeartbeatSnapshot, NetworkStats, NetworkStatusJson,
+    PoolState, ProviderInfo, ProviderSummary, HEARTBEAT_TIMEOUT_SECS,
+};
+use crate::money;
 
-pub async fn execute(provider: Option<String>, json: bool, pool: &str) -> Result<()> {
+pub async fn execute(
+    provider: Option<String>,
+    json: bool,
+    store: &dyn Storage,
+    pool: &str,
+) -> Result<()> {
     if let Some(provider_ens) = provider {
         // Show specific provider status
-        show_provider_status(&provider_ens, json, pool).await
+        show_provider_status(&provider_ens, json, store, pool).await
     } else {
         // Show network status
-        show_network_status(json, pool).await
+        show_network_status(json, store, pool).await
     }
 }
 
-async fn show_network_status(json: bool, pool: &str) -> Result<()> {
+/// Providers listed in the network summary
+const TOP_PROVIDERS: usize = 5;
+
+async fn show_network_status(json: bool, store: &dyn Storage, pool: &str) -> Result<()> {
     // Fetch pool state from IPFS
-    let state = ipfs::fetch_pool_state(pool).await?;
+    let state = with_heartbeats(store, ipfs::fetch_pool_state(store, pool).await?).await;
+
+    // The active epoch's start and duration give the time until seal
+    let active_epoch = match state.current_epoch.as_deref() {
+        Some(epoch_id) => ipfs::fetch_epoch(store, pool, epoch_id)
+            .await
+            .ok()
+            .filter(|e| e.status == "active"),
+        None => None,
+    };
+
+    let summary = network_summary(
+        &state,
+        active_epoch.as_ref(),
+        chrono::Utc::now().timestamp(),
+        TOP_PROVIDERS,
+    );
 
     if json {
-        println!("{}", serde_json::to_string_pretty(&state)?);
+        println!("{}", serde_json::to_string_pretty(&summary)?);
         return Ok(());
     }
 
+    let stats = &summary.stats;
+
     println!("{}", "SwarmPool Network Status".cyan().bold());
     println!();
 
@@ -34,34 +66,43 @@ async fn show_network_status(json: bool, pool: &str) -> Result<()> {
     println!(
         "    {} {}",
         "Total Jobs:".bright_black(),
-        state.total_jobs.to_string().green()
+        stats.total_jobs.to_string().green()
     );
     println!(
         "    {} {}",
         "Total Volume:".bright_black(),
-        format!("${:.2}", state.total_volume_usdc).green()
+        money::format_usdc_display(to_microunits(stats.total_volume)).green()
     );
     println!(
-        "    {} {}",
+        "    {} {} ({} online)",
         "Active Providers:".bright_black(),
-        state.active_providers.len()
+        stats.active_providers,
+        stats.online_providers
     );
     println!();
 
     // Current epoch
-    if let Some(epoch) = &state.current_epoch {
+    if !stats.current_epoch.is_empty() {
         println!("  {}", "Current Epoch".bright_black());
         println!("  {}", "━".repeat(40).bright_black());
-        println!("    {} {}", "ID:".bright_black(), epoch.cyan());
+        println!("    {} {}", "ID:".bright_black(), stats.current_epoch.cyan());
         println!(
             "    {} {}",
             "Jobs:".bright_black(),
-            state.epoch_jobs
+            stats.epoch_jobs
         );
+        if let Some(remaining) = &stats.epoch_time_remaining {
+            let remaining = if remaining == "sealing overdue" {
+                remaining.yellow()
+            } else {
+                remaining.normal()
+            };
+            println!("    {} {}", "Time Left:".bright_black(), remaining);
+        }
         println!(
             "    {} {}",
             "Volume:".bright_black(),
-            format!("${:.2}", state.epoch_volume)
+            money::format_usdc_display(to_microunits(summary.epoch_volume))
         );
         println!();
     }
@@ -72,29 +113,26 @@ async fn show_network_status(json: bool, pool: &str) -> Result<()> {
     println!(
         "    {} {}",
         "Pending Jobs:".bright_black(),
-        state.pending_jobs.len()
+        stats.pending_jobs
     );
     println!();
 
     // Top providers
-    if !state.active_providers.is_empty() {
+    if !summary.top_providers.is_empty() {
         println!("  {}", "Active Providers".bright_black());
         println!("  {}", "━".repeat(40).bright_black());
 
-        let mut providers: Vec<_> = state.active_providers.values().collect();
-        providers.sort_by(|a, b| b.jobs_completed.cmp(&a.jobs_completed));
-
-        for (i, p) in providers.iter().take(5).enumerate() {
-            let status_icon = match p.status.as_str() {
-                "online" => "🟢",
-                "busy" => "🟡",
-                _ => "🔴",
+        for p in &summary.top_providers {
+            let status_icon = match (p.online, p.status.as_str()) {
+                (true, "busy") => "🟡",
+                (true, _) => "🟢",
+                (false, _) => "🔴",
             };
             println!(
                 "    {} {} {} ({} jobs)",
                 status_icon,
                 truncate_ens(&p.ens, 24),
-                format!("${:.2}", p.total_earnings).green(),
+                money::format_usdc_display(to_microunits(p.total_earnings)).green(),
                 p.jobs_completed
             );
         }
@@ -103,8 +141,115 @@ async fn show_network_status(json: bool, pool: &str) -> Result<()> {
     Ok(())
 }
 
-async fn show_provider_status(provider_ens: &str, json: bool, pool: &str) -> Result<()> {
-    let state = ipfs::fetch_pool_state(pool).await?;
+/// Summarize pool state for display and `--json`
+///
+/// A provider is online when its last heartbeat is within
+/// `HEARTBEAT_TIMEOUT_SECS` of `now` and it hasn't reported itself offline.
+/// Top providers are ranked by jobs completed, ties broken by ENS.
+/// Time remaining is only known when the active epoch could be fetched.
+fn network_summary(
+    state: &PoolState,
+    active_epoch: Option<&EpochSnapshot>,
+    now: i64,
+    top_n: usize,
+) -> NetworkStatusJson {
+    let is_online = |p: &ProviderInfo| {
+        p.status != "offline" && now - p.last_heartbeat <= HEARTBEAT_TIMEOUT_SECS
+    };
+
+    let mut providers: Vec<&ProviderInfo> = state.active_providers.values().collect();
+    providers.sort_by(|a, b| {
+        b.jobs_completed
+            .cmp(&a.jobs_completed)
+            .then_with(|| a.ens.cmp(&b.ens))
+    });
+
+    let top_providers = providers
+        .iter()
+        .take(top_n)
+        .map(|p| ProviderSummary {
+            ens: p.ens.clone(),
+            status: p.status.clone(),
+            online: is_online(p),
+            jobs_completed: p.jobs_completed,
+            total_earnings: p.total_earnings,
+            reputation: p.reputation,
+        })
+        .collect();
+
+    NetworkStatusJson {
+        pool_id: state.pool_id.clone(),
+        stats: NetworkStats {
+            total_jobs: state.total_jobs,
+            total_volume: state.total_volume_usdc,
+            active_providers: providers.len() as u64,
+            online_providers: providers.iter().filter(|p| is_online(p)).count() as u64,
+            pending_jobs: state.pending_jobs.len() as u64,
+            current_epoch: state.current_epoch.clone().unwrap_or_default(),
+            epoch_jobs: state.epoch_jobs,
+            epoch_time_remaining: active_epoch
+                .map(|e| format_time_remaining(e.time_remaining(now))),
+        },
+        epoch_volume: state.epoch_volume,
+        top_providers,
+        last_updated: state.last_updated,
+    }
+}
+
+/// Overlay each provider's latest heartbeat record onto the pool state
+///
+/// Records that are missing or fail verification are skipped, leaving the
+/// state's own `last_heartbeat` in place.
+async fn with_heartbeats(store: &dyn Storage, mut state: PoolState) -> PoolState {
+    for info in state.active_providers.values_mut() {
+        let applied = match ipfs::fetch_heartbeat(store, &info.ens).await {
+            Ok(heartbeat) => apply_heartbeat(info, &heartbeat),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = applied {
+            tracing::debug!("No heartbeat record for {}: {}", info.ens, e);
+        }
+    }
+    state
+}
+
+/// Apply a heartbeat record to its provider
+///
+/// The record must name the provider and be signed by its registered wallet.
+/// Returns `Ok(false)` when the record is older than what the state holds.
+pub fn apply_heartbeat(info: &mut ProviderInfo, heartbeat: &HeartbeatSnapshot) -> Result<bool> {
+    if heartbeat.provider != info.ens {
+        bail!("heartbeat is for {}, not {}", heartbeat.provider, info.ens);
+    }
+
+    let sig = match heartbeat.sig.as_deref() {
+        Some(sig) => sig,
+        None => bail!("heartbeat for {} is unsigned", info.ens),
+    };
+    let mut unsigned = heartbeat.clone();
+    unsigned.sig = None;
+
+    let signer = crypto::recover_snapshot_signer(&unsigned, sig)?;
+    if !signer.eq_ignore_ascii_case(&info.wallet) {
+        bail!("heartbeat for {} signed by {}, not {}", info.ens, signer, info.wallet);
+    }
+
+    if heartbeat.timestamp <= info.last_heartbeat {
+        return Ok(false);
+    }
+
+    info.last_heartbeat = heartbeat.timestamp;
+    info.status = heartbeat.status.clone();
+    Ok(true)
+}
+
+async fn show_provider_status(
+    provider_ens: &str,
+    json: bool,
+    store: &dyn Storage,
+    pool: &str,
+) -> Result<()> {
+    let state = with_heartbeats(store, ipfs::fetch_pool_state(store, pool).await?).await;
 
     let provider = state
         .active_providers
@@ -121,6 +266,8 @@ async fn show_provider_status(provider_ens: &str, json: bool, pool: &str) -> Res
         "busy" => "🟡",
         _ => "🔴",
     };
+    // Pool state is only a cache; the signed genesis is what proves the wallet
+    let genesis = ipfs::fetch_verified_genesis(store, provider_ens).await;
 
     println!("{}", "Provider Status".cyan().bold());
     println!();
@@ -130,6 +277,24 @@ async fn show_provider_status(provider_ens: &str, json: bool, pool: &str) -> Res
         provider.ens.green(),
         format!("({})", provider.status).bright_black()
     );
+    match &genesis {
+        Ok(g) if !g.wallet.eq_ignore_ascii_case(&provider.wallet) => {
+            println!("    {}", "✗ unverified".red().bold());
+            println!(
+                "    {}",
+                format!(
+                    "Pool state lists wallet {}, but the signed genesis names {}",
+                    provider.wallet, g.wallet
+                )
+                .red()
+            );
+        }
+        Ok(_) => println!("    {}", "✓ genesis verified".green()),
+        Err(e) => {
+            println!("    {}", "✗ unverified".red().bold());
+            println!("    {}", e.to_string().red());
+        }
+    }
     println!();
 
     println!("  {}", "Stats".bright_black());
@@ -142,13 +307,20 @@ async fn show_provider_status(provider_ens: &str, json: bool, pool: &str) -> Res
     println!(
         "    {} {}",
         "Total Earnings:".bright_black(),
-        format!("${:.2}", provider.total_earnings).green()
+        money::format_usdc_display(to_microunits(provider.total_earnings)).green()
     );
     println!(
         "    {} {}",
         "Available Balance:".bright_black(),
-        format!("${:.2}", provider.available_balance).yellow()
+        money::format_usdc_display(to_microunits(provider.available_balance)).yellow()
     );
+    if let Some(score) = provider.reputation {
+        println!(
+            "    {} {}",
+            "Reputation:".bright_black(),
+            format!("{:.1}/100", score).cyan()
+        );
+    }
     println!();
 
     println!("  {}", "Hardware".bright_black());
@@ -171,3 +343,185 @@ fn truncate_ens(ens: &str, max_len: usize) -> String {
         format!("{}...{}", &ens[..8], &ens[ens.len() - 8..])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::UsdcAmount;
+    use crate::test_support::{TEST_ADDRESS, TEST_KEY};
+    use std::collections::HashMap;
+
+    fn provider(ens: &str, status: &str, last_heartbeat: i64, jobs: u64) -> ProviderInfo {
+        ProviderInfo {
+            ens: ens.to_string(),
+            wallet: "0x1234567890123456789012345678901234567890".to_string(),
+            status: status.to_string(),
+            registered_at: 1704067200,
+            last_heartbeat,
+            gpus: vec!["RTX 5090".to_string()],
+            models: vec!["queenbee-spine".to_string()],
+            jobs_completed: jobs,
+            total_earnings: jobs as f64 * 0.25,
+            available_balance: 0.0,
+            reputation: None,
+        }
+    }
+
+    #[test]
+    fn test_network_status_json_snapshot() {
+        let now = 1704070800;
+        let providers: HashMap<String, ProviderInfo> = [
+            provider("alpha.swarmbee.eth", "online", now - 30, 12),
+            provider("beta.swarmbee.eth", "busy", now - 60, 40),
+            provider("gamma.swarmbee.eth", "online", now - 3600, 12),
+        ]
+        .into_iter()
+        .map(|p| (p.ens.clone(), p))
+        .collect();
+
+        let state = PoolState {
+            pool_id: "swarmpool.eth".to_string(),
+            version: "1.0.0".to_string(),
+            total_jobs: 64,
+            total_proofs: 70,
+            total_volume_usdc: 6.4,
+            current_epoch: Some("epoch-048".to_string()),
+            epoch_jobs: 4,
+            epoch_volume: 0.4,
+            pending_jobs: vec!["job-a".to_string(), "job-b".to_string()],
+            active_providers: providers,
+            last_updated: now - 10,
+            prev_state_hash: None,
+        };
+
+        let summary = network_summary(&state, None, now, 2);
+        let json = serde_json::to_string_pretty(&summary).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{
+  "pool_id": "swarmpool.eth",
+  "total_jobs": 64,
+  "total_volume": 6.4,
+  "active_providers": 3,
+  "online_providers": 2,
+  "pending_jobs": 2,
+  "current_epoch": "epoch-048",
+  "epoch_jobs": 4,
+  "epoch_time_remaining": null,
+  "epoch_volume": 0.4,
+  "top_providers": [
+    {
+      "ens": "beta.swarmbee.eth",
+      "status": "busy",
+      "online": true,
+      "jobs_completed": 40,
+      "total_earnings": 10.0
+    },
+    {
+      "ens": "alpha.swarmbee.eth",
+      "status": "online",
+      "online": true,
+      "jobs_completed": 12,
+      "total_earnings": 3.0
+    }
+  ],
+  "last_updated": 1704070790
+}"#
+        );
+    }
+
+    async fn signed_heartbeat(ens: &str, status: &str, timestamp: i64) -> HeartbeatSnapshot {
+        let mut heartbeat = HeartbeatSnapshot {
+            snapshot_type: "heartbeat".to_string(),
+            version: "1.0.0".to_string(),
+            provider: ens.to_string(),
+            status: status.to_string(),
+            models: vec!["queenbee-spine".to_string()],
+            gpus: vec![],
+            timestamp,
+            sig: None,
+        };
+        heartbeat.sig = Some(crypto::sign_snapshot(&heartbeat, TEST_KEY).await.unwrap());
+        heartbeat
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_record_read_path() {
+        let now = 1704070800;
+        let mut alpha = ProviderInfo {
+            wallet: TEST_ADDRESS.to_string(),
+            ..provider("alpha.swarmbee.eth", "online", now - 3600, 12)
+        };
+
+        let state = |p: &ProviderInfo| PoolState {
+            pool_id: "swarmpool.eth".to_string(),
+            version: "1.0.0".to_string(),
+            total_jobs: 12,
+            total_proofs: 12,
+            total_volume_usdc: 1.2,
+            current_epoch: None,
+            epoch_jobs: 0,
+            epoch_volume: 0.0,
+            pending_jobs: vec![],
+            active_providers: [(p.ens.clone(), p.clone())].into_iter().collect(),
+            last_updated: now,
+            prev_state_hash: None,
+        };
+        assert_eq!(network_summary(&state(&alpha), None, now, 5).stats.online_providers, 0);
+
+        // A fresh signed record brings the provider back online
+        let heartbeat = signed_heartbeat("alpha.swarmbee.eth", "watching", now - 20).await;
+        assert!(apply_heartbeat(&mut alpha, &heartbeat).unwrap());
+        assert_eq!(alpha.last_heartbeat, now - 20);
+        assert_eq!(alpha.status, "watching");
+        assert_eq!(network_summary(&state(&alpha), None, now, 5).stats.online_providers, 1);
+
+        // Older records don't roll it back
+        let stale = signed_heartbeat("alpha.swarmbee.eth", "offline", now - 600).await;
+        assert!(!apply_heartbeat(&mut alpha, &stale).unwrap());
+        assert_eq!(alpha.status, "watching");
+
+        // Records signed by anyone but the provider's wallet are rejected
+        let mut forged = signed_heartbeat("alpha.swarmbee.eth", "online", now).await;
+        forged.timestamp += 1;
+        assert!(apply_heartbeat(&mut alpha, &forged).is_err());
+
+        let mut other = provider("beta.swarmbee.eth", "online", now - 3600, 0);
+        let beta = signed_heartbeat("beta.swarmbee.eth", "online", now).await;
+        assert!(apply_heartbeat(&mut other, &beta).is_err());
+    }
+
+    #[test]
+    fn test_epoch_time_remaining_format() {
+        assert_eq!(format_time_remaining(2 * 3600 + 15 * 60 + 59), "2h 15m");
+        assert_eq!(format_time_remaining(59), "0h 0m");
+        assert_eq!(format_time_remaining(-1), "sealing overdue");
+
+        let epoch = EpochSnapshot {
+            snapshot_type: "epoch".to_string(),
+            version: "1.0.0".to_string(),
+            epoch_id: "epoch-048".to_string(),
+            name: "Golf".to_string(),
+            status: "active".to_string(),
+            started_at: 1704067200,
+            duration_secs: Some(7200),
+            ended_at: None,
+            jobs_count: 0,
+            proofs_count: 0,
+            proofs: vec![],
+            total_volume_usdc: UsdcAmount::from_microunits(0),
+            merkle_root: None,
+            inclusion_paths: Default::default(),
+            settlements: None,
+            splits: None,
+            controller: "merlin.swarmos.eth".to_string(),
+            timestamp: 1704067200,
+            state_link: None,
+            processed_withdrawals: vec![],
+            sig: None,
+        };
+        assert_eq!(epoch.time_remaining(1704067200 + 1800), 5400);
+        assert_eq!(epoch.time_remaining(1704067200 + 7300), -100);
+    }
+}
@@ -2,54 +2,163 @@
 
 use anyhow::{bail, Context, Result};
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Duration;
+use std::path::Path;
+use tracing::{info_span, Instrument};
 
+use crate::config;
 use crate::crypto;
 use crate::ipfs;
-use crate::models::{JobParams, JobSnapshot, Payment};
+use crate::storage::Storage;
+use crate::models::{
+    has_dicom_magic, to_microunits, InputType, JobParams, JobSnapshot, Payment, PricingConfig,
+};
+use crate::money;
+use crate::nonces;
+use crate::report::ReportFormat;
+use crate::say;
+use crate::schema::{self, SchemaType};
+use crate::ui;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn execute(
     file: Option<String>,
     model: String,
     input: String,
+    series: bool,
     client: String,
+    payment: String,
+    token: String,
+    confidence_threshold: f64,
+    output_format: String,
+    strict: bool,
+    no_confirm: bool,
     key: Option<String>,
+    store: &dyn Storage,
     pool: &str,
 ) -> Result<()> {
-    println!("{}", "Submitting job to SwarmPool".cyan().bold());
-    println!();
+    say!("{}", "Submitting job to SwarmPool".cyan().bold());
+    say!();
 
     // Get private key
-    let private_key = key
-        .or_else(|| std::env::var("SWARM_PRIVATE_KEY").ok())
-        .context("Private key required. Use --key or set SWARM_PRIVATE_KEY")?;
+    let private_key = crypto::resolve_signer(key)?;
 
     // Show job details
-    println!("  {} {}", "Model:".bright_black(), model.green());
-    println!("  {} {}", "Input:".bright_black(), input);
-    println!("  {} {}", "Client:".bright_black(), client);
-    println!("  {} {}", "Pool:".bright_black(), pool);
-    println!();
+    say!("  {} {}", "Model:".bright_black(), model.green());
+    say!("  {} {}", "Input:".bright_black(), input);
+    say!("  {} {}", "Client:".bright_black(), client);
+    say!("  {} {} {}", "Payment:".bright_black(), payment, token);
+    say!("  {} {}", "Pool:".bright_black(), pool);
+    say!();
+
+    let amount = parse_amount(&payment)?;
+    let params = build_params(&model, confidence_threshold, &output_format)?;
+    parse_token(&token)?;
 
-    // Upload input to IPFS if it's a file path
-    let input_cid = if input.starts_with("bafy") || input.starts_with("Qm") {
-        input.clone()
+    // A directory is only accepted as an explicit series
+    let is_cid = input.starts_with("bafy") || input.starts_with("Qm");
+    let manifest = if is_cid {
+        None
+    } else if Path::new(&input).is_dir() {
+        if !series {
+            bail!("{} is a directory. Use --series to upload it as one input.", input);
+        }
+        let files = series_manifest(Path::new(&input))?;
+        if files.is_empty() {
+            bail!("No files found in {}", input);
+        }
+        say!("  {} {} files", "Series:".bright_black(), files.len());
+        say!();
+        Some(files)
     } else {
-        let pb = ProgressBar::new_spinner();
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.cyan} {msg}")
-                .unwrap(),
-        );
-        pb.set_message("Uploading input to IPFS...");
-        pb.enable_steady_tick(Duration::from_millis(100));
+        if series {
+            bail!("--series requires a directory input");
+        }
+        None
+    };
+
+    let pricing = config::load_config()?.pricing;
+
+    // Preflight: refuse (or warn about) payments no miner would claim.
+    // Only local inputs can be sized before upload.
+    if !is_cid {
+        let input_bytes = match &manifest {
+            Some(files) => files.iter().map(|f| f.size).sum(),
+            None => std::fs::metadata(&input)
+                .with_context(|| format!("Failed to stat input file {}", input))?
+                .len(),
+        };
+
+        if let Some(warning) = check_payment(amount, input_bytes, &model, &pricing, strict)? {
+            println!("  {} {}", "⚠".yellow(), warning.yellow());
+            say!();
+        }
+    }
+
+    // Upload input to IPFS if it's a file path. Local inputs also get a
+    // sha256 and an input type in the job so `prove` can check what it
+    // fetched.
+    let (input_cid, input_sha256, input_type) = if is_cid {
+        (input.clone(), None, None)
+    } else if let Some(files) = &manifest {
+        let pb = ui::spinner(format!("Uploading {} files...", files.len()));
 
-        let cid = ipfs::upload_file(&input).await?;
-        pb.finish_with_message(format!("Uploaded: {}", cid.green()));
-        cid
+        let mut contents = Vec::with_capacity(files.len());
+        for file in files {
+            let bytes = tokio::fs::read(Path::new(&input).join(&file.path))
+                .await
+                .with_context(|| format!("Failed to read {}", file.path))?;
+            contents.push((file.path.clone(), bytes));
+        }
+        let input_type = match series_type(&contents) {
+            Ok(input_type) => input_type,
+            Err(e) => {
+                pb.done(format!("{} Series rejected", "✗".red()));
+                return Err(e);
+            }
+        };
+        let digest = crypto::series_sha256(&contents);
+        let cid = store
+            .put_dir(contents)
+            .instrument(info_span!("upload", input = %input))
+            .await?;
+        pb.done(format!("Uploaded series: {}", cid.green()));
+        (cid, Some(digest), Some(input_type))
+    } else {
+        let pb = ui::spinner("Uploading input...");
+
+        let file_name = Path::new(&input)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("file")
+            .to_string();
+        let bytes = tokio::fs::read(&input).await.context("Failed to read file")?;
+        let input_type = match is_dicom_file(&file_name, &bytes) {
+            Ok(true) => InputType::Dicom,
+            Ok(false) => InputType::File,
+            Err(e) => {
+                pb.done(format!("{} Input rejected", "✗".red()));
+                return Err(e);
+            }
+        };
+        let digest = crypto::file_sha256(&bytes);
+        let cid = store
+            .put_bytes(bytes, &file_name)
+            .instrument(info_span!("upload", input = %input))
+            .await?;
+        pb.done(format!("Uploaded: {}", cid.green()));
+        (cid, Some(digest), Some(input_type))
     };
 
+    // The same input submitted twice by the same client is usually a mistake
+    // that would be paid for twice
+    if pricing.dedup_window_secs > 0 {
+        if let Some((existing_cid, existing)) =
+            ipfs::find_recent_job(store, &input_cid, &client, pricing.dedup_window_secs).await?
+        {
+            confirm_duplicate(&existing_cid, &existing, no_confirm)?;
+        }
+    }
+
     // Create job snapshot
     let job_id = format!(
         "job-{}-{}",
@@ -58,93 +167,528 @@ pub async fn execute(
     );
 
     let timestamp = chrono::Utc::now().timestamp();
-    let nonce = crypto::random_hex(16);
+    let mut job = build_job(job_id.clone(), &model, input_cid, &client, &payment, timestamp);
+    job.input_files = manifest.as_ref().map(|files| files.len() as u64);
+    job.input_sha256 = input_sha256;
+    job.input_type = input_type;
+    job.payment.token = token;
+    job.params = params;
+
+    let job_cid = publish_job(&mut job, &private_key, store, pool).await?;
+
+    // Summary
+    say!();
+    say!("{}", "Job Submitted Successfully".green().bold());
+    say!();
+    println!("  {} {}", "Job ID:".bright_black(), job_id.cyan());
+    println!("  {} {}", "CID:".bright_black(), job_cid);
+    say!();
+    say!(
+        "  {}",
+        "Waiting for a compute provider to process...".bright_black()
+    );
+    say!(
+        "  {}",
+        format!("Check status: swarm status --job {}", job_cid).bright_black()
+    );
+
+    Ok(())
+}
+
+/// Sign `job`, write it to /swarmpool/jobs/ and announce it to `pool`,
+/// returning its CID. Shared by `submit` and `resubmit`.
+pub async fn publish_job(
+    job: &mut JobSnapshot,
+    private_key: &str,
+    store: &dyn Storage,
+    pool: &str,
+) -> Result<String> {
+    // Sign job
+    let pb = ui::spinner("Signing job...");
+
+    job.sig = Some(
+        crypto::sign_snapshot(&*job, private_key)
+            .instrument(info_span!("sign", job_id = %job.job_id))
+            .await?,
+    );
+    pb.done(format!("{} Job signed", "✓".green()));
+    schema::check_before_publish(&*job, SchemaType::Job)?;
+    nonces::check(&job.client, &job.nonce)?;
+
+    // Write job to canonical IPFS path: /swarmpool/jobs/{job_id}.json
+    let pb = ui::spinner("Publishing to IPFS mempool...");
+
+    let publish = info_span!("publish", job_id = %job.job_id, cid = tracing::field::Empty);
+    let job_cid = ipfs::write_job(store, &job.job_id, &*job)
+        .instrument(publish.clone())
+        .await?;
+    publish.record("cid", job_cid.as_str());
+    nonces::record_published(store, &job.client, &job.nonce, job.timestamp);
+    pb.done(format!("{} Published: {}", "✓".green(), job_cid.cyan()));
+
+    // Announce to pool (via IPFS pubsub or Redis signal)
+    let pb = ui::spinner("Announcing to pool...");
+
+    store.announce(
+        &format!("/{}/jobs", pool),
+        &serde_json::json!({
+            "cid": job_cid,
+            "client": job.client,
+            "model": job.model,
+            "timestamp": job.timestamp
+        }),
+    )
+    .instrument(publish)
+    .await?;
+
+    pb.done(format!("{} Announced to {}", "✓".green(), pool));
+
+    Ok(job_cid)
+}
+
+/// Show the earlier job and ask whether to submit again anyway. Under
+/// `--no-confirm` there is nobody to ask, so the duplicate is refused.
+/// Shared by `submit` and `resubmit`.
+pub fn confirm_duplicate(
+    existing_cid: &str,
+    existing: &JobSnapshot,
+    no_confirm: bool,
+) -> Result<()> {
+    let age = chrono::Utc::now().timestamp() - existing.timestamp;
+    println!(
+        "  {} {} already submitted this input {} min ago",
+        "⚠".yellow(),
+        existing.client,
+        age / 60
+    );
+    println!("  {} {}", "Job ID:".bright_black(), existing.job_id);
+    println!("  {} {}", "CID:".bright_black(), existing_cid.cyan());
+    println!();
+
+    if no_confirm {
+        bail!(
+            "Duplicate of job {} ({}). Check it with: swarm status --job {}",
+            existing.job_id,
+            existing_cid,
+            existing_cid
+        );
+    }
+
+    let proceed = dialoguer::Confirm::new()
+        .with_prompt("Submit (and pay for) it again?")
+        .default(false)
+        .interact()
+        .unwrap_or(false);
+    if !proceed {
+        bail!("Not submitted: duplicate of job {} ({})", existing.job_id, existing_cid);
+    }
+    println!();
+    Ok(())
+}
+
+/// One file of a series input
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesFile {
+    /// Path relative to the series root, `/`-separated
+    pub path: String,
+    pub size: u64,
+}
+
+/// Every file under `dir`, recursively, sorted by relative path
+///
+/// Hidden entries (`.DS_Store`, `.git`, ...) are skipped so the same series
+/// always produces the same directory CID.
+pub fn series_manifest(dir: &Path) -> Result<Vec<SeriesFile>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let entries = std::fs::read_dir(&current)
+            .with_context(|| format!("Failed to read directory {}", current.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() {
+                let relative = path
+                    .strip_prefix(dir)?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push(SeriesFile {
+                    path: relative,
+                    size: entry.metadata()?.len(),
+                });
+            }
+        }
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Whether an input file is DICOM: it carries the "DICM" magic. A file named
+/// `.dcm`/`.dicom` without it is rejected rather than uploaded.
+pub fn is_dicom_file(name: &str, bytes: &[u8]) -> Result<bool> {
+    if has_dicom_magic(bytes) {
+        return Ok(true);
+    }
+    let claims_dicom = Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("dcm") || ext.eq_ignore_ascii_case("dicom"));
+    if claims_dicom {
+        bail!("{} is named as DICOM but has no DICM header at offset 128", name);
+    }
+    Ok(false)
+}
+
+/// `DicomSeries` when every file of a series is DICOM, `Series` otherwise
+pub fn series_type(files: &[(String, Vec<u8>)]) -> Result<InputType> {
+    let mut all_dicom = true;
+    for (name, bytes) in files {
+        all_dicom &= is_dicom_file(name, bytes)?;
+    }
+    Ok(if all_dicom {
+        InputType::DicomSeries
+    } else {
+        InputType::Series
+    })
+}
 
-    let mut job = JobSnapshot {
+/// Compare a payment against the pricing heuristic for this workload
+///
+/// Returns a warning suggesting a payment when it is implausibly low, or an
+/// error instead under `strict`.
+pub fn check_payment(
+    amount: f64,
+    input_bytes: u64,
+    model: &str,
+    pricing: &PricingConfig,
+    strict: bool,
+) -> Result<Option<String>> {
+    let minimum = pricing.min_payment(model, input_bytes);
+    if amount >= minimum {
+        return Ok(None);
+    }
+
+    // Round the suggestion up to whole cents
+    let suggested = to_microunits(minimum).div_ceil(10_000) * 10_000;
+    let message = format!(
+        "Payment {} is low for a {:.2} GB {} job; miners are unlikely to claim it. Suggested: --payment {}",
+        money::format_usdc_display(to_microunits(amount)),
+        input_bytes as f64 / 1_000_000_000.0,
+        model,
+        money::format_usdc(suggested, 2)
+    );
+    if strict {
+        bail!("{} (refused under --strict)", message);
+    }
+    Ok(Some(message))
+}
+
+/// Payment amount as given on the command line, checked against the JOB
+/// schema's `payment.amount` pattern so negative, signed or exponent forms
+/// never reach signing
+pub fn parse_amount(amount: &str) -> Result<f64> {
+    schema::validate_field(SchemaType::Job, "payment.amount", &serde_json::json!(amount))
+        .with_context(|| format!("Invalid payment amount: {}", amount))?;
+    amount
+        .parse()
+        .ok()
+        .filter(|a: &f64| a.is_finite() && *a > 0.0)
+        .with_context(|| format!("Invalid payment amount: {}", amount))
+}
+
+/// `--token`, checked against the tokens the JOB schema allows. Seal sums
+/// every job's amount into one settlement, so only USDC is accepted for now.
+pub fn parse_token(token: &str) -> Result<()> {
+    schema::validate_field(SchemaType::Job, "payment.token", &serde_json::json!(token))
+        .with_context(|| format!("Unsupported payment token: {:?}. Use USDC", token))
+}
+
+/// Job params from `--confidence-threshold` and `--output-format`, checked
+/// against the JOB schema and the model's supported formats
+pub fn build_params(
+    model: &str,
+    confidence_threshold: f64,
+    output_format: &str,
+) -> Result<JobParams> {
+    schema::validate_field(
+        SchemaType::Job,
+        "params.confidence_threshold",
+        // As a decimal string, the way a job carries it (`models::decimal`)
+        &serde_json::json!(confidence_threshold.to_string()),
+    )
+    .with_context(|| {
+        format!(
+            "Invalid confidence threshold: {}. Use a value from 0.0 to 1.0",
+            confidence_threshold
+        )
+    })?;
+    let format: ReportFormat = output_format
+        .parse()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
+    format.ensure_supported(model)?;
+
+    Ok(JobParams {
+        confidence_threshold,
+        output_format: format.as_str().to_string(),
+        deadline: None,
+    })
+}
+
+/// Assemble an unsigned job snapshot with the default params
+pub fn build_job(
+    job_id: String,
+    model: &str,
+    input_cid: String,
+    client: &str,
+    payment: &str,
+    timestamp: i64,
+) -> JobSnapshot {
+    JobSnapshot {
         snapshot_type: "job".to_string(),
         version: "1.0.0".to_string(),
-        job_id: job_id.clone(),
+        job_id,
         job_type: format!("{}-inference", model),
-        model: model.clone(),
+        model: model.to_string(),
         input_cid,
+        input_files: None,
+        input_sha256: None,
+        input_type: None,
         params: JobParams {
             confidence_threshold: 0.6,
             output_format: "pdf".to_string(),
+            deadline: None,
         },
         payment: Payment {
-            amount: "0.10".to_string(),
+            amount: payment.to_string(),
             token: "USDC".to_string(),
         },
-        client: client.clone(),
+        client: client.to_string(),
         timestamp,
-        nonce,
+        nonce: crypto::random_hex(16),
         sig: None,
-    };
+    }
+}
 
-    // Sign job
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Signing job...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{self, SchemaType};
+    use crate::test_support::{self, TEST_KEY};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing_subscriber::layer::{Context as LayerContext, SubscriberExt};
+    use tracing_subscriber::Layer;
 
-    job.sig = Some(crypto::sign_snapshot(&job, &private_key).await?);
-    pb.finish_with_message(format!("{} Job signed", "✓".green()));
+    /// Span ID, name and recorded `field=value` pairs
+    type RecordedSpan = (Id, String, Vec<String>);
 
-    // Write job to canonical IPFS path: /swarmpool/jobs/{job_id}.json
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Publishing to IPFS mempool...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    /// Test subscriber layer recording each span as `name{field=value ...}`
+    #[derive(Clone, Default)]
+    struct SpanRecorder(Arc<Mutex<Vec<RecordedSpan>>>);
 
-    let job_cid = ipfs::write_job(&job_id, &job).await?;
-    pb.finish_with_message(format!("{} Published: {}", "✓".green(), job_cid.cyan()));
+    struct Fields<'a>(&'a mut Vec<String>);
 
-    // Announce to pool (via IPFS pubsub or Redis signal)
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Announcing to pool...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.push(format!("{}={:?}", field.name(), value));
+        }
 
-    ipfs::pubsub_publish(
-        &format!("/{}/jobs", pool),
-        &serde_json::json!({
-            "cid": job_cid,
-            "client": client,
-            "model": model,
-            "timestamp": timestamp
-        }),
-    )
-    .await?;
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push(format!("{}={}", field.name(), value));
+        }
+    }
 
-    pb.finish_with_message(format!("{} Announced to {}", "✓".green(), pool));
+    impl<S: tracing::Subscriber> Layer<S> for SpanRecorder {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _ctx: LayerContext<'_, S>) {
+            let mut fields = Vec::new();
+            attrs.record(&mut Fields(&mut fields));
+            let name = attrs.metadata().name().to_string();
+            self.0.lock().unwrap().push((id.clone(), name, fields));
+        }
 
-    // Summary
-    println!();
-    println!("{}", "Job Submitted Successfully".green().bold());
-    println!();
-    println!("  {} {}", "Job ID:".bright_black(), job_id.cyan());
-    println!("  {} {}", "CID:".bright_black(), job_cid);
-    println!();
-    println!(
-        "  {}",
-        "Waiting for a compute provider to process...".bright_black()
-    );
-    println!(
-        "  {}",
-        format!("Check status: swarm status --job {}", job_cid).bright_black()
-    );
+        fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: LayerContext<'_, S>) {
+            let mut spans = self.0.lock().unwrap();
+            if let Some((_, _, fields)) = spans.iter_mut().find(|(span, _, _)| span == id) {
+                values.record(&mut Fields(fields));
+            }
+        }
+    }
 
-    Ok(())
+    impl SpanRecorder {
+        fn spans(&self) -> Vec<String> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(_, name, fields)| format!("{}{{{}}}", name, fields.join(" ")))
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_emits_phase_spans() {
+        let recorder = SpanRecorder::default();
+        let _guard = tracing::subscriber::set_default(
+            tracing_subscriber::registry().with(recorder.clone()),
+        );
+
+        let (_root, store) = test_support::fs_store();
+
+        execute(
+            None,
+            "queenbee-spine".to_string(),
+            "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+            false,
+            "client.alice.eth".to_string(),
+            "0.10".to_string(),
+            "USDC".to_string(),
+            0.6,
+            "pdf".to_string(),
+            false,
+            false,
+            Some(TEST_KEY.to_string()),
+            &store,
+            "swarmpool.eth",
+        )
+        .await
+        .unwrap();
+
+        let spans = recorder.spans();
+        let sign = spans.iter().find(|s| s.starts_with("sign{")).unwrap();
+        assert!(sign.starts_with("sign{job_id=job-"), "{}", sign);
+
+        // The publish span gains the job's CID once it is written
+        let publish = spans.iter().find(|s| s.starts_with("publish{")).unwrap();
+        assert!(publish.contains("job_id=job-"), "{}", publish);
+        assert!(publish.contains(" cid="), "{}", publish);
+
+        // Input given as a CID: nothing to upload
+        assert!(!spans.iter().any(|s| s.starts_with("upload{")));
+    }
+
+    #[test]
+    fn test_built_job_matches_schema() {
+        let mut job = build_job(
+            "job-20250101000000-abcd1234".to_string(),
+            "queenbee-spine",
+            "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+            "client.alice.eth",
+            "0.10",
+            1704067200,
+        );
+        job.sig = Some(format!("0x{}", "a".repeat(130)));
+
+        let data = serde_json::to_value(&job).unwrap();
+        schema::validate(&data, SchemaType::Job).unwrap();
+        assert_eq!(data["job_type"], "queenbee-spine-inference");
+    }
+
+    #[test]
+    fn test_series_manifest_recurses_sorted() {
+        let dir = test_support::temp_dir();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("series-2")).unwrap();
+        std::fs::create_dir_all(root.join("series-1/sub")).unwrap();
+        std::fs::write(root.join("series-2/IM0001.dcm"), b"bb").unwrap();
+        std::fs::write(root.join("series-1/IM0001.dcm"), b"a").unwrap();
+        std::fs::write(root.join("series-1/sub/IM0002.dcm"), b"ccc").unwrap();
+        std::fs::write(root.join(".DS_Store"), b"junk").unwrap();
+
+        let manifest = series_manifest(root).unwrap();
+        let paths: Vec<&str> = manifest.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["series-1/IM0001.dcm", "series-1/sub/IM0002.dcm", "series-2/IM0001.dcm"]
+        );
+        assert_eq!(manifest.iter().map(|f| f.size).sum::<u64>(), 6);
+    }
+
+    #[test]
+    fn test_dicom_inputs_need_the_magic() {
+        let mut dicom = vec![0u8; 128];
+        dicom.extend_from_slice(b"DICM\x02\x00");
+
+        assert!(is_dicom_file("scan.dcm", &dicom).unwrap());
+        // DICOM slices often have no extension; the header is what counts
+        assert!(is_dicom_file("IM0001", &dicom).unwrap());
+        assert!(!is_dicom_file("notes.txt", b"plain text").unwrap());
+
+        let err = is_dicom_file("scan.DICOM", b"plain text").unwrap_err();
+        assert!(err.to_string().contains("no DICM header"), "{}", err);
+        assert!(is_dicom_file("short.dcm", b"DICM").is_err());
+
+        let slice = |name: &str| (name.to_string(), dicom.clone());
+        let study = [slice("IM0001.dcm"), slice("IM0002.dcm")];
+        assert_eq!(series_type(&study).unwrap(), InputType::DicomSeries);
+
+        let mixed = [slice("IM0001.dcm"), ("report.txt".to_string(), b"text".to_vec())];
+        assert_eq!(series_type(&mixed).unwrap(), InputType::Series);
+
+        let forged = [slice("IM0001.dcm"), ("IM0002.dcm".to_string(), b"text".to_vec())];
+        assert!(series_type(&forged).is_err());
+    }
+
+    #[test]
+    fn test_underpriced_job_refused_under_strict() {
+        let pricing = PricingConfig::default();
+        let five_gb = 5_000_000_000;
+
+        // $0.05 base + 5 GB x $0.10 = $0.55 minimum
+        let err = check_payment(0.10, five_gb, "queenbee-spine", &pricing, true).unwrap_err();
+        assert!(err.to_string().contains("--payment 0.55"));
+
+        let warning = check_payment(0.10, five_gb, "queenbee-spine", &pricing, false).unwrap();
+        assert!(warning.unwrap().contains("Suggested"));
+
+        assert!(check_payment(0.60, five_gb, "queenbee-spine", &pricing, true)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_parse_amount_follows_schema_pattern() {
+        assert_eq!(parse_amount("5.00").unwrap(), 5.0);
+        assert_eq!(parse_amount("12").unwrap(), 12.0);
+        for bad in ["-1.00", "abc", "1e3", "+5", "", "0", "0.00"] {
+            let err = parse_amount(bad).unwrap_err();
+            assert!(err.to_string().contains("Invalid payment amount"), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_only_settled_tokens_accepted() {
+        assert!(parse_token("USDC").is_ok());
+        for bad in ["DAI", "usdc", "", "USDC "] {
+            let err = parse_token(bad).unwrap_err();
+            assert!(err.to_string().contains("Unsupported payment token"), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_params_reject_out_of_range_threshold() {
+        let params = build_params("queenbee-spine", 0.9, "JSON").unwrap();
+        assert_eq!(params.confidence_threshold, 0.9);
+        assert_eq!(params.output_format, "json");
+        assert!(build_params("queenbee-spine", 0.0, "pdf").is_ok());
+        assert!(build_params("queenbee-spine", 1.0, "pdf").is_ok());
+
+        for bad in [-0.1, 1.01, f64::NAN] {
+            let err = build_params("queenbee-spine", bad, "pdf").unwrap_err();
+            assert!(err.to_string().contains("Invalid confidence threshold"), "{}", bad);
+        }
+        let err = build_params("queenbee-spine", 0.6, "docx").unwrap_err();
+        assert!(err.to_string().contains("Unsupported output format"), "{}", err);
+    }
 }
@@ -14,7 +14,7 @@ pub async fn execute(file: String, schema: String) -> Result<()> {
 
     // Parse schema type
     let schema_type = SchemaType::from_str(&schema)
-        .context(format!("Unknown schema type: {}. Use: genesis, job, claim, proof, epoch", schema))?;
+        .context(format!("Unknown schema type: {}. Use: genesis, job, claim, proof, epoch, heartbeat, withdrawal", schema))?;
 
     println!("  {} {}", "File:".bright_black(), file);
     println!("  {} {:?}", "Schema:".bright_black(), schema);
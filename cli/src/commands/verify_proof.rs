@@ -0,0 +1,246 @@
+//! verify-proof command - check a proof's inclusion in a sealed epoch
+//!
+//! Recomputes the proof's leaf from its `proof_hash` and hashes it up the
+//! epoch's inclusion path. The proof was settled in that epoch only if this
+//! rebuilds the published `merkle_root`. When the seal was published without
+//! `--inclusion-paths`, the path is rebuilt from the epoch's proof list.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+
+use crate::crypto::merkle::{self, Hash};
+use crate::ipfs;
+use crate::models::{EpochSnapshot, ProofSnapshot};
+use crate::storage::Storage;
+use crate::ui;
+
+/// Outcome of walking a proof's inclusion path
+#[derive(Debug, Clone, PartialEq)]
+pub struct InclusionCheck {
+    /// The epoch's published root
+    pub expected_root: String,
+    /// Root rebuilt from the proof's leaf, or `None` when the epoch holds no
+    /// path for this proof
+    pub recovered_root: Option<String>,
+    pub valid: bool,
+}
+
+pub async fn execute(
+    proof_cid: String,
+    epoch_id: String,
+    store: &dyn Storage,
+    _pool: &str,
+) -> Result<()> {
+    println!("{}", "Verifying Proof Inclusion".cyan().bold());
+    println!();
+
+    let pb = ui::spinner(format!("Fetching {} and {}...", proof_cid, epoch_id));
+
+    let proof: ProofSnapshot = store
+        .get(&proof_cid)
+        .await
+        .with_context(|| format!("Failed to fetch proof {}", proof_cid))?;
+    let path = format!("{}/{}.json", ipfs::paths::EPOCHS, epoch_id);
+    let epoch: EpochSnapshot = store
+        .read(&path)
+        .await
+        .with_context(|| format!("Failed to read {}", path))?;
+
+    let check = check_inclusion(store, &proof_cid, &proof, &epoch).await;
+    pb.clear();
+    let check = check?;
+
+    println!("  {} {}", "Proof:".bright_black(), proof_cid);
+    println!("  {} {}", "Job:".bright_black(), proof.job_id);
+    println!("  {} {}", "Provider:".bright_black(), proof.provider);
+    println!("  {} {}", "Epoch:".bright_black(), epoch_id.cyan());
+    println!("  {} {}", "Leaf:".bright_black(), proof.proof_hash);
+    println!();
+    println!(
+        "  {} {}",
+        "Expected root: ".bright_black(),
+        check.expected_root
+    );
+    println!(
+        "  {} {}",
+        "Recovered root:".bright_black(),
+        check
+            .recovered_root
+            .as_deref()
+            .unwrap_or("(proof not in epoch)")
+    );
+    println!();
+
+    if check.valid {
+        println!("{}", "✅ VALID".green().bold());
+        println!(
+            "  {}",
+            format!("Proof was settled in {}", epoch_id).bright_black()
+        );
+        Ok(())
+    } else {
+        println!("{}", "❌ INVALID".red().bold());
+        bail!("Proof {} is not included in {}", proof_cid, epoch_id);
+    }
+}
+
+/// Walk `proof`'s inclusion path in `epoch` back to its Merkle root
+///
+/// The path comes from the epoch's `inclusion_paths`, or is rebuilt from the
+/// epoch's proof list (fetching each proof's hash) when none was published.
+pub async fn check_inclusion(
+    store: &dyn Storage,
+    proof_cid: &str,
+    proof: &ProofSnapshot,
+    epoch: &EpochSnapshot,
+) -> Result<InclusionCheck> {
+    if epoch.status != "sealed" {
+        bail!(
+            "Epoch {} is {}, not sealed. Wait for seal before verifying inclusion.",
+            epoch.epoch_id,
+            epoch.status
+        );
+    }
+    let expected_root = epoch.merkle_root.clone().with_context(|| {
+        format!(
+            "Epoch {} has no merkle root. Wait for seal before verifying inclusion.",
+            epoch.epoch_id
+        )
+    })?;
+
+    let leaf = merkle::parse_hash(&proof.proof_hash)?;
+    let path = match epoch.inclusion_paths.get(proof_cid) {
+        Some(path) => Some(
+            path.iter()
+                .map(|h| merkle::parse_hash(h))
+                .collect::<Result<Vec<Hash>>>()?,
+        ),
+        None => rebuild_path(store, proof_cid, epoch).await?,
+    };
+
+    let recovered_root = path.map(|path| merkle::to_hex(&merkle::root_from_path(&leaf, &path)));
+    let valid = recovered_root.as_deref() == Some(expected_root.as_str());
+    Ok(InclusionCheck {
+        expected_root,
+        recovered_root,
+        valid,
+    })
+}
+
+/// Inclusion path from the epoch's proof list, or `None` if the proof isn't in it
+async fn rebuild_path(
+    store: &dyn Storage,
+    proof_cid: &str,
+    epoch: &EpochSnapshot,
+) -> Result<Option<Vec<Hash>>> {
+    let Some(index) = epoch.proofs.iter().position(|cid| cid == proof_cid) else {
+        return Ok(None);
+    };
+
+    let mut leaves = Vec::with_capacity(epoch.proofs.len());
+    for cid in &epoch.proofs {
+        let proof: ProofSnapshot = store
+            .get(cid)
+            .await
+            .with_context(|| format!("Failed to fetch proof {}", cid))?;
+        leaves.push(merkle::parse_hash(&proof.proof_hash)?);
+    }
+    Ok(Some(merkle::MerkleTree::from_leaves(&leaves).proof(index)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto;
+    use crate::test_support;
+    use serde_json::json;
+
+    fn proof(job_id: &str) -> ProofSnapshot {
+        serde_json::from_value(json!({
+            "type": "proof",
+            "version": "1.0.0",
+            "proof_id": format!("proof-{}", job_id),
+            "job_id": job_id,
+            "job_cid": format!("bafy{}", job_id),
+            "status": "completed",
+            "output_cid": "bafyoutput",
+            "metrics": {
+                "inference_seconds": 1.0,
+                "compute_seconds": 1.0,
+                "confidence": 0.9,
+                "model_version": "1.0.0"
+            },
+            "provider": "alpha.swarmbee.eth",
+            "timestamp": 1704067200,
+            "proof_hash": crypto::keccak256_hash(job_id.as_bytes())
+        }))
+        .unwrap()
+    }
+
+    fn epoch(status: &str, proofs: &[String], root: Option<String>) -> EpochSnapshot {
+        serde_json::from_value(json!({
+            "type": "epoch",
+            "version": "1.0.0",
+            "epoch_id": "epoch-001",
+            "name": "Alpha",
+            "status": status,
+            "started_at": 1704063600,
+            "jobs_count": proofs.len(),
+            "proofs": proofs,
+            "total_volume_usdc": "0.300000",
+            "merkle_root": root,
+            "controller": "merlin.swarmos.eth",
+            "timestamp": 1704067200
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_inclusion_with_and_without_stored_paths() {
+        let (_dir, fs) = test_support::fs_store();
+        let store: &dyn Storage = &fs;
+
+        let proofs: Vec<ProofSnapshot> = ["job-001", "job-002", "job-003"]
+            .iter()
+            .map(|id| proof(id))
+            .collect();
+        let mut cids = Vec::new();
+        for p in &proofs {
+            cids.push(store.put(p).await.unwrap());
+        }
+        let hashes: Vec<String> = proofs.iter().map(|p| p.proof_hash.clone()).collect();
+        let root = crypto::merkle_root(&hashes).unwrap();
+
+        // No stored paths: rebuilt from the epoch's proof list
+        let mut sealed = epoch("sealed", &cids, Some(root.clone()));
+        let check = check_inclusion(store, &cids[2], &proofs[2], &sealed)
+            .await
+            .unwrap();
+        assert!(check.valid);
+        assert_eq!(check.recovered_root.as_deref(), Some(root.as_str()));
+
+        // A stored path that doesn't lead to the root is INVALID
+        sealed
+            .inclusion_paths
+            .insert(cids[0].clone(), vec![hashes[2].clone()]);
+        let check = check_inclusion(store, &cids[0], &proofs[0], &sealed)
+            .await
+            .unwrap();
+        assert!(!check.valid);
+        assert_ne!(check.recovered_root.as_deref(), Some(root.as_str()));
+
+        // A proof the epoch never settled has no path at all
+        let stranger = proof("job-999");
+        let check = check_inclusion(store, "0xdeadbeef", &stranger, &sealed)
+            .await
+            .unwrap();
+        assert!(!check.valid);
+        assert_eq!(check.recovered_root, None);
+
+        let active = epoch("active", &[], None);
+        let err = check_inclusion(store, &cids[0], &proofs[0], &active)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Wait for seal"), "{}", err);
+    }
+}
@@ -0,0 +1,176 @@
+//! verify-sig command - Recover who signed a snapshot file
+//!
+//! Offline forensics: no network, no schema enforcement. The snapshot type
+//! is read from its `type` field only to pick which field names the claimed
+//! signer; the signature is recovered over the canonical payload (the file
+//! minus `sig`, see docs/SIGNING.md) for any type.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::Value;
+
+use crate::crypto;
+
+/// Outcome of recovering a snapshot's signer
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignatureReport {
+    pub snapshot_type: String,
+    /// Field naming the claimed signer, and its value
+    pub claimed: Option<(&'static str, String)>,
+    pub signer: String,
+}
+
+pub async fn execute(file: String) -> Result<()> {
+    println!("{}", "Verifying Signature".cyan().bold());
+    println!();
+    println!("  {} {}", "File:".bright_black(), file);
+
+    let content = std::fs::read_to_string(&file).context("Failed to read file")?;
+    let data: Value = serde_json::from_str(&content).context("Failed to parse JSON")?;
+
+    let report = recover(&data)?;
+
+    println!("  {} {}", "Type:".bright_black(), report.snapshot_type);
+    match &report.claimed {
+        Some((field, ens)) => {
+            println!("  {} {} ({})", "Claimed:".bright_black(), ens.cyan(), field)
+        }
+        None => println!(
+            "  {} {}",
+            "Claimed:".bright_black(),
+            "(no signer field)".bright_black()
+        ),
+    }
+    println!("  {} {}", "Signer:".bright_black(), report.signer.green());
+    println!();
+    println!(
+        "  {}",
+        "Signature recovers. Check the signer against the claimed name's registered wallet."
+            .bright_black()
+    );
+
+    Ok(())
+}
+
+/// Field holding the name that claims to have signed each snapshot type
+pub fn signer_field(snapshot_type: &str) -> Option<&'static str> {
+    match snapshot_type {
+        "genesis" | "provider-init" | "claim" | "proof" | "heartbeat" => Some("provider"),
+        "job" => Some("client"),
+        "epoch" => Some("controller"),
+        _ => None,
+    }
+}
+
+/// Recover the signer of any snapshot JSON
+pub fn recover(data: &Value) -> Result<SignatureReport> {
+    let object = data.as_object().context("Snapshot is not a JSON object")?;
+
+    let snapshot_type = object
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown")
+        .to_string();
+    let sig = object
+        .get("sig")
+        .and_then(Value::as_str)
+        .context("Snapshot has no sig field")?;
+
+    let mut unsigned = object.clone();
+    unsigned.remove("sig");
+    let signer = crypto::recover_snapshot_signer(&Value::Object(unsigned), sig)?;
+
+    let claimed = signer_field(&snapshot_type).and_then(|field| {
+        object
+            .get(field)
+            .and_then(Value::as_str)
+            .map(|ens| (field, ens.to_string()))
+    });
+
+    Ok(SignatureReport {
+        snapshot_type,
+        claimed,
+        signer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{TEST_ADDRESS, TEST_KEY};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_recovers_every_snapshot_type() {
+        let snapshots = [
+            (
+                "genesis",
+                json!({"type": "genesis", "provider": "miner.alice.eth", "wallet": TEST_ADDRESS}),
+                Some("miner.alice.eth"),
+            ),
+            (
+                "job",
+                json!({"type": "job", "client": "client.bob.eth", "payment": {"amount": "0.10"}}),
+                Some("client.bob.eth"),
+            ),
+            (
+                "claim",
+                json!({"type": "claim", "provider": "miner.alice.eth", "mode": "SOLO"}),
+                Some("miner.alice.eth"),
+            ),
+            (
+                "proof",
+                json!({"type": "proof", "provider": "miner.alice.eth", "metrics": {"confidence": "0.847"}}),
+                Some("miner.alice.eth"),
+            ),
+            (
+                "epoch",
+                json!({"type": "epoch", "controller": "merlin.swarmos.eth", "jobs_count": 3}),
+                Some("merlin.swarmos.eth"),
+            ),
+            (
+                "heartbeat",
+                json!({"type": "heartbeat", "provider": "miner.alice.eth", "status": "online"}),
+                Some("miner.alice.eth"),
+            ),
+            (
+                "dispute",
+                json!({"type": "dispute", "provider": "miner.alice.eth", "status": "open"}),
+                None,
+            ),
+        ];
+
+        for (snapshot_type, unsigned, claimed) in snapshots {
+            let signed = crypto::sign_json(&unsigned, TEST_KEY).await.unwrap();
+            let report = recover(&signed).unwrap();
+
+            assert_eq!(report.snapshot_type, snapshot_type);
+            assert_eq!(
+                report.signer.to_lowercase(),
+                TEST_ADDRESS,
+                "{}",
+                snapshot_type
+            );
+            assert_eq!(report.claimed.map(|(_, ens)| ens).as_deref(), claimed);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tampered_or_unsigned_snapshot() {
+        let signed = crypto::sign_json(&json!({"type": "job", "client": "a.eth"}), TEST_KEY)
+            .await
+            .unwrap();
+
+        // Any edit changes the payload, so a different address recovers
+        let mut tampered = signed.clone();
+        tampered["client"] = json!("b.eth");
+        assert_ne!(
+            recover(&tampered).unwrap().signer.to_lowercase(),
+            TEST_ADDRESS
+        );
+
+        let mut unsigned = signed;
+        unsigned.as_object_mut().unwrap().remove("sig");
+        assert!(recover(&unsigned).is_err());
+    }
+}
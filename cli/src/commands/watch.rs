@@ -1,19 +1,49 @@
 //! Watch command - Subscribe to job feed and monitor for claimable jobs
+//!
+//! With `--auto-prove`, jobs for watched models are claimed and proven as
+//! they appear, as many at once as the detected GPUs' VRAM allows; the rest
+//! wait in a queue (see `provider::VramScheduler`).
+//!
+//! Pending jobs are read from the jobs directory at startup and every minute
+//! after, skipping any already claimed or proven and any for other models;
+//! each job is shown once per session.
+//!
+//! Jobs for catalog models that need more VRAM than this box has are listed
+//! as "insufficient VRAM" and never claimed. `--vram-strategy` decides
+//! whether that's the largest single GPU (default) or all GPUs summed.
 
 use anyhow::{Context, Result};
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 use tokio::signal;
 
+use crate::commands::{claim, prove};
 use crate::config;
+use crate::crypto;
 use crate::ipfs;
+use crate::storage::Storage;
+use crate::models::{self, JobSnapshot};
+use crate::provider::{self, Placement, VramScheduler, VramStrategy};
+use crate::ui;
+
+/// How often the jobs directory is rescanned for pending jobs (seconds)
+const RESCAN_INTERVAL_SECS: u64 = 60;
 
 pub async fn execute(
     models: Option<String>,
     provider_override: Option<String>,
+    auto_prove: bool,
+    vram_strategy: String,
+    store: &dyn Storage,
+    ipfs_client: &ipfs::IpfsClient,
     pool: &str,
 ) -> Result<()> {
+    let vram_strategy: VramStrategy = vram_strategy
+        .parse()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
+
     // Load config
     let config = config::load_config()?;
 
@@ -25,32 +55,90 @@ pub async fn execute(
         .map(|m| m.split(',').map(|s| s.trim().to_string()).collect())
         .unwrap_or_else(|| config.models.clone());
 
+    // Heartbeats are signed; without a key the provider shows as offline
+    let heartbeat_key = std::env::var(crypto::PRIVATE_KEY_ENV)
+        .ok()
+        .map(|key| crypto::normalize_key(&key))
+        .transpose()?;
+
+    // Auto-prove signs claims and proofs, so it needs a key up front
+    let prove_key = if auto_prove {
+        Some(crypto::resolve_signer(None)?)
+    } else {
+        None
+    };
+    // Heartbeats name concrete models, so `queenbee-*` is expanded against
+    // the catalog
+    let mut served: Vec<String> = model_list
+        .iter()
+        .filter(|m| !m.ends_with('*'))
+        .cloned()
+        .collect();
+    for spec in models::MODEL_CATALOG {
+        if models::matches_any(&model_list, spec.name)
+            && !served.iter().any(|m| m.eq_ignore_ascii_case(spec.name))
+        {
+            served.push(spec.name.to_string());
+        }
+    }
+    let gpus = provider::detect_gpus();
+    let gpu_names = provider::gpu_names(&gpus);
+    let available_mb = vram_strategy.available_mb(&gpus);
+    let eligible: Vec<&str> = provider::model_requirements()
+        .into_iter()
+        .filter(|&(_, required)| required <= available_mb)
+        .map(|(name, _)| name)
+        .collect();
+    let mut scheduler = VramScheduler::new(&gpus);
+    let mut in_flight = FuturesUnordered::new();
+    let mut offered: HashSet<String> = HashSet::new();
+
     // Print startup banner
     println!("{}", "SwarmPool Job Watcher".cyan().bold());
     println!();
     println!("  {} {}", "Provider:".bright_black(), provider_ens.green());
     println!("  {} {}", "Pool:".bright_black(), pool);
     println!("  {} {:?}", "Models:".bright_black(), model_list);
+    println!(
+        "  {} {} ({} GB, {})",
+        "Eligible:".bright_black(),
+        if eligible.is_empty() {
+            "none".red().to_string()
+        } else {
+            eligible.join(", ")
+        },
+        available_mb / 1024,
+        vram_strategy
+    );
+    if auto_prove {
+        let total_mb: u64 = gpus.iter().map(|g| g.vram_mb).sum();
+        println!(
+            "  {} on, {} GPU(s), {} GB VRAM",
+            "Auto-prove:".bright_black(),
+            gpus.len(),
+            total_mb / 1024
+        );
+    }
     println!();
 
     // Connect to IPFS
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
+    let pb = ui::spinner("Connecting to IPFS...");
+    store.check_connection().await?;
+    pb.done(format!("{} Connected to IPFS", "✓".green()));
 
-    pb.set_message("Connecting to IPFS...");
-    pb.enable_steady_tick(Duration::from_millis(100));
-    ipfs::check_connection().await?;
-    pb.finish_with_message(format!("{} Connected to IPFS", "✓".green()));
+    // Subscribe to job feed. The stream resubscribes by itself if the
+    // daemon restarts, so it is only read, never re-opened here.
+    let topic = format!("/{}/jobs", pool);
+    let mut feed = Box::pin(ipfs_client.pubsub_stream(&topic));
+    println!("{} Subscribed to {}", "✓".green(), topic);
 
-    // Subscribe to job feed
-    pb.set_message("Subscribing to job feed...");
-    pb.enable_steady_tick(Duration::from_millis(100));
-    ipfs::pubsub_subscribe(&format!("/{}/jobs", pool)).await?;
-    pb.finish_with_message(format!("{} Subscribed to /{}/jobs", "✓".green(), pool));
+    // Jobs already pending when the watcher starts were announced before we
+    // subscribed, so they come from the jobs directory; it is rescanned
+    // periodically in case an announcement is missed. A scan reads the whole
+    // directory, so it runs alongside the loop rather than holding it up.
+    let mut scans = FuturesUnordered::new();
+    scans.push(unclaimed_jobs(store, &model_list));
+    let mut batch: Vec<String> = Vec::new();
 
     // Print ready message
     println!();
@@ -69,68 +157,316 @@ pub async fn execute(
     println!("{}", "━".repeat(60).bright_black());
     println!();
 
+    if heartbeat_key.is_none() {
+        println!(
+            "  {} {}",
+            "⚠️".yellow(),
+            "SWARM_PRIVATE_KEY not set; heartbeats disabled".yellow()
+        );
+        println!();
+    }
+
     // Watch loop
     let mut jobs_seen: u64 = 0;
+    let mut jobs_proven: u64 = 0;
+    let mut heartbeat = tokio::time::interval(Duration::from_secs(30));
+    let mut rescan = tokio::time::interval(Duration::from_secs(RESCAN_INTERVAL_SECS));
+    rescan.tick().await;
 
     loop {
-        tokio::select! {
+        // Offer each job in the batch, nearest deadline first
+        let mut pending = Vec::with_capacity(batch.len());
+        let mut job_models = HashMap::new();
+        for job_cid in std::mem::take(&mut batch) {
+            // Unreadable jobs are still listed, just without a deadline
+            let deadline = match store.get::<JobSnapshot>(&job_cid).await {
+                Ok(job) => {
+                    job_models.insert(job_cid.clone(), job.model);
+                    job.params.deadline
+                }
+                Err(e) => {
+                    tracing::debug!("Couldn't read job {}: {}", job_cid, e);
+                    None
+                }
+            };
+            pending.push((job_cid, deadline));
+        }
+
+        let (claimable, expired) = prioritize(pending, chrono::Utc::now().timestamp());
+        for (job_cid, deadline) in &expired {
+            if !offered.insert(job_cid.clone()) {
+                continue;
+            }
+            tracing::info!("Skipping {}: deadline {} has passed", job_cid, deadline);
+            println!(
+                "  {} Past deadline, skipped: {}",
+                "⏰".bright_black(),
+                job_cid.bright_black()
+            );
+        }
+        for job_cid in &claimable {
+            if !offered.insert(job_cid.clone()) {
+                continue;
+            }
+            jobs_seen += 1;
+            println!(
+                "  {} Job available: {}",
+                "📋".yellow(),
+                job_cid.cyan()
+            );
+
+            let model = job_models.get(job_cid).map(String::as_str).unwrap_or("");
+            if let Some(required_gb) = provider::vram_shortfall(model, available_mb) {
+                println!(
+                    "       {}",
+                    format!(
+                        "Insufficient VRAM: {} needs {} GB, {:.0} GB available",
+                        model,
+                        required_gb,
+                        available_mb as f64 / 1024.0
+                    )
+                    .bright_black()
+                );
+                continue;
+            }
+
+            let Some(key) = &prove_key else {
+                println!(
+                    "       {}",
+                    format!("Claim with: swarm claim --job {}", job_cid).bright_black()
+                );
+                continue;
+            };
+            let Some(spec) = job_models
+                .get(job_cid)
+                .filter(|m| models::matches_any(&model_list, m))
+                .and_then(|m| models::model_spec(m))
+            else {
+                println!("       {}", "Not a watched catalog model; not auto-proving".bright_black());
+                continue;
+            };
+
+            match scheduler.offer(job_cid, spec.vram_mb()) {
+                Placement::Start(gpu) => {
+                    println!("       {}", format!("Proving on GPU {}", gpu).green());
+                    in_flight.push(claim_and_prove(job_cid.clone(), &provider_ens, key, store, pool));
+                }
+                Placement::Queued => println!(
+                    "       {}",
+                    format!(
+                        "Queued: needs {} GB, {:.0} GB already committed",
+                        spec.vram_gb,
+                        scheduler.committed_mb() as f64 / 1024.0
+                    )
+                    .yellow()
+                ),
+                Placement::TooLarge => println!(
+                    "       {}",
+                    format!("Skipped: {} needs {} GB, more than any GPU here", spec.name, spec.vram_gb)
+                        .bright_black()
+                ),
+            }
+        }
+
+        batch = tokio::select! {
             _ = signal::ctrl_c() => {
                 println!();
                 println!("{}", "Stopping watcher...".yellow());
                 break;
             }
 
-            // Poll for new jobs (in production: SSE stream from IPFS pubsub)
-            _ = tokio::time::sleep(Duration::from_secs(2)) => {
-                // In production: check actual pubsub messages
-                // For now: poll pending jobs from pool state
-                match ipfs::fetch_pool_state(pool).await {
-                    Ok(state) => {
-                        for job_cid in &state.pending_jobs {
-                            jobs_seen += 1;
-                            println!(
-                                "  {} Job available: {}",
-                                "📋".yellow(),
-                                job_cid.cyan()
-                            );
-                            println!(
-                                "       {}",
-                                format!("Claim with: swarm claim --job {}", job_cid).bright_black()
-                            );
-                        }
+            // A job announced on the feed (see `submit`)
+            Some(announcement) = feed.next() => {
+                match announcement["cid"].as_str() {
+                    Some(job_cid) => vec![job_cid.to_string()],
+                    None => {
+                        tracing::debug!("Ignoring announcement without a cid: {}", announcement);
+                        continue;
+                    }
+                }
+            }
+
+            // An auto-proved job finished: free its VRAM and start what fits
+            Some((job_cid, result)) = in_flight.next(), if !in_flight.is_empty() => {
+                match result {
+                    Ok(()) => {
+                        jobs_proven += 1;
+                        println!("  {} Proven: {}", "✓".green(), job_cid.cyan());
                     }
-                    Err(e) => {
-                        // Silent retry on error
-                        tracing::debug!("Error fetching pool state: {}", e);
+                    Err(e) => eprintln!("  {} Auto-prove failed for {}: {:#}", "✗".red(), job_cid, e),
+                }
+                for (next, gpu) in scheduler.finish(&job_cid) {
+                    println!("  {} Dequeued {} onto GPU {}", "▶".green(), next.cyan(), gpu);
+                    if let Some(key) = &prove_key {
+                        in_flight.push(claim_and_prove(next, &provider_ens, key, store, pool));
                     }
                 }
+                continue;
             }
 
-            // Send heartbeat every 30 seconds
-            _ = tokio::time::sleep(Duration::from_secs(30)) => {
-                let timestamp = chrono::Utc::now().timestamp();
-                let heartbeat = serde_json::json!({
-                    "provider": provider_ens,
-                    "status": "watching",
-                    "models": model_list,
-                    "timestamp": timestamp
-                });
-
-                if let Err(e) = ipfs::pubsub_publish(
-                    &format!("/{}/heartbeats", pool),
-                    &heartbeat
-                ).await {
-                    eprintln!("  {} Heartbeat failed: {}", "⚠️".yellow(), e);
+            // Pick up pending jobs whose announcement we missed; ones already
+            // shown this session are skipped above
+            Some(jobs) = scans.next(), if !scans.is_empty() => jobs,
+
+            // Start the next scan unless the last one is still running
+            _ = rescan.tick() => {
+                if scans.is_empty() {
+                    scans.push(unclaimed_jobs(store, &model_list));
                 }
+                continue;
             }
-        }
+
+            // Overwrite our heartbeat record every 30 seconds
+            _ = heartbeat.tick() => {
+                if let Some(key) = &heartbeat_key {
+                    let beat =
+                        provider::build_heartbeat(&provider_ens, "watching", &served, &gpu_names);
+                    if let Err(e) = provider::publish_heartbeat(store, pool, beat, key).await {
+                        eprintln!("  {} Heartbeat failed: {}", "⚠️".yellow(), e);
+                    }
+                }
+                continue;
+            }
+        };
     }
 
     // Summary
     println!();
     println!("{}", "Watch Session Summary".cyan().bold());
     println!("  {} {}", "Jobs Seen:".bright_black(), jobs_seen);
+    if auto_prove {
+        println!("  {} {}", "Jobs Proven:".bright_black(), jobs_proven);
+        if !in_flight.is_empty() || scheduler.queued() > 0 {
+            println!(
+                "  {} {} running, {} queued",
+                "Abandoned:".bright_black(),
+                in_flight.len(),
+                scheduler.queued()
+            );
+        }
+    }
     println!();
 
     Ok(())
 }
+
+/// Claim `job_cid` (SOLO) and prove it, for `--auto-prove`
+async fn claim_and_prove(
+    job_cid: String,
+    provider_ens: &str,
+    key: &str,
+    store: &dyn Storage,
+    pool: &str,
+) -> (String, Result<()>) {
+    let result = async {
+        claim::execute(
+            job_cid.clone(),
+            "SOLO".to_string(),
+            Some(provider_ens.to_string()),
+            Some(key.to_string()),
+            false,
+            false,
+            store,
+            pool,
+        )
+        .await?;
+        prove::execute(
+            job_cid.clone(),
+            None,
+            Some(provider_ens.to_string()),
+            Some(key.to_string()),
+            None,
+            false,
+            false,
+            false,
+            None,
+            store,
+            pool,
+        )
+        .await
+    }
+    .await;
+    (job_cid, result)
+}
+
+/// Unclaimed, unproven jobs for the watched models (all models when none are
+/// configured), by CID. Listing errors are logged and yield no jobs.
+async fn unclaimed_jobs(store: &dyn Storage, model_list: &[String]) -> Vec<String> {
+    match ipfs::list_pending_jobs(store, chrono::Utc::now().timestamp()).await {
+        Ok(jobs) => watched_jobs(jobs, model_list),
+        Err(e) => {
+            tracing::debug!("Error listing pending jobs: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// CIDs of the jobs matching `model_list` (see `models::matches`), or of
+/// every job when it's empty
+pub fn watched_jobs(jobs: Vec<(String, JobSnapshot)>, model_list: &[String]) -> Vec<String> {
+    jobs.into_iter()
+        .filter(|(_, job)| model_list.is_empty() || models::matches_any(model_list, &job.model))
+        .map(|(cid, _)| cid)
+        .collect()
+}
+
+/// Order pending jobs for claiming: nearest deadline first, then jobs with no
+/// deadline in feed order. Jobs already past their deadline are returned
+/// separately (with it) so the caller can log them.
+pub fn prioritize(
+    pending: Vec<(String, Option<i64>)>,
+    now: i64,
+) -> (Vec<String>, Vec<(String, i64)>) {
+    let mut expired = Vec::new();
+    let mut claimable = Vec::new();
+    for (job_cid, deadline) in pending {
+        match deadline {
+            Some(deadline) if deadline <= now => expired.push((job_cid, deadline)),
+            _ => claimable.push((job_cid, deadline)),
+        }
+    }
+
+    // Stable: equal deadlines, and deadline-less jobs, keep feed order
+    claimable.sort_by_key(|(_, deadline)| deadline.unwrap_or(i64::MAX));
+    (claimable.into_iter().map(|(cid, _)| cid).collect(), expired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline_ordering_and_expired_skipped() {
+        let now = 1_704_070_800;
+        let pending = vec![
+            ("QmRoutine".to_string(), None),
+            ("QmTomorrow".to_string(), Some(now + 86_400)),
+            ("QmStale".to_string(), Some(now - 60)),
+            ("QmStat".to_string(), Some(now + 600)),
+            ("QmRoutine2".to_string(), None),
+        ];
+
+        let (claimable, expired) = prioritize(pending, now);
+        assert_eq!(
+            claimable,
+            ["QmStat", "QmTomorrow", "QmRoutine", "QmRoutine2"]
+        );
+        assert_eq!(expired, [("QmStale".to_string(), now - 60)]);
+    }
+
+    #[test]
+    fn test_watched_jobs_filtered_by_model() {
+        use crate::commands::submit::build_job;
+
+        let job = |id: &str, model: &str| {
+            let input = "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string();
+            let job = build_job(id.to_string(), model, input, "clinic.clientswarm.eth", "0.10", 0);
+            (format!("Qm{}", id), job)
+        };
+        let jobs = vec![job("Spine", "queenbee-spine"), job("Brain", "queenbee-brain")];
+
+        let spine = vec!["queenbee-spine".to_string()];
+        assert_eq!(watched_jobs(jobs.clone(), &spine), ["QmSpine"]);
+        assert_eq!(watched_jobs(jobs, &[]), ["QmSpine", "QmBrain"]);
+    }
+}
@@ -1,95 +1,109 @@
 //! Withdraw command - withdraw earnings to wallet
+//!
+//! `available_balance` in pool state only drops once a settlement pays a
+//! withdrawal out, so requests still pending are subtracted first; running
+//! `withdraw` twice never asks for the same balance twice.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Duration;
 
+use crate::config;
 use crate::crypto;
 use crate::ipfs;
+use crate::models::{from_microunits, to_microunits, UsdcAmount};
+use crate::money;
+use crate::say;
+use crate::schema::{self, SchemaType};
+use crate::storage::Storage;
+use crate::ui;
 
 pub async fn execute(
     amount: Option<String>,
     provider: String,
     key: Option<String>,
+    store: &dyn Storage,
     pool: &str,
 ) -> Result<()> {
-    println!("{}", "Withdraw Earnings".cyan().bold());
-    println!();
+    say!("{}", "Withdraw Earnings".cyan().bold());
+    say!();
 
     // Get private key
-    let private_key = key
-        .or_else(|| std::env::var("SWARM_PRIVATE_KEY").ok())
-        .context("Private key required. Use --key or set SWARM_PRIVATE_KEY")?;
+    let private_key = crypto::resolve_signer(key)?;
 
     // Fetch current balance
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Fetching balance...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Fetching balance...");
 
-    let state = ipfs::fetch_pool_state(pool).await?;
+    let state = ipfs::fetch_pool_state(store, pool).await?;
     let provider_info = state
         .active_providers
         .get(&provider)
         .context("Provider not found")?;
 
-    let available = provider_info.available_balance;
-    pb.finish_with_message(format!(
+    let controller = config::controller_address(None)?;
+    let pending: Vec<u64> = ipfs::fetch_pending_withdrawals(store, Some(&provider), &controller)
+        .await?
+        .iter()
+        .map(|w| w.amount.microunits())
+        .collect();
+
+    let balance = to_microunits(provider_info.available_balance);
+    let available = effective_available(balance, &pending);
+    pb.done(format!(
         "{} Available: {}",
         "✓".green(),
-        format!("${:.2}", available).green()
+        money::format_usdc_display(available).green()
     ));
+    if !pending.is_empty() {
+        say!(
+            "  {} {} already requested in {} pending withdrawal(s)",
+            "Note:".bright_black(),
+            money::format_usdc_display(pending.iter().sum()),
+            pending.len()
+        );
+    }
 
-    if available <= 0.0 {
-        println!();
+    if available == 0 {
+        say!();
         println!("{}", "No balance available to withdraw".yellow());
         return Ok(());
     }
 
     // Determine withdrawal amount
-    let withdraw_amount = match amount.as_deref() {
+    let requested = match amount.as_deref() {
         Some("all") | None => available,
-        Some(amt) => amt.parse::<f64>().context("Invalid amount")?,
+        Some(amt) => amt
+            .parse::<UsdcAmount>()
+            .map_err(anyhow::Error::msg)
+            .context("Invalid amount")?
+            .microunits(),
     };
+    if requested == 0 {
+        bail!("Nothing to withdraw: the amount is zero");
+    }
 
-    if withdraw_amount > available {
-        println!();
-        println!(
-            "{} Requested ${:.2} but only ${:.2} available",
-            "⚠️".yellow(),
-            withdraw_amount,
-            available
+    if requested > available {
+        bail!(
+            "Requested {} but only {} available after pending withdrawals",
+            money::format_usdc_display(requested),
+            money::format_usdc_display(available)
         );
-        return Ok(());
     }
 
-    println!();
-    println!(
-        "  {} ${:.2}",
+    say!();
+    say!(
+        "  {} {}",
         "Withdrawing:".bright_black(),
-        withdraw_amount
+        money::format_usdc_display(requested)
     );
-    println!(
+    say!(
         "  {} {}",
         "To wallet:".bright_black(),
         &provider_info.wallet
     );
-    println!();
+    say!();
 
     // Create withdrawal request
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Creating withdrawal request...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Creating withdrawal request...");
 
     let timestamp = chrono::Utc::now().timestamp();
     let nonce = crypto::random_hex(16);
@@ -97,56 +111,210 @@ pub async fn execute(
     let withdrawal = serde_json::json!({
         "type": "withdrawal",
         "provider": provider,
-        "amount": format!("{:.2}", withdraw_amount),
+        "amount": money::format_usdc(requested, 6),
         "wallet": provider_info.wallet,
+        "status": "pending",
         "timestamp": timestamp,
         "nonce": nonce,
     });
 
     let signed = crypto::sign_json(&withdrawal, &private_key).await?;
-    pb.finish_with_message(format!("{} Request signed", "✓".green()));
+    schema::validate(&signed, SchemaType::Withdrawal)?;
+    pb.done(format!("{} Request signed", "✓".green()));
 
     // Submit withdrawal
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Submitting withdrawal...");
-    pb.enable_steady_tick(Duration::from_millis(100));
+    let pb = ui::spinner("Submitting withdrawal...");
 
-    let cid = ipfs::upload_json(&signed).await?;
+    let withdrawal_id = withdrawal_id(&provider, timestamp, &nonce);
+    let cid = ipfs::write_withdrawal(store, &withdrawal_id, &signed).await?;
 
-    ipfs::pubsub_publish(
+    store.announce(
         &format!("/{}/withdrawals", pool),
         &serde_json::json!({
             "cid": cid,
             "provider": provider,
-            "amount": withdraw_amount,
+            "amount": from_microunits(requested),
             "timestamp": timestamp
         }),
     )
     .await?;
 
-    pb.finish_with_message(format!("{} Withdrawal submitted", "✓".green()));
+    pb.done(format!("{} Withdrawal submitted", "✓".green()));
 
     // Summary
-    println!();
-    println!("{}", "✅ Withdrawal Requested".green().bold());
-    println!();
-    println!(
+    say!();
+    say!("{}", "✅ Withdrawal Requested".green().bold());
+    say!();
+    say!(
         "  {} {}",
         "Amount:".bright_black(),
-        format!("${:.2} USDC", withdraw_amount).green()
+        format!("{} USDC", money::format_usdc_display(requested)).green()
     );
-    println!("  {} {}", "To:".bright_black(), provider_info.wallet);
+    say!("  {} {}", "To:".bright_black(), provider_info.wallet);
+    println!("  {} {}", "ID:".bright_black(), withdrawal_id);
     println!("  {} {}", "CID:".bright_black(), cid.cyan());
-    println!();
-    println!(
+    say!();
+    say!(
         "  {}",
         "Withdrawal will be processed in the next epoch settlement.".bright_black()
     );
 
     Ok(())
 }
+
+/// Id a withdrawal is published under, derived from the signed request so
+/// the same request always lands at the same path
+pub fn withdrawal_id(provider: &str, timestamp: i64, nonce: &str) -> String {
+    format!(
+        "withdrawal-{}-{}-{}",
+        provider.replace('.', "_"),
+        timestamp,
+        &nonce[..nonce.len().min(8)]
+    )
+}
+
+/// Balance left to withdraw once `pending` requests (microunits) are paid
+pub fn effective_available(available: u64, pending: &[u64]) -> u64 {
+    available.saturating_sub(pending.iter().sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, OTHER_KEY, TEST_ADDRESS, TEST_KEY};
+
+    #[tokio::test]
+    async fn test_withdrawal_written_and_counted_as_pending() {
+        let (_root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        let genesis = serde_json::json!({
+            "type": "provider-init",
+            "provider": "alpha.swarmbee.eth",
+            "wallet": TEST_ADDRESS,
+            "gpus": ["RTX 5090"],
+            "models": ["queenbee-spine"],
+            "timestamp": 1704067200,
+            "nonce": "abcdef1234567890"
+        });
+        let genesis = crypto::sign_json(&genesis, TEST_KEY).await.unwrap();
+        ipfs::write_genesis(store, "alpha.swarmbee.eth", &genesis)
+            .await
+            .unwrap();
+
+        let id = withdrawal_id("alpha.swarmbee.eth", 1704067200, "abcdef1234567890");
+        assert_eq!(id, "withdrawal-alpha_swarmbee_eth-1704067200-abcdef12");
+
+        let withdrawal = serde_json::json!({
+            "type": "withdrawal",
+            "provider": "alpha.swarmbee.eth",
+            "amount": "1.250000",
+            "wallet": TEST_ADDRESS,
+            "status": "pending",
+            "timestamp": 1704067200,
+            "nonce": "abcdef1234567890"
+        });
+        let signed = crypto::sign_json(&withdrawal, TEST_KEY).await.unwrap();
+        ipfs::write_withdrawal(store, &id, &signed).await.unwrap();
+        assert_eq!(
+            store.list(ipfs::paths::WITHDRAWALS).await.unwrap(),
+            [format!("{}.json", id)]
+        );
+
+        // Signed by someone else, or for a negative amount: never counted
+        let forged = crypto::sign_json(&withdrawal, OTHER_KEY).await.unwrap();
+        ipfs::write_withdrawal(store, "withdrawal-forged", &forged)
+            .await
+            .unwrap();
+        let mut negative = withdrawal.clone();
+        negative["amount"] = serde_json::json!("-5.0");
+        let negative = crypto::sign_json(&negative, TEST_KEY).await.unwrap();
+        ipfs::write_withdrawal(store, "withdrawal-negative", &negative)
+            .await
+            .unwrap();
+
+        let controller = TEST_ADDRESS;
+        let pending =
+            ipfs::fetch_pending_withdrawals(store, Some("alpha.swarmbee.eth"), controller)
+                .await
+                .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, id);
+        assert_eq!(pending[0].amount.microunits(), 1_250_000);
+        assert!(
+            ipfs::fetch_pending_withdrawals(store, Some("beta.swarmbee.eth"), controller)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // A self-reported "processed" status changes nothing
+        let mut claimed = withdrawal.clone();
+        claimed["status"] = serde_json::json!("processed");
+        claimed["nonce"] = serde_json::json!("1234567890abcdef");
+        let claimed = crypto::sign_json(&claimed, TEST_KEY).await.unwrap();
+        ipfs::write_withdrawal(store, "withdrawal-claimed", &claimed)
+            .await
+            .unwrap();
+        let pending = ipfs::fetch_pending_withdrawals(store, None, controller)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 2);
+
+        // Only an epoch sealed by the controller marks a withdrawal processed
+        let epoch = |epoch_id: &str, processed: Vec<String>| {
+            serde_json::json!({
+                "type": "epoch",
+                "version": "1.0.0",
+                "epoch_id": epoch_id,
+                "name": "Alpha",
+                "status": "sealed",
+                "started_at": 1704067200,
+                "jobs_count": 0,
+                "total_volume_usdc": "0.000000",
+                "controller": "merlin.swarmos.eth",
+                "processed_withdrawals": processed,
+                "timestamp": 1704070800
+            })
+        };
+        let fake = crypto::sign_json(&epoch("epoch-001", vec![id.clone()]), OTHER_KEY)
+            .await
+            .unwrap();
+        ipfs::write_epoch(store, "epoch-001", &fake).await.unwrap();
+        let pending = ipfs::fetch_pending_withdrawals(store, None, controller)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 2);
+
+        let sealed = crypto::sign_json(&epoch("epoch-002", vec![id.clone()]), TEST_KEY)
+            .await
+            .unwrap();
+        ipfs::write_epoch(store, "epoch-002", &sealed).await.unwrap();
+        let pending = ipfs::fetch_pending_withdrawals(store, None, controller)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = pending.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, ["withdrawal-claimed"]);
+
+        // `balance` counts the processed one as paid, the other as pending
+        let sealed = ipfs::fetch_sealed_epochs(store, controller).await.unwrap();
+        let (processed, pending) =
+            ipfs::split_withdrawals(store, Some("alpha.swarmbee.eth"), &sealed)
+                .await
+                .unwrap();
+        let ids = |ws: &[ipfs::VerifiedWithdrawal]| -> Vec<String> {
+            ws.iter().map(|w| w.id.clone()).collect()
+        };
+        assert_eq!(ids(&processed), [id]);
+        assert_eq!(ids(&pending), ["withdrawal-claimed"]);
+    }
+
+    #[test]
+    fn test_pending_withdrawals_reduce_balance() {
+        assert_eq!(effective_available(10_000_000, &[]), 10_000_000);
+        assert_eq!(effective_available(10_000_000, &[2_500_000, 1_250_000]), 6_250_000);
+        assert_eq!(effective_available(10_000_000, &[10_000_000]), 0);
+        // Over-committed (e.g. balance already reduced by an earlier seal)
+        assert_eq!(effective_available(1_000_000, &[750_000, 750_000]), 0);
+    }
+}
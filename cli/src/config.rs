@@ -5,6 +5,8 @@ use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::models::{PricingConfig, SettlementConfig};
+
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
@@ -25,6 +27,35 @@ pub struct Config {
 
     /// IPFS API URL
     pub ipfs_api: String,
+
+    /// Public gateway tried when the local daemon doesn't have a CID
+    #[serde(default = "default_ipfs_gateway")]
+    pub ipfs_gateway: String,
+
+    /// Ethereum JSON-RPC endpoint used to resolve ENS names
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eth_rpc: Option<String>,
+
+    /// Address of the pool controller; sealed epochs not signed by it are
+    /// not trusted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub controller: Option<String>,
+
+    /// Settlement parameters (controller only)
+    #[serde(default)]
+    pub settlement: SettlementConfig,
+
+    /// Payment preflight heuristics (clients)
+    #[serde(default)]
+    pub pricing: PricingConfig,
+
+    /// Run the inference runner sandboxed (as if `prove --sandbox`)
+    #[serde(default)]
+    pub sandbox: bool,
+
+    /// Keystore label to sign with when no raw key is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keystore: Option<String>,
 }
 
 impl Config {
@@ -36,10 +67,35 @@ impl Config {
             models: vec!["queenbee-spine".to_string()],
             pool: "swarmpool.eth".to_string(),
             ipfs_api: "http://localhost:5001".to_string(),
+            ipfs_gateway: default_ipfs_gateway(),
+            eth_rpc: None,
+            controller: None,
+            settlement: SettlementConfig::default(),
+            pricing: PricingConfig::default(),
+            sandbox: false,
+            keystore: None,
         }
     }
 }
 
+fn default_ipfs_gateway() -> String {
+    "https://ipfs.io/ipfs".to_string()
+}
+
+/// The controller address to check epoch signatures against: `flag` (a
+/// `--controller` option) or else the `controller` config field
+pub fn controller_address(flag: Option<String>) -> Result<String> {
+    let controller = match flag {
+        Some(controller) => controller,
+        None => load_config()?.controller.context(
+            "No controller address: pass --controller or set `controller` in config.toml",
+        )?,
+    };
+    crate::crypto::checksum_address(&controller)
+        .with_context(|| format!("Invalid controller address {:?}", controller))?;
+    Ok(controller)
+}
+
 /// Get the config file path
 pub fn get_config_path() -> Result<PathBuf> {
     let proj_dirs = ProjectDirs::from("eth", "swarmpool", "swarm-cli")
@@ -1,22 +1,81 @@
 //! Cryptographic utilities for EIP-191 signing and verification
 
+pub mod merkle;
+
 use anyhow::{Context, Result};
+use ethers::providers::{Http, Middleware, Provider, ProviderError};
 use ethers::signers::{LocalWallet, Signer};
-use ethers::types::Signature;
+use ethers::types::{Address, Signature};
+#[cfg(test)]
+use rand::rngs::StdRng;
+use rand::RngCore;
+#[cfg(test)]
+use rand::SeedableRng;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
+#[cfg(test)]
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::config;
+use crate::error::SwarmError;
+use crate::keystore::{self, Keystore};
+
+/// Env var holding the signing key when `--key` isn't given
+pub const PRIVATE_KEY_ENV: &str = "SWARM_PRIVATE_KEY";
+
+/// Resolve a command's signing key from `--key`, `SWARM_PRIVATE_KEY`, or
+/// the keystore entry named by `keystore` in config
+///
+/// The key is validated up front (see `normalize_key`) so a typo fails before
+/// the command fetches or computes anything.
+pub fn resolve_signer(key: Option<String>) -> Result<String> {
+    if let Some(key) = key.or_else(|| std::env::var(PRIVATE_KEY_ENV).ok()) {
+        return Ok(normalize_key(&key)?);
+    }
+
+    let label = config::load_config()?.keystore.context(
+        "Private key required. Use --key, set SWARM_PRIVATE_KEY, or run 'swarm migrate-keys'",
+    )?;
+    let wallet = Keystore::open()?.load(&label, &keystore::read_password(false)?)?;
+    Ok(hex::encode(wallet.signer().to_bytes()))
+}
+
+/// Canonical form of a hex private key: surrounding whitespace and any
+/// `0x`/`0X` prefix removed, lowercase, exactly 64 hex characters
+pub fn normalize_key(key: &str) -> std::result::Result<String, SwarmError> {
+    let trimmed = key.trim();
+    let hex = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+
+    if hex.len() != 64 {
+        return Err(SwarmError::BadKey {
+            reason: format!("expected 64 hex chars, got {}", hex.chars().count()),
+        });
+    }
+    if let Some((i, c)) = hex.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(SwarmError::BadKey {
+            reason: format!("{:?} at position {} is not a hex digit", c, i + 1),
+        });
+    }
+    Ok(hex.to_ascii_lowercase())
+}
 
 /// Sign a snapshot/struct with EIP-191 personal sign (async)
-/// Uses keccak256 for hashing (Ethereum standard)
+/// Uses keccak256 over `canonical_bytes`, so field declaration order never
+/// changes the signature and a float never reaches it (see docs/SIGNING.md)
 pub async fn sign_snapshot<T: Serialize>(data: &T, private_key: &str) -> Result<String> {
-    // Serialize to canonical JSON (sorted keys)
-    let json = serde_json::to_string(data)?;
+    // Serialize to canonical JSON (sorted keys, integers only)
+    let canonical = canonical_bytes(&serde_json::to_value(data)?)?;
 
     // Hash with keccak256 (Ethereum standard)
-    let hash = ethers::utils::keccak256(json.as_bytes());
+    let hash = ethers::utils::keccak256(&canonical);
 
     // Parse private key and sign
-    let wallet: LocalWallet = private_key
-        .trim_start_matches("0x")
+    let wallet: LocalWallet = normalize_key(private_key)?
         .parse()
         .context("Invalid private key format")?;
 
@@ -37,6 +96,109 @@ pub async fn sign_snapshot<T: Serialize>(data: &T, private_key: &str) -> Result<
     Ok(format!("0x{}", hex::encode(signature.to_vec())))
 }
 
+/// The bytes `sign_snapshot` signs: `canonical_json` of a payload holding
+/// no number but integers
+///
+/// A float anywhere is an error naming its field. Money goes in signed
+/// snapshots as integer microunits and other fractions as decimal strings
+/// (`models::microunits`, `models::decimal`), so no implementation has to
+/// agree with ours on float formatting to verify a new signature.
+pub fn canonical_bytes(value: &serde_json::Value) -> Result<Vec<u8>> {
+    if let Some(path) = first_float(value, "") {
+        anyhow::bail!(
+            "Can't sign {}: not an integer. Signed payloads carry fractions as strings or integer microunits",
+            path
+        );
+    }
+    Ok(canonical_json(value).into_bytes())
+}
+
+/// Path (`a.b[2]`) and value of the first non-integer number in `value`
+fn first_float(value: &serde_json::Value, path: &str) -> Option<String> {
+    use serde_json::Value;
+
+    match value {
+        Value::Number(n) if n.is_f64() => Some(format!("{} = {}", path, n)),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .find_map(|(i, item)| first_float(item, &format!("{}[{}]", path, i))),
+        Value::Object(map) => map.iter().find_map(|(key, item)| {
+            let path = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            first_float(item, &path)
+        }),
+        _ => None,
+    }
+}
+
+/// Canonical encoding of a JSON value, as signatures are verified
+///
+/// Stable across languages so partners can verify our signatures (Merlin's
+/// `crypto.canonical_json` produces the same bytes):
+/// - Object keys sorted by byte order, no whitespace
+/// - Numbers stay numbers. Floats are written in their shortest round-trip
+///   digits without an exponent, and integral floats as integers
+///   (`12.0` -> `12`, `1e-7` -> `0.0000001`), so any change to a value
+///   changes the digest
+/// - Strings escaped as standard compact JSON, non-ASCII left as is
+///
+/// We only sign integers (see `canonical_bytes`); floats are encoded so
+/// snapshots signed before that, and by other signers, still verify.
+/// Conformance vectors live in `tests/vectors/`.
+pub fn canonical_json(value: &serde_json::Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+    use serde_json::Value;
+
+    match value {
+        Value::Number(n) if n.is_f64() => {
+            // `Display` prints the shortest digits that round-trip and never
+            // an exponent; -0 and 0 are the same amount
+            let f = n.as_f64().unwrap_or(0.0);
+            if f == 0.0 {
+                out.push('0');
+            } else {
+                out.push_str(&f.to_string());
+            }
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        // null, bool, integers and strings already have one compact form
+        other => out.push_str(&other.to_string()),
+    }
+}
+
 /// Sign raw JSON value, returns JSON with sig field added
 pub async fn sign_json(data: &serde_json::Value, private_key: &str) -> Result<serde_json::Value> {
     let sig = sign_snapshot(data, private_key).await?;
@@ -49,20 +211,93 @@ pub async fn sign_json(data: &serde_json::Value, private_key: &str) -> Result<se
     Ok(signed)
 }
 
+/// Env var that pins nonce/id generation to a seeded RNG. Only test builds
+/// read it; release binaries always draw from `thread_rng`.
+#[cfg(test)]
+pub const TEST_SEED_ENV: &str = "SWARM_TEST_SEED";
+
+#[cfg(test)]
+thread_local! {
+    /// Per-thread seeded generator; `None` means OS-backed `thread_rng`
+    static SEEDED_RNG: RefCell<Option<StdRng>> = RefCell::new(
+        std::env::var(TEST_SEED_ENV)
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .map(StdRng::seed_from_u64),
+    );
+}
+
+/// Seed the current thread's nonce generator for reproducible output.
+/// Passing `None` restores cryptographic randomness.
+#[cfg(test)]
+pub fn seed_rng(seed: Option<u64>) {
+    SEEDED_RNG.with(|rng| *rng.borrow_mut() = seed.map(StdRng::seed_from_u64));
+}
+
+/// Fill `buf` from the seeded generator if one is set, else from `thread_rng`
+#[cfg(test)]
+fn fill_random(buf: &mut [u8]) {
+    SEEDED_RNG.with(|rng| match rng.borrow_mut().as_mut() {
+        Some(seeded) => seeded.fill_bytes(buf),
+        None => rand::thread_rng().fill_bytes(buf),
+    });
+}
+
+#[cfg(not(test))]
+fn fill_random(buf: &mut [u8]) {
+    rand::thread_rng().fill_bytes(buf);
+}
+
 /// Generate random hex string
+///
+/// Test builds honor `SWARM_TEST_SEED` / `seed_rng` so nonces and ids are
+/// reproducible; other builds ignore both.
 pub fn random_hex(bytes: usize) -> String {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    let random_bytes: Vec<u8> = (0..bytes).map(|_| rng.gen()).collect();
+    let mut random_bytes = vec![0u8; bytes];
+    fill_random(&mut random_bytes);
     hex::encode(random_bytes)
 }
 
-/// Verify an EIP-191 signature
+/// Whether `address` is `0x` + 40 hex characters and, if its letters are
+/// mixed case, matches its EIP-55 checksum
+///
+/// All-lowercase and all-uppercase addresses carry no checksum and are
+/// accepted; a mixed-case address with one character off is not.
+pub fn is_valid_checksum_address(address: &str) -> bool {
+    let Some(hex) = address.strip_prefix("0x") else {
+        return false;
+    };
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return false;
+    }
+    if hex == hex.to_lowercase() || hex == hex.to_uppercase() {
+        return true;
+    }
+    checksum_address(address).is_ok_and(|checksummed| checksummed == address)
+}
+
+/// EIP-55 checksummed form of a hex address
+pub fn checksum_address(address: &str) -> Result<String> {
+    let parsed: Address = address
+        .parse()
+        .with_context(|| format!("Not an Ethereum address: {}", address))?;
+    Ok(ethers::utils::to_checksum(&parsed, None))
+}
+
+/// Verify an EIP-191 signature over the canonical form of `data`, as
+/// produced by `sign_snapshot`
 pub fn verify_signature(
-    data: &str,
+    data: &serde_json::Value,
     signature: &str,
     expected_address: &str,
 ) -> Result<bool> {
+    let recovered_addr = recover_signer(&canonical_json(data), signature)?;
+
+    Ok(recovered_addr.to_lowercase() == expected_address.to_lowercase())
+}
+
+/// Recover the address that produced an EIP-191 signature over `data`
+pub fn recover_signer(data: &str, signature: &str) -> Result<String> {
     // Hash the data with keccak256
     let hash = ethers::utils::keccak256(data.as_bytes());
 
@@ -87,9 +322,91 @@ pub fn verify_signature(
         .recover(&message_hash[..])
         .context("Failed to recover address from signature")?;
 
-    let recovered_addr = format!("{:?}", recovered);
+    Ok(format!("{:?}", recovered))
+}
 
-    Ok(recovered_addr.to_lowercase() == expected_address.to_lowercase())
+/// Recover the signer of a snapshot from its unsigned form (no `sig` field)
+pub fn recover_snapshot_signer<T: Serialize>(unsigned: &T, signature: &str) -> Result<String> {
+    recover_signer(&canonical_json(&serde_json::to_value(unsigned)?), signature)
+}
+
+/// Recover the signer of a signed snapshot's JSON (its `sig` over the rest)
+/// and, given `expected_address`, check it signed
+///
+/// A missing, malformed or unrecoverable `sig` is an error either way.
+pub fn verify_snapshot_json(
+    data: &serde_json::Value,
+    expected_address: Option<&str>,
+) -> Result<String> {
+    let object = data.as_object().context("Snapshot is not a JSON object")?;
+    let sig = object
+        .get("sig")
+        .and_then(serde_json::Value::as_str)
+        .context("Snapshot is unsigned")?;
+
+    let mut unsigned = object.clone();
+    unsigned.remove("sig");
+    let signer = recover_snapshot_signer(&serde_json::Value::Object(unsigned), sig)?;
+
+    if let Some(expected) = expected_address {
+        if !signer.eq_ignore_ascii_case(expected) {
+            anyhow::bail!("Signed by {}, not {}", signer, expected);
+        }
+    }
+    Ok(signer)
+}
+
+/// ENS names resolved so far in this process, by lowercased name
+fn ens_cache() -> &'static Mutex<HashMap<String, Address>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Address>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Resolve an ENS name such as `merlin.swarmos.eth` to its address through
+/// the JSON-RPC endpoint `rpc_url` (the `eth_rpc` config field)
+///
+/// Results are cached for the life of the process, so a `seal` touching the
+/// same providers many times looks each up once. Only `.eth` names are
+/// accepted; a name with no resolver or no address record is an error.
+pub async fn resolve_ens(name: &str, rpc_url: &str) -> Result<Address> {
+    let name = name.trim().to_ascii_lowercase();
+    let label = name.strip_suffix(".eth").unwrap_or_default();
+    if label.is_empty() || label.split('.').any(str::is_empty) {
+        anyhow::bail!("Not an ENS name: {:?} (expected a name ending in .eth)", name);
+    }
+
+    if let Some(address) = ens_cache().lock().unwrap().get(&name) {
+        return Ok(*address);
+    }
+
+    let provider = Provider::<Http>::try_from(rpc_url)
+        .with_context(|| format!("Invalid eth_rpc URL: {}", rpc_url))?;
+    let address = match provider.resolve_name(&name).await {
+        Ok(address) if address == Address::zero() => {
+            anyhow::bail!("ENS name {} has no address record", name)
+        }
+        Ok(address) => address,
+        Err(ProviderError::EnsError(_)) => {
+            anyhow::bail!("ENS name {} has no resolver set", name)
+        }
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to resolve {} via {}", name, rpc_url))
+        }
+    };
+
+    ens_cache().lock().unwrap().insert(name, address);
+    Ok(address)
+}
+
+/// Merkle root over 0x-prefixed keccak256 leaves (e.g. proof hashes)
+///
+/// See `merkle::MerkleTree`; no leaves gives a zero root.
+pub fn merkle_root(leaves: &[String]) -> Result<String> {
+    let leaves: Vec<merkle::Hash> = leaves
+        .iter()
+        .map(|leaf| merkle::parse_hash(leaf))
+        .collect::<Result<_>>()?;
+    Ok(merkle::to_hex(&merkle::MerkleTree::from_leaves(&leaves).root()))
 }
 
 /// Hash data with keccak256 (Ethereum standard)
@@ -98,6 +415,29 @@ pub fn keccak256_hash(data: &[u8]) -> String {
     format!("0x{}", hex::encode(result))
 }
 
+/// SHA-256 of a single input file, as 0x-prefixed hex
+pub fn file_sha256(data: &[u8]) -> String {
+    format!("0x{}", hex::encode(Sha256::digest(data)))
+}
+
+/// SHA-256 of a series input, as 0x-prefixed hex
+///
+/// Files are hashed in path order as `path NUL length(u64 BE) bytes`, so a
+/// rename or a byte moved between files changes the digest.
+pub fn series_sha256(files: &[(String, Vec<u8>)]) -> String {
+    let mut sorted: Vec<&(String, Vec<u8>)> = files.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut hasher = Sha256::new();
+    for (path, bytes) in sorted {
+        hasher.update(path.as_bytes());
+        hasher.update([0u8]);
+        hasher.update((bytes.len() as u64).to_be_bytes());
+        hasher.update(bytes);
+    }
+    format!("0x{}", hex::encode(hasher.finalize()))
+}
+
 /// Legacy alias - use keccak256_hash instead
 #[deprecated(note = "Use keccak256_hash for Ethereum compatibility")]
 pub fn sha256_hash(data: &[u8]) -> String {
@@ -107,6 +447,74 @@ pub fn sha256_hash(data: &[u8]) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{OTHER_ADDRESS, TEST_ADDRESS, TEST_KEY};
+
+    fn bad_key_reason(key: &str) -> String {
+        match normalize_key(key) {
+            Err(SwarmError::BadKey { reason }) => reason,
+            Ok(_) => panic!("{:?} was accepted", key),
+        }
+    }
+
+    #[test]
+    fn test_key_normalization() {
+        for key in [
+            TEST_KEY.to_string(),
+            format!("0x{}", TEST_KEY),
+            format!("0X{}", TEST_KEY),
+            format!("  0x{}\n", TEST_KEY),
+            TEST_KEY.to_uppercase(),
+        ] {
+            assert_eq!(normalize_key(&key).unwrap(), TEST_KEY, "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn test_malformed_keys_rejected() {
+        assert_eq!(bad_key_reason(&TEST_KEY[1..]), "expected 64 hex chars, got 63");
+        assert_eq!(
+            bad_key_reason(&format!("0x{}00", TEST_KEY)),
+            "expected 64 hex chars, got 66"
+        );
+        assert_eq!(bad_key_reason(""), "expected 64 hex chars, got 0");
+        assert_eq!(
+            bad_key_reason(&format!("0x{}g", &TEST_KEY[1..])),
+            "'g' at position 64 is not a hex digit"
+        );
+        // Inner whitespace isn't trimmed away
+        assert_eq!(
+            bad_key_reason(&format!("{} {}", &TEST_KEY[..32], &TEST_KEY[33..])),
+            "' ' at position 33 is not a hex digit"
+        );
+        // Prefix only once
+        assert!(normalize_key(&format!("0x0x{}", &TEST_KEY[2..])).is_err());
+
+        let err = resolve_signer(Some("0x1234".to_string())).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<SwarmError>(),
+            Some(SwarmError::BadKey { .. })
+        ));
+        assert!(err.to_string().contains("got 4"));
+    }
+
+    #[test]
+    fn test_checksum_address_validation() {
+        // EIP-55 test vector
+        let good = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        assert!(is_valid_checksum_address(good));
+        assert_eq!(checksum_address(&good.to_lowercase()).unwrap(), good);
+
+        // One letter's case flipped breaks the checksum
+        assert!(!is_valid_checksum_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"));
+
+        // No checksum to check
+        assert!(is_valid_checksum_address(&good.to_lowercase()));
+        assert!(is_valid_checksum_address(&format!("0x{}", good[2..].to_uppercase())));
+
+        assert!(!is_valid_checksum_address("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+        assert!(!is_valid_checksum_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA"));
+        assert!(!is_valid_checksum_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg"));
+    }
 
     #[test]
     fn test_random_hex() {
@@ -117,6 +525,23 @@ mod tests {
         assert_ne!(hex1, hex2);
     }
 
+    #[test]
+    fn test_seeded_random_hex_is_reproducible() {
+        seed_rng(Some(42));
+        let first = (random_hex(16), random_hex(4));
+
+        seed_rng(Some(42));
+        let second = (random_hex(16), random_hex(4));
+
+        seed_rng(Some(7));
+        let other = random_hex(16);
+        seed_rng(None);
+
+        assert_eq!(first, second);
+        assert_ne!(first.0, first.1);
+        assert_ne!(first.0, other);
+    }
+
     #[test]
     fn test_keccak256_hash() {
         let hash = keccak256_hash(b"hello");
@@ -128,4 +553,182 @@ mod tests {
             "0x1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8"
         );
     }
+
+    #[tokio::test]
+    async fn test_recover_signer_roundtrip() {
+        let sig = sign_snapshot(&serde_json::json!({"a": 1}), TEST_KEY).await.unwrap();
+
+        let signer = recover_signer(r#"{"a":1}"#, &sig).unwrap();
+        assert_eq!(signer.to_lowercase(), TEST_ADDRESS);
+        assert!(verify_signature(&serde_json::json!({"a": 1}), &sig, &signer).unwrap());
+        assert!(!verify_signature(&serde_json::json!({"a": 2}), &sig, &signer).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_signature_ignores_field_order() {
+        #[derive(Serialize)]
+        struct Forward {
+            amount_microunits: u64,
+            client: &'static str,
+            nonce: u64,
+        }
+        #[derive(Serialize)]
+        struct Reversed {
+            nonce: u64,
+            client: &'static str,
+            amount_microunits: u64,
+        }
+
+        let forward = Forward {
+            amount_microunits: 250_000,
+            client: "clinic.clientswarm.eth",
+            nonce: 7,
+        };
+        let reversed = Reversed {
+            nonce: 7,
+            client: "clinic.clientswarm.eth",
+            amount_microunits: 250_000,
+        };
+        let a = serde_json::to_value(&forward).unwrap();
+        let b = serde_json::to_value(&reversed).unwrap();
+        assert_ne!(
+            serde_json::to_string(&forward).unwrap(),
+            serde_json::to_string(&reversed).unwrap()
+        );
+        assert_eq!(canonical_json(&a), canonical_json(&b));
+
+        let sig = sign_snapshot(&forward, TEST_KEY).await.unwrap();
+        assert_eq!(sig, sign_snapshot(&reversed, TEST_KEY).await.unwrap());
+        let signer = recover_snapshot_signer(&forward, &sig).unwrap();
+        assert!(verify_signature(&b, &sig, &signer).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_ens_input_and_cache() {
+        // Rejected before any RPC call, so the unreachable endpoint is never hit
+        for name in [TEST_ADDRESS, "merlin.swarmos.xyz", ".eth", "a..eth"] {
+            let err = resolve_ens(name, "http://127.0.0.1:1").await.unwrap_err();
+            assert!(err.to_string().starts_with("Not an ENS name"), "{}", err);
+        }
+
+        // A cached name is answered without the endpoint
+        let address: Address = OTHER_ADDRESS.parse().unwrap();
+        ens_cache()
+            .lock()
+            .unwrap()
+            .insert("cached.swarmbee.eth".to_string(), address);
+        let resolved = resolve_ens(" Cached.SwarmBee.eth", "http://127.0.0.1:1")
+            .await
+            .unwrap();
+        assert_eq!(resolved, address);
+
+        assert!(resolve_ens("uncached.swarmbee.eth", "http://127.0.0.1:1")
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let a = keccak256_hash(b"a");
+        let b = keccak256_hash(b"b");
+        let c = keccak256_hash(b"c");
+
+        // Single leaf is its own root; pair order does not matter
+        assert_eq!(merkle_root(&[a.clone()]).unwrap(), a);
+        assert_eq!(
+            merkle_root(&[a.clone(), b.clone()]).unwrap(),
+            merkle_root(&[b.clone(), a.clone()]).unwrap()
+        );
+
+        let three = merkle_root(&[a.clone(), b.clone(), c.clone()]).unwrap();
+        assert_ne!(three, merkle_root(&[a, b]).unwrap());
+        assert!(merkle_root(&["0x1234".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_canonical_json() {
+        let value = serde_json::json!({
+            "b": [1.0, 12.5, -3],
+            "a": {"z": null, "y": "q\"uote", "x": 0.1 + 0.2},
+            "c": true
+        });
+        assert_eq!(
+            canonical_json(&value),
+            r#"{"a":{"x":0.30000000000000004,"y":"q\"uote","z":null},"b":[1,12.5,-3],"c":true}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_floats_are_never_signed() {
+        let value = serde_json::json!({
+            "epoch_id": "epoch-001",
+            "settlements": { "providers": { "alpha.swarmbee.eth": 0.105 } }
+        });
+        let err = canonical_bytes(&value).unwrap_err().to_string();
+        assert!(
+            err.contains("settlements.providers.alpha.swarmbee.eth = 0.105"),
+            "{}",
+            err
+        );
+        assert!(sign_snapshot(&value, TEST_KEY).await.is_err());
+        assert!(canonical_bytes(&serde_json::json!({ "x": [1, 2.0] })).is_err());
+
+        let micro = serde_json::json!({ "amount": 105_000, "note": "0.105" });
+        assert_eq!(
+            canonical_bytes(&micro).unwrap(),
+            canonical_json(&micro).into_bytes()
+        );
+    }
+
+    /// Conformance vectors shared with other implementations
+    #[test]
+    fn test_canonical_vectors() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/vectors");
+        let mut checked = 0;
+
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            let vector: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            let canonical = canonical_json(&vector["input"]);
+
+            // Vectors holding floats verify but must never be signed
+            let signable = vector["signable"].as_bool().unwrap_or(true);
+            assert_eq!(
+                canonical_bytes(&vector["input"]).is_ok(),
+                signable,
+                "signable mismatch in {}",
+                path.display()
+            );
+
+            assert_eq!(
+                canonical,
+                vector["canonical"].as_str().unwrap(),
+                "canonical mismatch in {}",
+                path.display()
+            );
+            assert_eq!(
+                keccak256_hash(canonical.as_bytes()),
+                vector["digest"].as_str().unwrap(),
+                "digest mismatch in {}",
+                path.display()
+            );
+            // Vectors carrying a signature must verify against their signer
+            if let Some(sig) = vector["sig"].as_str() {
+                assert!(
+                    verify_signature(&vector["input"], sig, vector["signer"].as_str().unwrap())
+                        .unwrap(),
+                    "signature mismatch in {}",
+                    path.display()
+                );
+            }
+            checked += 1;
+        }
+
+        assert!(checked > 0, "no vectors found in {}", dir.display());
+    }
 }
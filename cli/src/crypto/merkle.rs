@@ -0,0 +1,139 @@
+//! Keccak256 Merkle tree over 32-byte leaves
+//!
+//! Pairs are hashed sorted, `keccak256(min || max)`, the way OpenZeppelin's
+//! `MerkleProof.verify` expects, so an inclusion path is just the sibling
+//! hashes with no left/right flags. An odd node is carried up unchanged.
+
+use anyhow::{Context, Result};
+
+pub type Hash = [u8; 32];
+
+/// Every level of the tree, leaves first and the root last
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn from_leaves(leaves: &[Hash]) -> MerkleTree {
+        let mut levels = vec![leaves.to_vec()];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        MerkleTree { levels }
+    }
+
+    /// Root hash; all zeros for a tree with no leaves
+    pub fn root(&self) -> Hash {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or([0u8; 32])
+    }
+
+    /// Sibling hashes from leaf `index` up to the root. Levels where the
+    /// node was carried up without a sibling contribute nothing.
+    ///
+    /// Panics if `index` is not a leaf.
+    pub fn proof(&self, index: usize) -> Vec<Hash> {
+        assert!(index < self.levels[0].len(), "leaf {} out of range", index);
+
+        let mut path = Vec::new();
+        let mut index = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                path.push(*sibling);
+            }
+            index /= 2;
+        }
+        path
+    }
+}
+
+/// Hash two nodes in sorted order
+pub fn hash_pair(a: &Hash, b: &Hash) -> Hash {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    ethers::utils::keccak256([&lo[..], &hi[..]].concat())
+}
+
+/// Root reached by hashing `leaf` up through `path`
+pub fn root_from_path(leaf: &Hash, path: &[Hash]) -> Hash {
+    path.iter()
+        .fold(*leaf, |node, sibling| hash_pair(&node, sibling))
+}
+
+/// Whether `path` leads from `leaf` to `root`
+pub fn verify(leaf: &Hash, path: &[Hash], root: &Hash) -> bool {
+    &root_from_path(leaf, path) == root
+}
+
+/// Parse a 0x-prefixed 32-byte hex hash
+pub fn parse_hash(hex_hash: &str) -> Result<Hash> {
+    let bytes = hex::decode(hex_hash.trim_start_matches("0x"))
+        .with_context(|| format!("Invalid merkle hash: {}", hex_hash))?;
+    Hash::try_from(bytes.as_slice())
+        .map_err(|_| anyhow::anyhow!("Merkle hash must be 32 bytes: {}", hex_hash))
+}
+
+/// 0x-prefixed hex form of a hash
+pub fn to_hex(hash: &Hash) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(byte: u8) -> Hash {
+        [byte; 32]
+    }
+
+    #[test]
+    fn test_known_roots() {
+        let four = MerkleTree::from_leaves(&[leaf(1), leaf(2), leaf(3), leaf(4)]);
+        assert_eq!(
+            to_hex(&four.root()),
+            "0x0b242b9a6559f2d9f8563485a0697b746ec58ce879e0e5ac94d4c8a250723121"
+        );
+
+        // The odd third leaf is carried up and paired at the next level
+        let three = MerkleTree::from_leaves(&[leaf(1), leaf(2), leaf(3)]);
+        assert_eq!(
+            three.root(),
+            hash_pair(&hash_pair(&leaf(1), &leaf(2)), &leaf(3))
+        );
+        assert_eq!(
+            to_hex(&three.root()),
+            "0x1d614fa3c8de62938b0948972494f9a3858575db69ce1d34c77926f30732c981"
+        );
+
+        assert_eq!(MerkleTree::from_leaves(&[leaf(7)]).root(), leaf(7));
+        assert_eq!(MerkleTree::from_leaves(&[]).root(), [0u8; 32]);
+    }
+
+    #[test]
+    fn test_every_proof_verifies() {
+        for n in 1..=7u8 {
+            let leaves: Vec<Hash> = (1..=n).map(leaf).collect();
+            let tree = MerkleTree::from_leaves(&leaves);
+            let root = tree.root();
+
+            for (i, l) in leaves.iter().enumerate() {
+                let path = tree.proof(i);
+                assert!(verify(l, &path, &root), "leaf {} of {}", i, n);
+                assert!(!verify(&leaf(99), &path, &root));
+            }
+        }
+    }
+}
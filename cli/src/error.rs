@@ -0,0 +1,13 @@
+//! Typed errors for failures a user can fix themselves
+//!
+//! Most errors are plain `anyhow` context chains; these carry enough detail
+//! to say exactly what to change, and can be matched with `downcast_ref`.
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SwarmError {
+    /// The signing key isn't a 32-byte hex secp256k1 key
+    #[error("Invalid private key: {reason}. Expected 64 hex characters, optionally prefixed with 0x")]
+    BadKey { reason: String },
+}
@@ -8,383 +8,2867 @@
 //! │   └── {epoch_id}.json
 //! ├── jobs/             # Job submissions
 //! │   └── {job_id}.json
+//! ├── cancellations/    # Jobs withdrawn by their client before settlement
+//! │   └── {job_id}.json
 //! ├── claims/           # Job claims
 //! │   └── {claim_id}.json
 //! ├── proofs/           # Completed proofs
 //! │   └── {proof_id}.json
+//! ├── disputes/         # Disputes against proofs
+//! │   └── {dispute_id}.json
+//! ├── refunds/          # Jobs refunded to the client
+//! │   └── {job_id}.json
+//! ├── withdrawals/      # Provider withdrawal requests
+//! │   └── {withdrawal_id}.json
+//! ├── providers/        # Latest provider heartbeats (overwritten)
+//! │   └── {provider}/status.json
 //! └── index/            # Indexes and state
 //!     ├── state.json
 //!     └── providers.json
 
 use anyhow::{Context, Result};
+use colored::Colorize;
+use futures::Stream;
 use serde::{de::DeserializeOwned, Serialize};
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::instrument;
 
-use crate::models::{EpochSnapshot, PoolState};
+use crate::cache;
+use crate::config::Config;
+use crate::crypto;
+use crate::storage::Storage;
+use crate::models::{
+    ClaimSnapshot, DisputeSnapshot, EpochSnapshot, HeartbeatSnapshot, JobSnapshot, PoolState,
+    ProofSnapshot, ProviderRegistration, UsdcAmount, WithdrawalSnapshot, WithdrawnJob,
+    DEFAULT_EPOCH_DURATION_SECS,
+};
 
 const IPFS_API: &str = "http://localhost:5001/api/v0";
 const IPFS_GATEWAY: &str = "https://ipfs.io/ipfs";
 
-/// Canonical IPFS directory paths
-pub mod paths {
-    pub const ROOT: &str = "/swarmpool";
-    pub const GENESIS: &str = "/swarmpool/genesis";
-    pub const EPOCHS: &str = "/swarmpool/epochs";
-    pub const JOBS: &str = "/swarmpool/jobs";
-    pub const CLAIMS: &str = "/swarmpool/claims";
-    pub const PROOFS: &str = "/swarmpool/proofs";
-    pub const INDEX: &str = "/swarmpool/index";
+/// Idle keep-alive connections kept per host
+const POOL_MAX_IDLE_PER_HOST: usize = 8;
+
+/// Delay before the first pubsub resubscribe, and the cap it doubles up to
+const PUBSUB_MIN_BACKOFF: Duration = Duration::from_secs(1);
+const PUBSUB_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Largest JSON response body read before giving up (snapshots are a few KB)
+pub const DEFAULT_MAX_SNAPSHOT_BYTES: usize = 4 * 1024 * 1024;
+
+static MAX_SNAPSHOT_BYTES: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_SNAPSHOT_BYTES);
+
+/// Cap on JSON response bodies for every later fetch
+pub fn set_max_snapshot_bytes(max_bytes: usize) {
+    MAX_SNAPSHOT_BYTES.store(max_bytes, Ordering::Relaxed);
+}
+
+/// Client for the IPFS API and gateway
+///
+/// Holds one `reqwest::Client`, whose keep-alive pool is reused by every
+/// call, so a multi-write `seal` doesn't reconnect for each request. Built
+/// once from the config by `init` and handed to `IpfsStorage`.
+pub struct IpfsClient {
+    http: reqwest::Client,
+    api_base: String,
+    gateway_base: String,
+    /// Where content fetched by CID is kept; `None` if there's no config
+    /// directory to put it in
+    cache: Option<cache::Cache>,
 }
 
-/// Check IPFS connection
-pub async fn check_connection() -> Result<()> {
-    let client = reqwest::Client::new();
+static CLIENT: OnceLock<IpfsClient> = OnceLock::new();
 
-    let response = client
-        .post(&format!("{}/id", IPFS_API))
-        .send()
-        .await
-        .context("Failed to connect to IPFS daemon")?;
+impl IpfsClient {
+    pub fn new(api_base: &str, gateway_base: &str) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .tcp_keepalive(Duration::from_secs(60))
+            .build()
+            .context("Failed to build HTTP client")?;
 
-    if !response.status().is_success() {
-        anyhow::bail!("IPFS daemon returned error: {}", response.status());
+        Ok(Self {
+            http,
+            api_base: api_base.trim_end_matches('/').to_string(),
+            gateway_base: gateway_base.trim_end_matches('/').to_string(),
+            cache: cache::Cache::open().ok(),
+        })
     }
 
-    Ok(())
-}
+    /// Keep fetched content in `cache` instead of the config directory's
+    pub fn with_cache(mut self, cache: cache::Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
 
-/// Initialize canonical directory structure
-pub async fn init_directories() -> Result<()> {
-    let client = reqwest::Client::new();
+    /// Client for the daemon and gateway named in `config`
+    ///
+    /// `ipfs_api` is the daemon's address (`http://localhost:5001`); the
+    /// `/api/v0` prefix is added unless it's already there.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let api = config.ipfs_api.trim_end_matches('/');
+        let api_base = if api.ends_with("/api/v0") {
+            api.to_string()
+        } else {
+            format!("{}/api/v0", api)
+        };
+        Self::new(&api_base, &config.ipfs_gateway)
+    }
 
-    for dir in [
-        paths::ROOT,
-        paths::GENESIS,
-        paths::EPOCHS,
-        paths::JOBS,
-        paths::CLAIMS,
-        paths::PROOFS,
-        paths::INDEX,
-    ] {
-        client
-            .post(&format!("{}/files/mkdir?arg={}&parents=true", IPFS_API, dir))
+    /// Build the process-wide client from `config`, or the defaults when
+    /// there is none. Call once at startup, before any IPFS access.
+    pub fn init(config: Option<&Config>) -> Result<&'static IpfsClient> {
+        let client = match config {
+            Some(config) => Self::from_config(config)?,
+            None => Self::new(IPFS_API, IPFS_GATEWAY)?,
+        };
+        Ok(CLIENT.get_or_init(|| client))
+    }
+
+    /// POST to an API endpoint, e.g. `pin/add?arg=<cid>`
+    pub fn post(&self, endpoint: &str) -> reqwest::RequestBuilder {
+        self.http.post(format!("{}/{}", self.api_base, endpoint))
+    }
+
+    /// The pooled client, for requests outside the API (gateway fetches)
+    pub fn http(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    /// Check IPFS connection
+    pub async fn check_connection(&self) -> Result<()> {
+        let response = self
+            .post("id")
             .send()
             .await
-            .context(format!("Failed to create directory: {}", dir))?;
+            .context("Failed to connect to IPFS daemon")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("IPFS daemon returned error: {}", response.status());
+        }
+
+        Ok(())
     }
 
-    Ok(())
-}
+    /// Upload JSON to IPFS (returns CID)
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn upload_json<T: Serialize>(&self, data: &T) -> Result<String> {
+        let json_str = serde_json::to_string_pretty(data)?;
+        self.upload_bytes(json_str.into_bytes(), "data.json").await
+    }
 
-/// Upload file to IPFS (returns CID)
-pub async fn upload_file(path: &str) -> Result<String> {
-    let client = reqwest::Client::new();
-    let file_path = Path::new(path);
+    /// Upload raw bytes to IPFS under a file name (returns CID)
+    #[instrument(level = "debug", skip(self, bytes), fields(size = bytes.len()))]
+    pub async fn upload_bytes(&self, bytes: Vec<u8>, file_name: &str) -> Result<String> {
+        let form = reqwest::multipart::Form::new().part(
+            "file",
+            reqwest::multipart::Part::bytes(bytes).file_name(file_name.to_string()),
+        );
 
-    let file_name = file_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("file");
+        let response = self
+            .post("add")
+            .multipart(form)
+            .send()
+            .await
+            .context("Failed to upload to IPFS")?;
 
-    let file_bytes = tokio::fs::read(path)
-        .await
-        .context("Failed to read file")?;
+        let result: serde_json::Value = response.json().await?;
 
-    let form = reqwest::multipart::Form::new().part(
-        "file",
-        reqwest::multipart::Part::bytes(file_bytes).file_name(file_name.to_string()),
-    );
+        result["Hash"]
+            .as_str()
+            .map(|s| s.to_string())
+            .context("Invalid response from IPFS")
+    }
 
-    let response = client
-        .post(&format!("{}/add", IPFS_API))
-        .multipart(form)
-        .send()
-        .await
-        .context("Failed to upload to IPFS")?;
+    /// Upload files as one directory (returns the wrapping directory CID)
+    ///
+    /// `files` are `(relative path, bytes)` pairs using `/` separators; nested
+    /// directories are created as needed.
+    #[instrument(level = "debug", skip(self, files), fields(files = files.len()))]
+    pub async fn upload_directory(&self, files: Vec<(String, Vec<u8>)>) -> Result<String> {
+        // Parent directories must be announced before the files inside them
+        let mut dirs: Vec<String> = files
+            .iter()
+            .flat_map(|(path, _)| {
+                let parts: Vec<&str> = path.split('/').collect();
+                (1..parts.len())
+                    .map(|i| parts[..i].join("/"))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        dirs.sort();
+        dirs.dedup();
 
-    let result: serde_json::Value = response.json().await?;
+        let mut form = reqwest::multipart::Form::new();
+        for dir in dirs {
+            form = form.part(
+                "file",
+                reqwest::multipart::Part::bytes(Vec::new())
+                    .file_name(dir)
+                    .mime_str("application/x-directory")?,
+            );
+        }
+        for (path, bytes) in files {
+            form = form.part("file", reqwest::multipart::Part::bytes(bytes).file_name(path));
+        }
 
-    result["Hash"]
-        .as_str()
-        .map(|s| s.to_string())
-        .context("Invalid response from IPFS")
-}
+        let response = self
+            .post("add?wrap-with-directory=true")
+            .multipart(form)
+            .send()
+            .await
+            .context("Failed to upload directory to IPFS")?;
 
-/// Upload JSON to IPFS (returns CID)
-pub async fn upload_json<T: Serialize>(data: &T) -> Result<String> {
-    let client = reqwest::Client::new();
-    let json_str = serde_json::to_string_pretty(data)?;
+        if !response.status().is_success() {
+            anyhow::bail!("IPFS add failed: {}", response.status());
+        }
 
-    let form = reqwest::multipart::Form::new().part(
-        "file",
-        reqwest::multipart::Part::bytes(json_str.into_bytes()).file_name("data.json"),
-    );
+        // One JSON line per added entry; the wrapping directory has an empty name
+        let body = response.text().await?;
+        body.lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .find(|entry| entry["Name"].as_str() == Some(""))
+            .and_then(|entry| entry["Hash"].as_str().map(|s| s.to_string()))
+            .context("Invalid response from IPFS: no directory CID")
+    }
 
-    let response = client
-        .post(&format!("{}/add", IPFS_API))
-        .multipart(form)
-        .send()
-        .await
-        .context("Failed to upload to IPFS")?;
+    /// Fetch every file under a directory CID as `(relative path, bytes)`
+    #[instrument(level = "debug", skip(self))]
+    pub async fn fetch_directory(&self, cid: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let mut files = Vec::new();
+        let mut pending = vec![(String::new(), cid.to_string())];
 
-    let result: serde_json::Value = response.json().await?;
+        while let Some((prefix, dir_cid)) = pending.pop() {
+            let response = self
+                .post(&format!("ls?arg={}", dir_cid))
+                .send()
+                .await
+                .context("Failed to list directory CID")?;
+            let listing: serde_json::Value = response.json().await?;
 
-    result["Hash"]
-        .as_str()
-        .map(|s| s.to_string())
-        .context("Invalid response from IPFS")
-}
+            let links = listing["Objects"][0]["Links"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            for link in links {
+                let name = link["Name"].as_str().context("Directory entry without a name")?;
+                let hash = link["Hash"].as_str().context("Directory entry without a CID")?;
+                let path = if prefix.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}/{}", prefix, name)
+                };
 
-/// Write JSON to canonical MFS path
-pub async fn write_to_path<T: Serialize>(mfs_path: &str, data: &T) -> Result<String> {
-    let client = reqwest::Client::new();
-    let json_str = serde_json::to_string_pretty(data)?;
+                // Type 1 is a directory in the unixfs listing
+                if link["Type"].as_i64() == Some(1) {
+                    pending.push((path, hash.to_string()));
+                } else {
+                    files.push((path, self.fetch_bytes(hash).await?));
+                }
+            }
+        }
 
-    // First add to IPFS to get CID
-    let cid = upload_json(data).await?;
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(files)
+    }
 
-    // Then copy to MFS path
-    client
-        .post(&format!(
-            "{}/files/cp?arg=/ipfs/{}&arg={}",
-            IPFS_API, cid, mfs_path
-        ))
-        .send()
-        .await
-        .context("Failed to write to MFS path")?;
+    /// Fetch raw bytes by CID (binary inputs such as DICOM)
+    ///
+    /// Tries the local node, then the public gateway. Unlike `fetch_json` the
+    /// content is not cached, so this fails under `--offline`.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn fetch_bytes(&self, cid: &str) -> Result<Vec<u8>> {
+        if cache::is_offline() {
+            anyhow::bail!("Offline: raw content {} is not cached", cid);
+        }
 
-    Ok(cid)
-}
+        let local = self
+            .post(&format!("cat?arg={}", cid))
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await;
+        if let Ok(response) = local {
+            if response.status().is_success() {
+                if let Ok(bytes) = response.bytes().await {
+                    return Ok(bytes.to_vec());
+                }
+            }
+        }
 
-/// Write job to canonical path: /swarmpool/jobs/{job_id}.json
-pub async fn write_job<T: Serialize>(job_id: &str, data: &T) -> Result<String> {
-    let path = format!("{}/{}.json", paths::JOBS, job_id);
-    write_to_path(&path, data).await
-}
+        let response = self
+            .http()
+            .get(&format!("{}/{}", self.gateway_base, cid))
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await
+            .with_context(|| format!("Failed to fetch {}", cid))?;
 
-/// Write claim to canonical path: /swarmpool/claims/{claim_id}.json
-pub async fn write_claim<T: Serialize>(claim_id: &str, data: &T) -> Result<String> {
-    let path = format!("{}/{}.json", paths::CLAIMS, claim_id);
-    write_to_path(&path, data).await
-}
+        if !response.status().is_success() {
+            anyhow::bail!("IPFS fetch {} failed: {}", cid, response.status());
+        }
+        Ok(response.bytes().await?.to_vec())
+    }
 
-/// Write proof to canonical path: /swarmpool/proofs/{proof_id}.json
-pub async fn write_proof<T: Serialize>(proof_id: &str, data: &T) -> Result<String> {
-    let path = format!("{}/{}.json", paths::PROOFS, proof_id);
-    write_to_path(&path, data).await
-}
+    /// Write JSON to canonical MFS path
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn write_to_path<T: Serialize>(&self, mfs_path: &str, data: &T) -> Result<String> {
+        let json_str = serde_json::to_string_pretty(data)?;
 
-/// Write epoch to canonical path: /swarmpool/epochs/{epoch_id}.json
-pub async fn write_epoch<T: Serialize>(epoch_id: &str, data: &T) -> Result<String> {
-    let path = format!("{}/{}.json", paths::EPOCHS, epoch_id);
-    write_to_path(&path, data).await
-}
+        // First add to IPFS to get CID
+        let cid = self.upload_json(data).await?;
 
-/// Write genesis (provider init) to canonical path: /swarmpool/genesis/{provider}.json
-pub async fn write_genesis<T: Serialize>(provider: &str, data: &T) -> Result<String> {
-    // Sanitize provider name for path
-    let safe_name = provider.replace('.', "_");
-    let path = format!("{}/{}.json", paths::GENESIS, safe_name);
-    write_to_path(&path, data).await
-}
-
-/// Fetch JSON from IPFS by CID (tries local API first, then gateway)
-pub async fn fetch_json<T: DeserializeOwned>(cid: &str) -> Result<T> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
-        .build()?;
-
-    // Try local IPFS API first
-    let local_url = format!("{}/cat?arg={}", IPFS_API, cid);
-    if let Ok(response) = client.post(&local_url).send().await {
-        if response.status().is_success() {
-            if let Ok(data) = response.json().await {
-                return Ok(data);
+        if let Some((parent, _)) = mfs_path.rsplit_once('/') {
+            self.post(&format!("files/mkdir?arg={}&parents=true", parent))
+                .send()
+                .await
+                .context(format!("Failed to create directory: {}", parent))?;
+        }
+
+        // Then copy to MFS path
+        self.post(&format!("files/cp?arg=/ipfs/{}&arg={}", cid, mfs_path))
+            .send()
+            .await
+            .context("Failed to write to MFS path")?;
+
+        Ok(cid)
+    }
+
+    /// Replace the JSON at an MFS path
+    ///
+    /// The new content is written beside the old under a hidden temporary
+    /// name first, so a failed upload or copy leaves the existing entry as
+    /// it was. Only then is the old entry removed and the new one moved into
+    /// its place; readers never see a missing or partly written file unless
+    /// that final move fails, and then the error names the temporary file.
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn replace_at_path<T: Serialize>(&self, mfs_path: &str, data: &T) -> Result<String> {
+        let (parent, name) = mfs_path
+            .rsplit_once('/')
+            .with_context(|| format!("Not an MFS path: {}", mfs_path))?;
+        let staged = format!("{}/.{}.tmp-{}", parent, name, crypto::random_hex(4));
+        let cid = self.upload_json(data).await?;
+        self.files_op(&format!("files/mkdir?arg={}&parents=true", parent))
+            .await
+            .with_context(|| format!("Failed to create directory: {}", parent))?;
+        self.files_op(&format!("files/cp?arg=/ipfs/{}&arg={}", cid, staged))
+            .await
+            .with_context(|| format!("Failed to write {}", staged))?;
+
+        // Nothing to remove the first time a path is written
+        if let Err(e) = self
+            .files_op(&format!("files/rm?arg={}&force=true", mfs_path))
+            .await
+        {
+            if !e.to_string().contains("does not exist") {
+                return Err(e.context("Failed to remove existing MFS entry"));
+            }
+        }
+        self.files_op(&format!("files/mv?arg={}&arg={}", staged, mfs_path))
+            .await
+            .with_context(|| format!("Failed to move {} into place at {}", staged, mfs_path))?;
+
+        Ok(cid)
+    }
+
+    /// Run an MFS API call, failing on an error status as well as on a
+    /// transport error
+    async fn files_op(&self, endpoint: &str) -> Result<()> {
+        let response = self.post(endpoint).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("IPFS {} failed ({}): {}", endpoint, status, body.trim());
+        }
+        Ok(())
+    }
+
+    /// Fetch JSON from IPFS by CID (tries local API first, then gateway)
+    ///
+    /// Content that doesn't hash to `cid` is refused. CIDs are immutable, so
+    /// content that does is written through to the local cache and a CID
+    /// already cached is never fetched again (under `--offline` the cache is
+    /// all there is). Cached bytes are rehashed when read back, and content
+    /// too large to rehash here (see `verify_content`) is never cached. MFS
+    /// paths (starting with `/`) are read with `read_from_path`; anything
+    /// else must parse as a CID, so a typo fails here instead of as a
+    /// gateway 404.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn fetch_json<T: DeserializeOwned>(&self, cid: &str) -> Result<T> {
+        if cid.starts_with('/') {
+            return self.read_from_path(cid).await;
+        }
+        self.fetch_cid(cid).await
+    }
+
+    /// `fetch_json` for a CID
+    async fn fetch_cid<T: DeserializeOwned>(&self, cid: &str) -> Result<T> {
+        parse_cid(cid)?;
+
+        let bytes = if cache::is_offline() {
+            let cache = self
+                .cache
+                .as_ref()
+                .context("Offline: no local cache directory")?;
+            let bytes = cache.require_bytes(cid)?;
+            verify_content(cid, &bytes)?;
+            bytes
+        } else if let Some(bytes) = self.cached(cid) {
+            bytes
+        } else {
+            let max_bytes = MAX_SNAPSHOT_BYTES.load(Ordering::Relaxed);
+            let bytes = self.fetch_snapshot_online(cid, max_bytes).await?;
+            if verify_content(cid, &bytes)? {
+                self.keep(cid, &bytes);
             }
+            bytes
+        };
+        serde_json::from_slice(&bytes).context("Failed to parse JSON")
+    }
+
+    /// Cached content of `cid`, unless `--no-cache`; an entry that no longer
+    /// hashes to `cid` is a miss
+    fn cached(&self, cid: &str) -> Option<Vec<u8>> {
+        let cache = self.cache.as_ref().filter(|_| cache::enabled())?;
+        match cache.get_bytes(cid) {
+            Ok(Some(bytes)) if matches!(verify_content(cid, &bytes), Ok(true)) => Some(bytes),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::debug!("Cache read for {} skipped: {}", cid, e);
+                None
+            }
+        }
+    }
+
+    /// Best-effort write-through of content verified against `cid`
+    fn keep(&self, cid: &str, bytes: &[u8]) {
+        let Some(cache) = self.cache.as_ref().filter(|_| cache::enabled()) else {
+            return;
+        };
+        if let Err(e) = cache.put_bytes(cid, bytes) {
+            tracing::debug!("Cache write for {} skipped: {}", cid, e);
         }
     }
 
-    // Fall back to public gateway
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()?;
+    /// Raw snapshot bytes for `cid`; the local node's answer is only taken
+    /// if it doesn't contradict `cid`, otherwise the gateway is asked
+    async fn fetch_snapshot_online(&self, cid: &str, max_bytes: usize) -> Result<Vec<u8>> {
+        // Try local IPFS API first
+        let local = self
+            .post(&format!("cat?arg={}", cid))
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await;
+        if let Ok(response) = local {
+            if response.status().is_success() {
+                if let Ok(bytes) = read_capped(response, max_bytes).await {
+                    if verify_content(cid, &bytes).is_ok() {
+                        return Ok(bytes);
+                    }
+                }
+            }
+        }
+
+        // Fall back to the gateway
+        let url = format!("{}/{}", self.gateway_base, cid);
+
+        let response = self
+            .http()
+            .get(&url)
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await
+            .context("Failed to fetch from IPFS")?;
 
-    let url = format!("{}/{}", IPFS_GATEWAY, cid);
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            anyhow::bail!("CID not found: {} (not on the local node or {})", cid, self.gateway_base);
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("IPFS fetch failed: {}", response.status());
+        }
 
-    let response = client
-        .get(&url)
-        .send()
-        .await
-        .context("Failed to fetch from IPFS")?;
+        read_capped(response, max_bytes)
+            .await
+            .with_context(|| format!("Failed to read {} from {}", cid, self.gateway_base))
+    }
+
+    /// Read JSON from MFS path
+    ///
+    /// A path is only a name: whatever is there can be replaced, so it's
+    /// resolved to its current CID (`files/stat`) on every read and the
+    /// content fetched by that CID, from the cache when it's there. Offline,
+    /// the CID last seen at the path is used, with a warning that it may be
+    /// out of date. Index and heartbeat files are read directly and never
+    /// served offline here.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn read_from_path<T: DeserializeOwned>(&self, mfs_path: &str) -> Result<T> {
+        if is_mutable(mfs_path) {
+            if cache::is_offline() {
+                anyhow::bail!(
+                    "Offline: {} is mutable and can't be read from cache",
+                    mfs_path
+                );
+            }
+            return self.read_from_path_online(mfs_path).await;
+        }
+
+        let cid = if cache::is_offline() {
+            let cache = self
+                .cache
+                .as_ref()
+                .context("Offline: no local cache directory")?;
+            let cid: String = cache.require(mfs_path)?;
+            warn_paths_as_last_seen();
+            cid
+        } else {
+            let cid = self
+                .path_cid(mfs_path)
+                .await?
+                .with_context(|| format!("{} does not exist", mfs_path))?;
+            if let Some(cache) = self.cache.as_ref().filter(|_| cache::enabled()) {
+                if let Err(e) = cache.put(mfs_path, &cid) {
+                    tracing::debug!("Cache write for {} skipped: {}", mfs_path, e);
+                }
+            }
+            cid
+        };
+        self.fetch_cid(&cid)
+            .await
+            .with_context(|| format!("Failed to read {}", mfs_path))
+    }
+
+    async fn read_from_path_online<T: DeserializeOwned>(&self, mfs_path: &str) -> Result<T> {
+        let response = self
+            .post(&format!("files/read?arg={}", mfs_path))
+            .send()
+            .await
+            .context("Failed to read from MFS")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("MFS read failed: {}", response.status());
+        }
+
+        read_json_capped(response, MAX_SNAPSHOT_BYTES.load(Ordering::Relaxed))
+            .await
+            .with_context(|| format!("Failed to read {}", mfs_path))
+    }
+
+    /// Pin CID to local IPFS node
+    #[instrument(level = "debug", skip(self))]
+    pub async fn pin(&self, cid: &str) -> Result<()> {
+        self.post(&format!("pin/add?arg={}", cid))
+            .send()
+            .await
+            .context("Failed to pin CID")?;
+
+        Ok(())
+    }
+
+    /// Messages published to a pubsub topic, decoded, as they arrive
+    ///
+    /// Holds one `pubsub/sub` response open and reads its newline-delimited
+    /// envelopes. When the stream closes or the daemon can't be reached (a
+    /// restart), it resubscribes after a backoff that doubles up to
+    /// `PUBSUB_MAX_BACKOFF`, so the stream itself never ends. Lines that
+    /// don't decode are skipped.
+    pub fn pubsub_stream(&self, topic: &str) -> impl Stream<Item = serde_json::Value> + '_ {
+        struct Subscription {
+            endpoint: String,
+            response: Option<reqwest::Response>,
+            buffer: Vec<u8>,
+            backoff: Duration,
+        }
+
+        let start = Subscription {
+            endpoint: format!("pubsub/sub?arg={}", multibase_encode(topic.as_bytes())),
+            response: None,
+            buffer: Vec::new(),
+            backoff: PUBSUB_MIN_BACKOFF,
+        };
+
+        futures::stream::unfold(start, move |mut sub| async move {
+            loop {
+                if let Some(end) = sub.buffer.iter().position(|&b| b == b'\n') {
+                    let line: Vec<u8> = sub.buffer.drain(..=end).collect();
+                    let line = String::from_utf8_lossy(&line);
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match decode_pubsub_line(&line) {
+                        Ok(message) => return Some((message.message, sub)),
+                        Err(e) => tracing::debug!("Skipping pubsub line: {:#}", e),
+                    }
+                    continue;
+                }
+
+                let Some(response) = sub.response.as_mut() else {
+                    match self.post(&sub.endpoint).send().await {
+                        Ok(response) if response.status().is_success() => {
+                            sub.response = Some(response);
+                        }
+                        Ok(response) => {
+                            tracing::warn!("Pubsub subscribe failed: {}", response.status());
+                            sub.backoff = backoff(sub.backoff).await;
+                        }
+                        Err(e) => {
+                            tracing::warn!("Pubsub subscribe failed: {}", e);
+                            sub.backoff = backoff(sub.backoff).await;
+                        }
+                    }
+                    continue;
+                };
+
+                match response.chunk().await {
+                    Ok(Some(chunk)) => {
+                        sub.buffer.extend_from_slice(&chunk);
+                        sub.backoff = PUBSUB_MIN_BACKOFF;
+                    }
+                    Ok(None) | Err(_) => {
+                        tracing::warn!("Pubsub stream closed; resubscribing");
+                        sub.response = None;
+                        sub.buffer.clear();
+                        sub.backoff = backoff(sub.backoff).await;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Publish to IPFS pubsub topic
+    ///
+    /// The topic goes multibase-encoded in the URL and the JSON as the
+    /// request body, as the daemon expects; subscribers get it back through
+    /// `decode_pubsub_line`.
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn pubsub_publish<T: Serialize>(&self, topic: &str, data: &T) -> Result<()> {
+        let form = reqwest::multipart::Form::new().part(
+            "file",
+            reqwest::multipart::Part::bytes(serde_json::to_vec(data)?).file_name("message.json"),
+        );
+
+        let response = self
+            .post(&format!("pubsub/pub?arg={}", multibase_encode(topic.as_bytes())))
+            .multipart(form)
+            .send()
+            .await
+            .context("Failed to publish to topic")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("IPFS pubsub publish failed: {}", response.status());
+        }
+        Ok(())
+    }
+
+    /// Whether an MFS directory exists
+    pub async fn dir_exists(&self, mfs_path: &str) -> Result<bool> {
+        let response = self
+            .post(&format!("files/stat?arg={}", mfs_path))
+            .send()
+            .await
+            .context("Failed to stat directory")?;
+
+        if response.status().is_success() {
+            let stat: serde_json::Value = response.json().await?;
+            return Ok(stat["Type"] == "directory");
+        }
+        let body = response.text().await.unwrap_or_default();
+        if body.contains("does not exist") {
+            return Ok(false);
+        }
+        anyhow::bail!("IPFS files/stat failed for {}: {}", mfs_path, body.trim())
+    }
+
+    /// CID of the file at an MFS path, or `None` when the daemon says it
+    /// does not exist
+    pub async fn path_cid(&self, mfs_path: &str) -> Result<Option<String>> {
+        let response = self
+            .post(&format!("files/stat?arg={}", mfs_path))
+            .send()
+            .await
+            .context("Failed to stat path")?;
 
-    if !response.status().is_success() {
-        anyhow::bail!("IPFS fetch failed: {}", response.status());
+        if response.status().is_success() {
+            let stat: serde_json::Value = response.json().await?;
+            let cid = stat["Hash"]
+                .as_str()
+                .with_context(|| format!("IPFS files/stat gave no CID for {}", mfs_path))?;
+            return Ok(Some(cid.to_string()));
+        }
+        let body = response.text().await.unwrap_or_default();
+        if body.contains("does not exist") {
+            return Ok(None);
+        }
+        anyhow::bail!("IPFS files/stat failed for {}: {}", mfs_path, body.trim())
     }
 
-    let data: T = response.json().await.context("Failed to parse JSON")?;
-    Ok(data)
+    /// List files in MFS directory
+    pub async fn list_directory(&self, mfs_path: &str) -> Result<Vec<String>> {
+        let response = self
+            .post(&format!("files/ls?arg={}&long=true", mfs_path))
+            .send()
+            .await
+            .context("Failed to list directory")?;
+
+        let result: serde_json::Value = response.json().await?;
+
+        let entries = result["Entries"]
+            .as_array()
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|e| e["Name"].as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(entries)
+    }
 }
 
-/// Read JSON from MFS path
-pub async fn read_from_path<T: DeserializeOwned>(mfs_path: &str) -> Result<T> {
-    let client = reqwest::Client::new();
+/// Wait out a pubsub reconnect delay and return the next, longer one
+async fn backoff(delay: Duration) -> Duration {
+    tokio::time::sleep(delay).await;
+    (delay * 2).min(PUBSUB_MAX_BACKOFF)
+}
 
-    let response = client
-        .post(&format!("{}/files/read?arg={}", IPFS_API, mfs_path))
-        .send()
-        .await
-        .context("Failed to read from MFS")?;
+/// One message read from a pubsub subscription
+#[derive(Debug, Clone, PartialEq)]
+pub struct PubsubMessage {
+    /// Peer ID of the publisher
+    pub from: String,
+    pub seqno: Vec<u8>,
+    pub topics: Vec<String>,
+    /// The published bytes
+    pub data: Vec<u8>,
+    /// `data` parsed as JSON (every announcement is a JSON object)
+    pub message: serde_json::Value,
+}
 
-    if !response.status().is_success() {
-        anyhow::bail!("MFS read failed: {}", response.status());
+/// Decode one line of a `pubsub/sub` response
+///
+/// The daemon wraps each message in a JSON envelope whose `data`, `seqno`
+/// and `topicIDs` are multibase base64url (`u` prefix). Daemons from before
+/// multibase send plain base64 and raw topic names, which are accepted too.
+pub fn decode_pubsub_line(line: &str) -> Result<PubsubMessage> {
+    #[derive(serde::Deserialize)]
+    struct Envelope {
+        #[serde(default)]
+        from: String,
+        data: String,
+        #[serde(default)]
+        seqno: String,
+        #[serde(default, rename = "topicIDs")]
+        topic_ids: Vec<String>,
     }
 
-    let data: T = response.json().await.context("Failed to parse JSON")?;
-    Ok(data)
+    let envelope: Envelope =
+        serde_json::from_str(line.trim()).context("Not a pubsub message envelope")?;
+    let data = multibase_decode(&envelope.data).context("Pubsub data is not base64")?;
+    let message = serde_json::from_slice(&data).context("Pubsub message is not JSON")?;
+    let topics = envelope
+        .topic_ids
+        .iter()
+        .map(|topic| match topic.strip_prefix('u') {
+            Some(_) => multibase_decode(topic)
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .unwrap_or_else(|| topic.clone()),
+            None => topic.clone(),
+        })
+        .collect();
+
+    Ok(PubsubMessage {
+        from: envelope.from,
+        seqno: multibase_decode(&envelope.seqno).unwrap_or_default(),
+        topics,
+        data,
+        message,
+    })
 }
 
-/// Pin CID to local IPFS node
-pub async fn pin(cid: &str) -> Result<()> {
-    let client = reqwest::Client::new();
+/// Multibase base64url (`u` prefix, no padding), as the pubsub API uses
+fn multibase_encode(bytes: &[u8]) -> String {
+    use base64::Engine;
+    format!(
+        "u{}",
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    )
+}
 
-    client
-        .post(&format!("{}/pin/add?arg={}", IPFS_API, cid))
-        .send()
-        .await
-        .context("Failed to pin CID")?;
+/// Inverse of `multibase_encode`; unprefixed input is read as plain base64
+fn multibase_decode(encoded: &str) -> Result<Vec<u8>> {
+    use base64::Engine;
+    let bytes = match encoded.strip_prefix('u') {
+        Some(url_safe) => base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(url_safe)?,
+        None => base64::engine::general_purpose::STANDARD.decode(encoded)?,
+    };
+    Ok(bytes)
+}
 
-    Ok(())
+/// Canonical IPFS directory paths
+pub mod paths {
+    pub const ROOT: &str = "/swarmpool";
+    pub const GENESIS: &str = "/swarmpool/genesis";
+    pub const EPOCHS: &str = "/swarmpool/epochs";
+    pub const JOBS: &str = "/swarmpool/jobs";
+    pub const CANCELLATIONS: &str = "/swarmpool/cancellations";
+    pub const CLAIMS: &str = "/swarmpool/claims";
+    pub const PROOFS: &str = "/swarmpool/proofs";
+    pub const DISPUTES: &str = "/swarmpool/disputes";
+    pub const REFUNDS: &str = "/swarmpool/refunds";
+    pub const WITHDRAWALS: &str = "/swarmpool/withdrawals";
+    pub const PROVIDERS: &str = "/swarmpool/providers";
+    pub const INDEX: &str = "/swarmpool/index";
+
+    /// Every directory `init_directories` creates
+    pub const DIRECTORIES: [&str; 12] = [
+        ROOT,
+        GENESIS,
+        EPOCHS,
+        JOBS,
+        CANCELLATIONS,
+        CLAIMS,
+        PROOFS,
+        DISPUTES,
+        REFUNDS,
+        WITHDRAWALS,
+        PROVIDERS,
+        INDEX,
+    ];
 }
 
-/// Subscribe to IPFS pubsub topic
-pub async fn pubsub_subscribe(topic: &str) -> Result<()> {
-    let client = reqwest::Client::new();
+/// `ipfs add`'s default chunk size; content up to this fits in one block
+pub const DEFAULT_CHUNK_SIZE: usize = 256 * 1024;
 
-    client
-        .post(&format!(
-            "{}/pubsub/sub?arg={}",
-            IPFS_API,
-            urlencoding::encode(topic)
-        ))
-        .send()
-        .await
-        .context("Failed to subscribe to topic")?;
+/// CIDv0 that `ipfs add` (default settings) assigns to `bytes`, computed
+/// locally. Only single-chunk content is supported; larger content is
+/// split into a DAG this doesn't rebuild, so it returns `None`.
+pub fn local_cid(bytes: &[u8]) -> Option<String> {
+    use cid::multihash::{Code, MultihashDigest};
 
-    Ok(())
+    if bytes.len() > DEFAULT_CHUNK_SIZE {
+        return None;
+    }
+    cid::Cid::new_v0(Code::Sha2_256.digest(&unixfs_file_node(bytes)))
+        .ok()
+        .map(|cid| cid.to_string())
 }
 
-/// Publish to IPFS pubsub topic
-pub async fn pubsub_publish<T: Serialize>(topic: &str, data: &T) -> Result<()> {
-    let client = reqwest::Client::new();
-    let json_str = serde_json::to_string(data)?;
+/// The dag-pb block `ipfs add` stores for single-chunk content
+fn unixfs_file_node(bytes: &[u8]) -> Vec<u8> {
+    fn varint(mut n: u64, out: &mut Vec<u8>) {
+        while n >= 0x80 {
+            out.push((n as u8 & 0x7f) | 0x80);
+            n >>= 7;
+        }
+        out.push(n as u8);
+    }
 
-    client
-        .post(&format!(
-            "{}/pubsub/pub?arg={}&arg={}",
-            IPFS_API,
-            urlencoding::encode(topic),
-            urlencoding::encode(&json_str)
-        ))
-        .send()
-        .await
-        .context("Failed to publish to topic")?;
+    // UnixFS Data { Type: File, Data: bytes, filesize } inside a dag-pb
+    // PBNode { Data } with no links
+    let mut unixfs = vec![0x08, 0x02];
+    if !bytes.is_empty() {
+        unixfs.push(0x12);
+        varint(bytes.len() as u64, &mut unixfs);
+        unixfs.extend_from_slice(bytes);
+    }
+    unixfs.push(0x18);
+    varint(bytes.len() as u64, &mut unixfs);
 
-    Ok(())
+    let mut node = vec![0x0a];
+    varint(unixfs.len() as u64, &mut node);
+    node.extend_from_slice(&unixfs);
+    node
 }
 
-/// Fetch pool state from index
-pub async fn fetch_pool_state(pool: &str) -> Result<PoolState> {
-    // Try to read from MFS first
-    match read_from_path::<PoolState>(&format!("{}/state.json", paths::INDEX)).await {
-        Ok(state) => return Ok(state),
-        Err(_) => {
-            // Fall back to mock data for development
-            Ok(PoolState {
-                pool_id: pool.to_string(),
-                version: "1.0.0".to_string(),
-                total_jobs: 12847,
-                total_proofs: 12800,
-                total_volume_usdc: 1284.70,
-                current_epoch: Some("epoch-048".to_string()),
-                epoch_jobs: 156,
-                epoch_volume: 15.60,
-                pending_jobs: vec![],
-                active_providers: std::collections::HashMap::new(),
-                last_updated: chrono::Utc::now().timestamp(),
-            })
+/// Multicodec of a CID addressing its bytes directly (CIDv1 raw leaves)
+const RAW_CODEC: u64 = 0x55;
+/// Multicodec of a CID addressing a dag-pb node (every CIDv0)
+const DAG_PB_CODEC: u64 = 0x70;
+
+/// Check fetched `bytes` against the content id they were fetched by
+///
+/// A CID is recomputed with its own codec and multihash: over the bytes
+/// for raw CIDv1, over the single-chunk UnixFS node `ipfs add` builds for
+/// CIDv0 and dag-pb CIDv1. Local store ids (`0x` + keccak256) are rehashed
+/// the same way. Returns `Ok(false)` for content this can't recompute
+/// (several chunks, other codecs or hash functions) and an error when the
+/// bytes hash to something else.
+pub fn verify_content(id: &str, bytes: &[u8]) -> Result<bool> {
+    use cid::multihash::{Code, MultihashDigest};
+
+    if id.starts_with("0x") {
+        if crypto::keccak256_hash(bytes) != id {
+            anyhow::bail!("Content of {} does not match its id", id);
         }
+        return Ok(true);
     }
+
+    let cid = parse_cid(id)?;
+    let Ok(code) = Code::try_from(cid.hash().code()) else {
+        return Ok(false);
+    };
+    let digest = match cid.codec() {
+        RAW_CODEC => code.digest(bytes),
+        DAG_PB_CODEC if bytes.len() <= DEFAULT_CHUNK_SIZE => code.digest(&unixfs_file_node(bytes)),
+        _ => return Ok(false),
+    };
+    if digest.digest() != cid.hash().digest() {
+        anyhow::bail!("Content of {} does not match its CID", id);
+    }
+    Ok(true)
 }
 
-/// Fetch epochs
-pub async fn fetch_epochs(pool: &str, limit: u32) -> Result<Vec<EpochSnapshot>> {
-    // In production: list /swarmpool/epochs/ and fetch each
-    // Mock data for now
-    Ok(vec![
-        EpochSnapshot {
-            snapshot_type: "epoch".to_string(),
-            version: "1.0.0".to_string(),
-            epoch_id: "epoch-048".to_string(),
-            name: "Golf".to_string(),
-            status: "active".to_string(),
-            started_at: chrono::Utc::now().timestamp() - 3600,
-            ended_at: None,
-            jobs_count: 156,
-            total_volume_usdc: "15.60".to_string(),
-            merkle_root: None,
-            settlements: None,
-            controller: "merlin.swarmos.eth".to_string(),
-            timestamp: chrono::Utc::now().timestamp(),
-            sig: None,
-        },
-        EpochSnapshot {
-            snapshot_type: "epoch".to_string(),
-            version: "1.0.0".to_string(),
-            epoch_id: "epoch-047".to_string(),
-            name: "Foxtrot".to_string(),
-            status: "sealed".to_string(),
-            started_at: chrono::Utc::now().timestamp() - 7200,
-            ended_at: Some(chrono::Utc::now().timestamp() - 3600),
-            jobs_count: 312,
-            total_volume_usdc: "31.20".to_string(),
-            merkle_root: Some("0xabc123...".to_string()),
-            settlements: None,
-            controller: "merlin.swarmos.eth".to_string(),
-            timestamp: chrono::Utc::now().timestamp() - 3600,
-            sig: Some("0x...".to_string()),
-        },
-    ])
-}
-
-/// Fetch single epoch
-pub async fn fetch_epoch(pool: &str, epoch_id: &str) -> Result<EpochSnapshot> {
-    let epochs = fetch_epochs(pool, 100).await?;
-    epochs
-        .into_iter()
-        .find(|e| e.epoch_id == epoch_id)
-        .context("Epoch not found")
+/// Fetch raw bytes and refuse them unless they hash to `cid`
+///
+/// Guards against a node or gateway serving corrupted or substituted data.
+/// Content `verify_content` can't recompute (e.g. over `DEFAULT_CHUNK_SIZE`)
+/// is only accepted when it matches `sha256`, a digest the caller got from a
+/// signed snapshot such as a job's `input_sha256`; without one it is refused.
+pub async fn fetch_verified(
+    store: &dyn Storage,
+    cid: &str,
+    sha256: Option<&str>,
+) -> Result<Vec<u8>> {
+    let bytes = store
+        .get_bytes(cid)
+        .await
+        .with_context(|| format!("Failed to fetch {}", cid))?;
+    if verify_content(cid, &bytes)? {
+        return Ok(bytes);
+    }
+    match sha256 {
+        Some(expected) if expected.eq_ignore_ascii_case(&crypto::file_sha256(&bytes)) => Ok(bytes),
+        Some(_) => anyhow::bail!("Content of {} does not match its sha256", cid),
+        None => anyhow::bail!(
+            "{} ({} bytes) can't be rehashed locally and has no sha256 to check; refusing it",
+            cid,
+            bytes.len()
+        ),
+    }
+}
+
+/// Write pool state to the index: /swarmpool/index/state.json
+pub async fn write_pool_state(store: &dyn Storage, state: &PoolState) -> Result<String> {
+    store.replace(&format!("{}/state.json", paths::INDEX), state).await
 }
 
-/// List files in MFS directory
-pub async fn list_directory(mfs_path: &str) -> Result<Vec<String>> {
-    let client = reqwest::Client::new();
+/// Path of a provider's latest heartbeat record
+pub fn heartbeat_path(provider: &str) -> String {
+    format!("{}/{}/status.json", paths::PROVIDERS, provider)
+}
 
-    let response = client
-        .post(&format!("{}/files/ls?arg={}&long=true", IPFS_API, mfs_path))
-        .send()
-        .await
-        .context("Failed to list directory")?;
+/// Overwrite a provider's heartbeat: /swarmpool/providers/{provider}/status.json
+pub async fn write_heartbeat(
+    store: &dyn Storage,
+    provider: &str,
+    heartbeat: &HeartbeatSnapshot,
+) -> Result<String> {
+    store.replace(&heartbeat_path(provider), heartbeat).await
+}
 
-    let result: serde_json::Value = response.json().await?;
+/// Read a provider's latest heartbeat record
+pub async fn fetch_heartbeat(store: &dyn Storage, provider: &str) -> Result<HeartbeatSnapshot> {
+    store.read(&heartbeat_path(provider)).await
+}
 
-    let entries = result["Entries"]
-        .as_array()
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|e| e["Name"].as_str().map(|s| s.to_string()))
-                .collect()
-        })
-        .unwrap_or_default();
+/// Latest heartbeat time of each of `providers` whose record is signed by
+/// the wallet in its verified genesis. Providers with no such record are
+/// left out; they never extend a claim (see `ClaimSnapshot::expires_at`).
+pub async fn last_heartbeats<'a>(
+    store: &dyn Storage,
+    providers: impl IntoIterator<Item = &'a str>,
+) -> HashMap<String, i64> {
+    let mut seen = HashMap::new();
+    for provider in providers {
+        if seen.contains_key(provider) {
+            continue;
+        }
+        match verified_heartbeat(store, provider).await {
+            Ok(heartbeat) => {
+                seen.insert(provider.to_string(), heartbeat.timestamp);
+            }
+            Err(e) => tracing::debug!("No usable heartbeat for {}: {:#}", provider, e),
+        }
+    }
+    seen
+}
+
+async fn verified_heartbeat(store: &dyn Storage, provider: &str) -> Result<HeartbeatSnapshot> {
+    let raw: serde_json::Value = store.read(&heartbeat_path(provider)).await?;
+    let heartbeat: HeartbeatSnapshot = serde_json::from_value(raw.clone())?;
+    if heartbeat.provider != provider {
+        anyhow::bail!("record is for {}", heartbeat.provider);
+    }
+    let genesis = fetch_verified_genesis(store, provider).await?;
+    crypto::verify_snapshot_json(&raw, Some(&genesis.wallet))?;
+    Ok(heartbeat)
+}
+
+/// Write job to canonical path: /swarmpool/jobs/{job_id}.json
+pub async fn write_job<T: Serialize>(
+    store: &dyn Storage,
+    job_id: &str,
+    data: &T,
+) -> Result<String> {
+    let path = format!("{}/{}.json", paths::JOBS, job_id);
+    store.write(&path, data).await
+}
+
+/// Write claim to canonical path: /swarmpool/claims/{claim_id}.json
+pub async fn write_claim<T: Serialize>(
+    store: &dyn Storage,
+    claim_id: &str,
+    data: &T,
+) -> Result<String> {
+    let path = format!("{}/{}.json", paths::CLAIMS, claim_id);
+    store.write(&path, data).await
+}
 
-    Ok(entries)
+/// Write proof to canonical path: /swarmpool/proofs/{proof_id}.json
+pub async fn write_proof<T: Serialize>(
+    store: &dyn Storage,
+    proof_id: &str,
+    data: &T,
+) -> Result<String> {
+    let path = format!("{}/{}.json", paths::PROOFS, proof_id);
+    store.write(&path, data).await
+}
+
+/// Write epoch to canonical path: /swarmpool/epochs/{epoch_id}.json
+pub async fn write_epoch<T: Serialize>(
+    store: &dyn Storage,
+    epoch_id: &str,
+    data: &T,
+) -> Result<String> {
+    let path = format!("{}/{}.json", paths::EPOCHS, epoch_id);
+    store.write(&path, data).await
+}
+
+/// Write withdrawal request to canonical path: /swarmpool/withdrawals/{withdrawal_id}.json
+pub async fn write_withdrawal<T: Serialize>(
+    store: &dyn Storage,
+    withdrawal_id: &str,
+    data: &T,
+) -> Result<String> {
+    let path = format!("{}/{}.json", paths::WITHDRAWALS, withdrawal_id);
+    store.write(&path, data).await
+}
+
+/// Write a job's cancellation: /swarmpool/cancellations/{job_id}.json
+pub async fn write_cancellation(
+    store: &dyn Storage,
+    job_id: &str,
+    data: &serde_json::Value,
+) -> Result<String> {
+    let path = format!("{}/{}.json", paths::CANCELLATIONS, job_id);
+    store.write(&path, data).await
+}
+
+/// Canonical genesis path for a provider; dots in the ENS name become `_`
+pub fn genesis_path(provider: &str) -> String {
+    format!("{}/{}.json", paths::GENESIS, provider.replace('.', "_"))
+}
+
+/// Write genesis (provider init) to canonical path: /swarmpool/genesis/{provider}.json
+pub async fn write_genesis<T: Serialize>(
+    store: &dyn Storage,
+    provider: &str,
+    data: &T,
+) -> Result<String> {
+    store.write(&genesis_path(provider), data).await
+}
+
+/// Read a provider's genesis and check it was signed by the wallet it names
+///
+/// The EIP-191 signer is recovered over the published JSON minus `sig` (see
+/// docs/SIGNING.md). A missing, unsigned, mis-signed or mis-named genesis is
+/// an error, so callers can show the registration as unverified.
+pub async fn fetch_verified_genesis(
+    store: &dyn Storage,
+    provider: &str,
+) -> Result<ProviderRegistration> {
+    let path = genesis_path(provider);
+    let data: serde_json::Value = store
+        .read(&path)
+        .await
+        .with_context(|| format!("No genesis for {}", provider))?;
+    verified_genesis(data, &path)
+}
+
+/// Check a genesis document read from `path`: signed by the wallet it names,
+/// and `path` is its provider's canonical genesis path
+pub fn verified_genesis(data: serde_json::Value, path: &str) -> Result<ProviderRegistration> {
+    let object = data
+        .as_object()
+        .with_context(|| format!("Genesis at {} is not a JSON object", path))?;
+    let provider = object
+        .get("provider")
+        .and_then(|v| v.as_str())
+        .with_context(|| format!("Genesis at {} names no provider", path))?;
+    if genesis_path(provider) != path {
+        anyhow::bail!(
+            "Genesis at {} registers {}, not the provider at that path",
+            path,
+            provider
+        );
+    }
+
+    let sig = object
+        .get("sig")
+        .and_then(|v| v.as_str())
+        .with_context(|| format!("Genesis for {} is unsigned", provider))?;
+    let wallet = object
+        .get("wallet")
+        .and_then(|v| v.as_str())
+        .with_context(|| format!("Genesis for {} names no wallet", provider))?;
+
+    let mut unsigned = object.clone();
+    unsigned.remove("sig");
+    if !crypto::verify_signature(&serde_json::Value::Object(unsigned), sig, wallet)? {
+        anyhow::bail!("Genesis for {} is not signed by its wallet {}", provider, wallet);
+    }
+
+    serde_json::from_value(data).with_context(|| format!("Failed to parse {}", path))
+}
+
+/// Check that `cid` is a well-formed CID (v0 or v1)
+pub fn parse_cid(cid: &str) -> Result<cid::Cid> {
+    cid::Cid::try_from(cid).map_err(|e| anyhow::anyhow!("Not a valid CID: {:?} ({})", cid, e))
+}
+
+
+/// Parse a JSON response body, reading at most `max_bytes` of it (see
+/// `read_capped`)
+async fn read_json_capped<T: DeserializeOwned>(
+    response: reqwest::Response,
+    max_bytes: usize,
+) -> Result<T> {
+    let body = read_capped(response, max_bytes).await?;
+    serde_json::from_slice(&body).context("Failed to parse JSON")
+}
+
+/// A snapshot response body, reading at most `max_bytes` of it
+///
+/// A declared `Content-Length` over the cap fails before any body is read;
+/// otherwise the body is read chunk by chunk and abandoned once it passes
+/// the cap. A `Content-Type` that can't be a raw snapshot (an HTML error
+/// page, an image) is rejected; `text/plain` and `application/octet-stream`
+/// are allowed since that's what the IPFS API and gateways send for files.
+async fn read_capped(mut response: reqwest::Response, max_bytes: usize) -> Result<Vec<u8>> {
+    if let Some(content_type) = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    {
+        let media_type = content_type.split(';').next().unwrap_or("").trim();
+        let acceptable = media_type.eq_ignore_ascii_case("application/json")
+            || media_type.ends_with("+json")
+            || media_type.eq_ignore_ascii_case("text/plain")
+            || media_type.eq_ignore_ascii_case("application/octet-stream");
+        if !acceptable {
+            anyhow::bail!("Expected a JSON snapshot, got Content-Type {}", content_type);
+        }
+    }
+
+    if let Some(length) = response.content_length() {
+        if length > max_bytes as u64 {
+            anyhow::bail!(
+                "Snapshot is {} bytes, over the {} byte limit (raise with --max-snapshot-bytes)",
+                length,
+                max_bytes
+            );
+        }
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.context("Failed to read response body")? {
+        if body.len() + chunk.len() > max_bytes {
+            anyhow::bail!(
+                "Snapshot exceeds the {} byte limit (raise with --max-snapshot-bytes)",
+                max_bytes
+            );
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Paths that are overwritten in place and so never served from cache
+fn is_mutable(mfs_path: &str) -> bool {
+    mfs_path.starts_with(paths::INDEX) || mfs_path.starts_with(paths::PROVIDERS)
+}
+
+/// Offline, say once per run that snapshots read by path are the copies
+/// last seen online
+fn warn_paths_as_last_seen() {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+    if !WARNED.swap(true, Ordering::Relaxed) {
+        eprintln!(
+            "{} Offline: snapshots are shown as last seen online; they may have changed since",
+            "⚠".yellow()
+        );
+    }
+}
+
+
+/// Pool state not updated for this long (seconds) is reported as stale
+pub const DEFAULT_MAX_STATE_AGE_SECS: i64 = 3 * DEFAULT_EPOCH_DURATION_SECS as i64;
+
+static MAX_STATE_AGE: AtomicI64 = AtomicI64::new(DEFAULT_MAX_STATE_AGE_SECS);
+static STRICT_FRESHNESS: AtomicBool = AtomicBool::new(false);
+
+/// Staleness threshold for pool state, and whether stale state is an error
+pub fn set_freshness(max_age_secs: i64, strict: bool) {
+    MAX_STATE_AGE.store(max_age_secs, Ordering::Relaxed);
+    STRICT_FRESHNESS.store(strict, Ordering::Relaxed);
+}
+
+/// Warning for pool state last updated at `last_updated`, if it is older
+/// than `max_age` seconds; an error instead under `strict`
+pub fn check_freshness(
+    last_updated: i64,
+    now: i64,
+    max_age: i64,
+    strict: bool,
+) -> Result<Option<String>> {
+    let age = now - last_updated;
+    if age <= max_age {
+        return Ok(None);
+    }
+
+    let message = format!(
+        "Pool state is {}h {}m old (max {}s); the controller may be down and balances stale",
+        age / 3600,
+        (age % 3600) / 60,
+        max_age
+    );
+    if strict {
+        anyhow::bail!("{} (refused under --strict-freshness)", message);
+    }
+    Ok(Some(message))
+}
+
+/// Apply the configured freshness guard to a fetched state
+fn guard_freshness(state: &PoolState) -> Result<()> {
+    let warning = check_freshness(
+        state.last_updated,
+        chrono::Utc::now().timestamp(),
+        MAX_STATE_AGE.load(Ordering::Relaxed),
+        STRICT_FRESHNESS.load(Ordering::Relaxed),
+    )?;
+    if let Some(warning) = warning {
+        eprintln!("{} {}", "⚠".yellow(), warning.yellow());
+    }
+    Ok(())
+}
+
+/// Fetch pool state from index
+///
+/// Offline, the last state seen online is returned with a staleness warning.
+/// State older than `--max-age` is warned about, or refused under
+/// `--strict-freshness`.
+pub async fn fetch_pool_state(store: &dyn Storage, pool: &str) -> Result<PoolState> {
+    let state_path = format!("{}/state.json", paths::INDEX);
+
+    if cache::is_offline() {
+        let state: PoolState = cache::load_offline(&state_path)
+            .context("Offline: no cached pool state")?;
+        let age = chrono::Utc::now().timestamp() - state.last_updated;
+        eprintln!(
+            "{} Offline: showing cached pool state from {}s ago; balances may be stale",
+            "⚠".yellow(),
+            age
+        );
+        guard_freshness(&state)?;
+        return Ok(state);
+    }
+
+    // Try to read from MFS first
+    match store.read::<PoolState>(&state_path).await {
+        Ok(state) => {
+            cache::store(&state_path, &state);
+            guard_freshness(&state)?;
+            return Ok(state);
+        }
+        Err(e) => {
+            if STRICT_FRESHNESS.load(Ordering::Relaxed) {
+                return Err(e.context("No pool state (mock data refused under --strict-freshness)"));
+            }
+            eprintln!(
+                "{} Pool state unavailable; showing MOCK development data",
+                "⚠".yellow()
+            );
+
+            // Fall back to mock data for development
+            Ok(PoolState {
+                pool_id: pool.to_string(),
+                version: "1.0.0".to_string(),
+                total_jobs: 12847,
+                total_proofs: 12800,
+                total_volume_usdc: 1284.70,
+                current_epoch: Some("epoch-048".to_string()),
+                epoch_jobs: 156,
+                epoch_volume: 15.60,
+                pending_jobs: vec![],
+                active_providers: std::collections::HashMap::new(),
+                last_updated: chrono::Utc::now().timestamp(),
+                prev_state_hash: None,
+            })
+        }
+    }
+}
+
+/// Which epochs `fetch_epochs` returns
+///
+/// Bounds apply to when an epoch ended, or to `started_at` when it has no
+/// `ended_at` (active epochs, and sealed ones published without it).
+#[derive(Debug, Clone, Default)]
+pub struct EpochFilter {
+    /// Exclusive upper bound, unix time
+    pub before: Option<i64>,
+    /// Inclusive lower bound, unix time
+    pub after: Option<i64>,
+    /// Matching epochs to skip, newest first
+    pub offset: usize,
+    /// At most this many epochs; `None` for all
+    pub limit: Option<usize>,
+}
+
+impl EpochFilter {
+    /// The `limit` most recent epochs
+    pub fn latest(limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Self::default()
+        }
+    }
+
+    fn matches(&self, epoch: &EpochSnapshot) -> bool {
+        let at = epoch.ended_at.unwrap_or(epoch.started_at);
+        self.before.is_none_or(|before| at < before) && self.after.is_none_or(|after| at >= after)
+    }
+
+    /// Matching epochs, newest `started_at` first, paged by `offset`/`limit`
+    pub fn apply(&self, mut epochs: Vec<EpochSnapshot>) -> Vec<EpochSnapshot> {
+        epochs.retain(|e| self.matches(e));
+        epochs.sort_by(|a, b| {
+            b.started_at
+                .cmp(&a.started_at)
+                .then_with(|| b.epoch_id.cmp(&a.epoch_id))
+        });
+        epochs
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
+
+/// Fetch epochs from /swarmpool/epochs/. Unreadable entries are skipped.
+pub async fn fetch_epochs(
+    store: &dyn Storage,
+    _pool: &str,
+    filter: &EpochFilter,
+) -> Result<Vec<EpochSnapshot>> {
+    let epochs: Vec<EpochSnapshot> = read_all(store, paths::EPOCHS).await?;
+    Ok(filter.apply(epochs))
+}
+
+/// Fetch single epoch from its canonical path
+pub async fn fetch_epoch(store: &dyn Storage, _pool: &str, epoch_id: &str) -> Result<EpochSnapshot> {
+    let path = format!("{}/{}.json", paths::EPOCHS, epoch_id);
+    store
+        .read(&path)
+        .await
+        .with_context(|| format!("Epoch not found: {}", epoch_id))
+}
+
+/// An entry in a snapshot directory that couldn't be read or parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanError {
+    pub path: String,
+    pub error: String,
+}
+
+/// Read every snapshot in an MFS directory, keeping going past bad entries
+///
+/// A truncated or non-JSON file never blocks the scan: parsed snapshots and
+/// per-file errors come back separately and the caller decides whether to
+/// proceed with the good ones. Only a failure to list the directory is fatal.
+pub async fn scan_snapshots<T: DeserializeOwned>(
+    store: &dyn Storage,
+    mfs_dir: &str,
+) -> Result<(Vec<T>, Vec<ScanError>)> {
+    let mut snapshots = Vec::new();
+    let mut errors = Vec::new();
+
+    for name in store.list(mfs_dir).await? {
+        let path = format!("{}/{}", mfs_dir, name);
+        match store.read::<T>(&path).await {
+            Ok(snapshot) => snapshots.push(snapshot),
+            Err(e) => errors.push(ScanError {
+                path,
+                error: format!("{:#}", e),
+            }),
+        }
+    }
+
+    Ok((snapshots, errors))
+}
+
+/// Read every snapshot in an MFS directory, skipping (and logging) entries
+/// that fail to parse
+pub async fn read_all<T: DeserializeOwned>(store: &dyn Storage, mfs_dir: &str) -> Result<Vec<T>> {
+    let (snapshots, errors) = scan_snapshots(store, mfs_dir).await?;
+    for e in errors {
+        tracing::warn!("Skipping unreadable {}: {}", e.path, e.error);
+    }
+    Ok(snapshots)
+}
+
+/// Claims on a job CID signed by their provider's genesis wallet
+///
+/// Anyone can write a file under /swarmpool/claims/, so a claim that is
+/// unsigned, or whose provider has no verified genesis or a different
+/// wallet, is skipped with a warning instead of counting as competition.
+pub async fn verified_claims_for_job(
+    store: &dyn Storage,
+    job_cid: &str,
+) -> Result<Vec<ClaimSnapshot>> {
+    verified_claims_matching(store, Some(job_cid)).await
+}
+
+/// Every claim in /swarmpool/claims/ that `verified_claims_for_job` would
+/// accept, whatever its job
+pub async fn verified_claims(store: &dyn Storage) -> Result<Vec<ClaimSnapshot>> {
+    verified_claims_matching(store, None).await
+}
+
+async fn verified_claims_matching(
+    store: &dyn Storage,
+    job_cid: Option<&str>,
+) -> Result<Vec<ClaimSnapshot>> {
+    let mut wallets: std::collections::HashMap<String, Option<String>> = Default::default();
+    let mut claims = Vec::new();
+    for name in store.list(paths::CLAIMS).await? {
+        let path = format!("{}/{}", paths::CLAIMS, name);
+        let raw = store.read_path(&path).await?;
+        let claim: ClaimSnapshot = match serde_json::from_value::<ClaimSnapshot>(raw.clone()) {
+            Ok(claim) if job_cid.is_none_or(|cid| claim.job_cid == cid) => claim,
+            Ok(_) => continue,
+            Err(e) => {
+                tracing::warn!("Skipping unreadable {}: {}", path, e);
+                continue;
+            }
+        };
+
+        if !wallets.contains_key(&claim.provider) {
+            let wallet = match fetch_verified_genesis(store, &claim.provider).await {
+                Ok(genesis) => Some(genesis.wallet),
+                Err(e) => {
+                    tracing::warn!("{}: {:#}", claim.provider, e);
+                    None
+                }
+            };
+            wallets.insert(claim.provider.clone(), wallet);
+        }
+        let Some(wallet) = wallets[&claim.provider].as_deref() else {
+            tracing::warn!(
+                "Skipping {}: {} has no verified genesis",
+                path,
+                claim.provider
+            );
+            continue;
+        };
+        if let Err(e) = crypto::verify_snapshot_json(&raw, Some(wallet)) {
+            tracing::warn!("Skipping {}: {:#}", path, e);
+            continue;
+        }
+        claims.push(claim);
+    }
+    Ok(claims)
+}
+
+/// Fetch all proofs referencing a job CID from /swarmpool/proofs/
+pub async fn proofs_for_job(store: &dyn Storage, job_cid: &str) -> Result<Vec<ProofSnapshot>> {
+    let proofs: Vec<ProofSnapshot> = read_all(store, paths::PROOFS).await?;
+    Ok(proofs.into_iter().filter(|p| p.job_cid == job_cid).collect())
+}
+
+/// Positions of entries whose `(signer, nonce)` pair already appeared
+/// earlier in `pairs`: replays of a snapshot that was already counted.
+/// Order the entries first (earliest proof first, say) so the original is
+/// the one kept.
+pub fn replayed_nonces<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Vec<usize> {
+    let mut seen = HashSet::new();
+    pairs
+        .into_iter()
+        .enumerate()
+        .filter(|(_, pair)| !seen.insert(*pair))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Proofs in /swarmpool/proofs/, all or only those for `job_cid`, each with
+/// the CID it was published under (from the MFS entry, nothing is re-added)
+///
+/// An entry that can't be read is an error rather than a proof silently
+/// left out; one that reads but isn't a proof is skipped with a warning.
+pub async fn proofs_with_cids(
+    store: &dyn Storage,
+    job_cid: Option<&str>,
+) -> Result<Vec<(String, ProofSnapshot)>> {
+    let mut proofs = Vec::new();
+    for name in store.list(paths::PROOFS).await? {
+        let path = format!("{}/{}", paths::PROOFS, name);
+        let raw = store.read_path(&path).await?;
+        match serde_json::from_value::<ProofSnapshot>(raw) {
+            Ok(proof) if job_cid.is_none_or(|cid| proof.job_cid == cid) => {
+                // Removed since it was listed
+                if let Some(cid) = store.path_cid(&path).await? {
+                    proofs.push((cid, proof));
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Skipping unreadable {}: {}", path, e),
+        }
+    }
+    Ok(proofs)
+}
+
+/// Proofs referencing a job CID, each with the CID it was published under
+pub async fn proofs_for_job_with_cids(
+    store: &dyn Storage,
+    job_cid: &str,
+) -> Result<Vec<(String, ProofSnapshot)>> {
+    proofs_with_cids(store, Some(job_cid)).await
+}
+
+/// Jobs cancelled or refunded (`/swarmpool/cancellations/`, `/swarmpool/refunds/`)
+///
+/// A record only counts when it is signed by the client that signed the job
+/// itself; any other record is skipped with a warning. Failing to list or
+/// read either directory is an error, so no caller settles a job whose
+/// cancellation it couldn't see.
+pub async fn withdrawn_jobs(store: &dyn Storage) -> Result<Vec<WithdrawnJob>> {
+    let mut withdrawn = Vec::new();
+    for dir in [paths::CANCELLATIONS, paths::REFUNDS] {
+        for name in store.list(dir).await? {
+            let path = format!("{}/{}", dir, name);
+            let raw = store
+                .read_path(&path)
+                .await
+                .with_context(|| format!("Failed to read {}", path))?;
+            let record: WithdrawnJob = match serde_json::from_value(raw.clone()) {
+                Ok(record) => record,
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable {}: {}", path, e);
+                    continue;
+                }
+            };
+            if record.job_id.is_empty() || record.job_id.contains('/') {
+                tracing::warn!("Skipping {}: bad job_id {:?}", path, record.job_id);
+                continue;
+            }
+
+            let job_path = format!("{}/{}.json", paths::JOBS, record.job_id);
+            if store.path_cid(&job_path).await?.is_none() {
+                tracing::warn!("Skipping {}: no job {}", path, record.job_id);
+                continue;
+            }
+            let job = store.read_path(&job_path).await?;
+            let signed = crypto::verify_snapshot_json(&job, None)
+                .and_then(|client| crypto::verify_snapshot_json(&raw, Some(&client)));
+            if let Err(e) = signed {
+                tracing::warn!("Skipping {}: not signed by the job's client: {:#}", path, e);
+                continue;
+            }
+            withdrawn.push(record);
+        }
+    }
+    Ok(withdrawn)
+}
+
+/// Parse an epoch document once its `sig` is checked against `controller`
+///
+/// The signature is checked over the document as published, so fields this
+/// version doesn't model can't break verification.
+pub fn verified_epoch(raw: serde_json::Value, controller: &str) -> Result<EpochSnapshot> {
+    crypto::verify_snapshot_json(&raw, Some(controller)).context("Not signed by the controller")?;
+    Ok(serde_json::from_value(raw)?)
+}
+
+/// Fetch an epoch from its canonical path, requiring `controller`'s signature
+pub async fn fetch_verified_epoch(
+    store: &dyn Storage,
+    epoch_id: &str,
+    controller: &str,
+) -> Result<EpochSnapshot> {
+    let path = format!("{}/{}.json", paths::EPOCHS, epoch_id);
+    let raw: serde_json::Value = store
+        .read(&path)
+        .await
+        .with_context(|| format!("Epoch not found: {}", epoch_id))?;
+    verified_epoch(raw, controller).with_context(|| format!("Epoch {} rejected", epoch_id))
+}
+
+/// Sealed epochs signed by `controller`; any other epoch is skipped with a
+/// warning
+pub async fn fetch_sealed_epochs(
+    store: &dyn Storage,
+    controller: &str,
+) -> Result<Vec<EpochSnapshot>> {
+    let mut epochs = Vec::new();
+    for name in store.list(paths::EPOCHS).await? {
+        let path = format!("{}/{}", paths::EPOCHS, name);
+        let epoch = match store.read_path(&path).await {
+            Ok(raw) => verified_epoch(raw, controller),
+            Err(e) => Err(e),
+        };
+        match epoch {
+            Ok(epoch) if epoch.status == "sealed" => epochs.push(epoch),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Ignoring {}: {:#}", path, e),
+        }
+    }
+    Ok(epochs)
+}
+
+/// A withdrawal request signed by its provider's genesis wallet
+#[derive(Debug, Clone)]
+pub struct VerifiedWithdrawal {
+    /// Name it is published under in /swarmpool/withdrawals/, without `.json`
+    pub id: String,
+    pub amount: UsdcAmount,
+    pub withdrawal: WithdrawalSnapshot,
+}
+
+/// Withdrawals in /swarmpool/withdrawals/, all or only `provider`'s, that
+/// no epoch sealed by `controller` has processed
+///
+/// The request's own `status` is never trusted: a withdrawal is processed
+/// once a sealed epoch lists it in `processed_withdrawals`. Requests not
+/// signed by the wallet in the provider's genesis, paying elsewhere, or with
+/// a malformed amount are skipped with a warning.
+pub async fn fetch_pending_withdrawals(
+    store: &dyn Storage,
+    provider: Option<&str>,
+    controller: &str,
+) -> Result<Vec<VerifiedWithdrawal>> {
+    let sealed = fetch_sealed_epochs(store, controller).await?;
+    let (_, pending) = scan_withdrawals(store, provider, &sealed, false).await?;
+    Ok(pending)
+}
+
+/// Withdrawals in /swarmpool/withdrawals/, all or only `provider`'s, split
+/// into those one of the `sealed` epochs processed and those still pending
+///
+/// `sealed` must already be checked against the controller (see
+/// `fetch_sealed_epochs`). Processed requests are verified like pending
+/// ones, so a forged request listed by id is never counted as paid.
+pub async fn split_withdrawals(
+    store: &dyn Storage,
+    provider: Option<&str>,
+    sealed: &[EpochSnapshot],
+) -> Result<(Vec<VerifiedWithdrawal>, Vec<VerifiedWithdrawal>)> {
+    scan_withdrawals(store, provider, sealed, true).await
+}
+
+/// Verified withdrawals, processed (only read with `with_processed`) and
+/// pending
+async fn scan_withdrawals(
+    store: &dyn Storage,
+    provider: Option<&str>,
+    sealed: &[EpochSnapshot],
+    with_processed: bool,
+) -> Result<(Vec<VerifiedWithdrawal>, Vec<VerifiedWithdrawal>)> {
+    let processed_ids: HashSet<&str> = sealed
+        .iter()
+        .flat_map(|e| e.processed_withdrawals.iter().map(String::as_str))
+        .collect();
+
+    let (mut processed, mut pending) = (Vec::new(), Vec::new());
+    for name in store.list(paths::WITHDRAWALS).await? {
+        let Some(id) = name.strip_suffix(".json") else {
+            continue;
+        };
+        let was_processed = processed_ids.contains(id);
+        if was_processed && !with_processed {
+            continue;
+        }
+        let path = format!("{}/{}", paths::WITHDRAWALS, name);
+        let verified = match verify_withdrawal(store, &path, provider).await {
+            Ok(Some((amount, withdrawal))) => VerifiedWithdrawal {
+                id: id.to_string(),
+                amount,
+                withdrawal,
+            },
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!("Skipping withdrawal {}: {:#}", path, e);
+                continue;
+            }
+        };
+        if was_processed {
+            processed.push(verified);
+        } else {
+            pending.push(verified);
+        }
+    }
+    Ok((processed, pending))
+}
+
+/// The withdrawal at `path` with its parsed amount, once its signature is
+/// checked against the provider's genesis wallet; `None` when it belongs to
+/// a provider other than `provider`
+async fn verify_withdrawal(
+    store: &dyn Storage,
+    path: &str,
+    provider: Option<&str>,
+) -> Result<Option<(UsdcAmount, WithdrawalSnapshot)>> {
+    let raw: serde_json::Value = store.read_path(path).await?;
+    let withdrawal: WithdrawalSnapshot = serde_json::from_value(raw.clone())?;
+    if provider.is_some_and(|p| p != withdrawal.provider) {
+        return Ok(None);
+    }
+
+    let genesis = fetch_verified_genesis(store, &withdrawal.provider).await?;
+    crypto::verify_snapshot_json(&raw, Some(&genesis.wallet))?;
+    if !withdrawal.wallet.eq_ignore_ascii_case(&genesis.wallet) {
+        anyhow::bail!(
+            "pays {}, not the genesis wallet {}",
+            withdrawal.wallet,
+            genesis.wallet
+        );
+    }
+    let amount: UsdcAmount = withdrawal.amount.parse().map_err(anyhow::Error::msg)?;
+    if amount.microunits() == 0 {
+        anyhow::bail!("zero amount");
+    }
+    Ok(Some((amount, withdrawal)))
+}
+
+/// Jobs in /swarmpool/jobs/ that no provider has proven or holds an
+/// unexpired claim on at `now` (heartbeats included), each with the CID it
+/// was published under (from the MFS entry itself, nothing is re-added).
+/// Cancelled and refunded jobs are left out.
+pub async fn list_pending_jobs(store: &dyn Storage, now: i64) -> Result<Vec<(String, JobSnapshot)>> {
+    let claims: Vec<ClaimSnapshot> = read_all(store, paths::CLAIMS).await?;
+    let proofs: Vec<ProofSnapshot> = read_all(store, paths::PROOFS).await?;
+    let heartbeats = last_heartbeats(store, claims.iter().map(|c| c.provider.as_str())).await;
+    let taken: HashSet<String> = claims
+        .into_iter()
+        .filter(|c| !c.is_expired(now, heartbeats.get(&c.provider).copied()))
+        .map(|c| c.job_cid)
+        .chain(proofs.into_iter().map(|p| p.job_cid))
+        .collect();
+    let withdrawn: HashSet<String> = withdrawn_jobs(store)
+        .await?
+        .into_iter()
+        .map(|w| w.job_id)
+        .collect();
+
+    let mut pending = Vec::new();
+    for name in store.list(paths::JOBS).await? {
+        if !name.ends_with(".json") {
+            continue;
+        }
+        let path = format!("{}/{}", paths::JOBS, name);
+        let Ok(raw) = store.read_path(&path).await else {
+            continue;
+        };
+        let job = match serde_json::from_value::<JobSnapshot>(raw.clone()) {
+            Ok(job) => job,
+            Err(e) => {
+                tracing::warn!("Skipping unreadable {}: {}", path, e);
+                continue;
+            }
+        };
+        if withdrawn.contains(&job.job_id) {
+            continue;
+        }
+        // Removed since it was listed
+        let Some(cid) = store.path_cid(&path).await? else {
+            continue;
+        };
+        if !taken.contains(&cid) {
+            pending.push((cid, job));
+        }
+    }
+    Ok(pending)
+}
+
+/// Most recent job in /swarmpool/jobs/ for the same `input_cid` and
+/// `client`, published within the last `within_secs` seconds. Returns the
+/// job with its CID. Jobs since cancelled or refunded don't count.
+pub async fn find_recent_job(
+    store: &dyn Storage,
+    input_cid: &str,
+    client: &str,
+    within_secs: u64,
+) -> Result<Option<(String, JobSnapshot)>> {
+    let cutoff = chrono::Utc::now().timestamp() - within_secs as i64;
+    let withdrawn: Vec<String> = withdrawn_jobs(store)
+        .await?
+        .into_iter()
+        .map(|w| w.job_id)
+        .collect();
+
+    let mut found: Option<(String, JobSnapshot)> = None;
+    for name in store.list(paths::JOBS).await? {
+        if !name.ends_with(".json") {
+            continue;
+        }
+        let path = format!("{}/{}", paths::JOBS, name);
+        let Ok(raw) = store.read_path(&path).await else {
+            continue;
+        };
+        let Ok(job) = serde_json::from_value::<JobSnapshot>(raw) else {
+            continue;
+        };
+        if job.input_cid != input_cid
+            || job.client != client
+            || job.timestamp < cutoff
+            || withdrawn.contains(&job.job_id)
+        {
+            continue;
+        }
+        if found.as_ref().is_none_or(|(_, f)| job.timestamp > f.timestamp) {
+            found = Some((path, job));
+        }
+    }
+
+    // The CID the job was published under, from its MFS entry
+    let Some((path, job)) = found else {
+        return Ok(None);
+    };
+    match store.path_cid(&path).await? {
+        Some(cid) => Ok(Some((cid, job))),
+        None => Ok(None),
+    }
+}
+
+/// Fetch all disputes raised in an epoch from /swarmpool/disputes/
+pub async fn disputes_for_epoch(
+    store: &dyn Storage,
+    epoch_id: &str,
+) -> Result<Vec<DisputeSnapshot>> {
+    let disputes: Vec<DisputeSnapshot> = read_all(store, paths::DISPUTES).await?;
+    Ok(disputes.into_iter().filter(|d| d.epoch_id == epoch_id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, OTHER_ADDRESS, OTHER_KEY, TEST_ADDRESS, TEST_KEY};
+    use futures::StreamExt;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const OK_EMPTY_JSON: &str =
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n{}";
+    const NOT_FOUND: &str = "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n";
+    /// What the API answers for a missing file or path
+    const NOT_FOUND_FILE: &str =
+        "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 19\r\n\r\nfile does not exist";
+
+    /// A cancellation of `job_id` signed with `key`
+    async fn cancellation(job_id: &str, timestamp: i64, key: &str) -> serde_json::Value {
+        let record = WithdrawnJob {
+            job_id: job_id.to_string(),
+            epoch_id: None,
+            amount: None,
+            timestamp,
+            sig: None,
+        };
+        crypto::sign_json(&serde_json::to_value(record).unwrap(), key)
+            .await
+            .unwrap()
+    }
+
+    /// Minimal keep-alive HTTP server answering `{}` and counting connections
+    async fn mock_api() -> (String, Arc<AtomicUsize>) {
+        mock_server(OK_EMPTY_JSON).await
+    }
+
+    /// Keep-alive HTTP server giving every request the same raw `response`
+    async fn mock_server(response: &'static str) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let counter = connections.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    loop {
+                        let n = match socket.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => n,
+                        };
+                        request.extend_from_slice(&buf[..n]);
+                        // Requests here carry no body, so a blank line ends each one
+                        while let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                            request.drain(..end + 4);
+                            if socket.write_all(response.as_bytes()).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        (format!("http://{}/api/v0", addr), connections)
+    }
+
+    /// MFS paths and the CIDs `mock_ipfs` reports for them
+    type MockPaths = Arc<std::sync::Mutex<HashMap<String, String>>>;
+
+    /// HTTP server answering `cat?arg=<cid>` and gateway `/ipfs/<cid>`
+    /// requests from `objects` and `files/stat?arg=<path>` from `paths`,
+    /// 404 for anything else, counting requests. Returns the API and
+    /// gateway bases.
+    async fn mock_ipfs(
+        objects: HashMap<String, Vec<u8>>,
+        paths: MockPaths,
+    ) -> (String, String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let objects = Arc::new(objects);
+
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let (counter, objects, paths) = (counter.clone(), objects.clone(), paths.clone());
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    loop {
+                        let n = match socket.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => n,
+                        };
+                        request.extend_from_slice(&buf[..n]);
+                        while let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                            let head = String::from_utf8_lossy(&request[..end]).to_string();
+                            request.drain(..end + 4);
+                            counter.fetch_add(1, Ordering::SeqCst);
+
+                            let target = head.split(' ').nth(1).unwrap_or("");
+                            let body = match target.split_once("files/stat?arg=") {
+                                Some((_, path)) => {
+                                    let cid = paths.lock().unwrap().get(path).cloned();
+                                    cid.map(|cid| format!("{{\"Hash\":\"{}\"}}", cid).into_bytes())
+                                }
+                                None => target
+                                    .rsplit_once("cat?arg=")
+                                    .or_else(|| target.rsplit_once("/ipfs/"))
+                                    .and_then(|(_, cid)| objects.get(cid).cloned()),
+                            };
+                            let response = match body {
+                                Some(body) => {
+                                    let mut response = format!(
+                                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n",
+                                        body.len()
+                                    )
+                                    .into_bytes();
+                                    response.extend_from_slice(&body);
+                                    response
+                                }
+                                None => NOT_FOUND_FILE.as_bytes().to_vec(),
+                            };
+                            if socket.write_all(&response).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        (
+            format!("http://{}/api/v0", addr),
+            format!("http://{}/ipfs", addr),
+            requests,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_cached_proofs_not_fetched_again() {
+        // The proofs a seal of 120 proofs reads, each under its own CID
+        let proofs: Vec<Vec<u8>> = (0..120)
+            .map(|i| {
+                serde_json::to_vec_pretty(&serde_json::json!({
+                    "type": "proof",
+                    "proof_id": format!("proof-{:03}", i),
+                    "proof_hash": crypto::keccak256_hash(format!("proof-{}", i).as_bytes())
+                }))
+                .unwrap()
+            })
+            .collect();
+        let cids: Vec<String> = proofs.iter().map(|p| local_cid(p).unwrap()).collect();
+        let mut objects: HashMap<String, Vec<u8>> =
+            cids.iter().cloned().zip(proofs.iter().cloned()).collect();
+        // Served for a CID it doesn't hash to
+        let forged = local_cid(b"{}").unwrap();
+        objects.insert(forged.clone(), proofs[0].clone());
+
+        let (api, gateway, requests) = mock_ipfs(objects, MockPaths::default()).await;
+        let dir = test_support::temp_dir();
+        let client = IpfsClient::new(&api, &gateway)
+            .unwrap()
+            .with_cache(cache::Cache::at(dir.path()));
+
+        // Cold: one round trip per proof. Warm (the next seal, or the same
+        // CID again within this one): none.
+        for (cid, proof) in cids.iter().zip(&proofs) {
+            let data: serde_json::Value = client.fetch_json(cid).await.unwrap();
+            assert_eq!(
+                data,
+                serde_json::from_slice::<serde_json::Value>(proof).unwrap()
+            );
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 120);
+        for cid in &cids {
+            client.fetch_json::<serde_json::Value>(cid).await.unwrap();
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 120);
+
+        // Content that doesn't match its CID is refused and never cached;
+        // the local node's answer is checked, then the gateway's
+        let err = client
+            .fetch_json::<serde_json::Value>(&forged)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{}", err);
+        assert_eq!(requests.load(Ordering::SeqCst), 122);
+        let cache = cache::Cache::at(dir.path());
+        assert!(cache.get_bytes(&forged).unwrap().is_none());
+
+        // A cache entry edited on disk is a miss, so it's fetched again
+        cache.put_bytes(&cids[0], &proofs[1]).unwrap();
+        client
+            .fetch_json::<serde_json::Value>(&cids[0])
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 123);
+        assert_eq!(cache.get_bytes(&cids[0]).unwrap().unwrap(), proofs[0]);
+    }
+
+    #[tokio::test]
+    async fn test_path_read_follows_what_is_there_now() {
+        let epoch =
+            |name: &str| serde_json::to_vec_pretty(&serde_json::json!({ "name": name })).unwrap();
+        let (first, second) = (epoch("Alpha"), epoch("Alpha-2"));
+        let (first_cid, second_cid) = (local_cid(&first).unwrap(), local_cid(&second).unwrap());
+        let objects = HashMap::from([(first_cid.clone(), first), (second_cid.clone(), second)]);
+
+        let path = "/swarmpool/epochs/epoch-001.json";
+        let paths = MockPaths::default();
+        paths.lock().unwrap().insert(path.to_string(), first_cid);
+        let (api, gateway, _) = mock_ipfs(objects, paths.clone()).await;
+        let dir = test_support::temp_dir();
+        let client = IpfsClient::new(&api, &gateway)
+            .unwrap()
+            .with_cache(cache::Cache::at(dir.path()));
+
+        let read: serde_json::Value = client.read_from_path(path).await.unwrap();
+        assert_eq!(read["name"], "Alpha");
+
+        // The file at the path is replaced: the cached copy of the old
+        // content isn't served for it
+        paths
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), second_cid.clone());
+        let read: serde_json::Value = client.read_from_path(path).await.unwrap();
+        assert_eq!(read["name"], "Alpha-2");
+        // Offline, the path resolves to the CID last seen there
+        let last_seen: String = cache::Cache::at(dir.path()).require(path).unwrap();
+        assert_eq!(last_seen, second_cid);
+
+        paths.lock().unwrap().remove(path);
+        let err = client
+            .read_from_path::<serde_json::Value>(path)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("does not exist"), "{}", err);
+    }
+
+    /// API server recording each request's endpoint (up to any `?`): `add`
+    /// answers a CID, endpoints named in `failing` a 500 saying the file
+    /// does not exist, anything else `{}`
+    async fn mock_mfs(
+        failing: &'static [&'static str],
+    ) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let log = calls.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let log = log.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    loop {
+                        let n = match socket.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => n,
+                        };
+                        request.extend_from_slice(&buf[..n]);
+                        while let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                            let head = String::from_utf8_lossy(&request[..end]).to_lowercase();
+                            let length: usize = head
+                                .lines()
+                                .find_map(|l| l.strip_prefix("content-length:"))
+                                .map_or(0, |v| v.trim().parse().unwrap());
+                            if request.len() < end + 4 + length {
+                                break;
+                            }
+                            request.drain(..end + 4 + length);
+
+                            let target = head.split(' ').nth(1).unwrap_or("").to_string();
+                            let endpoint = target
+                                .trim_start_matches("/api/v0/")
+                                .split('?')
+                                .next()
+                                .unwrap()
+                                .to_string();
+                            log.lock().unwrap().push(endpoint.clone());
+                            let response = if failing.contains(&endpoint.as_str()) {
+                                NOT_FOUND_FILE.to_string()
+                            } else if endpoint == "add" {
+                                let body = r#"{"Hash":"QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"}"#;
+                                format!(
+                                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                                    body.len(),
+                                    body
+                                )
+                            } else {
+                                OK_EMPTY_JSON.to_string()
+                            };
+                            if socket.write_all(response.as_bytes()).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        (format!("http://{}/api/v0", addr), calls)
+    }
+
+    #[tokio::test]
+    async fn test_replace_writes_aside_before_touching_the_old_entry() {
+        let state = serde_json::json!({ "total_jobs": 1 });
+
+        let (api, calls) = mock_mfs(&[]).await;
+        let client = IpfsClient::new(&api, IPFS_GATEWAY).unwrap();
+        client
+            .replace_at_path("/swarmpool/index/state.json", &state)
+            .await
+            .unwrap();
+        assert_eq!(
+            *calls.lock().unwrap(),
+            ["add", "files/mkdir", "files/cp", "files/rm", "files/mv"]
+        );
+
+        // The copy fails: the existing entry is never removed
+        let (api, calls) = mock_mfs(&["files/cp"]).await;
+        let client = IpfsClient::new(&api, IPFS_GATEWAY).unwrap();
+        let err = client
+            .replace_at_path("/swarmpool/index/state.json", &state)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("Failed to write /swarmpool/index/.state.json.tmp-"),
+            "{:#}",
+            err
+        );
+        assert_eq!(*calls.lock().unwrap(), ["add", "files/mkdir", "files/cp"]);
+
+        // First write of the path: there's nothing to remove
+        let (api, calls) = mock_mfs(&["files/rm"]).await;
+        let client = IpfsClient::new(&api, IPFS_GATEWAY).unwrap();
+        client
+            .replace_at_path("/swarmpool/index/state.json", &state)
+            .await
+            .unwrap();
+        assert_eq!(calls.lock().unwrap().last().unwrap(), "files/mv");
+    }
+
+    #[tokio::test]
+    async fn test_client_reuses_connections() {
+        let (api, connections) = mock_api().await;
+        let client = IpfsClient::new(&api, IPFS_GATEWAY).unwrap();
+
+        for i in 0..10 {
+            let response = client
+                .post(&format!("pin/add?arg=bafy{}", i))
+                .send()
+                .await
+                .unwrap();
+            assert!(response.status().is_success());
+            response.bytes().await.unwrap();
+        }
+
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_decode_pubsub_envelope() {
+        // A line as kubo 0.2x prints it for `pubsub sub /swarmpool.eth/jobs`
+        let line = r#"{"from":"12D3KooWDpJ7As7BWAwRMfu1VU2WCqNjvq387JEYKDBj4kx6nXTN","data":"ueyJjaWQiOiJRbUpvYkEiLCJtb2RlbCI6InF1ZWVuYmVlLXNwaW5lIn0","seqno":"uF8rCXqbIw0A","topicIDs":["uL3N3YXJtcG9vbC5ldGgvam9icw"]}"#;
+        let message = decode_pubsub_line(line).unwrap();
+        assert_eq!(message.from, "12D3KooWDpJ7As7BWAwRMfu1VU2WCqNjvq387JEYKDBj4kx6nXTN");
+        assert_eq!(message.topics, ["/swarmpool.eth/jobs"]);
+        assert_eq!(message.seqno.len(), 8);
+        assert_eq!(
+            message.message,
+            serde_json::json!({"cid": "QmJobA", "model": "queenbee-spine"})
+        );
+
+        // What `pubsub_publish` sends comes back out unchanged
+        let announcement = serde_json::json!({
+            "cid": "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o",
+            "client": "clinic.clientswarm.eth",
+            "model": "queenbee-spine",
+            "timestamp": 1704067200
+        });
+        let published = serde_json::to_vec(&announcement).unwrap();
+        let line = serde_json::json!({
+            "from": "12D3KooWSwarm",
+            "data": multibase_encode(&published),
+            "seqno": multibase_encode(&[0, 1]),
+            "topicIDs": [multibase_encode(b"/swarmpool.eth/jobs")]
+        })
+        .to_string();
+        let message = decode_pubsub_line(&line).unwrap();
+        assert_eq!(message.data, published);
+        assert_eq!(message.message, announcement);
+
+        // Raw JSON in `data` is not a valid envelope
+        let raw = r#"{"from":"12D3KooWSwarm","data":"{\"cid\":\"QmJobA\"}"}"#;
+        assert!(decode_pubsub_line(raw).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pubsub_stream_decodes_and_resubscribes() {
+        // Two envelopes around a garbage line; the body then ends, as when
+        // the daemon restarts, and the stream resubscribes
+        let body = concat!(
+            r#"{"from":"12D3KooWSwarm","data":"ueyJjaWQiOiJRbUpvYkEiLCJtb2RlbCI6InF1ZWVuYmVlLXNwaW5lIn0","seqno":"uAAE","topicIDs":["uL3N3YXJtcG9vbC5ldGgvam9icw"]}"#,
+            "\n",
+            r#"not an envelope"#,
+            "\n",
+            r#"{"from":"12D3KooWSwarm","data":"eyJjaWQiOiJRbUpvYkIiLCJtb2RlbCI6InF1ZWVuYmVlLWNoZXN0In0=","seqno":"AAI=","topicIDs":["/swarmpool.eth/jobs"]}"#,
+            "\n",
+        );
+        let response: &'static str = Box::leak(
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .into_boxed_str(),
+        );
+        let (base, _) = mock_server(response).await;
+        let client = IpfsClient::new(&base, IPFS_GATEWAY).unwrap();
+
+        let cids: Vec<String> = client
+            .pubsub_stream("/swarmpool.eth/jobs")
+            .take(3)
+            .map(|message| message["cid"].as_str().unwrap().to_string())
+            .collect()
+            .await;
+        assert_eq!(cids, ["QmJobA", "QmJobB", "QmJobA"]);
+    }
+
+    #[test]
+    fn test_client_from_config() {
+        let mut config = Config::new();
+        config.ipfs_api = "http://10.0.0.5:5001/".to_string();
+        config.ipfs_gateway = "https://gateway.example/ipfs/".to_string();
+        let client = IpfsClient::from_config(&config).unwrap();
+        assert_eq!(client.api_base, "http://10.0.0.5:5001/api/v0");
+        assert_eq!(client.gateway_base, "https://gateway.example/ipfs");
+
+        // A full API URL is taken as is
+        config.ipfs_api = "http://10.0.0.5:5001/api/v0".to_string();
+        let client = IpfsClient::from_config(&config).unwrap();
+        assert_eq!(client.api_base, "http://10.0.0.5:5001/api/v0");
+    }
+
+    #[tokio::test]
+    async fn test_malformed_cid_rejected_before_fetch() {
+        let err = IpfsClient::new(IPFS_API, IPFS_GATEWAY)
+            .unwrap()
+            .fetch_json::<serde_json::Value>("QmNotACid!")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().starts_with("Not a valid CID"), "{}", err);
+        assert!(parse_cid("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_valid_but_missing_cid_reported_not_found() {
+        let (base, _) = mock_server(NOT_FOUND).await;
+        let client = IpfsClient::new(&base, &base.replace("/api/v0", "/ipfs")).unwrap();
+
+        let cid = "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH";
+        assert!(parse_cid(cid).is_ok());
+        let err = client.fetch_snapshot_online(cid, 1024).await.unwrap_err();
+        assert!(err.to_string().starts_with("CID not found"), "{}", err);
+    }
+
+    #[test]
+    fn test_local_cid_matches_ipfs_add() {
+        // Well-known `ipfs add` results
+        assert_eq!(
+            local_cid(b"").as_deref(),
+            Some("QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH")
+        );
+        assert_eq!(
+            local_cid(b"hello world\n").as_deref(),
+            Some("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o")
+        );
+        assert!(local_cid(&vec![0u8; DEFAULT_CHUNK_SIZE]).is_some());
+        assert_eq!(local_cid(&vec![0u8; DEFAULT_CHUNK_SIZE + 1]), None);
+    }
+
+    #[test]
+    fn test_content_verified_against_cid() {
+        use cid::multihash::{Code, MultihashDigest};
+
+        let cid = "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o";
+        assert!(verify_content(cid, b"hello world\n").unwrap());
+        let err = verify_content(cid, b"hello world!").unwrap_err();
+        assert!(err.to_string().contains("match its CID"), "{}", err);
+
+        // CIDv1 raw leaves hash the bytes themselves
+        let raw = cid::Cid::new_v1(RAW_CODEC, Code::Sha2_256.digest(b"scan")).to_string();
+        assert!(verify_content(&raw, b"scan").unwrap());
+        assert!(verify_content(&raw, b"scam").is_err());
+
+        // Several chunks: not recomputed here, not rejected either
+        let big = "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH";
+        assert!(!verify_content(big, &vec![0u8; DEFAULT_CHUNK_SIZE + 1]).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tampered_content_rejected_on_fetch() {
+        let (dir, store) = test_support::fs_store();
+        let id = store.put_bytes(b"scan".to_vec(), "scan.dcm").await.unwrap();
+        assert_eq!(fetch_verified(&store, &id, None).await.unwrap(), b"scan");
+
+        std::fs::write(dir.path().join("objects").join(&id), b"scam").unwrap();
+        let err = fetch_verified(&store, &id, None).await.unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_unverifiable_content_needs_a_matching_sha256() {
+        // Over one chunk, so its CID can't be rebuilt here
+        let big = vec![7u8; DEFAULT_CHUNK_SIZE + 1];
+        let cid = "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH";
+        let objects = HashMap::from([(cid.to_string(), big.clone())]);
+        let (api, gateway, _) = mock_ipfs(objects, MockPaths::default()).await;
+        let client = Box::leak(Box::new(IpfsClient::new(&api, &gateway).unwrap()));
+        let store = crate::storage::IpfsStorage::new(client);
+
+        let err = fetch_verified(&store, cid, None).await.unwrap_err();
+        assert!(err.to_string().contains("refusing it"), "{}", err);
+        let other = crypto::file_sha256(b"other");
+        let err = fetch_verified(&store, cid, Some(&other)).await.unwrap_err();
+        assert!(
+            err.to_string().contains("does not match its sha256"),
+            "{}",
+            err
+        );
+
+        let digest = crypto::file_sha256(&big);
+        assert_eq!(
+            fetch_verified(&store, cid, Some(&digest)).await.unwrap(),
+            big
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversized_response_rejected() {
+        let cid = "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH";
+        let body = format!("{{\"pad\": \"{}\"}}", "x".repeat(4096));
+
+        // Declared length over the cap fails without reading the body
+        let sized: &'static str = Box::leak(
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .into_boxed_str(),
+        );
+        // Chunked, so the size is only discovered while streaming
+        let chunked: &'static str = Box::leak(
+            format!(
+                "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n",
+                body.len(),
+                body
+            )
+            .into_boxed_str(),
+        );
+
+        for response in [sized, chunked] {
+            let (base, _) = mock_server(response).await;
+            let client = IpfsClient::new(&base, &base.replace("/api/v0", "/ipfs")).unwrap();
+
+            let err = client.fetch_snapshot_online(cid, 1024).await.unwrap_err();
+            assert!(format!("{:#}", err).contains("byte limit"), "{:#}", err);
+
+            // The same body is fine under a larger cap
+            let body = client.fetch_snapshot_online(cid, 8192).await.unwrap();
+            let data: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(data["pad"].as_str().unwrap().len(), 4096);
+        }
+
+        let (base, _) = mock_server(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 13\r\n\r\n<html></html>",
+        )
+        .await;
+        let client = IpfsClient::new(&base, &base.replace("/api/v0", "/ipfs")).unwrap();
+        let err = client.fetch_snapshot_online(cid, 1024).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("text/html"), "{:#}", err);
+    }
+
+    #[tokio::test]
+    async fn test_genesis_signature_verification() {
+        let (_root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        let genesis = |provider: &str, wallet: &str| {
+            serde_json::json!({
+                "type": "provider-init",
+                "provider": provider,
+                "wallet": wallet,
+                "gpus": ["RTX 5090"],
+                "models": ["queenbee-spine"],
+                "timestamp": 1704067200,
+                "nonce": "abcdef1234567890"
+            })
+        };
+
+        let signed = crypto::sign_json(&genesis("alice.swarmbee.eth", TEST_ADDRESS), TEST_KEY)
+            .await
+            .unwrap();
+        write_genesis(store, "alice.swarmbee.eth", &signed).await.unwrap();
+        let verified = fetch_verified_genesis(store, "alice.swarmbee.eth").await.unwrap();
+        assert_eq!(verified.wallet, TEST_ADDRESS);
+
+        // Claims a wallet it doesn't hold the key for
+        let forged = crypto::sign_json(&genesis("bob.swarmbee.eth", OTHER_ADDRESS), TEST_KEY)
+            .await
+            .unwrap();
+        write_genesis(store, "bob.swarmbee.eth", &forged).await.unwrap();
+        let err = fetch_verified_genesis(store, "bob.swarmbee.eth").await.unwrap_err();
+        assert!(err.to_string().contains("not signed by its wallet"), "{}", err);
+
+        // No sig at all is unverified, not a panic
+        write_genesis(store, "carol.swarmbee.eth", &genesis("carol.swarmbee.eth", TEST_ADDRESS))
+            .await
+            .unwrap();
+        let err = fetch_verified_genesis(store, "carol.swarmbee.eth").await.unwrap_err();
+        assert!(err.to_string().contains("unsigned"), "{}", err);
+
+        assert!(fetch_verified_genesis(store, "dave.swarmbee.eth").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_only_signed_claims_count() {
+        let (_root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        let genesis = serde_json::json!({
+            "type": "provider-init",
+            "provider": "alpha.swarmbee.eth",
+            "wallet": TEST_ADDRESS,
+            "gpus": ["RTX 5090"],
+            "models": ["queenbee-spine"],
+            "timestamp": 1704067200,
+            "nonce": "abcdef1234567890"
+        });
+        let genesis = crypto::sign_json(&genesis, TEST_KEY).await.unwrap();
+        write_genesis(store, "alpha.swarmbee.eth", &genesis)
+            .await
+            .unwrap();
+
+        let claim = |claim_id: &str, provider: &str, job_cid: &str| {
+            serde_json::json!({
+                "type": "claim",
+                "version": "1.0.0",
+                "claim_id": claim_id,
+                "job_id": "job-001",
+                "job_cid": job_cid,
+                "provider": provider,
+                "mode": "SOLO",
+                "timestamp": 1704067200,
+                "nonce": "abcdef1234567890"
+            })
+        };
+        let claims = [
+            ("claim-signed", "alpha.swarmbee.eth", "QmJob", TEST_KEY),
+            ("claim-forged", "alpha.swarmbee.eth", "QmJob", OTHER_KEY),
+            ("claim-no-genesis", "beta.swarmbee.eth", "QmJob", OTHER_KEY),
+            ("claim-other-job", "alpha.swarmbee.eth", "QmOther", TEST_KEY),
+        ];
+        for (id, provider, job_cid, key) in claims {
+            let signed = crypto::sign_json(&claim(id, provider, job_cid), key)
+                .await
+                .unwrap();
+            write_claim(store, id, &signed).await.unwrap();
+        }
+
+        let verified = verified_claims_for_job(store, "QmJob").await.unwrap();
+        let ids: Vec<&str> = verified.iter().map(|c| c.claim_id.as_str()).collect();
+        assert_eq!(ids, ["claim-signed"]);
+    }
+
+    #[tokio::test]
+    async fn test_scan_collects_corrupt_entries_separately() {
+        let (root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        for id in ["d-1", "d-2"] {
+            let dispute = serde_json::json!({
+                "type": "dispute",
+                "version": "1.0.0",
+                "dispute_id": id,
+                "epoch_id": "epoch-001",
+                "proof_cid": "QmProof",
+                "provider": "alpha.swarmbee.eth",
+                "status": "open",
+                "reason": "wrong level",
+                "timestamp": 1704067200
+            });
+            store
+                .write(&format!("{}/{}.json", paths::DISPUTES, id), &dispute)
+                .await
+                .unwrap();
+        }
+        let dir = root.path().join(paths::DISPUTES.trim_start_matches('/'));
+        std::fs::write(dir.join("truncated.json"), br#"{"type": "dispute", "disp"#).unwrap();
+        std::fs::write(dir.join("wrong-shape.json"), br#"{"type": "proof"}"#).unwrap();
+
+        let (disputes, errors) = scan_snapshots::<DisputeSnapshot>(store, paths::DISPUTES)
+            .await
+            .unwrap();
+        let mut ids: Vec<&str> = disputes.iter().map(|d| d.dispute_id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, ["d-1", "d-2"]);
+
+        let mut bad: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        bad.sort();
+        assert_eq!(
+            bad,
+            [
+                "/swarmpool/disputes/truncated.json",
+                "/swarmpool/disputes/wrong-shape.json"
+            ]
+        );
+
+        // The lenient reader proceeds with the good entries
+        let all: Vec<DisputeSnapshot> = read_all(store, paths::DISPUTES).await.unwrap();
+        assert_eq!(all.len(), 2);
+    }
+
+    /// Content objects an `FsStorage` rooted in `root` holds
+    fn stored_objects(root: &tempfile::TempDir) -> usize {
+        std::fs::read_dir(root.path().join("objects")).unwrap().count()
+    }
+
+    #[tokio::test]
+    async fn test_proofs_listed_with_published_cids() {
+        let (root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        let mut cids = Vec::new();
+        for (id, job_cid) in [("proof-a", "QmJobA"), ("proof-b", "QmJobB")] {
+            let proof = serde_json::json!({
+                "type": "proof",
+                "version": "1.0.0",
+                "proof_id": id,
+                "job_id": "job-a",
+                "job_cid": job_cid,
+                "status": "completed",
+                "output_cid": "QmOutput",
+                "metrics": {
+                    "inference_seconds": 1.0,
+                    "compute_seconds": 1.0,
+                    "confidence": 0.9,
+                    "model_version": "1.0.0"
+                },
+                "provider": "alpha.swarmbee.eth",
+                "timestamp": 1704067400,
+                "proof_hash": crypto::keccak256_hash(id.as_bytes())
+            });
+            cids.push(write_proof(store, id, &proof).await.unwrap());
+        }
+
+        let stored = stored_objects(&root);
+        let proofs = proofs_with_cids(store, Some("QmJobB")).await.unwrap();
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].0, cids[1]);
+        assert_eq!(proofs[0].1.proof_id, "proof-b");
+        assert_eq!(proofs_with_cids(store, None).await.unwrap().len(), 2);
+        // Nothing is re-uploaded to learn the CIDs
+        assert_eq!(stored_objects(&root), stored);
+
+        // A proof that can't be read fails the listing instead of vanishing
+        let proofs_dir = root.path().join(paths::PROOFS.trim_start_matches('/'));
+        std::fs::write(proofs_dir.join("proof-c.json"), "{ not json").unwrap();
+        assert!(proofs_with_cids(store, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_recent_job_matches_input_and_client() {
+        use crate::commands::submit::build_job;
+
+        let (root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        let input = "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o";
+        let now = chrono::Utc::now().timestamp();
+        let jobs = [
+            ("job-old", input, "clinic.clientswarm.eth", now - 7200),
+            ("job-a", input, "clinic.clientswarm.eth", now - 600),
+            ("job-b", input, "clinic.clientswarm.eth", now - 60),
+            ("job-other-client", input, "other.clientswarm.eth", now),
+            ("job-other-input", "bafyother", "clinic.clientswarm.eth", now),
+        ];
+        let mut cids = std::collections::HashMap::new();
+        for (id, input_cid, client, timestamp) in jobs {
+            let mut job = build_job(
+                id.to_string(),
+                "queenbee-spine",
+                input_cid.to_string(),
+                client,
+                "0.10",
+                timestamp,
+            );
+            job.sig = Some(crypto::sign_snapshot(&job, TEST_KEY).await.unwrap());
+            cids.insert(id, write_job(store, id, &job).await.unwrap());
+        }
+
+        let stored = stored_objects(&root);
+        let (cid, job) = find_recent_job(store, input, "clinic.clientswarm.eth", 3600)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.job_id, "job-b");
+        assert_eq!(cid, cids["job-b"]);
+        // The CID comes from the jobs directory; nothing is re-uploaded
+        assert_eq!(stored_objects(&root), stored);
+
+        // A cancelled job is not a duplicate
+        let cancelled = cancellation("job-b", now, TEST_KEY).await;
+        store
+            .write(&format!("{}/job-b.json", paths::CANCELLATIONS), &cancelled)
+            .await
+            .unwrap();
+        let (_, job) = find_recent_job(store, input, "clinic.clientswarm.eth", 3600)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.job_id, "job-a");
+
+        // Outside the window
+        assert!(find_recent_job(store, input, "clinic.clientswarm.eth", 30)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_epochs_sorts_filters_and_pages() {
+        let (root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        // Nothing published yet
+        let none = fetch_epochs(store, "swarmpool.eth", &EpochFilter::default()).await;
+        assert!(none.unwrap().is_empty());
+
+        // (id, started_at, ended_at); epoch-002 was sealed without ended_at
+        let epochs = [
+            ("epoch-001", 1_000, Some(2_000)),
+            ("epoch-002", 2_000, None),
+            ("epoch-003", 3_000, Some(4_000)),
+            ("epoch-004", 4_000, None),
+        ];
+        for (id, started_at, ended_at) in epochs {
+            let epoch = serde_json::json!({
+                "type": "epoch",
+                "version": "1.0.0",
+                "epoch_id": id,
+                "name": "Golf",
+                "status": if id == "epoch-004" { "active" } else { "sealed" },
+                "started_at": started_at,
+                "ended_at": ended_at,
+                "jobs_count": 0,
+                "proofs": [],
+                "total_volume_usdc": "0",
+                "controller": "merlin.swarmos.eth",
+                "timestamp": started_at
+            });
+            write_epoch(store, id, &epoch).await.unwrap();
+        }
+        let dir = root.path().join(paths::EPOCHS.trim_start_matches('/'));
+        std::fs::write(dir.join("truncated.json"), br#"{"type": "epoch", "ep"#).unwrap();
+
+        let ids = |epochs: Vec<EpochSnapshot>| -> Vec<String> {
+            epochs.into_iter().map(|e| e.epoch_id).collect()
+        };
+        let fetch = |filter: EpochFilter| async move {
+            ids(fetch_epochs(store, "swarmpool.eth", &filter).await.unwrap())
+        };
+
+        assert_eq!(
+            fetch(EpochFilter::default()).await,
+            ["epoch-004", "epoch-003", "epoch-002", "epoch-001"]
+        );
+        let page = EpochFilter {
+            offset: 1,
+            limit: Some(2),
+            ..EpochFilter::default()
+        };
+        assert_eq!(fetch(page).await, ["epoch-003", "epoch-002"]);
+
+        // epoch-002 has no ended_at, so it's placed by started_at
+        let window = EpochFilter {
+            after: Some(2_000),
+            before: Some(4_000),
+            ..EpochFilter::default()
+        };
+        assert_eq!(fetch(window).await, ["epoch-002", "epoch-001"]);
+
+        let epoch = fetch_epoch(store, "swarmpool.eth", "epoch-003").await.unwrap();
+        assert_eq!(epoch.ended_at, Some(4_000));
+        assert!(fetch_epoch(store, "swarmpool.eth", "epoch-999").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_pending_jobs_excludes_claimed_and_proven() {
+        use crate::commands::submit::build_job;
+
+        let (root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        let mut cids = Vec::new();
+        for id in ["job-open", "job-claimed", "job-proven", "job-cancelled"] {
+            let mut job = build_job(
+                id.to_string(),
+                "queenbee-spine",
+                "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+                "clinic.clientswarm.eth",
+                "0.10",
+                1704067200,
+            );
+            job.sig = Some(crypto::sign_snapshot(&job, TEST_KEY).await.unwrap());
+            cids.push(write_job(store, id, &job).await.unwrap());
+        }
+
+        let claim = serde_json::json!({
+            "type": "claim",
+            "version": "1.0.0",
+            "claim_id": "claim-job-claimed",
+            "job_id": "job-claimed",
+            "job_cid": cids[1],
+            "provider": "alpha.swarmbee.eth",
+            "mode": "SOLO",
+            "timestamp": 1704067300,
+            "nonce": "abcdef1234567890"
+        });
+        write_claim(store, "claim-job-claimed", &claim).await.unwrap();
+        let proof = serde_json::json!({
+            "type": "proof",
+            "version": "1.0.0",
+            "proof_id": "proof-job-proven",
+            "job_id": "job-proven",
+            "job_cid": cids[2],
+            "status": "completed",
+            "output_cid": "QmOutput",
+            "metrics": {
+                "inference_seconds": 1.0,
+                "compute_seconds": 1.0,
+                "confidence": 0.9,
+                "model_version": "1.0.0"
+            },
+            "provider": "beta.swarmbee.eth",
+            "timestamp": 1704067400,
+            "proof_hash": crypto::keccak256_hash(b"proof-job-proven")
+        });
+        write_proof(store, "proof-job-proven", &proof).await.unwrap();
+        let cancelled = cancellation("job-cancelled", 1704067500, TEST_KEY).await;
+        store
+            .write(&format!("{}/job-cancelled.json", paths::CANCELLATIONS), &cancelled)
+            .await
+            .unwrap();
+        // Only the job's client can cancel it
+        let forged = cancellation("job-open", 1704067500, OTHER_KEY).await;
+        store
+            .write(&format!("{}/job-open.json", paths::CANCELLATIONS), &forged)
+            .await
+            .unwrap();
+
+        let stored = stored_objects(&root);
+        let ttl = crate::models::DEFAULT_CLAIM_TTL_SECS as i64;
+        let pending = list_pending_jobs(store, 1704067300 + ttl - 1)
+            .await
+            .unwrap();
+        // The CIDs come from the jobs directory; nothing is re-uploaded
+        assert_eq!(stored_objects(&root), stored);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, cids[0]);
+        assert_eq!(pending[0].1.job_id, "job-open");
+
+        // Once the claim lapses (default TTL, no heartbeat) its job is claimable again
+        let pending = list_pending_jobs(store, 1704067300 + ttl).await.unwrap();
+        let mut ids: Vec<&str> = pending.iter().map(|(_, job)| job.job_id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, ["job-claimed", "job-open"]);
+    }
+
+    #[test]
+    fn test_fresh_state_passes() {
+        let now = 1_704_070_800;
+        assert!(check_freshness(now - 60, now, 3600, true).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_stale_state_warns() {
+        let now = 1_704_070_800;
+        let warning = check_freshness(now - 3 * 86_400, now, 3600, false).unwrap();
+        assert!(warning.unwrap().contains("72h 0m old"));
+    }
+
+    #[test]
+    fn test_stale_state_refused_under_strict() {
+        let now = 1_704_070_800;
+        let err = check_freshness(now - 7200, now, 3600, true).unwrap_err();
+        assert!(err.to_string().contains("--strict-freshness"));
+    }
 }
@@ -0,0 +1,143 @@
+//! Encrypted keystore for signing keys
+//!
+//! Keys are stored as standard Ethereum V3 keystore files (scrypt + AES-128-CTR)
+//! under `<config_dir>/keystore/<label>.json`, so they can also be opened by
+//! any Ethereum wallet. The password comes from `SWARM_KEYSTORE_PASSWORD` or an
+//! interactive prompt; it is never written to disk.
+
+use anyhow::{bail, Context, Result};
+use directories::ProjectDirs;
+use ethers::signers::LocalWallet;
+use std::path::PathBuf;
+
+/// Environment variable holding the keystore password (non-interactive use)
+pub const PASSWORD_ENV: &str = "SWARM_KEYSTORE_PASSWORD";
+
+/// Keystore directory holding one file per key label
+pub struct Keystore {
+    dir: PathBuf,
+}
+
+impl Keystore {
+    /// Keystore under the CLI config directory
+    pub fn open() -> Result<Self> {
+        let proj_dirs = ProjectDirs::from("eth", "swarmpool", "swarm-cli")
+            .context("Failed to determine config directory")?;
+        Ok(Self::at(proj_dirs.config_dir().join("keystore")))
+    }
+
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// File backing a key label
+    pub fn key_path(&self, label: &str) -> Result<PathBuf> {
+        validate_label(label)?;
+        Ok(self.dir.join(format!("{}.json", label)))
+    }
+
+    pub fn contains(&self, label: &str) -> bool {
+        self.key_path(label).map(|p| p.exists()).unwrap_or(false)
+    }
+
+    /// Encrypt `wallet` under `label`. Never overwrites an existing key.
+    pub fn save(&self, label: &str, wallet: &LocalWallet, password: &str) -> Result<PathBuf> {
+        let path = self.key_path(label)?;
+        if path.exists() {
+            bail!(
+                "Keystore entry '{}' already exists ({}). Choose another --label.",
+                label,
+                path.display()
+            );
+        }
+
+        std::fs::create_dir_all(&self.dir).context("Failed to create keystore directory")?;
+        LocalWallet::encrypt_keystore(
+            &self.dir,
+            &mut rand::thread_rng(),
+            wallet.signer().to_bytes(),
+            password,
+            Some(&format!("{}.json", label)),
+        )
+        .context("Failed to encrypt key")?;
+
+        Ok(path)
+    }
+
+    /// Decrypt the key stored under `label`
+    pub fn load(&self, label: &str, password: &str) -> Result<LocalWallet> {
+        let path = self.key_path(label)?;
+        if !path.exists() {
+            bail!("No keystore entry '{}' in {}", label, self.dir.display());
+        }
+
+        LocalWallet::decrypt_keystore(&path, password)
+            .with_context(|| format!("Failed to decrypt keystore entry '{}' (wrong password?)", label))
+    }
+}
+
+/// Labels become file names, so keep them to a safe character set
+fn validate_label(label: &str) -> Result<()> {
+    if label.is_empty()
+        || !label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!(
+            "Invalid keystore label '{}': use letters, digits, '-' or '_'",
+            label
+        );
+    }
+    Ok(())
+}
+
+/// Keystore password from `SWARM_KEYSTORE_PASSWORD`, or prompt for it.
+///
+/// With `confirm`, the prompt asks twice (used when creating a key).
+pub fn read_password(confirm: bool) -> Result<String> {
+    if let Ok(password) = std::env::var(PASSWORD_ENV) {
+        return Ok(password);
+    }
+
+    let prompt = dialoguer::Password::new().with_prompt("Keystore password");
+    let prompt = if confirm {
+        prompt.with_confirmation("Confirm password", "Passwords do not match")
+    } else {
+        prompt
+    };
+
+    prompt.interact().context("Failed to read keystore password")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use ethers::signers::Signer;
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        let dir = test_support::temp_dir();
+        let keystore = Keystore::at(dir.path());
+        let wallet = LocalWallet::new(&mut rand::thread_rng());
+
+        let path = keystore.save("provider", &wallet, "hunter2").unwrap();
+        assert_eq!(path, dir.path().join("provider.json"));
+        assert!(keystore.contains("provider"));
+
+        let loaded = keystore.load("provider", "hunter2").unwrap();
+        assert_eq!(loaded.address(), wallet.address());
+
+        assert!(keystore.load("provider", "wrong").is_err());
+        assert!(keystore.save("provider", &wallet, "hunter2").is_err());
+    }
+
+    #[test]
+    fn test_invalid_label_rejected() {
+        let dir = test_support::temp_dir();
+        let keystore = Keystore::at(dir.path());
+        assert!(keystore.key_path("../config").is_err());
+        assert!(keystore.key_path("").is_err());
+        assert!(keystore.key_path("miner_alice-01").is_ok());
+    }
+}
@@ -0,0 +1,225 @@
+//! Local append-only ledger of published snapshots
+//!
+//! Every snapshot written to its canonical path is recorded as one JSON line
+//! in `<data_dir>/ledger.jsonl`, so the CIDs survive an MFS reset. Each entry
+//! is appended with a single `O_APPEND` write and fsynced, so concurrent
+//! `swarm` processes never interleave lines. A line torn by a crash is
+//! skipped when reading.
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// One published snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    /// Unix time of the publish
+    pub timestamp: i64,
+    /// The snapshot's `type` (job, claim, proof, epoch, genesis, ...)
+    pub snapshot_type: String,
+    /// The snapshot's own id, e.g. `job-20250101000000-abcd1234`
+    pub local_id: String,
+    pub cid: String,
+    /// Canonical MFS path it was written to
+    pub path: String,
+}
+
+impl LedgerEntry {
+    /// Entry for a snapshot just written to `path`
+    pub fn new(path: &str, cid: &str, data: &serde_json::Value, timestamp: i64) -> Self {
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        Self {
+            timestamp,
+            snapshot_type: data["type"].as_str().unwrap_or("unknown").to_string(),
+            local_id: file_name.trim_end_matches(".json").to_string(),
+            cid: cid.to_string(),
+            path: path.to_string(),
+        }
+    }
+}
+
+/// Which entries `Ledger::query` returns
+#[derive(Debug, Clone, Default)]
+pub struct LedgerFilter {
+    pub snapshot_type: Option<String>,
+    /// Inclusive lower bound, unix time
+    pub since: Option<i64>,
+    /// Inclusive upper bound, unix time
+    pub until: Option<i64>,
+}
+
+impl LedgerFilter {
+    fn matches(&self, entry: &LedgerEntry) -> bool {
+        self.snapshot_type
+            .as_ref()
+            .is_none_or(|t| t.eq_ignore_ascii_case(&entry.snapshot_type))
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp <= until)
+    }
+}
+
+/// The ledger file
+pub struct Ledger {
+    path: PathBuf,
+}
+
+impl Ledger {
+    /// Ledger under the CLI data directory
+    pub fn open() -> Result<Self> {
+        let proj_dirs = ProjectDirs::from("eth", "swarmpool", "swarm-cli")
+            .context("Failed to determine data directory")?;
+        Ok(Self::at(proj_dirs.data_dir().join("ledger.jsonl")))
+    }
+
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Append `entry` durably
+    pub fn append(&self, entry: &LedgerEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        // The whole line goes out in one write so appends from other
+        // processes land before or after it, never inside it
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        file.write_all(&line)
+            .with_context(|| format!("Failed to append to {}", self.path.display()))?;
+        file.flush()?;
+        file.sync_all()
+            .with_context(|| format!("Failed to sync {}", self.path.display()))
+    }
+
+    /// Entries matching `filter`, oldest first. Unparseable lines are
+    /// skipped and counted.
+    pub fn query(&self, filter: &LedgerFilter) -> Result<(Vec<LedgerEntry>, usize)> {
+        if !self.path.exists() {
+            return Ok((vec![], 0));
+        }
+        let content = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+
+        let mut entries = Vec::new();
+        let mut skipped = 0;
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            match serde_json::from_str::<LedgerEntry>(line) {
+                Ok(entry) if filter.matches(&entry) => entries.push(entry),
+                Ok(_) => {}
+                Err(_) => skipped += 1,
+            }
+        }
+        Ok((entries, skipped))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use tempfile::TempDir;
+
+    fn temp_ledger() -> (TempDir, Ledger) {
+        let dir = test_support::temp_dir();
+        let ledger = Ledger::at(dir.path().join("ledger.jsonl"));
+        (dir, ledger)
+    }
+
+    fn entry(snapshot_type: &str, timestamp: i64) -> LedgerEntry {
+        let path = format!(
+            "/swarmpool/{}s/{}-{}.json",
+            snapshot_type, snapshot_type, timestamp
+        );
+        let data = serde_json::json!({ "type": snapshot_type });
+        LedgerEntry::new(
+            &path,
+            "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o",
+            &data,
+            timestamp,
+        )
+    }
+
+    #[test]
+    fn test_filter_by_type_and_date() {
+        let (_dir, ledger) = temp_ledger();
+        for e in [entry("job", 100), entry("claim", 200), entry("job", 300)] {
+            ledger.append(&e).unwrap();
+        }
+        // A torn line from a crash mid-append doesn't hide the rest
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(ledger.path())
+            .unwrap()
+            .write_all(b"{\"timestamp\": 4")
+            .unwrap();
+
+        let (all, skipped) = ledger.query(&LedgerFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(skipped, 1);
+        assert_eq!(all[0].local_id, "job-100");
+        assert_eq!(all[0].path, "/swarmpool/jobs/job-100.json");
+
+        let jobs = LedgerFilter {
+            snapshot_type: Some("JOB".to_string()),
+            since: Some(200),
+            until: None,
+        };
+        assert_eq!(ledger.query(&jobs).unwrap().0, [entry("job", 300)]);
+
+        let window = LedgerFilter {
+            snapshot_type: None,
+            since: Some(100),
+            until: Some(300),
+        };
+        let (entries, _) = ledger.query(&window).unwrap();
+        // --until is inclusive
+        assert_eq!(
+            entries,
+            [entry("job", 100), entry("claim", 200), entry("job", 300)]
+        );
+        let earlier = LedgerFilter {
+            until: Some(299),
+            ..window
+        };
+        assert_eq!(ledger.query(&earlier).unwrap().0.len(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_appends_never_interleave() {
+        let (_dir, ledger) = temp_ledger();
+        let path = ledger.path().clone();
+
+        let writers: Vec<_> = (0..8)
+            .map(|w| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let ledger = Ledger::at(path);
+                    for i in 0..25 {
+                        ledger.append(&entry("proof", w * 1000 + i)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let (entries, skipped) = ledger.query(&LedgerFilter::default()).unwrap();
+        assert_eq!(skipped, 0);
+        assert_eq!(entries.len(), 200);
+    }
+}
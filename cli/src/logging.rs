@@ -0,0 +1,78 @@
+//! Log output format
+//!
+//! `--log-format text` (the default) is the human output: colored status
+//! lines and animated spinners. `--log-format json` switches tracing to JSON
+//! lines, turns off ANSI colors and has `ui` replace spinners with plain log
+//! events, so nothing written contains terminal control characters.
+
+use std::io::IsTerminal;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// How log and progress output is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Invalid log format: {}. Use text or json", s)),
+        }
+    }
+}
+
+/// Set once by `init`
+static JSON: AtomicBool = AtomicBool::new(false);
+
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// Install the tracing subscriber for `format`, writing to stderr so logs
+/// never mix with the status lines on stdout. Phase spans report their
+/// timing on close, but only when RUST_LOG asks for tracing output (e.g.
+/// RUST_LOG=swarm=debug).
+pub fn init(format: LogFormat) {
+    let span_events = if std::env::var_os("RUST_LOG").is_some() {
+        FmtSpan::CLOSE
+    } else {
+        FmtSpan::NONE
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::from_default_env()
+                .add_directive(tracing::Level::INFO.into()),
+        )
+        .with_span_events(span_events)
+        .with_writer(std::io::stderr);
+
+    match format {
+        LogFormat::Text => builder.with_ansi(std::io::stderr().is_terminal()).init(),
+        LogFormat::Json => {
+            JSON.store(true, Ordering::Relaxed);
+            colored::control::set_override(false);
+            builder.json().init();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_format_parse() {
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("TEXT".parse::<LogFormat>().unwrap(), LogFormat::Text);
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+}
@@ -6,16 +6,31 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use colored::Colorize;
+use std::io::IsTerminal;
 
+mod cache;
 mod commands;
 mod config;
 mod crypto;
+mod error;
 mod ipfs;
+mod keystore;
+mod ledger;
+mod logging;
 mod models;
+mod money;
+mod nonces;
 mod provider;
+mod report;
+mod reputation;
+mod sandbox;
 mod schema;
+mod storage;
+#[cfg(test)]
+mod test_support;
+mod ui;
 
-use commands::{claim, epochs, init, prove, seal, status, submit, validate, watch, withdraw};
+use commands::{audit_state, balance, claim, claims, diff_epoch, doctor, epochs, heartbeat, import_epoch, init, keygen, migrate_keys, payout_manifest, proofs, prove, publish, recover, resubmit, seal, status, submit, validate, verify_proof, verify_sig, watch, withdraw};
 
 /// SwarmPool CLI - Decentralized Medical Compute Network
 #[derive(Parser)]
@@ -32,9 +47,51 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Print only results (IDs, CIDs), warnings and errors: no banner,
+    /// spinners or progress details
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Log output: text, or json (JSON lines, no colors or spinners)
+    #[arg(long, global = true, env = "SWARM_LOG_FORMAT", default_value = "text")]
+    log_format: String,
+
     /// Pool ENS address
     #[arg(long, global = true, default_value = "swarmpool.eth")]
     pool: String,
+
+    /// Serve reads from the local snapshot cache only (no IPFS)
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Fetch every snapshot from IPFS, bypassing the local cache
+    #[arg(long, global = true, env = "SWARM_NO_CACHE")]
+    no_cache: bool,
+
+    /// Warn when pool state is older than this many seconds
+    #[arg(long, global = true, default_value_t = ipfs::DEFAULT_MAX_STATE_AGE_SECS)]
+    max_age: i64,
+
+    /// Refuse stale (or mock) pool state instead of warning
+    #[arg(long, global = true)]
+    strict_freshness: bool,
+
+    /// Largest snapshot (JSON response body) fetched from IPFS, in bytes
+    #[arg(long, global = true, env = "SWARM_MAX_SNAPSHOT_BYTES", default_value_t = ipfs::DEFAULT_MAX_SNAPSHOT_BYTES)]
+    max_snapshot_bytes: usize,
+
+    /// Refuse to publish snapshots that deviate from their schema (default: warn)
+    #[arg(long, global = true)]
+    strict_schema: bool,
+
+    /// Sign and validate as normal, but print what would be published instead
+    /// of writing it (reads still use the storage backend)
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Store snapshots in a local directory instead of IPFS
+    #[arg(long, global = true, env = "SWARM_STORAGE_DIR")]
+    storage_dir: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -53,6 +110,10 @@ enum Commands {
         #[arg(long)]
         gpus: Option<String>,
 
+        /// Models to register (comma-separated, or all that fit the detected GPUs)
+        #[arg(long)]
+        models: Option<String>,
+
         /// Private key for signing
         #[arg(long, env = "SWARM_PRIVATE_KEY")]
         key: Option<String>,
@@ -60,13 +121,21 @@ enum Commands {
 
     /// Watch the pool for available jobs (read-only observation)
     Watch {
-        /// Models to watch for (comma-separated)
+        /// Models to watch for (comma-separated; `queenbee-*` matches a family)
         #[arg(long)]
         models: Option<String>,
 
         /// Provider ENS (if different from config)
         #[arg(long, env = "SWARM_PROVIDER_ENS")]
         provider: Option<String>,
+
+        /// Claim and prove matching jobs, as many at once as GPU VRAM allows
+        #[arg(long)]
+        auto_prove: bool,
+
+        /// How GPUs count toward model eligibility: max-single or sum
+        #[arg(long, default_value = "max-single")]
+        vram_strategy: String,
     },
 
     /// Submit an inference job to the network (client action)
@@ -79,14 +148,66 @@ enum Commands {
         #[arg(long)]
         model: String,
 
-        /// Input file path or IPFS CID
+        /// Input file path, directory (with --series) or IPFS CID
         #[arg(long)]
         input: String,
 
+        /// Upload a directory input recursively as one series (e.g. DICOM slices)
+        #[arg(long)]
+        series: bool,
+
         /// Client ENS name
         #[arg(long, env = "SWARM_CLIENT_ENS")]
         client: String,
 
+        /// Payment amount, in units of --token
+        #[arg(long, visible_alias = "amount", default_value = "0.10")]
+        payment: String,
+
+        /// Settlement token for the payment (only USDC is settled)
+        #[arg(long, default_value = "USDC")]
+        token: String,
+
+        /// Minimum confidence the result must reach (0.0 to 1.0)
+        #[arg(long, default_value_t = 0.6)]
+        confidence_threshold: f64,
+
+        /// Report format: pdf, json (output object only) or dicom-sr
+        #[arg(long, default_value = "pdf")]
+        output_format: String,
+
+        /// Refuse to submit when the payment looks too low for the input
+        #[arg(long)]
+        strict: bool,
+
+        /// Refuse, rather than ask, when the same input was just submitted
+        #[arg(long)]
+        no_confirm: bool,
+
+        /// Private key for signing (or use SWARM_PRIVATE_KEY env)
+        #[arg(long, env = "SWARM_PRIVATE_KEY")]
+        key: Option<String>,
+
+        /// Sign and validate the job and write it to this file instead of
+        /// publishing it (see `swarm publish`)
+        #[arg(long, value_name = "PATH")]
+        sign_only: Option<String>,
+    },
+
+    /// Republish a job that no provider has proven (client action)
+    Resubmit {
+        /// CID of the original job
+        #[arg(long)]
+        job: String,
+
+        /// Seconds after the original was submitted before it may be resubmitted
+        #[arg(long, default_value_t = resubmit::DEFAULT_TIMEOUT_SECS)]
+        timeout: u64,
+
+        /// Refuse, rather than ask, when another job for the same input exists
+        #[arg(long)]
+        no_confirm: bool,
+
         /// Private key for signing (or use SWARM_PRIVATE_KEY env)
         #[arg(long, env = "SWARM_PRIVATE_KEY")]
         key: Option<String>,
@@ -109,6 +230,19 @@ enum Commands {
         /// Private key for signing
         #[arg(long, env = "SWARM_PRIVATE_KEY")]
         key: Option<String>,
+
+        /// Print expected SOLO vs PPL earnings without publishing a claim
+        #[arg(long)]
+        estimate: bool,
+
+        /// Claim SOLO even when another provider already holds a SOLO claim
+        #[arg(long)]
+        force: bool,
+
+        /// Sign and validate the claim and write it to this file instead of
+        /// publishing it (see `swarm publish`)
+        #[arg(long, value_name = "PATH")]
+        sign_only: Option<String>,
     },
 
     /// Process a claimed job and submit proof of work
@@ -128,6 +262,37 @@ enum Commands {
         /// Private key for signing
         #[arg(long, env = "SWARM_PRIVATE_KEY")]
         key: Option<String>,
+
+        /// Wallet the job's client must have signed with (otherwise the
+        /// signature is only checked to be well-formed)
+        #[arg(long)]
+        client_wallet: Option<String>,
+
+        /// Run the inference runner without network, writing only its
+        /// staging directory (Linux; needs bwrap or unshare)
+        #[arg(long)]
+        sandbox: bool,
+
+        /// Publish a result below the job's confidence threshold, marked
+        /// low_confidence, instead of refusing
+        #[arg(long)]
+        allow_low_confidence: bool,
+
+        /// Sign and validate the proof and write it to this file instead of
+        /// publishing it (see `swarm publish`)
+        #[arg(long, value_name = "PATH")]
+        sign_only: Option<String>,
+
+        /// Publish a canned result, with proof status "simulated" (never
+        /// paid), when the inference runner is unavailable (testing only)
+        #[arg(long)]
+        simulate: bool,
+
+        /// Save the full inference output to this file after a successful
+        /// run; for a directory, the signed proof is saved alongside it. A
+        /// failed save is only a warning, since the proof is already out
+        #[arg(long, value_name = "PATH")]
+        output: Option<String>,
     },
 
     /// Seal an epoch and calculate settlements (Merlin controller only)
@@ -139,6 +304,43 @@ enum Commands {
         /// Private key for signing (must be Merlin's key)
         #[arg(long, env = "SWARM_PRIVATE_KEY")]
         key: Option<String>,
+
+        /// Skip pinning the sealed epoch's CIDs
+        #[arg(long)]
+        no_pin: bool,
+
+        /// Record each proof's Merkle inclusion path in the epoch
+        #[arg(long)]
+        inclusion_paths: bool,
+
+        /// Continue from the proofs an interrupted seal of this epoch
+        /// collected, fetching only the proofs it is missing
+        #[arg(long)]
+        resume: bool,
+
+        /// Sign and validate the sealed epoch and write it to this file instead of
+        /// publishing it (see `swarm publish`)
+        #[arg(long, value_name = "PATH")]
+        sign_only: Option<String>,
+    },
+
+    /// Send one signed heartbeat for this provider
+    Heartbeat {
+        /// Provider ENS (if different from config)
+        #[arg(long, env = "SWARM_PROVIDER_ENS")]
+        provider: Option<String>,
+
+        /// Status to report: online, watching, busy or offline
+        #[arg(long, default_value = "online")]
+        status: String,
+
+        /// Models served (comma-separated, default from config)
+        #[arg(long)]
+        models: Option<String>,
+
+        /// Private key for signing
+        #[arg(long, env = "SWARM_PRIVATE_KEY")]
+        key: Option<String>,
     },
 
     /// Check network or provider status
@@ -152,6 +354,56 @@ enum Commands {
         json: bool,
     },
 
+    /// List published claims
+    Claims {
+        /// Only claims for this job CID
+        #[arg(long)]
+        job: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List published proofs, marking the SOLO winner of each job
+    Proofs {
+        /// Only proofs for this job CID
+        #[arg(long)]
+        job: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List registered providers from the genesis directory
+    Provider {
+        #[command(subcommand)]
+        action: ProviderAction,
+    },
+
+    /// Score a provider from published proofs, disputes and epochs
+    Reputation {
+        /// Provider ENS to score
+        #[arg(long)]
+        provider: String,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show a provider's sealed (claimable) and unsealed earnings
+    Balance {
+        /// Provider ENS
+        #[arg(long)]
+        provider: String,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Withdraw earnings to wallet
     Withdraw {
         /// Amount to withdraw (or "all")
@@ -167,6 +419,40 @@ enum Commands {
         key: Option<String>,
     },
 
+    /// Generate a new signing key
+    Keygen {
+        /// Save the key to the encrypted keystore
+        #[arg(long)]
+        save: bool,
+
+        /// Keystore label for the saved key
+        #[arg(long, default_value = "default")]
+        label: String,
+
+        /// Also write the new address into config as the payout wallet
+        #[arg(long)]
+        set_wallet: bool,
+
+        /// Print the raw private key (anyone who sees it controls the funds)
+        #[arg(long)]
+        unsafe_print: bool,
+    },
+
+    /// Move the raw --key / SWARM_PRIVATE_KEY into the encrypted keystore
+    MigrateKeys {
+        /// Private key to migrate (or set SWARM_PRIVATE_KEY)
+        #[arg(long)]
+        key: Option<String>,
+
+        /// Keystore label for the migrated key
+        #[arg(long, default_value = "default")]
+        label: String,
+
+        /// Address the key must derive (defaults to wallet in config)
+        #[arg(long)]
+        address: Option<String>,
+    },
+
     /// Show configuration
     Config {
         /// Show config file path
@@ -174,9 +460,22 @@ enum Commands {
         path: bool,
     },
 
+    /// Check IPFS, config, keys, GPUs and eth_rpc, with hints for anything broken
+    Doctor {
+        /// Private key to check (default: SWARM_PRIVATE_KEY or the keystore)
+        #[arg(long, env = "SWARM_PRIVATE_KEY")]
+        key: Option<String>,
+    },
+
     /// List available models
     Models,
 
+    /// Manage the local snapshot cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
     /// Show epoch information
     Epochs {
         /// Specific epoch ID
@@ -186,6 +485,135 @@ enum Commands {
         /// Number of recent epochs to show
         #[arg(long, default_value = "10")]
         limit: u32,
+
+        /// Skip this many of the most recent epochs
+        #[arg(long, default_value = "0")]
+        offset: u32,
+
+        /// Only epochs that ended before this time (unix seconds or RFC3339)
+        #[arg(long)]
+        before: Option<String>,
+
+        /// Only epochs that ended at or after this time (unix seconds or RFC3339)
+        #[arg(long)]
+        after: Option<String>,
+
+        /// Output format: table, json or csv
+        #[arg(long, default_value = "table")]
+        format: String,
+
+        /// Export every published epoch to one file (requires --out)
+        #[arg(long, requires = "out")]
+        export_all: bool,
+
+        /// File to write the export to
+        #[arg(long, requires = "export_all")]
+        out: Option<String>,
+
+        /// Write one epoch per line instead of a JSON array
+        #[arg(long, requires = "export_all")]
+        ndjson: bool,
+
+        /// Check each epoch's signature and merkle root while exporting
+        #[arg(long, requires = "export_all")]
+        verify: bool,
+
+        /// Controller address every exported epoch must be signed by
+        #[arg(long, requires = "verify")]
+        controller: Option<String>,
+    },
+
+    /// Verify the pool-state hash chain against sealed epochs
+    AuditState,
+
+    /// Verify an externally-provided sealed epoch and optionally mirror it
+    ImportEpoch {
+        /// Path to the epoch JSON file
+        #[arg(long)]
+        file: String,
+
+        /// Controller address the epoch must be signed by (otherwise
+        /// `controller` from config.toml)
+        #[arg(long)]
+        controller: Option<String>,
+
+        /// Write the verified epoch into /swarmpool/epochs/
+        #[arg(long)]
+        write: bool,
+    },
+
+    /// Recompute a sealed epoch's settlements and diff them against the published seal
+    DiffEpoch {
+        /// Epoch ID (e.g., epoch-001)
+        #[arg(long)]
+        epoch: String,
+
+        /// Largest per-amount difference, in USDC microunits, treated as rounding
+        #[arg(long, default_value_t = import_epoch::ROUNDING_TOLERANCE_MICRO)]
+        tolerance: u64,
+
+        /// Print the diff as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Write a signed manifest of the wallet transfers that pay out a sealed epoch
+    PayoutManifest {
+        /// Epoch ID (e.g., epoch-001)
+        #[arg(long)]
+        epoch: String,
+
+        /// Write the manifest to this file instead of stdout
+        #[arg(long, value_name = "PATH")]
+        out: Option<String>,
+
+        /// Output format: json (signed manifest) or disperse (CSV for Safe
+        /// CSV Airdrop / Disperse.app)
+        #[arg(long, default_value = "json")]
+        format: String,
+
+        /// Token contract in disperse rows
+        #[arg(long, default_value = payout_manifest::USDC_MAINNET)]
+        token_address: String,
+
+        /// Private key for signing the manifest
+        #[arg(long, env = "SWARM_PRIVATE_KEY")]
+        key: Option<String>,
+
+        /// Controller address the epoch must be signed by (otherwise
+        /// `controller` from config.toml)
+        #[arg(long)]
+        controller: Option<String>,
+    },
+
+    /// Check that a proof is included in a sealed epoch's Merkle root
+    VerifyProof {
+        /// Proof CID
+        #[arg(long)]
+        proof: String,
+
+        /// Epoch ID the proof was settled in (e.g., epoch-001)
+        #[arg(long)]
+        epoch: String,
+    },
+
+    /// List snapshots published from this machine (local ledger)
+    Ledger {
+        /// Only this snapshot type (job, claim, proof, epoch, provider-init)
+        #[arg(long = "type")]
+        snapshot_type: Option<String>,
+
+        /// Published on or after (YYYY-MM-DD or unix seconds)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Published on or before (YYYY-MM-DD or unix seconds)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Validate a snapshot against its schema (debug tool)
@@ -194,53 +622,190 @@ enum Commands {
         #[arg(long)]
         file: String,
 
-        /// Schema type: genesis, job, claim, proof, epoch
+        /// Schema type: genesis, job, claim, proof, epoch, heartbeat, withdrawal
+        #[arg(long)]
+        schema: String,
+    },
+
+    /// Publish a snapshot signed offline with --sign-only
+    Publish {
+        /// Path to the signed snapshot JSON file
+        #[arg(long)]
+        file: String,
+
+        /// Schema type: job, claim, proof, epoch
+        #[arg(long)]
+        schema: String,
+
+        /// Controller address an epoch must be signed by (otherwise
+        /// `controller` from config.toml)
+        #[arg(long)]
+        controller: Option<String>,
+    },
+
+    /// Show the address that signed a snapshot, and the digest it signed
+    Recover {
+        /// Path to the snapshot JSON file
+        #[arg(long)]
+        file: String,
+
+        /// Schema type: genesis, job, claim, proof, epoch, heartbeat, withdrawal
         #[arg(long)]
         schema: String,
     },
+
+    /// Recover who signed any snapshot file (offline, no schema checks)
+    VerifySig {
+        /// Path to the snapshot JSON file
+        file: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProviderAction {
+    /// List every registration in /swarmpool/genesis/ and verify its signature
+    List {
+        /// Only providers advertising this model
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Delete every cached snapshot
+    Clear,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(tracing::Level::INFO.into()),
-        )
-        .init();
-
     let cli = Cli::parse();
+    let log_format: logging::LogFormat = cli
+        .log_format
+        .parse()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
+    logging::init(log_format);
+    ui::set_quiet(cli.quiet);
+
+    if cli.offline && cli.no_cache {
+        anyhow::bail!("--offline reads only from the cache; it can't be combined with --no-cache");
+    }
+    cache::set_offline(cli.offline);
+    cache::set_no_cache(cli.no_cache);
+    ipfs::set_freshness(cli.max_age, cli.strict_freshness);
+    ipfs::set_max_snapshot_bytes(cli.max_snapshot_bytes);
+    schema::set_strict(cli.strict_schema);
+    // A broken config file is reported by the commands that need it
+    let ipfs_client = ipfs::IpfsClient::init(config::load_config().ok().as_ref())?;
+    let sign_only = match &cli.command {
+        Commands::Submit { sign_only, .. } => sign_only.clone().map(|p| (p, "job")),
+        Commands::Claim { sign_only, .. } => sign_only.clone().map(|p| (p, "claim")),
+        Commands::Prove { sign_only, .. } => sign_only.clone().map(|p| (p, "proof")),
+        Commands::Seal { sign_only, .. } => sign_only.clone().map(|p| (p, "epoch")),
+        _ => None,
+    };
+    if sign_only.is_some() && cli.dry_run {
+        anyhow::bail!("--sign-only and --dry-run can't be combined");
+    }
+    let store = storage::open(
+        cli.storage_dir.as_deref(),
+        cli.dry_run,
+        sign_only.as_ref().map(|(path, _)| path.as_str()),
+        ipfs_client,
+    );
+    let store = store.as_ref();
 
-    // Print banner
-    print_banner();
+    // Print banner (interactive text output only)
+    if !cli.quiet && !logging::is_json() && std::io::stdout().is_terminal() {
+        print_banner();
+    }
 
     match cli.command {
         Commands::Init {
             provider,
             wallet,
             gpus,
+            models,
             key,
         } => {
-            init::execute(provider, wallet, gpus, key, &cli.pool).await?;
+            init::execute(provider, wallet, gpus, models, key, store, &cli.pool).await?;
         }
 
-        Commands::Watch { models, provider } => {
-            watch::execute(models, provider, &cli.pool).await?;
+        Commands::Watch {
+            models,
+            provider,
+            auto_prove,
+            vram_strategy,
+        } => {
+            watch::execute(
+                models,
+                provider,
+                auto_prove,
+                vram_strategy,
+                store,
+                ipfs_client,
+                &cli.pool,
+            )
+            .await?;
         }
 
         Commands::Submit {
             file,
             model,
             input,
+            series,
             client,
+            payment,
+            token,
+            confidence_threshold,
+            output_format,
+            strict,
+            no_confirm,
+            key,
+            ..
+        } => {
+            submit::execute(
+                file,
+                model,
+                input,
+                series,
+                client,
+                payment,
+                token,
+                confidence_threshold,
+                output_format,
+                strict,
+                no_confirm,
+                key,
+                store,
+                &cli.pool,
+            )
+            .await?;
+        }
+
+        Commands::Resubmit {
+            job,
+            timeout,
+            no_confirm,
             key,
         } => {
-            submit::execute(file, model, input, client, key, &cli.pool).await?;
+            resubmit::execute(job, timeout, no_confirm, key, store, &cli.pool).await?;
         }
 
-        Commands::Claim { job, mode, provider, key } => {
-            claim::execute(job, mode, provider, key, &cli.pool).await?;
+        Commands::Claim {
+            job,
+            mode,
+            provider,
+            key,
+            estimate,
+            force,
+            ..
+        } => {
+            claim::execute(job, mode, provider, key, estimate, force, store, &cli.pool).await?;
         }
 
         Commands::Prove {
@@ -248,16 +813,75 @@ async fn main() -> Result<()> {
             claim,
             provider,
             key,
+            client_wallet,
+            sandbox,
+            allow_low_confidence,
+            simulate,
+            output,
+            ..
         } => {
-            prove::execute(job, claim, provider, key, &cli.pool).await?;
+            prove::execute(
+                job,
+                claim,
+                provider,
+                key,
+                client_wallet,
+                sandbox,
+                allow_low_confidence,
+                simulate,
+                output,
+                store,
+                &cli.pool,
+            )
+            .await?;
         }
 
-        Commands::Seal { epoch, key } => {
-            seal::execute(epoch, key, &cli.pool).await?;
+        Commands::Seal {
+            epoch,
+            key,
+            no_pin,
+            inclusion_paths,
+            resume,
+            ..
+        } => {
+            seal::execute(epoch, key, no_pin, inclusion_paths, resume, store, &cli.pool).await?;
+        }
+
+        Commands::Heartbeat {
+            provider,
+            status,
+            models,
+            key,
+        } => {
+            heartbeat::execute(provider, status, models, key, store, &cli.pool).await?;
         }
 
         Commands::Status { provider, json } => {
-            status::execute(provider, json, &cli.pool).await?;
+            status::execute(provider, json, store, &cli.pool).await?;
+        }
+
+        Commands::Claims { job, json } => {
+            claims::execute(job, json, store).await?;
+        }
+
+        Commands::Proofs { job, json } => {
+            proofs::execute(job, json, store).await?;
+        }
+
+        Commands::Provider { action } => match action {
+            ProviderAction::List { model, json } => {
+                // Fully qualified: `provider` is also the job processing module
+                commands::provider::list(model, json, store).await?;
+            }
+        },
+
+        Commands::Reputation { provider, json } => {
+            // Fully qualified: `reputation` is also the scoring module
+            commands::reputation::execute(provider, json, store, &cli.pool).await?;
+        }
+
+        Commands::Balance { provider, json } => {
+            balance::execute(provider, json, store, &cli.pool).await?;
         }
 
         Commands::Withdraw {
@@ -265,7 +889,24 @@ async fn main() -> Result<()> {
             provider,
             key,
         } => {
-            withdraw::execute(amount, provider, key, &cli.pool).await?;
+            withdraw::execute(amount, provider, key, store, &cli.pool).await?;
+        }
+
+        Commands::Keygen {
+            save,
+            label,
+            set_wallet,
+            unsafe_print,
+        } => {
+            keygen::execute(save, label, set_wallet, unsafe_print, &cli.pool).await?;
+        }
+
+        Commands::MigrateKeys {
+            key,
+            label,
+            address,
+        } => {
+            migrate_keys::execute(key, label, address, &cli.pool).await?;
         }
 
         Commands::Config { path } => {
@@ -283,17 +924,143 @@ async fn main() -> Result<()> {
             }
         }
 
+        Commands::Doctor { key } => {
+            doctor::execute(key, store).await?;
+        }
+
         Commands::Models => {
             print_models();
         }
 
-        Commands::Epochs { id, limit } => {
-            epochs::execute(id, limit, &cli.pool).await?;
+        Commands::Cache { action } => match action {
+            CacheAction::Clear => {
+                let cache = cache::Cache::open()?;
+                let removed = cache.clear()?;
+                println!("Removed {} cached snapshot(s) from {}", removed, cache.dir().display());
+            }
+        },
+
+        Commands::Epochs {
+            id,
+            limit,
+            offset,
+            before,
+            after,
+            format,
+            export_all,
+            out,
+            ndjson,
+            verify,
+            controller,
+        } => {
+            let export = out.filter(|_| export_all).map(|out| epochs::ExportOptions {
+                out,
+                ndjson,
+                verify,
+                controller,
+            });
+            epochs::execute(
+                id,
+                limit,
+                offset,
+                before,
+                after,
+                format,
+                export,
+                store,
+                &cli.pool,
+            )
+            .await?;
+        }
+
+        Commands::AuditState => {
+            audit_state::execute(store, &cli.pool).await?;
+        }
+
+        Commands::ImportEpoch {
+            file,
+            controller,
+            write,
+        } => {
+            import_epoch::execute(file, controller, write, store, &cli.pool).await?;
+        }
+
+        Commands::DiffEpoch {
+            epoch,
+            tolerance,
+            json,
+        } => {
+            diff_epoch::execute(epoch, tolerance, json, store, &cli.pool).await?;
+        }
+
+        Commands::PayoutManifest {
+            epoch,
+            out,
+            format,
+            token_address,
+            key,
+            controller,
+        } => {
+            payout_manifest::execute(
+                epoch,
+                out,
+                format,
+                token_address,
+                key,
+                controller,
+                store,
+                &cli.pool,
+            )
+            .await?;
+        }
+
+        Commands::VerifyProof { proof, epoch } => {
+            verify_proof::execute(proof, epoch, store, &cli.pool).await?;
+        }
+
+        Commands::Ledger {
+            snapshot_type,
+            since,
+            until,
+            json,
+        } => {
+            // Fully qualified: `ledger` is also the ledger file module
+            commands::ledger::execute(snapshot_type, since, until, json).await?;
         }
 
         Commands::Validate { file, schema } => {
             validate::execute(file, schema).await?;
         }
+
+        Commands::Publish {
+            file,
+            schema,
+            controller,
+        } => {
+            publish::execute(file, schema, controller, store, &cli.pool).await?;
+        }
+
+        Commands::Recover { file, schema } => {
+            recover::execute(file, schema).await?;
+        }
+
+        Commands::VerifySig { file } => {
+            verify_sig::execute(file).await?;
+        }
+    }
+
+    if cli.dry_run {
+        println!();
+        println!("{}", "Dry run: nothing was published".yellow());
+    }
+    if let Some((path, schema)) = sign_only {
+        println!();
+        println!(
+            "{} swarm publish --file {} --schema {}",
+            "Nothing was published; from a networked machine run:".yellow(),
+            path,
+            schema
+        );
     }
 
     Ok(())
@@ -324,14 +1091,6 @@ fn print_models() {
     println!("{}", "Available Models".cyan().bold());
     println!();
 
-    let models = vec![
-        ("queenbee-spine", "24 GB", "Lumbar MRI stenosis classification"),
-        ("queenbee-chest", "24 GB", "Chest X-ray/CT analysis"),
-        ("queenbee-foot", "16 GB", "Foot/ankle pathology detection"),
-        ("queenbee-brain", "32 GB", "Brain MRI segmentation (Beta)"),
-        ("queenbee-knee", "24 GB", "Knee MRI analysis (Beta)"),
-    ];
-
     println!(
         "  {:<18} {:<10} {}",
         "Model".bright_black(),
@@ -340,8 +1099,14 @@ fn print_models() {
     );
     println!("  {}", "-".repeat(60).bright_black());
 
-    for (name, vram, desc) in models {
-        println!("  {:<18} {:<10} {}", name.green(), vram.yellow(), desc);
+    for model in models::MODEL_CATALOG {
+        let vram = format!("{} GB", model.vram_gb);
+        let desc = if model.beta {
+            format!("{} (Beta)", model.description)
+        } else {
+            model.description.to_string()
+        };
+        println!("  {:<18} {:<10} {}", model.name.green(), vram.yellow(), desc);
     }
 
     println!();
@@ -0,0 +1,58 @@
+//! USDC amount rendering
+//!
+//! Amounts are formatted from integer microunits rather than `f64`, so one
+//! amount renders the same in `seal`, `epochs`, `status` and everywhere else.
+//! Digits beyond the requested precision are truncated, never rounded, so a
+//! summary never shows more than the ledger actually holds.
+
+use crate::models::USDC_DECIMALS;
+
+/// `micro` as a decimal USDC string with exactly `decimals` places
+/// (at most the token's 6), e.g. `format_usdc(26_250, 4)` is `"0.0262"`
+pub fn format_usdc(micro: u64, decimals: u32) -> String {
+    let decimals = decimals.min(USDC_DECIMALS);
+    let scale = 10u64.pow(USDC_DECIMALS);
+    let whole = micro / scale;
+    if decimals == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = (micro % scale) / 10u64.pow(USDC_DECIMALS - decimals);
+    format!(
+        "{}.{:0width$}",
+        whole,
+        fraction,
+        width = decimals as usize
+    )
+}
+
+/// Two-decimal dollar summary, e.g. `"$0.03"`
+pub fn format_usdc_display(micro: u64) -> String {
+    format!("${}", format_usdc(micro, 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_rendering() {
+        assert_eq!(format_usdc(30_000, 6), "0.030000");
+        assert_eq!(format_usdc(30_000, 2), "0.03");
+        assert_eq!(format_usdc_display(30_000), "$0.03");
+
+        // Truncated, not rounded (half-up would give 0.03 / 0.0263)
+        assert_eq!(format_usdc(26_250, 6), "0.026250");
+        assert_eq!(format_usdc(26_250, 4), "0.0262");
+        assert_eq!(format_usdc_display(26_250), "$0.02");
+
+        assert_eq!(format_usdc(15_600_000, 2), "15.60");
+        assert_eq!(format_usdc(999_999, 2), "0.99");
+        assert_eq!(format_usdc(1, 6), "0.000001");
+        assert_eq!(format_usdc(0, 2), "0.00");
+        assert_eq!(format_usdc(2_500_000, 0), "2");
+
+        // More precision than the token has is capped at 6 places
+        assert_eq!(format_usdc(75_000, 9), "0.075000");
+    }
+}
@@ -0,0 +1,185 @@
+//! Local record of the nonces this machine has signed with
+//!
+//! Every signed snapshot carries a random `nonce`. Before `submit`, `claim`,
+//! `init`, `prove` or `publish` writes a snapshot, the signer's nonce is
+//! looked up in `<data_dir>/nonces.jsonl`, and once the snapshot is written
+//! the nonce is recorded there, so one signed snapshot can't be published
+//! from here twice while a publish that fails leaves its nonce unused. Lines
+//! are appended like the ledger's: one write each, so concurrent `swarm`
+//! processes never interleave them.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::storage::Storage;
+
+/// One nonce a signer has used
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct NonceEntry {
+    /// Provider or client ENS the snapshot was signed for
+    signer: String,
+    nonce: String,
+    timestamp: i64,
+}
+
+/// The seen-nonce file
+pub struct NonceStore {
+    path: PathBuf,
+}
+
+impl NonceStore {
+    /// Nonce store under the CLI data directory
+    pub fn open() -> Result<Self> {
+        let proj_dirs = ProjectDirs::from("eth", "swarmpool", "swarm-cli")
+            .context("Failed to determine data directory")?;
+        Ok(Self::at(proj_dirs.data_dir().join("nonces.jsonl")))
+    }
+
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Whether `signer` already used `nonce`. Unparseable lines are skipped.
+    pub fn seen(&self, signer: &str, nonce: &str) -> Result<bool> {
+        if !self.path.exists() {
+            return Ok(false);
+        }
+        let content = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<NonceEntry>(line).ok())
+            .any(|entry| entry.signer == signer && entry.nonce == nonce))
+    }
+
+    /// Refuse `nonce` if `signer` already used it
+    pub fn check(&self, signer: &str, nonce: &str) -> Result<()> {
+        if self.seen(signer, nonce)? {
+            anyhow::bail!(
+                "Nonce {} was already used by {}; refusing to publish a replayed snapshot",
+                nonce,
+                signer
+            );
+        }
+        Ok(())
+    }
+
+    /// Record `nonce` for `signer`, refusing one it already used
+    pub fn record(&self, signer: &str, nonce: &str, timestamp: i64) -> Result<()> {
+        self.check(signer, nonce)?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let entry = NonceEntry {
+            signer: signer.to_string(),
+            nonce: nonce.to_string(),
+            timestamp,
+        };
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        file.write_all(&line)
+            .with_context(|| format!("Failed to append to {}", self.path.display()))
+    }
+
+    /// Record `nonce` once `signer` has published a snapshot with it
+    ///
+    /// Nothing is recorded when `store` doesn't publish (`--dry-run`, or
+    /// `--sign-only`, whose file `swarm publish` records when it goes out).
+    /// The snapshot is already written, so a failure here is a warning.
+    pub fn record_published(&self, store: &dyn Storage, signer: &str, nonce: &str, timestamp: i64) {
+        if !store.publishes() {
+            return;
+        }
+        if let Err(e) = self.record(signer, nonce, timestamp) {
+            warn_unrecorded(e);
+        }
+    }
+}
+
+fn warn_unrecorded(e: anyhow::Error) {
+    eprintln!(
+        "{} {:#}",
+        "⚠ Nonce store:".yellow(),
+        e.context("published, but nonce not recorded")
+    );
+}
+
+/// Refuse to publish with `nonce` if `signer` already did from here. Call it
+/// before writing the snapshot, and `record_published` after.
+pub fn check(signer: &str, nonce: &str) -> Result<()> {
+    NonceStore::open()?.check(signer, nonce)
+}
+
+/// `NonceStore::record_published` on the default store
+pub fn record_published(store: &dyn Storage, signer: &str, nonce: &str, timestamp: i64) {
+    match NonceStore::open() {
+        Ok(nonces) => nonces.record_published(store, signer, nonce, timestamp),
+        Err(e) => warn_unrecorded(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn test_nonce_used_once_per_signer() {
+        let dir = test_support::temp_dir();
+        let store = NonceStore::at(dir.path().join("nonces.jsonl"));
+
+        assert!(!store.seen("alice.swarmbee.eth", "abcdef1234567890").unwrap());
+        store
+            .record("alice.swarmbee.eth", "abcdef1234567890", 1704067200)
+            .unwrap();
+        assert!(store.seen("alice.swarmbee.eth", "abcdef1234567890").unwrap());
+
+        let err = store
+            .record("alice.swarmbee.eth", "abcdef1234567890", 1704067300)
+            .unwrap_err();
+        assert!(err.to_string().contains("replayed"), "{}", err);
+
+        // Nonces are per signer
+        store
+            .record("bob.swarmbee.eth", "abcdef1234567890", 1704067300)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_nonce_recorded_only_once_published() {
+        let dir = test_support::temp_dir();
+        let nonces = NonceStore::at(dir.path().join("nonces.jsonl"));
+        let (_root, fs) = test_support::fs_store();
+
+        // Nothing goes out under --dry-run, so the nonce stays usable
+        let dry_run = crate::storage::DryRunStorage::new(Box::new(fs));
+        nonces.record_published(
+            &dry_run,
+            "alice.swarmbee.eth",
+            "abcdef1234567890",
+            1704067200,
+        );
+        nonces
+            .check("alice.swarmbee.eth", "abcdef1234567890")
+            .unwrap();
+
+        let (_root, fs) = test_support::fs_store();
+        nonces.record_published(&fs, "alice.swarmbee.eth", "abcdef1234567890", 1704067200);
+        let err = nonces
+            .check("alice.swarmbee.eth", "abcdef1234567890")
+            .unwrap_err();
+        assert!(err.to_string().contains("replayed"), "{}", err);
+    }
+}
@@ -1,11 +1,17 @@
 //! Provider module - handles job processing for compute providers
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
 use crate::crypto;
 use crate::ipfs;
-use crate::models::{JobSnapshot, ProofMetrics, ProofSnapshot};
+use crate::storage::Storage;
+use crate::models::{
+    model_spec, HeartbeatSnapshot, JobSnapshot, ModelSpec, ProofMetrics, ProofSnapshot,
+    MODEL_CATALOG,
+};
+use crate::schema::{self, SchemaType};
 
 /// Compute provider instance
 pub struct Provider {
@@ -33,11 +39,11 @@ impl Provider {
     }
 
     /// Process a job and return proof
-    pub async fn process_job(&self, job: &JobSnapshot) -> Result<ProofSnapshot> {
+    pub async fn process_job(&self, store: &dyn Storage, job: &JobSnapshot) -> Result<ProofSnapshot> {
         let start = std::time::Instant::now();
 
         // 1. Fetch input from IPFS
-        let _input_data = ipfs::fetch_json::<serde_json::Value>(&job.input_cid).await?;
+        let _input_data = store.get_bytes(&job.input_cid).await?;
 
         // 2. Run inference (placeholder - integrate with actual model)
         // In production: load MONAI model, run inference
@@ -65,6 +71,7 @@ impl Provider {
             proof_id,
             job_id: job.job_id.clone(),
             job_cid: "".to_string(), // Would be the actual job CID
+            claim_cid: None,
             status: "completed".to_string(),
             output_cid,
             report_cid: Some(report_cid),
@@ -77,6 +84,7 @@ impl Provider {
             provider: self.ens.clone(),
             timestamp,
             proof_hash,
+            nonce: Some(crypto::random_hex(16)),
             sig: None,
         };
 
@@ -86,10 +94,10 @@ impl Provider {
         }
 
         // 5. Upload proof to IPFS
-        let proof_cid = ipfs::upload_json(&proof).await?;
+        let proof_cid = store.put(&proof).await?;
 
         // 6. Announce to pool
-        ipfs::pubsub_publish(
+        store.announce(
             &format!("/{}/proofs", self.pool),
             &serde_json::json!({
                 "job_id": job.job_id,
@@ -103,33 +111,235 @@ impl Provider {
         Ok(proof)
     }
 
-    /// Send heartbeat to pool
-    pub async fn send_heartbeat(&self) -> Result<()> {
-        let timestamp = chrono::Utc::now().timestamp();
-
-        let heartbeat = serde_json::json!({
-            "provider": self.ens,
-            "status": "online",
-            "models": self.models,
-            "timestamp": timestamp
-        });
-
-        ipfs::pubsub_publish(&format!("/{}/heartbeats", self.pool), &heartbeat).await?;
+    /// Publish this provider's heartbeat record
+    pub async fn send_heartbeat(&self, store: &dyn Storage) -> Result<()> {
+        let key = self
+            .private_key
+            .as_deref()
+            .context("Heartbeats must be signed. Set SWARM_PRIVATE_KEY")?;
 
+        let gpus = gpu_names(&detect_gpus());
+        let heartbeat = build_heartbeat(&self.ens, "online", &self.models, &gpus);
+        publish_heartbeat(store, &self.pool, heartbeat, key).await?;
         Ok(())
     }
 }
 
-/// GPU detection utility
+/// Unsigned heartbeat stamped with the current time
+pub fn build_heartbeat(
+    ens: &str,
+    status: &str,
+    models: &[String],
+    gpus: &[String],
+) -> HeartbeatSnapshot {
+    HeartbeatSnapshot {
+        snapshot_type: "heartbeat".to_string(),
+        version: "1.0.0".to_string(),
+        provider: ens.to_string(),
+        status: status.to_string(),
+        models: models.to_vec(),
+        gpus: gpus.to_vec(),
+        timestamp: chrono::Utc::now().timestamp(),
+        sig: None,
+    }
+}
+
+/// Sign `heartbeat`, check it against the HEARTBEAT schema, overwrite
+/// `/swarmpool/providers/{ens}/status.json` and announce it on
+/// `/{pool}/heartbeats`. Every heartbeat goes through here.
+///
+/// Returns the CID of the new record.
+pub async fn publish_heartbeat(
+    store: &dyn Storage,
+    pool: &str,
+    mut heartbeat: HeartbeatSnapshot,
+    private_key: &str,
+) -> Result<String> {
+    heartbeat.sig = Some(crypto::sign_snapshot(&heartbeat, private_key).await?);
+    schema::validate(&serde_json::to_value(&heartbeat)?, SchemaType::Heartbeat)?;
+
+    let cid = ipfs::write_heartbeat(store, &heartbeat.provider, &heartbeat).await?;
+    store
+        .announce(
+            &format!("/{}/heartbeats", pool),
+            &serde_json::json!({
+                "cid": cid,
+                "provider": heartbeat.provider,
+                "status": heartbeat.status,
+                "timestamp": heartbeat.timestamp
+            }),
+        )
+        .await?;
+    Ok(cid)
+}
+
+/// Names of detected GPUs, as heartbeats report them
+pub fn gpu_names(gpus: &[GpuInfo]) -> Vec<String> {
+    gpus.iter().map(|g| g.name.clone()).collect()
+}
+
+/// Query fields, in the column order `parse_nvidia_smi` expects
+const NVIDIA_SMI_QUERY: &str = "index,name,memory.total,driver_version";
+
+/// GPUs reported by `nvidia-smi`; empty if it's missing, fails or lists none
 pub fn detect_gpus() -> Vec<GpuInfo> {
-    // In production: use nvidia-smi or NVML to detect GPUs
-    // Placeholder implementation
-    vec![GpuInfo {
-        index: 0,
-        name: "NVIDIA GeForce RTX 5090".to_string(),
-        vram_mb: 32768,
-        cuda_version: "12.4".to_string(),
-    }]
+    let output = std::process::Command::new("nvidia-smi")
+        .arg(format!("--query-gpu={}", NVIDIA_SMI_QUERY))
+        .arg("--format=csv,noheader,nounits")
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout))
+        }
+        Ok(output) => {
+            tracing::debug!(
+                "nvidia-smi exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            vec![]
+        }
+        Err(e) => {
+            tracing::debug!("nvidia-smi unavailable: {}", e);
+            vec![]
+        }
+    }
+}
+
+/// Parse `nvidia-smi --query-gpu=index,name,memory.total,driver_version
+/// --format=csv,noheader,nounits` output, skipping lines that don't parse
+pub fn parse_nvidia_smi(csv: &str) -> Vec<GpuInfo> {
+    csv.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            // The name is the only field that could itself contain a comma
+            let [index, name @ .., vram_mb, driver_version] = fields.as_slice() else {
+                return None;
+            };
+            if name.is_empty() {
+                return None;
+            }
+            Some(GpuInfo {
+                index: index.parse().ok()?,
+                name: name.join(", "),
+                vram_mb: vram_mb.parse().ok()?,
+                driver_version: driver_version.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Non-beta catalog models that fit on the largest detected GPU
+///
+/// Models aren't split across cards, so the single largest GPU decides.
+pub fn eligible_models(gpus: &[GpuInfo]) -> Vec<String> {
+    model_eligibility(gpus)
+        .into_iter()
+        .filter(|(_, e)| *e == Eligibility::Eligible)
+        .map(|(m, _)| m.name.to_string())
+        .collect()
+}
+
+/// Why a catalog model is or isn't registered automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eligibility {
+    Eligible,
+    /// Needs more VRAM than the largest GPU has
+    TooLarge,
+    /// Fits, but beta models are only served when asked for with --models
+    Beta,
+}
+
+impl std::fmt::Display for Eligibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Eligibility::Eligible => write!(f, "eligible"),
+            Eligibility::TooLarge => write!(f, "too large"),
+            Eligibility::Beta => write!(f, "beta"),
+        }
+    }
+}
+
+/// Every catalog model with its eligibility on `gpus`, in catalog order
+pub fn model_eligibility(gpus: &[GpuInfo]) -> Vec<(&'static ModelSpec, Eligibility)> {
+    let max_vram_mb = VramStrategy::MaxSingle.available_mb(gpus);
+
+    MODEL_CATALOG
+        .iter()
+        .map(|m| {
+            let eligibility = if m.vram_mb() > max_vram_mb {
+                Eligibility::TooLarge
+            } else if m.beta {
+                Eligibility::Beta
+            } else {
+                Eligibility::Eligible
+            };
+            (m, eligibility)
+        })
+        .collect()
+}
+
+/// VRAM each catalog model needs, in MB as GPUs report it (see
+/// `ModelSpec::vram_mb`)
+pub fn model_requirements() -> BTreeMap<&'static str, u64> {
+    MODEL_CATALOG
+        .iter()
+        .map(|m| (m.name, m.vram_mb()))
+        .collect()
+}
+
+/// How detected GPUs add up to the VRAM a model may use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VramStrategy {
+    /// The largest single GPU (models aren't split across cards)
+    #[default]
+    MaxSingle,
+    /// All GPUs together, for runtimes that shard a model
+    Sum,
+}
+
+impl VramStrategy {
+    /// Usable VRAM in MB under this strategy
+    pub fn available_mb(self, gpus: &[GpuInfo]) -> u64 {
+        let sizes = gpus.iter().map(|g| g.vram_mb);
+        match self {
+            VramStrategy::MaxSingle => sizes.max().unwrap_or(0),
+            VramStrategy::Sum => sizes.sum(),
+        }
+    }
+}
+
+impl std::fmt::Display for VramStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VramStrategy::MaxSingle => write!(f, "max-single"),
+            VramStrategy::Sum => write!(f, "sum"),
+        }
+    }
+}
+
+impl std::str::FromStr for VramStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "max-single" => Ok(VramStrategy::MaxSingle),
+            "sum" => Ok(VramStrategy::Sum),
+            _ => Err(format!(
+                "Invalid VRAM strategy: {}. Use max-single or sum",
+                s
+            )),
+        }
+    }
+}
+
+/// The GB `model` needs when it's in the catalog and doesn't fit in
+/// `available_mb`; `None` when it fits or has no known requirement
+pub fn vram_shortfall(model: &str, available_mb: u64) -> Option<u32> {
+    model_spec(model)
+        .filter(|m| m.vram_mb() > available_mb)
+        .map(|m| m.vram_gb)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -137,7 +347,10 @@ pub struct GpuInfo {
     pub index: u32,
     pub name: String,
     pub vram_mb: u64,
-    pub cuda_version: String,
+    /// Serialized as `cuda_version`, the name it was published under before
+    /// it held the driver version
+    #[serde(rename = "cuda_version")]
+    pub driver_version: String,
 }
 
 impl GpuInfo {
@@ -146,13 +359,251 @@ impl GpuInfo {
     }
 }
 
+/// Where `VramScheduler::offer` put a job
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Placement {
+    /// Start now on the GPU with this index
+    Start(u32),
+    /// Wait until running jobs free enough VRAM
+    Queued,
+    /// Needs more VRAM than any single GPU has
+    TooLarge,
+}
+
+/// Admits inferences only while their VRAM fits on a GPU
+///
+/// Each running job holds its model's `vram_mb()` on one card until it
+/// finishes. Waiting jobs start strictly in arrival order, so a large job
+/// isn't starved by a stream of smaller ones.
+pub struct VramScheduler {
+    /// (GPU index, total MB, free MB)
+    gpus: Vec<(u32, u64, u64)>,
+    /// Running job -> (slot in `gpus`, MB held)
+    running: HashMap<String, (usize, u64)>,
+    queue: VecDeque<(String, u64)>,
+}
+
+impl VramScheduler {
+    pub fn new(gpus: &[GpuInfo]) -> Self {
+        Self {
+            gpus: gpus.iter().map(|g| (g.index, g.vram_mb, g.vram_mb)).collect(),
+            running: HashMap::new(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Start `job` if `vram_mb` fits now, otherwise queue it
+    pub fn offer(&mut self, job: &str, vram_mb: u64) -> Placement {
+        if !self.gpus.iter().any(|&(_, total, _)| total >= vram_mb) {
+            return Placement::TooLarge;
+        }
+        if self.queue.is_empty() {
+            if let Some(index) = self.place(job, vram_mb) {
+                return Placement::Start(index);
+            }
+        }
+        self.queue.push_back((job.to_string(), vram_mb));
+        Placement::Queued
+    }
+
+    /// Release `job`'s VRAM and start whatever queued jobs now fit,
+    /// returned with the GPU index each was placed on
+    pub fn finish(&mut self, job: &str) -> Vec<(String, u32)> {
+        if let Some((slot, held)) = self.running.remove(job) {
+            self.gpus[slot].2 += held;
+        }
+
+        let mut started = Vec::new();
+        while let Some((next, vram_mb)) = self.queue.front().cloned() {
+            let Some(index) = self.place(&next, vram_mb) else {
+                break;
+            };
+            self.queue.pop_front();
+            started.push((next, index));
+        }
+        started
+    }
+
+    /// VRAM held by running jobs, in MB
+    pub fn committed_mb(&self) -> u64 {
+        self.running.values().map(|&(_, held)| held).sum()
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// First GPU with room for `vram_mb`; commits it to `job`
+    fn place(&mut self, job: &str, vram_mb: u64) -> Option<u32> {
+        let slot = self.gpus.iter().position(|&(_, _, free)| free >= vram_mb)?;
+        self.gpus[slot].2 -= vram_mb;
+        self.running.insert(job.to_string(), (slot, vram_mb));
+        Some(self.gpus[slot].0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::VRAM_REPORTING_SLACK_MB;
+    use crate::test_support::{self, TEST_KEY};
+
+    #[test]
+    fn test_parse_nvidia_smi() {
+        let csv = "0, NVIDIA GeForce RTX 5090, 32607, 570.86.10\n\
+                   1, NVIDIA RTX A6000, 49140, 570.86.10\n\
+                   \n\
+                   No devices were found\n";
+        let gpus = parse_nvidia_smi(csv);
+
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].index, 0);
+        assert_eq!(gpus[0].name, "NVIDIA GeForce RTX 5090");
+        assert_eq!(gpus[0].vram_mb, 32607);
+        assert_eq!(gpus[0].driver_version, "570.86.10");
+        assert_eq!(gpus[1].index, 1);
+        assert_eq!(gpus[1].name, "NVIDIA RTX A6000");
+        assert_eq!(gpus[1].vram_mb, 49140);
+
+        assert!(parse_nvidia_smi("").is_empty());
+
+        // Still published under its old field name
+        let json = serde_json::to_value(&gpus[0]).unwrap();
+        assert_eq!(json["cuda_version"], "570.86.10");
+        assert!(json.get("driver_version").is_none());
+    }
+
+    /// A GPU reporting `vram_mb` the way `nvidia-smi` does: a little under
+    /// its nominal size
+    fn gpu(name: &str, vram_mb: u64) -> GpuInfo {
+        GpuInfo {
+            index: 0,
+            name: name.to_string(),
+            vram_mb,
+            driver_version: "570.86.10".to_string(),
+        }
+    }
 
     #[test]
-    fn test_detect_gpus() {
-        let gpus = detect_gpus();
-        assert!(!gpus.is_empty());
+    fn test_eligible_models_for_32gb_gpu() {
+        let models = eligible_models(&[gpu("NVIDIA GeForce RTX 5090", 32607)]);
+        assert_eq!(
+            models,
+            vec!["queenbee-spine", "queenbee-chest", "queenbee-foot", "queenbee-brain"]
+        );
+
+        let small = eligible_models(&[gpu("NVIDIA GeForce RTX 4080", 16376)]);
+        assert_eq!(small, vec!["queenbee-foot"]);
+        assert!(eligible_models(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_model_eligibility_reasons() {
+        let reasons = |gpus: &[GpuInfo]| -> Vec<(&str, Eligibility)> {
+            model_eligibility(gpus)
+                .into_iter()
+                .map(|(m, e)| (m.name, e))
+                .collect()
+        };
+
+        assert_eq!(
+            reasons(&[gpu("NVIDIA GeForce RTX 4080", 16376), gpu("NVIDIA GeForce RTX 4090", 24564)]),
+            vec![
+                ("queenbee-spine", Eligibility::Eligible),
+                ("queenbee-chest", Eligibility::Eligible),
+                ("queenbee-foot", Eligibility::Eligible),
+                ("queenbee-brain", Eligibility::TooLarge),
+                ("queenbee-knee", Eligibility::Beta),
+            ]
+        );
+        assert_eq!(
+            reasons(&[gpu("NVIDIA GeForce RTX 5090", 32607)])[3],
+            ("queenbee-brain", Eligibility::Eligible)
+        );
+        assert!(reasons(&[])
+            .iter()
+            .all(|(_, e)| *e == Eligibility::TooLarge));
+    }
+
+    #[test]
+    fn test_vram_strategy_on_multi_gpu_box() {
+        let requirements = model_requirements();
+        assert_eq!(
+            requirements["queenbee-brain"],
+            32 * 1024 - VRAM_REPORTING_SLACK_MB
+        );
+        assert_eq!(
+            requirements["queenbee-spine"],
+            24 * 1024 - VRAM_REPORTING_SLACK_MB
+        );
+
+        let gpus = [gpu("NVIDIA GeForce RTX 4090", 24564), gpu("NVIDIA GeForce RTX 4090", 24564)];
+        let single = VramStrategy::MaxSingle.available_mb(&gpus);
+        let sum = VramStrategy::Sum.available_mb(&gpus);
+        assert_eq!(single, 24564);
+        assert_eq!(sum, 2 * 24564);
+
+        assert_eq!(vram_shortfall("queenbee-brain", single), Some(32));
+        assert_eq!(vram_shortfall("queenbee-brain", sum), None);
+        assert_eq!(vram_shortfall("queenbee-spine", single), None);
+        // Models outside the catalog aren't filtered
+        assert_eq!(vram_shortfall("custom-model", 0), None);
+
+        assert_eq!("max-single".parse(), Ok(VramStrategy::MaxSingle));
+        assert_eq!("SUM".parse(), Ok(VramStrategy::Sum));
+        assert!("average".parse::<VramStrategy>().is_err());
+        assert_eq!(VramStrategy::default(), VramStrategy::MaxSingle);
+    }
+
+    #[test]
+    fn test_second_big_job_queued_not_oom() {
+        let brain = model_spec("queenbee-brain").unwrap().vram_mb();
+        let spine = model_spec("queenbee-spine").unwrap().vram_mb();
+        let mut scheduler = VramScheduler::new(&[gpu("NVIDIA GeForce RTX 5090", 32607)]);
+
+        assert_eq!(scheduler.offer("QmBrain1", brain), Placement::Start(0));
+        assert_eq!(scheduler.offer("QmBrain2", brain), Placement::Queued);
+        // Smaller jobs wait their turn rather than jumping the queue
+        assert_eq!(scheduler.offer("QmSpine", spine), Placement::Queued);
+        assert_eq!(scheduler.committed_mb(), brain);
+        assert_eq!(scheduler.offer("QmHuge", 48 * 1024), Placement::TooLarge);
+
+        assert_eq!(scheduler.finish("QmBrain1"), [("QmBrain2".to_string(), 0)]);
+        assert_eq!(scheduler.committed_mb(), brain);
+        assert_eq!(scheduler.queued(), 1);
+
+        assert_eq!(scheduler.finish("QmBrain2"), [("QmSpine".to_string(), 0)]);
+        assert_eq!(scheduler.committed_mb(), spine);
+        assert!(scheduler.finish("QmSpine").is_empty());
+        assert_eq!(scheduler.committed_mb(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_published_and_announced() {
+        let (root, store) = test_support::fs_store();
+        let store: &dyn Storage = &store;
+
+        let models = vec!["queenbee-spine".to_string()];
+        let gpus = gpu_names(&[gpu("NVIDIA GeForce RTX 5090", 32607)]);
+        let heartbeat = build_heartbeat("alpha.swarmbee.eth", "online", &models, &gpus);
+        publish_heartbeat(store, "swarmpool.eth", heartbeat, TEST_KEY)
+            .await
+            .unwrap();
+
+        let record = ipfs::fetch_heartbeat(store, "alpha.swarmbee.eth").await.unwrap();
+        assert_eq!(record.gpus, ["NVIDIA GeForce RTX 5090"]);
+        let announced =
+            std::fs::read_to_string(root.path().join("pubsub/swarmpool.eth_heartbeats.jsonl"))
+                .unwrap();
+        assert!(announced.contains("alpha.swarmbee.eth"));
+
+        // Never published if it doesn't fit the schema
+        let bad = build_heartbeat("alpha.swarmbee.eth", "asleep", &models, &gpus);
+        let err = publish_heartbeat(store, "swarmpool.eth", bad, TEST_KEY)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("status"), "{}", err);
+        let record = ipfs::fetch_heartbeat(store, "alpha.swarmbee.eth").await.unwrap();
+        assert_eq!(record.status, "online");
     }
 }
@@ -0,0 +1,300 @@
+//! Report rendering for completed inference jobs
+//!
+//! The job's `params.output_format` selects the renderer:
+//! - `pdf`: single-page human-readable report (default)
+//! - `json`: machine-readable report document
+//! - `dicom-sr`: DICOM Structured Report (not implemented yet)
+//!
+//! The rendered bytes are uploaded by `prove` and referenced as `report_cid`;
+//! if rendering or the upload fails, the proof is published without one.
+//! `prove` skips rendering for `json` jobs: the output object is the result.
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+use crate::models;
+
+/// Supported report output formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Pdf,
+    Json,
+    DicomSr,
+}
+
+impl ReportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportFormat::Pdf => "pdf",
+            ReportFormat::Json => "json",
+            ReportFormat::DicomSr => "dicom-sr",
+        }
+    }
+
+    /// File name used when uploading the rendered report
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ReportFormat::Pdf => "report.pdf",
+            ReportFormat::Json => "report.json",
+            ReportFormat::DicomSr => "report.dcm",
+        }
+    }
+
+    /// Fail if `model` cannot produce this report format
+    pub fn ensure_supported(&self, model: &str) -> Result<()> {
+        if !models::model_supports_format(model, self.as_str()) {
+            bail!(
+                "Model {} does not support output format '{}'",
+                model,
+                self.as_str()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pdf" => Ok(ReportFormat::Pdf),
+            "json" => Ok(ReportFormat::Json),
+            "dicom-sr" => Ok(ReportFormat::DicomSr),
+            _ => Err(format!(
+                "Unsupported output format: {}. Use pdf, json, or dicom-sr",
+                s
+            )),
+        }
+    }
+}
+
+/// Everything a renderer needs to describe one inference run
+#[derive(Debug, Clone)]
+pub struct ReportInput<'a> {
+    pub job_id: &'a str,
+    pub model: &'a str,
+    pub model_version: &'a str,
+    pub provider: &'a str,
+    pub result: Option<&'a Value>,
+    pub confidence: f64,
+    pub inference_seconds: f64,
+    pub timestamp: i64,
+}
+
+/// Render a report in the requested format
+pub fn render(format: ReportFormat, input: &ReportInput) -> Result<Vec<u8>> {
+    match format {
+        ReportFormat::Json => render_json(input),
+        ReportFormat::Pdf => Ok(render_pdf(input)),
+        ReportFormat::DicomSr => {
+            bail!("dicom-sr report rendering is not implemented yet; use pdf or json")
+        }
+    }
+}
+
+fn render_json(input: &ReportInput) -> Result<Vec<u8>> {
+    let report = serde_json::json!({
+        "type": "report",
+        "format": ReportFormat::Json.as_str(),
+        "job_id": input.job_id,
+        "model": input.model,
+        "model_version": input.model_version,
+        "provider": input.provider,
+        "confidence": input.confidence,
+        "inference_seconds": input.inference_seconds,
+        "result": input.result,
+        "timestamp": input.timestamp,
+    });
+
+    Ok(serde_json::to_vec_pretty(&report)?)
+}
+
+/// Text lines shown in the PDF report
+fn report_lines(input: &ReportInput) -> Vec<String> {
+    let generated = chrono::DateTime::from_timestamp(input.timestamp, 0)
+        .map(|d| d.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| input.timestamp.to_string());
+
+    let mut lines = vec![
+        format!("Job: {}", input.job_id),
+        format!("Model: {} ({})", input.model, input.model_version),
+        format!("Provider: {}", input.provider),
+        format!("Confidence: {:.1}%", input.confidence * 100.0),
+        format!("Inference time: {:.2}s", input.inference_seconds),
+        format!("Generated: {}", generated),
+        String::new(),
+        "Findings:".to_string(),
+    ];
+
+    match input.result {
+        Some(result) => {
+            if let Some(classification) = result["classification"].as_str() {
+                lines.push(format!("  Classification: {}", classification));
+            }
+            match result["findings"].as_array() {
+                Some(findings) if !findings.is_empty() => {
+                    for finding in findings {
+                        lines.push(format!("  - {}", describe_finding(finding)));
+                    }
+                }
+                _ => lines.push("  No discrete findings reported".to_string()),
+            }
+        }
+        None => lines.push("  No result".to_string()),
+    }
+
+    lines
+}
+
+/// Flatten a finding object into "key: value, ..." text
+fn describe_finding(finding: &Value) -> String {
+    match finding.as_object() {
+        Some(obj) => obj
+            .iter()
+            .map(|(k, v)| match v {
+                Value::String(s) => format!("{}: {}", k, s),
+                other => format!("{}: {}", k, other),
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+        None => finding.to_string(),
+    }
+}
+
+/// Escape text for a PDF string literal (ASCII only)
+fn pdf_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+/// Render a minimal single-page PDF 1.4 document with Helvetica text
+fn render_pdf(input: &ReportInput) -> Vec<u8> {
+    const MAX_LINES: usize = 48;
+
+    let mut content = String::from("BT\n/F1 16 Tf\n50 790 Td\n(SwarmPool Inference Report) Tj\n/F1 10 Tf\n0 -28 Td\n");
+    for line in report_lines(input).iter().take(MAX_LINES) {
+        content.push_str(&format!("({}) Tj\n0 -14 Td\n", pdf_escape(line)));
+    }
+    content.push_str("ET\n");
+
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 842] \
+         /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>"
+            .to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        format!("<< /Length {} >>\nstream\n{}endstream", content.len(), content),
+    ];
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, body) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, body));
+    }
+
+    let xref_offset = pdf.len();
+    pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+
+    pdf.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_result() -> Value {
+        serde_json::json!({
+            "classification": "L4-L5 moderate stenosis",
+            "findings": [
+                {"level": "L4-L5", "grade": "moderate", "confidence": 0.89}
+            ]
+        })
+    }
+
+    fn sample_input(result: &Value) -> ReportInput<'_> {
+        ReportInput {
+            job_id: "job-20250101000000-abcd1234",
+            model: "queenbee-spine",
+            model_version: "queenbee-spine-v1.0",
+            provider: "miner.alice.eth",
+            result: Some(result),
+            confidence: 0.847,
+            inference_seconds: 2.5,
+            timestamp: 1704067200,
+        }
+    }
+
+    #[test]
+    fn test_render_json_report() {
+        let result = sample_result();
+        let bytes = render(ReportFormat::Json, &sample_input(&result)).unwrap();
+        let report: Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(report["format"], "json");
+        assert_eq!(report["model"], "queenbee-spine");
+        assert_eq!(report["result"]["classification"], "L4-L5 moderate stenosis");
+    }
+
+    #[test]
+    fn test_render_pdf_report() {
+        let result = sample_result();
+        let bytes = render(ReportFormat::Pdf, &sample_input(&result)).unwrap();
+        let pdf = String::from_utf8(bytes).unwrap();
+
+        assert!(pdf.starts_with("%PDF-1.4"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("(Model: queenbee-spine \\(queenbee-spine-v1.0\\)) Tj"));
+        assert!(pdf.contains("grade: moderate, level: L4-L5"));
+
+        // startxref must point at the xref table
+        let startxref: usize = pdf
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .and_then(|n| n.parse().ok())
+            .unwrap();
+        assert!(pdf[startxref..].starts_with("xref\n"));
+    }
+
+    #[test]
+    fn test_dicom_sr_not_implemented() {
+        let result = sample_result();
+        let err = render(ReportFormat::DicomSr, &sample_input(&result)).unwrap_err();
+        assert!(err.to_string().contains("not implemented"));
+    }
+
+    #[test]
+    fn test_format_support_by_model() {
+        assert!(ReportFormat::DicomSr.ensure_supported("queenbee-spine").is_ok());
+        assert!(ReportFormat::DicomSr.ensure_supported("queenbee-brain").is_err());
+        assert!(ReportFormat::Json.ensure_supported("queenbee-brain").is_ok());
+        assert!("docx".parse::<ReportFormat>().is_err());
+    }
+}
@@ -0,0 +1,260 @@
+//! Provider reputation from published history
+//!
+//! Scores are derived only from snapshots anyone can read (proofs, claims,
+//! disputes and sealed epoch settlements), so any party can recompute them
+//! and check a pool's published figures.
+//!
+//! | Component       | Weight | Source                                      |
+//! |-----------------|--------|---------------------------------------------|
+//! | Acceptance rate | 40%    | completed proofs not slashed at seal        |
+//! | Dispute rate    | 30%    | disputes not rejected, per proof (inverted) |
+//! | Confidence      | 20%    | mean `metrics.confidence`                   |
+//! | Latency         | 10%    | claim -> proof time against a target        |
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::ipfs;
+use crate::models::{ClaimSnapshot, DisputeSnapshot, EpochSnapshot, ProofSnapshot, Settlements};
+use crate::storage::Storage;
+
+const WEIGHT_ACCEPTANCE: f64 = 0.40;
+const WEIGHT_DISPUTES: f64 = 0.30;
+const WEIGHT_CONFIDENCE: f64 = 0.20;
+const WEIGHT_LATENCY: f64 = 0.10;
+
+/// Claim-to-proof time that earns the full latency component
+pub const LATENCY_TARGET_SECS: f64 = 300.0;
+
+/// Published history a score is computed from
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    pub proofs: Vec<ProofSnapshot>,
+    pub claims: Vec<ClaimSnapshot>,
+    pub disputes: Vec<DisputeSnapshot>,
+    /// Settlements of sealed epochs
+    pub settlements: Vec<Settlements>,
+}
+
+impl History {
+    /// Read every proof, claim, dispute and sealed epoch from the pool
+    pub async fn load(store: &dyn Storage) -> Result<Self> {
+        let epochs: Vec<EpochSnapshot> = ipfs::read_all(store, ipfs::paths::EPOCHS).await?;
+
+        Ok(Self {
+            proofs: ipfs::read_all(store, ipfs::paths::PROOFS).await?,
+            claims: ipfs::read_all(store, ipfs::paths::CLAIMS).await?,
+            disputes: ipfs::read_all(store, ipfs::paths::DISPUTES).await?,
+            settlements: epochs
+                .into_iter()
+                .filter(|e| e.status == "sealed")
+                .filter_map(|e| e.settlements)
+                .collect(),
+        })
+    }
+}
+
+/// Reputation metrics and score for one provider
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Reputation {
+    pub provider: String,
+    pub proofs: u64,
+    pub accepted: u64,
+    pub disputes: u64,
+    pub acceptance_rate: f64,
+    pub dispute_rate: f64,
+    pub avg_confidence: f64,
+    /// Mean seconds from claim to proof (None when no proof had a claim)
+    pub avg_latency_secs: Option<f64>,
+    /// 0-100
+    pub score: f64,
+}
+
+/// Score `provider` from `history`, or None if it has never submitted a proof
+pub fn score(history: &History, provider: &str) -> Option<Reputation> {
+    let proofs: Vec<&ProofSnapshot> = history
+        .proofs
+        .iter()
+        .filter(|p| p.provider == provider)
+        .collect();
+    if proofs.is_empty() {
+        return None;
+    }
+    let total = proofs.len() as u64;
+
+    let completed = proofs.iter().filter(|p| p.status == "completed").count() as u64;
+    let slashed = history
+        .settlements
+        .iter()
+        .flat_map(|s| &s.slashes)
+        .filter(|s| s.provider == provider)
+        .count() as u64;
+    let accepted = completed.saturating_sub(slashed);
+
+    let disputes = history
+        .disputes
+        .iter()
+        .filter(|d| d.provider == provider && d.status != "rejected")
+        .count() as u64;
+
+    let avg_confidence =
+        proofs.iter().map(|p| p.metrics.confidence).sum::<f64>() / total as f64;
+
+    // Latency: proof time minus this provider's earliest claim on the job
+    let mut first_claim: HashMap<&str, i64> = HashMap::new();
+    for claim in history.claims.iter().filter(|c| c.provider == provider) {
+        let entry = first_claim.entry(claim.job_cid.as_str()).or_insert(claim.timestamp);
+        *entry = (*entry).min(claim.timestamp);
+    }
+    let latencies: Vec<f64> = proofs
+        .iter()
+        .filter_map(|p| first_claim.get(p.job_cid.as_str()).map(|t| (p.timestamp - t).max(0) as f64))
+        .collect();
+    let avg_latency_secs = if latencies.is_empty() {
+        None
+    } else {
+        Some(latencies.iter().sum::<f64>() / latencies.len() as f64)
+    };
+
+    let acceptance_rate = accepted as f64 / total as f64;
+    let dispute_rate = (disputes as f64 / total as f64).min(1.0);
+
+    // Unknown latency is neutral rather than rewarded or punished
+    let latency_component = match avg_latency_secs {
+        Some(secs) if secs <= LATENCY_TARGET_SECS => 1.0,
+        Some(secs) => LATENCY_TARGET_SECS / secs,
+        None => 0.5,
+    };
+
+    let score = 100.0
+        * (WEIGHT_ACCEPTANCE * acceptance_rate
+            + WEIGHT_DISPUTES * (1.0 - dispute_rate)
+            + WEIGHT_CONFIDENCE * avg_confidence.clamp(0.0, 1.0)
+            + WEIGHT_LATENCY * latency_component);
+
+    Some(Reputation {
+        provider: provider.to_string(),
+        proofs: total,
+        accepted,
+        disputes,
+        acceptance_rate,
+        dispute_rate,
+        avg_confidence,
+        avg_latency_secs,
+        score: (score * 10.0).round() / 10.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ExecutionMode, ProofMetrics, Slash};
+
+    fn proof(provider: &str, job_cid: &str, confidence: f64, timestamp: i64) -> ProofSnapshot {
+        ProofSnapshot {
+            snapshot_type: "proof".to_string(),
+            version: "1.0.0".to_string(),
+            proof_id: format!("proof-{}", job_cid),
+            job_id: format!("job-{}", job_cid),
+            job_cid: job_cid.to_string(),
+            claim_cid: None,
+            status: "completed".to_string(),
+            output_cid: "QmOutput".to_string(),
+            report_cid: None,
+            metrics: ProofMetrics {
+                inference_seconds: 10.0,
+                compute_seconds: 10.0,
+                confidence,
+                model_version: "queenbee-spine-v1.0".to_string(),
+            },
+            provider: provider.to_string(),
+            timestamp,
+            proof_hash: "0x00".to_string(),
+            nonce: None,
+            sig: None,
+        }
+    }
+
+    fn claim(provider: &str, job_cid: &str, timestamp: i64) -> ClaimSnapshot {
+        ClaimSnapshot {
+            snapshot_type: "claim".to_string(),
+            version: "1.0.0".to_string(),
+            claim_id: format!("claim-{}", job_cid),
+            job_id: format!("job-{}", job_cid),
+            job_cid: job_cid.to_string(),
+            provider: provider.to_string(),
+            mode: ExecutionMode::Solo,
+            timestamp,
+            ttl_seconds: None,
+            nonce: "abcdef1234567890".to_string(),
+            sig: None,
+        }
+    }
+
+    #[test]
+    fn test_score_from_synthetic_history() {
+        let alice = "alice.swarmbee.eth";
+        let mut history = History {
+            proofs: vec![
+                proof(alice, "QmJob1", 0.9, 1_100),
+                proof(alice, "QmJob2", 0.8, 2_600),
+                proof(alice, "QmJob3", 0.7, 3_000),
+                proof(alice, "QmJob4", 0.8, 4_000),
+                proof("bob.swarmbee.eth", "QmJob5", 0.1, 5_000),
+            ],
+            claims: vec![claim(alice, "QmJob1", 1_000), claim(alice, "QmJob2", 2_000)],
+            ..Default::default()
+        };
+
+        // One slashed proof, one open dispute, one rejected dispute
+        history.settlements.push(Settlements {
+            total_volume: 0.4,
+            miner_pool: 0.3,
+            hive_ops: 0.1,
+            providers: HashMap::new(),
+            dust_to_hive: 0.0,
+            slashes: vec![Slash {
+                provider: alice.to_string(),
+                proof_cid: "QmProof3".to_string(),
+                reason: "dispute upheld".to_string(),
+                slash_bps: 1000,
+                penalty: 0.01,
+                applied: 0.01,
+            }],
+            flagged: vec![],
+            hive_recipients: Default::default(),
+            compute_overhead_cap: None,
+            micro: None,
+        });
+        for (id, status) in [("d-1", "open"), ("d-2", "rejected")] {
+            history.disputes.push(DisputeSnapshot {
+                snapshot_type: "dispute".to_string(),
+                version: "1.0.0".to_string(),
+                dispute_id: id.to_string(),
+                epoch_id: "epoch-001".to_string(),
+                proof_cid: "QmProof3".to_string(),
+                provider: alice.to_string(),
+                status: status.to_string(),
+                reason: "wrong level".to_string(),
+                timestamp: 0,
+                sig: None,
+            });
+        }
+
+        let rep = score(&history, alice).unwrap();
+        assert_eq!(rep.proofs, 4);
+        assert_eq!(rep.accepted, 3);
+        assert_eq!(rep.disputes, 1);
+        assert_eq!(rep.acceptance_rate, 0.75);
+        assert_eq!(rep.dispute_rate, 0.25);
+        assert!((rep.avg_confidence - 0.8).abs() < 1e-9);
+        // (100s + 600s) / 2 = 350s, over the 300s target
+        assert_eq!(rep.avg_latency_secs, Some(350.0));
+
+        // 40*0.75 + 30*0.75 + 20*0.8 + 10*(300/350) = 77.07...
+        assert_eq!(rep.score, 77.1);
+
+        assert!(score(&history, "carol.swarmbee.eth").is_none());
+    }
+}
@@ -0,0 +1,163 @@
+//! Sandboxed launch of the inference runner
+//!
+//! Runners are third-party model code, so a provider can confine them: no
+//! network, a read-only view of the system, and only the job's staging
+//! directory writable. Linux only; bubblewrap is preferred, with plain
+//! `unshare` (network and mount namespaces) as a weaker fallback.
+
+use anyhow::{bail, Result};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Tool used to confine the runner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backend {
+    /// `bwrap`: fresh namespaces, read-only root, writable staging dir only
+    Bubblewrap(PathBuf),
+    /// `unshare`: no network and a private mount namespace, filesystem as-is
+    Unshare(PathBuf),
+}
+
+/// Find a sandbox backend on this host, or explain why there is none
+pub fn detect() -> Result<Backend> {
+    if !cfg!(target_os = "linux") {
+        bail!(
+            "--sandbox is only supported on Linux (this is {})",
+            std::env::consts::OS
+        );
+    }
+    if let Some(path) = find_in_path("bwrap") {
+        return Ok(Backend::Bubblewrap(path));
+    }
+    if let Some(path) = find_in_path("unshare") {
+        return Ok(Backend::Unshare(path));
+    }
+    bail!("--sandbox needs bubblewrap (bwrap) or util-linux unshare on PATH; neither was found")
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Full argv that runs `program args` confined to `workdir`.
+///
+/// `runner` is the script being executed; it is mounted read-only so it stays
+/// reachable when it lives outside the system directories.
+pub fn wrap(
+    backend: &Backend,
+    workdir: &Path,
+    runner: &Path,
+    program: &str,
+    args: &[OsString],
+) -> Vec<OsString> {
+    let mut argv: Vec<OsString> = Vec::new();
+    match backend {
+        Backend::Bubblewrap(bwrap) => {
+            argv.push(bwrap.into());
+            for flag in ["--unshare-all", "--die-with-parent", "--new-session"] {
+                argv.push(flag.into());
+            }
+            for dir in ["/usr", "/etc"] {
+                argv.extend(["--ro-bind".into(), dir.into(), dir.into()]);
+            }
+            // Merged-/usr distros symlink these; others have real directories
+            for dir in ["/bin", "/lib", "/lib64"] {
+                argv.extend(["--ro-bind-try".into(), dir.into(), dir.into()]);
+            }
+            argv.extend(["--proc".into(), "/proc".into()]);
+            argv.extend(["--dev".into(), "/dev".into()]);
+            argv.extend(["--tmpfs".into(), "/tmp".into()]);
+            if let Some(dir) = runner.parent() {
+                argv.extend(["--ro-bind".into(), dir.into(), dir.into()]);
+            }
+            argv.extend(["--bind".into(), workdir.into(), workdir.into()]);
+            argv.extend(["--chdir".into(), workdir.into()]);
+            argv.push("--".into());
+        }
+        Backend::Unshare(unshare) => {
+            argv.push(unshare.into());
+            for flag in ["--user", "--map-root-user", "--net", "--mount", "--fork"] {
+                argv.push(flag.into());
+            }
+            argv.push("--".into());
+        }
+    }
+    argv.push(program.into());
+    argv.extend(args.iter().cloned());
+    argv
+}
+
+/// Command for `argv`, started in `workdir`
+pub fn command(argv: &[OsString], workdir: &Path) -> Command {
+    let mut cmd = Command::new(&argv[0]);
+    cmd.args(&argv[1..]).current_dir(workdir);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(argv: &[OsString]) -> Vec<String> {
+        argv.iter()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_wrapper_command_construction() {
+        let workdir = Path::new("/tmp/swarm-input-job-1");
+        let runner = Path::new("/opt/swarm/inference/runner.py");
+        let args: Vec<OsString> = vec![
+            runner.into(),
+            "--input".into(),
+            workdir.join("input").into(),
+        ];
+
+        let bwrap = Backend::Bubblewrap("/usr/bin/bwrap".into());
+        let argv = strings(&wrap(&bwrap, workdir, runner, "python3", &args));
+        assert_eq!(argv[0], "/usr/bin/bwrap");
+        assert!(argv.contains(&"--unshare-all".to_string()));
+        let bind = argv.iter().position(|a| a == "--bind").unwrap();
+        assert_eq!(
+            argv[bind + 1..bind + 3],
+            [workdir.display().to_string(), workdir.display().to_string()]
+        );
+        let ro = argv.iter().rposition(|a| a == "--ro-bind").unwrap();
+        assert_eq!(argv[ro + 1], "/opt/swarm/inference");
+        let sep = argv.iter().position(|a| a == "--").unwrap();
+        assert_eq!(
+            argv[sep - 2..sep],
+            ["--chdir".to_string(), workdir.display().to_string()]
+        );
+        assert_eq!(
+            argv[sep + 1..],
+            [
+                "python3",
+                "/opt/swarm/inference/runner.py",
+                "--input",
+                "/tmp/swarm-input-job-1/input"
+            ]
+        );
+
+        let unshare = Backend::Unshare("/usr/bin/unshare".into());
+        let argv = strings(&wrap(&unshare, workdir, runner, "python3", &args));
+        assert_eq!(
+            argv[..7],
+            [
+                "/usr/bin/unshare",
+                "--user",
+                "--map-root-user",
+                "--net",
+                "--mount",
+                "--fork",
+                "--"
+            ]
+        );
+        assert_eq!(argv[7], "python3");
+    }
+}
@@ -2,9 +2,23 @@
 //!
 //! Schema-first publishing: Invalid snapshots never leave the box.
 //! All snapshots are validated against their schema before IPFS publish.
+//! While the typed models and schemas still drift apart, deviations found
+//! before publishing are warnings; `--strict-schema` makes them fatal.
 
 use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
 use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::crypto;
+
+static STRICT_SCHEMA: AtomicBool = AtomicBool::new(false);
+
+/// Global `--strict-schema` switch for `check_before_publish`
+pub fn set_strict(strict: bool) {
+    STRICT_SCHEMA.store(strict, Ordering::Relaxed);
+}
 
 /// Snapshot schema definitions
 pub mod schemas {
@@ -18,8 +32,8 @@ pub mod schemas {
             "type": { "const": "genesis" },
             "version": { "type": "string", "pattern": "^\\d+\\.\\d+\\.\\d+$" },
             "provider": { "type": "string", "pattern": "^[a-z0-9.-]+\\.eth$" },
-            "wallet": { "type": "string", "pattern": "^0x[a-fA-F0-9]{40}$" },
-            "gpus": { "type": "array", "items": { "type": "string" }, "minItems": 1 },
+            "wallet": { "type": "string", "pattern": "^0x[a-fA-F0-9]{40}$", "format": "eth-address" },
+            "gpus": { "type": "array", "items": { "type": "string" }, "minItems": 1, "maxItems": 16 },
             "models": { "type": "array", "items": { "type": "string" } },
             "timestamp": { "type": "integer", "minimum": 0 },
             "nonce": { "type": "string", "minLength": 16 },
@@ -38,16 +52,28 @@ pub mod schemas {
             "type": { "const": "job" },
             "version": { "type": "string", "pattern": "^\\d+\\.\\d+\\.\\d+$" },
             "job_id": { "type": "string", "minLength": 10 },
+            "job_type": { "type": "string", "minLength": 1 },
             "model": { "type": "string", "minLength": 1 },
             "input_cid": { "type": "string", "pattern": "^(bafy|Qm)[a-zA-Z0-9]+" },
-            "params": { "type": "object" },
+            "input_files": { "type": "integer", "minimum": 1 },
+            "input_sha256": { "type": "string", "pattern": "^0x[0-9a-f]{64}$" },
+            "input_type": { "enum": ["file", "dicom", "series", "dicom_series"] },
+            "params": {
+                "type": "object",
+                "properties": {
+                    "confidence_threshold": { "type": "string", "pattern": "^\\d+(\\.\\d+)?$", "minimum": 0, "maximum": 1 },
+                    "output_format": { "enum": ["pdf", "json", "dicom-sr"] },
+                    "deadline": { "type": "integer", "minimum": 0 }
+                }
+            },
             "payment": {
                 "type": "object",
                 "required": ["amount", "token"],
                 "properties": {
                     "amount": { "type": "string", "pattern": "^\\d+\\.?\\d*$" },
-                    "token": { "type": "string" }
-                }
+                    "token": { "enum": ["USDC"] }
+                },
+                "additionalProperties": false
             },
             "client": { "type": "string", "pattern": "^[a-z0-9.-]+\\.eth$" },
             "timestamp": { "type": "integer", "minimum": 0 },
@@ -67,10 +93,12 @@ pub mod schemas {
             "type": { "const": "claim" },
             "version": { "type": "string", "pattern": "^\\d+\\.\\d+\\.\\d+$" },
             "claim_id": { "type": "string", "minLength": 10 },
+            "job_id": { "type": "string", "minLength": 10 },
             "job_cid": { "type": "string", "pattern": "^(bafy|Qm)[a-zA-Z0-9]+" },
             "provider": { "type": "string", "pattern": "^[a-z0-9.-]+\\.eth$" },
             "mode": { "enum": ["SOLO", "PPL"] },
             "timestamp": { "type": "integer", "minimum": 0 },
+            "ttl_seconds": { "type": "integer", "minimum": 1, "maximum": 21600 },
             "nonce": { "type": "string", "minLength": 16 },
             "sig": { "type": "string", "pattern": "^0x[a-fA-F0-9]{130}$" }
         },
@@ -87,7 +115,9 @@ pub mod schemas {
             "type": { "const": "proof" },
             "version": { "type": "string", "pattern": "^\\d+\\.\\d+\\.\\d+$" },
             "proof_id": { "type": "string", "minLength": 10 },
+            "job_id": { "type": "string", "minLength": 10 },
             "job_cid": { "type": "string", "pattern": "^(bafy|Qm)[a-zA-Z0-9]+" },
+            "status": { "enum": ["completed", "low_confidence", "failed", "simulated"] },
             "claim_cid": { "type": "string", "pattern": "^(bafy|Qm)[a-zA-Z0-9]+" },
             "output_cid": { "type": "string", "pattern": "^(bafy|Qm)[a-zA-Z0-9]+" },
             "report_cid": { "type": "string", "pattern": "^(bafy|Qm)[a-zA-Z0-9]+" },
@@ -95,14 +125,17 @@ pub mod schemas {
                 "type": "object",
                 "required": ["inference_seconds", "confidence"],
                 "properties": {
-                    "inference_seconds": { "type": "number", "minimum": 0 },
-                    "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+                    "inference_seconds": { "type": "string", "pattern": "^\\d+(\\.\\d+)?$" },
+                    "compute_seconds": { "type": "string", "pattern": "^\\d+(\\.\\d+)?$" },
+                    "confidence": { "type": "string", "pattern": "^\\d+(\\.\\d+)?$", "minimum": 0, "maximum": 1 },
                     "model_version": { "type": "string" }
-                }
+                },
+                "additionalProperties": false
             },
             "provider": { "type": "string", "pattern": "^[a-z0-9.-]+\\.eth$" },
             "timestamp": { "type": "integer", "minimum": 0 },
             "proof_hash": { "type": "string", "pattern": "^0x[a-fA-F0-9]{64}$" },
+            "nonce": { "type": "string", "minLength": 16 },
             "sig": { "type": "string", "pattern": "^0x[a-fA-F0-9]{130}$" }
         },
         "additionalProperties": false
@@ -121,19 +154,86 @@ pub mod schemas {
             "name": { "type": "string" },
             "status": { "enum": ["active", "sealed"] },
             "started_at": { "type": "integer", "minimum": 0 },
+            "duration_secs": { "type": "integer", "minimum": 1 },
             "ended_at": { "type": "integer", "minimum": 0 },
             "jobs_count": { "type": "integer", "minimum": 0 },
             "proofs_count": { "type": "integer", "minimum": 0 },
             "total_volume_usdc": { "type": "string", "pattern": "^\\d+\\.?\\d*$" },
             "proofs": { "type": "array" },
-            "settlements": { "type": "object" },
+            "settlements": {
+                "type": "object",
+                "required": ["total_volume", "miner_pool", "hive_ops", "providers", "dust_to_hive"],
+                "properties": {
+                    "total_volume": { "type": "number", "minimum": 0 },
+                    "miner_pool": { "type": "number", "minimum": 0 },
+                    "hive_ops": { "type": "number", "minimum": 0 },
+                    "providers": { "type": "object" },
+                    "dust_to_hive": { "type": "number", "minimum": 0 },
+                    "slashes": { "type": "array" },
+                    "flagged": { "type": "array" },
+                    "hive_recipients": { "type": "object" },
+                    "compute_overhead_cap": { "type": ["string", "number"], "minimum": 0 }
+                }
+            },
+            "splits": {
+                "type": "object",
+                "required": ["miners_pct", "hive_pct"],
+                "properties": {
+                    "miners_pct": { "type": ["string", "number"], "minimum": 0, "maximum": 1 },
+                    "hive_pct": { "type": ["string", "number"], "minimum": 0, "maximum": 1 }
+                }
+            },
+            "state_link": {
+                "type": "object",
+                "required": ["prev_state_hash", "state_hash", "total_jobs", "total_volume_usdc"]
+            },
             "merkle_root": { "type": "string", "pattern": "^0x[a-fA-F0-9]{64}$" },
+            "inclusion_paths": { "type": "object" },
+            "processed_withdrawals": { "type": "array", "items": { "type": "string" } },
             "controller": { "type": "string", "pattern": "^[a-z0-9.-]+\\.eth$" },
             "timestamp": { "type": "integer", "minimum": 0 },
             "sig": { "type": "string", "pattern": "^0x[a-fA-F0-9]{130}$" }
         },
         "additionalProperties": false
     }"#;
+
+    pub const HEARTBEAT: &str = r#"{
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Heartbeat Snapshot",
+        "description": "Provider liveness record",
+        "type": "object",
+        "required": ["type", "version", "provider", "status", "models", "timestamp", "sig"],
+        "properties": {
+            "type": { "const": "heartbeat" },
+            "version": { "type": "string", "pattern": "^\\d+\\.\\d+\\.\\d+$" },
+            "provider": { "type": "string", "pattern": "^[a-z0-9.-]+\\.eth$" },
+            "status": { "enum": ["online", "watching", "busy", "offline"] },
+            "models": { "type": "array", "items": { "type": "string" } },
+            "gpus": { "type": "array", "items": { "type": "string" }, "maxItems": 16 },
+            "timestamp": { "type": "integer", "minimum": 0 },
+            "sig": { "type": "string", "pattern": "^0x[a-fA-F0-9]{130}$" }
+        },
+        "additionalProperties": false
+    }"#;
+
+    pub const WITHDRAWAL: &str = r#"{
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Withdrawal Snapshot",
+        "description": "Provider request to withdraw earnings",
+        "type": "object",
+        "required": ["type", "provider", "amount", "wallet", "timestamp", "nonce", "sig"],
+        "properties": {
+            "type": { "const": "withdrawal" },
+            "provider": { "type": "string", "pattern": "^[a-z0-9.-]+\\.eth$" },
+            "amount": { "type": "string", "pattern": "^\\d+\\.?\\d*$" },
+            "wallet": { "type": "string", "pattern": "^0x[a-fA-F0-9]{40}$" },
+            "status": { "enum": ["pending", "processed"] },
+            "timestamp": { "type": "integer", "minimum": 0 },
+            "nonce": { "type": "string", "minLength": 16 },
+            "sig": { "type": "string", "pattern": "^0x[a-fA-F0-9]{130}$" }
+        },
+        "additionalProperties": false
+    }"#;
 }
 
 /// Schema type enum
@@ -144,6 +244,8 @@ pub enum SchemaType {
     Claim,
     Proof,
     Epoch,
+    Heartbeat,
+    Withdrawal,
 }
 
 impl SchemaType {
@@ -154,6 +256,8 @@ impl SchemaType {
             "claim" => Some(SchemaType::Claim),
             "proof" => Some(SchemaType::Proof),
             "epoch" => Some(SchemaType::Epoch),
+            "heartbeat" => Some(SchemaType::Heartbeat),
+            "withdrawal" => Some(SchemaType::Withdrawal),
             _ => None,
         }
     }
@@ -165,6 +269,8 @@ impl SchemaType {
             SchemaType::Claim => schemas::CLAIM,
             SchemaType::Proof => schemas::PROOF,
             SchemaType::Epoch => schemas::EPOCH,
+            SchemaType::Heartbeat => schemas::HEARTBEAT,
+            SchemaType::Withdrawal => schemas::WITHDRAWAL,
         }
     }
 }
@@ -201,7 +307,8 @@ impl ValidationResult {
 
 /// Validate snapshot against schema
 ///
-/// Returns ValidationResult with any errors found.
+/// Returns ValidationResult with any errors found, including those in
+/// nested objects (named like `payment.amount`).
 /// This MUST pass before any IPFS publish.
 pub fn validate_snapshot(data: &Value, schema_type: SchemaType) -> ValidationResult {
     let schema_str = schema_type.schema();
@@ -226,110 +333,35 @@ pub fn validate_snapshot(data: &Value, schema_type: SchemaType) -> ValidationRes
         }
     }
 
+    validate_object(data, &schema, "", &mut errors);
+
+    ValidationResult::errors(errors)
+}
+
+/// Check `data` against an object schema: required fields, each property's
+/// keywords and `additionalProperties`. Properties that are objects with
+/// their own `properties` are checked the same way, reported as `outer.inner`.
+fn validate_object(data: &Value, schema: &Value, prefix: &str, errors: &mut Vec<String>) {
     // Check required fields
     if let Some(required) = schema["required"].as_array() {
         for field in required {
             if let Some(field_name) = field.as_str() {
                 if data.get(field_name).is_none() {
-                    errors.push(format!("Missing required field: {}", field_name));
+                    errors.push(format!("Missing required field: {}{}", prefix, field_name));
                 }
             }
         }
     }
 
-    // Check property types and patterns
+    // Check property types and patterns, recursing into nested objects
     if let Some(properties) = schema["properties"].as_object() {
         for (key, prop_schema) in properties {
             if let Some(value) = data.get(key) {
-                // Check pattern if specified
-                if let Some(pattern) = prop_schema["pattern"].as_str() {
-                    if let Some(str_val) = value.as_str() {
-                        let re = regex::Regex::new(pattern).unwrap();
-                        if !re.is_match(str_val) {
-                            errors.push(format!(
-                                "Field '{}' does not match pattern '{}': {}",
-                                key, pattern, str_val
-                            ));
-                        }
-                    }
-                }
-
-                // Check type
-                if let Some(expected_type) = prop_schema["type"].as_str() {
-                    let valid = match expected_type {
-                        "string" => value.is_string(),
-                        "integer" => value.is_i64() || value.is_u64(),
-                        "number" => value.is_number(),
-                        "boolean" => value.is_boolean(),
-                        "array" => value.is_array(),
-                        "object" => value.is_object(),
-                        _ => true,
-                    };
-                    if !valid {
-                        errors.push(format!(
-                            "Field '{}' has wrong type: expected {}, got {:?}",
-                            key, expected_type, value
-                        ));
-                    }
-                }
-
-                // Check enum if specified
-                if let Some(enum_values) = prop_schema["enum"].as_array() {
-                    let valid = enum_values.iter().any(|v| v == value);
-                    if !valid {
-                        errors.push(format!(
-                            "Field '{}' must be one of {:?}, got {:?}",
-                            key, enum_values, value
-                        ));
-                    }
-                }
-
-                // Check minimum for numbers
-                if let Some(min) = prop_schema["minimum"].as_f64() {
-                    if let Some(num) = value.as_f64() {
-                        if num < min {
-                            errors.push(format!(
-                                "Field '{}' must be >= {}, got {}",
-                                key, min, num
-                            ));
-                        }
-                    }
-                }
+                let key = format!("{}{}", prefix, key);
+                validate_property(&key, value, prop_schema, errors);
 
-                // Check maximum for numbers
-                if let Some(max) = prop_schema["maximum"].as_f64() {
-                    if let Some(num) = value.as_f64() {
-                        if num > max {
-                            errors.push(format!(
-                                "Field '{}' must be <= {}, got {}",
-                                key, max, num
-                            ));
-                        }
-                    }
-                }
-
-                // Check minLength for strings
-                if let Some(min_len) = prop_schema["minLength"].as_u64() {
-                    if let Some(str_val) = value.as_str() {
-                        if (str_val.len() as u64) < min_len {
-                            errors.push(format!(
-                                "Field '{}' must be at least {} characters",
-                                key, min_len
-                            ));
-                        }
-                    }
-                }
-
-                // Check minItems for arrays
-                if let Some(min_items) = prop_schema["minItems"].as_u64() {
-                    if let Some(arr) = value.as_array() {
-                        if (arr.len() as u64) < min_items {
-                            errors.push(format!(
-                                "Field '{}' must have at least {} items",
-                                key, min_items
-                            ));
-                        }
-                    }
+                if value.is_object() && prop_schema["properties"].is_object() {
+                    validate_object(value, prop_schema, &format!("{}.", key), errors);
                 }
             }
         }
@@ -341,13 +373,158 @@ pub fn validate_snapshot(data: &Value, schema_type: SchemaType) -> ValidationRes
         {
             for key in data_obj.keys() {
                 if !props.contains_key(key) {
-                    errors.push(format!("Unknown field: {}", key));
+                    errors.push(format!("Unknown field: {}{}", prefix, key));
                 }
             }
         }
     }
+}
 
-    ValidationResult::errors(errors)
+/// Check one property's value against its own keywords
+fn validate_property(key: &str, value: &Value, prop_schema: &Value, errors: &mut Vec<String>) {
+    // Check pattern if specified
+    if let Some(pattern) = prop_schema["pattern"].as_str() {
+        if let Some(str_val) = value.as_str() {
+            let re = regex::Regex::new(pattern).unwrap();
+            if !re.is_match(str_val) {
+                errors.push(format!(
+                    "Field '{}' does not match pattern '{}': {}",
+                    key, pattern, str_val
+                ));
+            }
+        }
+    }
+
+    // Check the EIP-55 checksum of mixed-case addresses
+    if prop_schema["format"] == "eth-address" {
+        if let Some(str_val) = value.as_str() {
+            // Malformed addresses are left to `pattern`
+            let well_formed = crypto::is_valid_checksum_address(&str_val.to_lowercase());
+            if well_formed && !crypto::is_valid_checksum_address(str_val) {
+                errors.push(format!(
+                    "Field '{}' is not a valid EIP-55 checksummed address: {}",
+                    key, str_val
+                ));
+            }
+        }
+    }
+
+    // Check type; a list of types accepts any of them (epochs sealed before
+    // fractions were signed as strings carry numbers)
+    let expected_types: Vec<&str> = match &prop_schema["type"] {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+    if !expected_types.is_empty() {
+        let valid = expected_types
+            .iter()
+            .any(|expected_type| match *expected_type {
+                "string" => value.is_string(),
+                "integer" => value.is_i64() || value.is_u64(),
+                "number" => value.is_number(),
+                "boolean" => value.is_boolean(),
+                "array" => value.is_array(),
+                "object" => value.is_object(),
+                _ => true,
+            });
+        if !valid {
+            errors.push(format!(
+                "Field '{}' has wrong type: expected {}, got {:?}",
+                key,
+                expected_types.join(" or "),
+                value
+            ));
+        }
+    }
+
+    // Check enum if specified
+    if let Some(enum_values) = prop_schema["enum"].as_array() {
+        let valid = enum_values.iter().any(|v| v == value);
+        if !valid {
+            errors.push(format!(
+                "Field '{}' must be one of {:?}, got {:?}",
+                key, enum_values, value
+            ));
+        }
+    }
+
+    // Numbers, and fractions written as decimal strings
+    let num = value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.parse::<f64>().ok()));
+
+    // Check minimum for numbers
+    if let Some(min) = prop_schema["minimum"].as_f64() {
+        if let Some(num) = num {
+            if num < min {
+                errors.push(format!(
+                    "Field '{}' must be >= {}, got {}",
+                    key, min, num
+                ));
+            }
+        }
+    }
+
+    // Check maximum for numbers
+    if let Some(max) = prop_schema["maximum"].as_f64() {
+        if let Some(num) = num {
+            if num > max {
+                errors.push(format!(
+                    "Field '{}' must be <= {}, got {}",
+                    key, max, num
+                ));
+            }
+        }
+    }
+
+    // Check minLength for strings
+    if let Some(min_len) = prop_schema["minLength"].as_u64() {
+        if let Some(str_val) = value.as_str() {
+            if (str_val.len() as u64) < min_len {
+                errors.push(format!(
+                    "Field '{}' must be at least {} characters",
+                    key, min_len
+                ));
+            }
+        }
+    }
+
+    // Check maxLength for strings (in characters, as JSON Schema counts)
+    if let Some(max_len) = prop_schema["maxLength"].as_u64() {
+        if let Some(str_val) = value.as_str() {
+            if (str_val.chars().count() as u64) > max_len {
+                errors.push(format!(
+                    "Field '{}' must be at most {} characters",
+                    key, max_len
+                ));
+            }
+        }
+    }
+
+    // Check minItems for arrays
+    if let Some(min_items) = prop_schema["minItems"].as_u64() {
+        if let Some(arr) = value.as_array() {
+            if (arr.len() as u64) < min_items {
+                errors.push(format!(
+                    "Field '{}' must have at least {} items",
+                    key, min_items
+                ));
+            }
+        }
+    }
+
+    // Check maxItems for arrays
+    if let Some(max_items) = prop_schema["maxItems"].as_u64() {
+        if let Some(arr) = value.as_array() {
+            if (arr.len() as u64) > max_items {
+                errors.push(format!(
+                    "Field '{}' must have at most {} items",
+                    key, max_items
+                ));
+            }
+        }
+    }
 }
 
 /// Validate and return Result
@@ -361,6 +538,53 @@ pub fn validate(data: &Value, schema_type: SchemaType) -> Result<()> {
     }
 }
 
+/// Check a single field value against its schema, e.g. `payment.amount`
+/// in the JOB schema, before the snapshot it goes into is built
+pub fn validate_field(schema_type: SchemaType, field: &str, value: &Value) -> Result<()> {
+    let schema: Value = serde_json::from_str(schema_type.schema()).context("Invalid schema")?;
+    let prop_schema = field
+        .split('.')
+        .try_fold(&schema, |schema, key| schema["properties"].get(key))
+        .with_context(|| format!("No field {} in the {:?} schema", field, schema_type))?;
+
+    let mut errors = Vec::new();
+    validate_property(field, value, prop_schema, &mut errors);
+    if !errors.is_empty() {
+        anyhow::bail!("{}", errors.join("; "));
+    }
+    Ok(())
+}
+
+/// Pre-publish check of a signed snapshot
+///
+/// Returns every deviation from the schema as a warning, or fails with all
+/// of them under `strict`.
+pub fn check_publish<T: Serialize>(
+    snapshot: &T,
+    schema_type: SchemaType,
+    strict: bool,
+) -> Result<Vec<String>> {
+    let data = serde_json::to_value(snapshot).context("Failed to serialize snapshot")?;
+    let result = validate_snapshot(&data, schema_type);
+    if strict && !result.valid {
+        anyhow::bail!(
+            "Schema validation failed (--strict-schema):\n  - {}",
+            result.errors.join("\n  - ")
+        );
+    }
+    Ok(result.errors)
+}
+
+/// `check_publish` under the global `--strict-schema` setting, printing any
+/// warnings
+pub fn check_before_publish<T: Serialize>(snapshot: &T, schema_type: SchemaType) -> Result<()> {
+    let warnings = check_publish(snapshot, schema_type, STRICT_SCHEMA.load(Ordering::Relaxed))?;
+    for warning in warnings {
+        eprintln!("{} {}", "⚠ Schema:".yellow(), warning.yellow());
+    }
+    Ok(())
+}
+
 /// Validate file contents
 pub fn validate_file(path: &str, schema_type: SchemaType) -> Result<ValidationResult> {
     let content = std::fs::read_to_string(path)
@@ -394,6 +618,37 @@ mod tests {
         assert!(result.valid, "Errors: {:?}", result.errors);
     }
 
+    #[test]
+    fn test_genesis_wallet_checksum() {
+        let genesis = |wallet: &str| {
+            serde_json::json!({
+                "type": "genesis",
+                "version": "1.0.0",
+                "provider": "miner.alice.eth",
+                "wallet": wallet,
+                "gpus": ["RTX 5090"],
+                "timestamp": 1704067200,
+                "nonce": "abcdef1234567890",
+                "sig": "0x".to_string() + &"a".repeat(130)
+            })
+        };
+
+        let good = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        assert!(validate_snapshot(&genesis(good), SchemaType::Genesis).valid);
+        assert!(validate_snapshot(&genesis(&good.to_lowercase()), SchemaType::Genesis).valid);
+
+        let result = validate_snapshot(
+            &genesis("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            SchemaType::Genesis,
+        );
+        assert!(!result.valid);
+        assert!(result.errors.iter().any(|e| e.contains("EIP-55")), "{:?}", result.errors);
+
+        // Malformed: only the pattern error
+        let result = validate_snapshot(&genesis("0x1234"), SchemaType::Genesis);
+        assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+    }
+
     #[test]
     fn test_invalid_genesis_missing_field() {
         let data = serde_json::json!({
@@ -429,4 +684,225 @@ mod tests {
         assert!(!result.valid);
         assert!(result.errors.iter().any(|e| e.contains("pattern")));
     }
+
+    #[test]
+    fn test_gpus_capped_at_16() {
+        let mut data = serde_json::json!({
+            "type": "genesis",
+            "version": "1.0.0",
+            "provider": "miner.alice.eth",
+            "wallet": "0x1234567890123456789012345678901234567890",
+            "gpus": vec!["RTX 5090"; 16],
+            "timestamp": 1704067200,
+            "nonce": "abcdef1234567890",
+            "sig": test_sig()
+        });
+        assert!(validate_snapshot(&data, SchemaType::Genesis).valid);
+
+        data["gpus"] = serde_json::json!(vec!["RTX 5090"; 17]);
+        let result = validate_snapshot(&data, SchemaType::Genesis);
+        assert_eq!(result.errors, ["Field 'gpus' must have at most 16 items"]);
+    }
+
+    #[test]
+    fn test_max_length() {
+        let mut errors = Vec::new();
+        let schema = serde_json::json!({ "type": "string", "maxLength": 4 });
+        validate_property("nonce", &serde_json::json!("abcd"), &schema, &mut errors);
+        assert!(errors.is_empty());
+
+        validate_property("nonce", &serde_json::json!("abcde"), &schema, &mut errors);
+        assert_eq!(errors, ["Field 'nonce' must be at most 4 characters"]);
+    }
+
+    fn test_sig() -> String {
+        format!("0x{}", "a".repeat(130))
+    }
+
+    #[test]
+    fn test_proof_snapshot_matches_schema() {
+        use crate::models::{ProofMetrics, ProofSnapshot};
+
+        let proof = ProofSnapshot {
+            snapshot_type: "proof".to_string(),
+            version: "1.0.0".to_string(),
+            proof_id: "proof-job-20250101000000-abcd1234-ef01".to_string(),
+            job_id: "job-20250101000000-abcd1234".to_string(),
+            job_cid: "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+            claim_cid: Some("QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH".to_string()),
+            status: "completed".to_string(),
+            output_cid: "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+            report_cid: None,
+            metrics: ProofMetrics {
+                inference_seconds: 2.5,
+                compute_seconds: 2.5,
+                confidence: 0.847,
+                model_version: "queenbee-spine-v1.0".to_string(),
+            },
+            provider: "miner.alice.eth".to_string(),
+            timestamp: 1704067200,
+            proof_hash: format!("0x{}", "b".repeat(64)),
+            nonce: Some("abcdef1234567890".to_string()),
+            sig: Some(test_sig()),
+        };
+
+        let data = serde_json::to_value(&proof).unwrap();
+        let result = validate_snapshot(&data, SchemaType::Proof);
+        assert!(result.valid, "Errors: {:?}", result.errors);
+        // Absent optional CIDs are omitted rather than published as null
+        assert!(data.get("report_cid").is_none());
+    }
+
+    #[test]
+    fn test_extra_field_warns_or_fails_under_strict() {
+        let data = serde_json::json!({
+            "type": "claim",
+            "version": "1.0.0",
+            "claim_id": "claim-job-20250101000000-abcd1234",
+            "job_id": "job-20250101000000-abcd1234",
+            "job_cid": "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o",
+            "provider": "miner.alice.eth",
+            "mode": "PPL",
+            "timestamp": 1704067200,
+            "nonce": "abcdef1234567890",
+            "sig": test_sig(),
+            "gpu_temp": 71
+        });
+
+        let warnings = check_publish(&data, SchemaType::Claim, false).unwrap();
+        assert_eq!(warnings, ["Unknown field: gpu_temp"]);
+
+        let err = check_publish(&data, SchemaType::Claim, true).unwrap_err();
+        assert!(err.to_string().contains("Unknown field: gpu_temp"), "{}", err);
+
+        // A clean snapshot passes either way
+        let mut clean = data;
+        clean.as_object_mut().unwrap().remove("gpu_temp");
+        assert!(check_publish(&clean, SchemaType::Claim, true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_nested_fields_rejected() {
+        let job = serde_json::json!({
+            "type": "job",
+            "version": "1.0.0",
+            "job_id": "job-20250101000000-abcd1234",
+            "model": "queenbee-spine",
+            "input_cid": "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o",
+            "payment": { "amount": "0.10", "token": "USDC", "foo": 1 },
+            "client": "client.alice.eth",
+            "timestamp": 1704067200,
+            "nonce": "abcdef1234567890",
+            "sig": test_sig()
+        });
+        let result = validate_snapshot(&job, SchemaType::Job);
+        assert_eq!(result.errors, ["Unknown field: payment.foo"]);
+
+        let proof = serde_json::json!({
+            "type": "proof",
+            "version": "1.0.0",
+            "proof_id": "proof-job-20250101000000-abcd1234-ef01",
+            "job_cid": "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o",
+            "output_cid": "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o",
+            "metrics": {
+                "inference_seconds": "2.5",
+                "compute_seconds": 3.0,
+                "confidence": "1.5",
+                "gpu_temp": 71
+            },
+            "provider": "miner.alice.eth",
+            "timestamp": 1704067200,
+            "proof_hash": format!("0x{}", "b".repeat(64)),
+            "sig": test_sig()
+        });
+        let result = validate_snapshot(&proof, SchemaType::Proof);
+        assert!(!result.valid);
+        assert!(result.errors.contains(&"Unknown field: metrics.gpu_temp".to_string()));
+        // Nested keywords are checked too, not just unknown keys
+        assert!(
+            result.errors.iter().any(|e| e.starts_with("Field 'metrics.confidence' must be <= 1")),
+            "{:?}",
+            result.errors
+        );
+        // Fractions are signed as decimal strings, never floats
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|e| e.starts_with("Field 'metrics.compute_seconds' has wrong type")),
+            "{:?}",
+            result.errors
+        );
+
+        let mut missing = proof;
+        missing["metrics"] = serde_json::json!({ "confidence": "0.9" });
+        let result = validate_snapshot(&missing, SchemaType::Proof);
+        assert_eq!(result.errors, ["Missing required field: metrics.inference_seconds"]);
+    }
+
+    #[test]
+    fn test_claim_snapshot_matches_schema() {
+        use crate::models::{ClaimSnapshot, ExecutionMode};
+
+        let claim = ClaimSnapshot {
+            snapshot_type: "claim".to_string(),
+            version: "1.0.0".to_string(),
+            claim_id: "claim-job-20250101000000-abcd1234".to_string(),
+            job_id: "job-20250101000000-abcd1234".to_string(),
+            job_cid: "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+            provider: "miner.alice.eth".to_string(),
+            mode: ExecutionMode::Ppl,
+            timestamp: 1704067200,
+            ttl_seconds: Some(600),
+            nonce: "abcdef1234567890".to_string(),
+            sig: Some(test_sig()),
+        };
+
+        let data = serde_json::to_value(&claim).unwrap();
+        let result = validate_snapshot(&data, SchemaType::Claim);
+        assert!(result.valid, "Errors: {:?}", result.errors);
+
+        // Claims published before ttl_seconds existed still validate
+        let old = serde_json::to_value(ClaimSnapshot { ttl_seconds: None, ..claim }).unwrap();
+        assert!(old.get("ttl_seconds").is_none());
+        let result = validate_snapshot(&old, SchemaType::Claim);
+        assert!(result.valid, "Errors: {:?}", result.errors);
+
+        let mut zero = data.clone();
+        zero["ttl_seconds"] = serde_json::json!(0);
+        assert!(!validate_snapshot(&zero, SchemaType::Claim).valid);
+
+        // Over the pool's maximum
+        let mut long = data;
+        long["ttl_seconds"] = serde_json::json!(crate::models::MAX_CLAIM_TTL_SECS + 1);
+        assert!(!validate_snapshot(&long, SchemaType::Claim).valid);
+    }
+
+    #[test]
+    fn test_withdrawal_schema() {
+        use crate::models::WithdrawalSnapshot;
+
+        let withdrawal = WithdrawalSnapshot {
+            snapshot_type: "withdrawal".to_string(),
+            provider: "miner.alice.eth".to_string(),
+            amount: "12.500000".to_string(),
+            wallet: "0x1234567890123456789012345678901234567890".to_string(),
+            status: "pending".to_string(),
+            timestamp: 1704067200,
+            nonce: "abcdef1234567890".to_string(),
+            sig: Some(test_sig()),
+        };
+        let data = serde_json::to_value(&withdrawal).unwrap();
+        let result = validate_snapshot(&data, SchemaType::Withdrawal);
+        assert!(result.valid, "Errors: {:?}", result.errors);
+
+        let mut invalid = data.clone();
+        invalid["amount"] = serde_json::json!("$12.50");
+        invalid["wallet"] = serde_json::json!("0x1234");
+        invalid["nonce"] = serde_json::json!("short");
+        let result = validate_snapshot(&invalid, SchemaType::Withdrawal);
+        assert!(!result.valid);
+        assert_eq!(result.errors.len(), 3, "{:?}", result.errors);
+        assert!(result.errors.iter().any(|e| e.contains("'amount'")));
+    }
 }
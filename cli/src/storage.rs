@@ -0,0 +1,949 @@
+//! Storage backends for pool snapshots
+//!
+//! Commands read and write snapshots through `&dyn Storage`, so the same flow
+//! runs against an IPFS daemon (`IpfsStorage`, the default) or a local
+//! directory tree (`FsStorage`, via `--storage-dir`) for testing and
+//! air-gapped use.
+//!
+//! Paths are always the canonical MFS layout (`/swarmpool/jobs/...`, see
+//! `ipfs::paths`); content ids are CIDs for IPFS and keccak256 digests for
+//! the filesystem backend.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use colored::Colorize;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+
+use crate::crypto;
+use crate::ipfs::{self, IpfsClient};
+use crate::ledger::{Ledger, LedgerEntry};
+use crate::schema;
+
+/// Snapshot storage backend
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Store a JSON document by content, returning its content id
+    async fn put_json(&self, data: &Value) -> Result<String>;
+
+    /// Store raw bytes by content, returning its content id
+    async fn put_bytes(&self, bytes: Vec<u8>, file_name: &str) -> Result<String>;
+
+    /// Store files as one directory, returning the directory's content id.
+    /// Paths are relative and `/`-separated.
+    async fn put_dir(&self, files: Vec<(String, Vec<u8>)>) -> Result<String>;
+
+    /// Fetch a JSON document by content id
+    async fn get_json(&self, id: &str) -> Result<Value>;
+
+    /// Fetch raw bytes by content id
+    async fn get_bytes(&self, id: &str) -> Result<Vec<u8>>;
+
+    /// Fetch every file of a directory stored with `put_dir`, sorted by path
+    async fn get_dir(&self, id: &str) -> Result<Vec<(String, Vec<u8>)>>;
+
+    /// Write a JSON document at a canonical path, returning its content id
+    async fn write_path(&self, path: &str, data: &Value) -> Result<String>;
+
+    /// Overwrite the document at a mutable path (state, heartbeats)
+    async fn replace_path(&self, path: &str, data: &Value) -> Result<String>;
+
+    /// Read the JSON document at a canonical path
+    async fn read_path(&self, path: &str) -> Result<Value>;
+
+    /// Content id of the document at a canonical path, or `None` when
+    /// nothing is there. Any other failure is an error, never "absent".
+    async fn path_cid(&self, path: &str) -> Result<Option<String>>;
+
+    /// Entry names in a directory
+    async fn list(&self, dir: &str) -> Result<Vec<String>>;
+
+    /// Whether a directory exists (`list` can't tell missing from empty)
+    async fn dir_exists(&self, dir: &str) -> Result<bool>;
+
+    /// Keep content available (no-op where storage is already durable)
+    async fn pin(&self, id: &str) -> Result<()>;
+
+    /// Announce a message on a pool topic
+    async fn publish(&self, topic: &str, message: &Value) -> Result<()>;
+
+    /// Fail early if the backend is unreachable
+    async fn check_connection(&self) -> Result<()>;
+
+    /// Whether writes reach the pool; `false` under `--dry-run` and
+    /// `--sign-only`, which publish nothing yet
+    fn publishes(&self) -> bool {
+        true
+    }
+}
+
+/// Typed helpers over the JSON-level trait methods
+impl dyn Storage + '_ {
+    pub async fn get<T: DeserializeOwned>(&self, id: &str) -> Result<T> {
+        let data = self.get_json(id).await?;
+        serde_json::from_value(data).with_context(|| format!("Failed to parse {}", id))
+    }
+
+    pub async fn read<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let data = self.read_path(path).await?;
+        serde_json::from_value(data).with_context(|| format!("Failed to parse {}", path))
+    }
+
+    pub async fn put<T: Serialize>(&self, data: &T) -> Result<String> {
+        self.put_json(&serde_json::to_value(data)?).await
+    }
+
+    pub async fn write<T: Serialize>(&self, path: &str, data: &T) -> Result<String> {
+        self.write_path(path, &serde_json::to_value(data)?).await
+    }
+
+    pub async fn replace<T: Serialize>(&self, path: &str, data: &T) -> Result<String> {
+        self.replace_path(path, &serde_json::to_value(data)?).await
+    }
+
+    pub async fn announce<T: Serialize>(&self, topic: &str, message: &T) -> Result<()> {
+        self.publish(topic, &serde_json::to_value(message)?).await
+    }
+}
+
+/// Backend selected by `--storage-dir` (filesystem) or IPFS by default.
+/// Published snapshots are recorded in the local ledger. Under `--dry-run`
+/// nothing is written at all; under `--sign-only` the snapshot goes to a file.
+pub fn open(
+    storage_dir: Option<&str>,
+    dry_run: bool,
+    sign_only: Option<&str>,
+    ipfs_client: &'static IpfsClient,
+) -> Box<dyn Storage> {
+    let store: Box<dyn Storage> = match storage_dir {
+        Some(dir) => Box::new(FsStorage::new(dir)),
+        None => Box::new(IpfsStorage::new(ipfs_client)),
+    };
+    let store: Box<dyn Storage> = match Ledger::open() {
+        Ok(ledger) => Box::new(LedgerStorage::new(store, ledger)),
+        Err(e) => {
+            tracing::warn!("Publish ledger unavailable: {}", e);
+            store
+        }
+    };
+    if dry_run {
+        Box::new(DryRunStorage::new(store))
+    } else if let Some(out) = sign_only {
+        Box::new(SignOnlyStorage::new(store, out))
+    } else {
+        store
+    }
+}
+
+/// The local IPFS daemon (MFS for paths, pubsub for announcements)
+pub struct IpfsStorage {
+    client: &'static IpfsClient,
+}
+
+impl IpfsStorage {
+    pub fn new(client: &'static IpfsClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Storage for IpfsStorage {
+    async fn put_json(&self, data: &Value) -> Result<String> {
+        self.client.upload_json(data).await
+    }
+
+    async fn put_bytes(&self, bytes: Vec<u8>, file_name: &str) -> Result<String> {
+        self.client.upload_bytes(bytes, file_name).await
+    }
+
+    async fn put_dir(&self, files: Vec<(String, Vec<u8>)>) -> Result<String> {
+        self.client.upload_directory(files).await
+    }
+
+    async fn get_json(&self, id: &str) -> Result<Value> {
+        self.client.fetch_json(id).await
+    }
+
+    async fn get_bytes(&self, id: &str) -> Result<Vec<u8>> {
+        self.client.fetch_bytes(id).await
+    }
+
+    async fn get_dir(&self, id: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        self.client.fetch_directory(id).await
+    }
+
+    async fn write_path(&self, path: &str, data: &Value) -> Result<String> {
+        self.client.write_to_path(path, data).await
+    }
+
+    async fn replace_path(&self, path: &str, data: &Value) -> Result<String> {
+        self.client.replace_at_path(path, data).await
+    }
+
+    async fn read_path(&self, path: &str) -> Result<Value> {
+        self.client.read_from_path(path).await
+    }
+
+    async fn path_cid(&self, path: &str) -> Result<Option<String>> {
+        self.client.path_cid(path).await
+    }
+
+    async fn list(&self, dir: &str) -> Result<Vec<String>> {
+        self.client.list_directory(dir).await
+    }
+
+    async fn dir_exists(&self, dir: &str) -> Result<bool> {
+        self.client.dir_exists(dir).await
+    }
+
+    async fn pin(&self, id: &str) -> Result<()> {
+        self.client.pin(id).await
+    }
+
+    async fn publish(&self, topic: &str, message: &Value) -> Result<()> {
+        self.client.pubsub_publish(topic, message).await
+    }
+
+    async fn check_connection(&self) -> Result<()> {
+        self.client.check_connection().await
+    }
+}
+
+/// Records every snapshot written to a canonical path in the local ledger
+///
+/// Only `write_path` is recorded: those are the immutable, published
+/// snapshots. Mutable paths (state, heartbeats) and raw uploads aren't. The
+/// snapshot is already published when the ledger is appended, so a ledger
+/// failure is reported, not returned.
+pub struct LedgerStorage {
+    inner: Box<dyn Storage>,
+    ledger: Ledger,
+}
+
+impl LedgerStorage {
+    pub fn new(inner: Box<dyn Storage>, ledger: Ledger) -> Self {
+        Self { inner, ledger }
+    }
+}
+
+#[async_trait]
+impl Storage for LedgerStorage {
+    async fn put_json(&self, data: &Value) -> Result<String> {
+        self.inner.put_json(data).await
+    }
+
+    async fn put_bytes(&self, bytes: Vec<u8>, file_name: &str) -> Result<String> {
+        self.inner.put_bytes(bytes, file_name).await
+    }
+
+    async fn put_dir(&self, files: Vec<(String, Vec<u8>)>) -> Result<String> {
+        self.inner.put_dir(files).await
+    }
+
+    async fn get_json(&self, id: &str) -> Result<Value> {
+        self.inner.get_json(id).await
+    }
+
+    async fn get_bytes(&self, id: &str) -> Result<Vec<u8>> {
+        self.inner.get_bytes(id).await
+    }
+
+    async fn get_dir(&self, id: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        self.inner.get_dir(id).await
+    }
+
+    async fn write_path(&self, path: &str, data: &Value) -> Result<String> {
+        let cid = self.inner.write_path(path, data).await?;
+        let entry = LedgerEntry::new(path, &cid, data, chrono::Utc::now().timestamp());
+        if let Err(e) = self.ledger.append(&entry) {
+            eprintln!(
+                "{} {:#}",
+                "⚠ Ledger:".yellow(),
+                e.context(format!("{} published as {} but not recorded", path, cid))
+            );
+        }
+        Ok(cid)
+    }
+
+    async fn replace_path(&self, path: &str, data: &Value) -> Result<String> {
+        self.inner.replace_path(path, data).await
+    }
+
+    async fn read_path(&self, path: &str) -> Result<Value> {
+        self.inner.read_path(path).await
+    }
+
+    async fn path_cid(&self, path: &str) -> Result<Option<String>> {
+        self.inner.path_cid(path).await
+    }
+
+    async fn list(&self, dir: &str) -> Result<Vec<String>> {
+        self.inner.list(dir).await
+    }
+
+    async fn dir_exists(&self, dir: &str) -> Result<bool> {
+        self.inner.dir_exists(dir).await
+    }
+
+    async fn pin(&self, id: &str) -> Result<()> {
+        self.inner.pin(id).await
+    }
+
+    async fn publish(&self, topic: &str, message: &Value) -> Result<()> {
+        self.inner.publish(topic, message).await
+    }
+
+    async fn check_connection(&self) -> Result<()> {
+        self.inner.check_connection().await
+    }
+
+    fn publishes(&self) -> bool {
+        self.inner.publishes()
+    }
+}
+
+/// `--dry-run`: reads go to the wrapped backend, writes are printed instead
+///
+/// Every snapshot a command would publish is dumped as its final signed JSON
+/// with the path or topic it would go to. Content ids are the CIDs `ipfs add`
+/// would assign where that can be computed locally (see `ipfs::local_cid`),
+/// and clearly fake `QmDryRun...` ids otherwise.
+pub struct DryRunStorage {
+    inner: Box<dyn Storage>,
+}
+
+impl DryRunStorage {
+    pub fn new(inner: Box<dyn Storage>) -> Self {
+        Self { inner }
+    }
+
+    fn content_id(bytes: &[u8]) -> String {
+        ipfs::local_cid(bytes).unwrap_or_else(|| {
+            format!("QmDryRun{}", &crypto::keccak256_hash(bytes)[2..34])
+        })
+    }
+
+    fn dump(action: &str, target: &str, id: Option<&str>, data: Option<&Value>) -> Result<()> {
+        println!();
+        match id {
+            Some(id) => println!(
+                "{} {} {} {}",
+                "[dry-run]".yellow().bold(),
+                action,
+                target.cyan(),
+                format!("({})", id).bright_black()
+            ),
+            None => println!("{} {} {}", "[dry-run]".yellow().bold(), action, target.cyan()),
+        }
+        if let Some(data) = data {
+            println!("{}", serde_json::to_string_pretty(data)?);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Storage for DryRunStorage {
+    async fn put_json(&self, data: &Value) -> Result<String> {
+        let id = Self::content_id(serde_json::to_string_pretty(data)?.as_bytes());
+        Self::dump("would upload", "data.json", Some(&id), Some(data))?;
+        Ok(id)
+    }
+
+    async fn put_bytes(&self, bytes: Vec<u8>, file_name: &str) -> Result<String> {
+        let id = Self::content_id(&bytes);
+        let target = format!("{} ({} bytes)", file_name, bytes.len());
+        Self::dump("would upload", &target, Some(&id), None)?;
+        Ok(id)
+    }
+
+    async fn put_dir(&self, files: Vec<(String, Vec<u8>)>) -> Result<String> {
+        let listing: Vec<u8> = files
+            .iter()
+            .flat_map(|(path, bytes)| [path.as_bytes(), &crypto::keccak256_hash(bytes).into_bytes()].concat())
+            .collect();
+        let id = format!("QmDryRun{}", &crypto::keccak256_hash(&listing)[2..34]);
+        let target = format!("directory of {} file(s)", files.len());
+        Self::dump("would upload", &target, Some(&id), None)?;
+        Ok(id)
+    }
+
+    async fn get_json(&self, id: &str) -> Result<Value> {
+        self.inner.get_json(id).await
+    }
+
+    async fn get_bytes(&self, id: &str) -> Result<Vec<u8>> {
+        self.inner.get_bytes(id).await
+    }
+
+    async fn get_dir(&self, id: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        self.inner.get_dir(id).await
+    }
+
+    async fn write_path(&self, path: &str, data: &Value) -> Result<String> {
+        let id = Self::content_id(serde_json::to_string_pretty(data)?.as_bytes());
+        Self::dump("would write", path, Some(&id), Some(data))?;
+        Ok(id)
+    }
+
+    async fn replace_path(&self, path: &str, data: &Value) -> Result<String> {
+        let id = Self::content_id(serde_json::to_string_pretty(data)?.as_bytes());
+        Self::dump("would replace", path, Some(&id), Some(data))?;
+        Ok(id)
+    }
+
+    async fn read_path(&self, path: &str) -> Result<Value> {
+        self.inner.read_path(path).await
+    }
+
+    async fn path_cid(&self, path: &str) -> Result<Option<String>> {
+        self.inner.path_cid(path).await
+    }
+
+    async fn list(&self, dir: &str) -> Result<Vec<String>> {
+        self.inner.list(dir).await
+    }
+
+    async fn dir_exists(&self, dir: &str) -> Result<bool> {
+        self.inner.dir_exists(dir).await
+    }
+
+    async fn pin(&self, id: &str) -> Result<()> {
+        Self::dump("would pin", id, None, None)
+    }
+
+    async fn publish(&self, topic: &str, message: &Value) -> Result<()> {
+        Self::dump("would announce on", topic, None, Some(message))
+    }
+
+    async fn check_connection(&self) -> Result<()> {
+        // Nothing is published, so the backend needn't be reachable
+        Ok(())
+    }
+
+    fn publishes(&self) -> bool {
+        false
+    }
+}
+
+/// `--sign-only <path>`: the snapshot a command would publish is written to
+/// a local file instead
+///
+/// The signed snapshot must pass its schema (strictly) and is written to
+/// `path` as pretty JSON; `swarm publish` publishes it later. Nothing is
+/// written to a canonical path or the wrapped backend, and nothing is
+/// announced or pinned. Content the snapshot refers to (outputs, inputs) and
+/// mutable paths a command replaces (the seal's pool state) are staged under
+/// `<path>.staged/` for `swarm publish` to upload with it. Staged content gets
+/// the CID `ipfs add` would give it, so it must fit one block; a directory
+/// can't be staged. Reads still go to the wrapped backend, so with
+/// `--storage-dir` no IPFS daemon is needed at all.
+pub struct SignOnlyStorage {
+    inner: Box<dyn Storage>,
+    out: PathBuf,
+    written: std::sync::atomic::AtomicBool,
+}
+
+impl SignOnlyStorage {
+    pub fn new(inner: Box<dyn Storage>, out: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            out: out.into(),
+            written: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+
+    /// Where `--sign-only <out>` stages content and mutable-path writes
+    pub fn staging_dir(out: &Path) -> PathBuf {
+        let mut dir = out.as_os_str().to_owned();
+        dir.push(".staged");
+        PathBuf::from(dir)
+    }
+
+    /// Staged file holding the content with `cid`
+    pub fn staged_blob(staging: &Path, cid: &str) -> Result<PathBuf> {
+        ipfs::parse_cid(cid)?;
+        Ok(staging.join("blobs").join(cid))
+    }
+
+    /// Staged file replacing the canonical `path`
+    pub fn staged_path(staging: &Path, path: &str) -> Result<PathBuf> {
+        let relative = Path::new(path.trim_start_matches('/'));
+        if relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            bail!("Invalid storage path: {}", path);
+        }
+        Ok(staging.join("paths").join(relative))
+    }
+
+    fn content_id(bytes: &[u8]) -> String {
+        ipfs::local_cid(bytes).unwrap_or_else(|| "(assigned when published)".to_string())
+    }
+
+    fn stage_blob(&self, bytes: &[u8]) -> Result<String> {
+        let cid = ipfs::local_cid(bytes).with_context(|| {
+            format!(
+                "--sign-only: {} bytes is more than one {} KiB block, so its CID can't be \
+                 computed offline; upload it from a networked machine and pass its CID",
+                bytes.len(),
+                ipfs::DEFAULT_CHUNK_SIZE / 1024
+            )
+        })?;
+        let path = Self::staged_blob(&Self::staging_dir(&self.out), &cid)?;
+        FsStorage::write_file(&path, bytes)?;
+        Ok(cid)
+    }
+
+    fn read_staged_blob(&self, id: &str) -> Option<Vec<u8>> {
+        let path = Self::staged_blob(&Self::staging_dir(&self.out), id).ok()?;
+        std::fs::read(path).ok()
+    }
+}
+
+#[async_trait]
+impl Storage for SignOnlyStorage {
+    async fn put_json(&self, data: &Value) -> Result<String> {
+        self.stage_blob(serde_json::to_string_pretty(data)?.as_bytes())
+    }
+
+    async fn put_bytes(&self, bytes: Vec<u8>, _file_name: &str) -> Result<String> {
+        self.stage_blob(&bytes)
+    }
+
+    async fn put_dir(&self, files: Vec<(String, Vec<u8>)>) -> Result<String> {
+        bail!(
+            "--sign-only: can't stage a directory of {} files offline; upload it from a \
+             networked machine and pass its CID",
+            files.len()
+        )
+    }
+
+    async fn get_json(&self, id: &str) -> Result<Value> {
+        match self.read_staged_blob(id) {
+            Some(bytes) => serde_json::from_slice(&bytes).context("Failed to parse JSON"),
+            None => self.inner.get_json(id).await,
+        }
+    }
+
+    async fn get_bytes(&self, id: &str) -> Result<Vec<u8>> {
+        match self.read_staged_blob(id) {
+            Some(bytes) => Ok(bytes),
+            None => self.inner.get_bytes(id).await,
+        }
+    }
+
+    async fn get_dir(&self, id: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        self.inner.get_dir(id).await
+    }
+
+    async fn write_path(&self, path: &str, data: &Value) -> Result<String> {
+        use std::sync::atomic::Ordering;
+
+        let snapshot_type = data["type"].as_str().unwrap_or("unknown");
+        let schema_type = schema::SchemaType::from_str(snapshot_type)
+            .with_context(|| format!("--sign-only: no schema for {} snapshots", snapshot_type))?;
+        schema::validate(data, schema_type)?;
+        if self.written.swap(true, Ordering::SeqCst) {
+            bail!("--sign-only: more than one snapshot to write ({})", path);
+        }
+
+        let json = serde_json::to_string_pretty(data)?;
+        std::fs::write(&self.out, format!("{}\n", json))
+            .with_context(|| format!("Failed to write {}", self.out.display()))?;
+        println!();
+        println!(
+            "{} signed {} written to {} (would go to {})",
+            "[sign-only]".yellow().bold(),
+            snapshot_type,
+            self.out.display().to_string().cyan(),
+            path
+        );
+        Ok(Self::content_id(json.as_bytes()))
+    }
+
+    async fn replace_path(&self, path: &str, data: &Value) -> Result<String> {
+        let bytes = serde_json::to_string_pretty(data)?;
+        let staged = Self::staged_path(&Self::staging_dir(&self.out), path)?;
+        FsStorage::write_file(&staged, bytes.as_bytes())?;
+        Ok(Self::content_id(bytes.as_bytes()))
+    }
+
+    async fn read_path(&self, path: &str) -> Result<Value> {
+        self.inner.read_path(path).await
+    }
+
+    async fn path_cid(&self, path: &str) -> Result<Option<String>> {
+        self.inner.path_cid(path).await
+    }
+
+    async fn list(&self, dir: &str) -> Result<Vec<String>> {
+        self.inner.list(dir).await
+    }
+
+    async fn dir_exists(&self, dir: &str) -> Result<bool> {
+        self.inner.dir_exists(dir).await
+    }
+
+    async fn pin(&self, _id: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn publish(&self, _topic: &str, _message: &Value) -> Result<()> {
+        Ok(())
+    }
+
+    async fn check_connection(&self) -> Result<()> {
+        self.inner.check_connection().await
+    }
+
+    fn publishes(&self) -> bool {
+        false
+    }
+}
+
+/// The MFS layout mapped onto a local directory
+///
+/// `/swarmpool/jobs/job-1.json` lives at `<root>/swarmpool/jobs/job-1.json`;
+/// content-addressed objects live under `<root>/objects/`. A directory is an
+/// object holding a `{path: id}` manifest. Announcements are appended to
+/// `<root>/pubsub/<topic>.jsonl`.
+pub struct FsStorage {
+    root: PathBuf,
+}
+
+impl FsStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Local file for a canonical path, refusing anything that escapes the root
+    fn local_path(&self, path: &str) -> Result<PathBuf> {
+        let relative = Path::new(path.trim_start_matches('/'));
+        if relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            bail!("Invalid storage path: {}", path);
+        }
+        Ok(self.root.join(relative))
+    }
+
+    fn object_path(&self, id: &str) -> Result<PathBuf> {
+        if !id.starts_with("0x") || !id[2..].chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("Not a local content id: {}", id);
+        }
+        Ok(self.root.join("objects").join(id))
+    }
+
+    fn write_file(path: &Path, bytes: &[u8]) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(path, bytes).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    fn read_json(path: &Path) -> Result<Value> {
+        let content = std::fs::read(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_slice(&content).context("Failed to parse JSON")
+    }
+
+    fn store_object(&self, bytes: &[u8]) -> Result<String> {
+        let id = crypto::keccak256_hash(bytes);
+        Self::write_file(&self.object_path(&id)?, bytes)?;
+        Ok(id)
+    }
+}
+
+#[async_trait]
+impl Storage for FsStorage {
+    async fn put_json(&self, data: &Value) -> Result<String> {
+        self.store_object(&serde_json::to_vec_pretty(data)?)
+    }
+
+    async fn put_bytes(&self, bytes: Vec<u8>, _file_name: &str) -> Result<String> {
+        self.store_object(&bytes)
+    }
+
+    async fn put_dir(&self, files: Vec<(String, Vec<u8>)>) -> Result<String> {
+        let mut manifest = serde_json::Map::new();
+        for (path, bytes) in files {
+            manifest.insert(path, Value::String(self.store_object(&bytes)?));
+        }
+        self.store_object(&serde_json::to_vec(&manifest)?)
+    }
+
+    async fn get_json(&self, id: &str) -> Result<Value> {
+        Self::read_json(&self.object_path(id)?)
+    }
+
+    async fn get_bytes(&self, id: &str) -> Result<Vec<u8>> {
+        let path = self.object_path(id)?;
+        std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))
+    }
+
+    async fn get_dir(&self, id: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let manifest = self.get_json(id).await?;
+        let entries = manifest
+            .as_object()
+            .with_context(|| format!("{} is not a directory", id))?;
+
+        let mut files = Vec::with_capacity(entries.len());
+        for (path, file_id) in entries {
+            let file_id = file_id.as_str().context("Invalid directory manifest")?;
+            let bytes = std::fs::read(self.object_path(file_id)?)
+                .with_context(|| format!("Missing {} in directory {}", path, id))?;
+            files.push((path.clone(), bytes));
+        }
+        Ok(files)
+    }
+
+    async fn write_path(&self, path: &str, data: &Value) -> Result<String> {
+        let local = self.local_path(path)?;
+        if local.exists() {
+            bail!("{} already exists (snapshots are append-only)", path);
+        }
+        self.replace_path(path, data).await
+    }
+
+    async fn replace_path(&self, path: &str, data: &Value) -> Result<String> {
+        let bytes = serde_json::to_vec_pretty(data)?;
+        Self::write_file(&self.local_path(path)?, &bytes)?;
+        self.store_object(&bytes)
+    }
+
+    async fn read_path(&self, path: &str) -> Result<Value> {
+        Self::read_json(&self.local_path(path)?)
+    }
+
+    async fn path_cid(&self, path: &str) -> Result<Option<String>> {
+        let local = self.local_path(path)?;
+        match std::fs::read(&local) {
+            Ok(bytes) => Ok(Some(crypto::keccak256_hash(&bytes))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", local.display())),
+        }
+    }
+
+    async fn list(&self, dir: &str) -> Result<Vec<String>> {
+        let local = self.local_path(dir)?;
+        if !local.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut names: Vec<String> = std::fs::read_dir(&local)
+            .with_context(|| format!("Failed to list {}", local.display()))?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    async fn dir_exists(&self, dir: &str) -> Result<bool> {
+        Ok(self.local_path(dir)?.is_dir())
+    }
+
+    async fn pin(&self, id: &str) -> Result<()> {
+        if !self.object_path(id)?.exists() {
+            bail!("Cannot pin {}: not in local storage", id);
+        }
+        Ok(())
+    }
+
+    async fn publish(&self, topic: &str, message: &Value) -> Result<()> {
+        use std::io::Write;
+
+        let name: String = topic
+            .trim_start_matches('/')
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+            .collect();
+        let path = self.root.join("pubsub").join(format!("{}.jsonl", name));
+        std::fs::create_dir_all(path.parent().expect("pubsub dir"))?;
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(message)?)?;
+        Ok(())
+    }
+
+    async fn check_connection(&self) -> Result<()> {
+        std::fs::create_dir_all(&self.root)
+            .with_context(|| format!("Storage directory {} is not writable", self.root.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Payment;
+    use crate::test_support::{self, TEST_KEY};
+    use tempfile::TempDir;
+
+    fn temp_store() -> (TempDir, Box<dyn Storage>) {
+        let (dir, store) = test_support::fs_store();
+        (dir, Box::new(store))
+    }
+
+    fn payment(amount: &str) -> Payment {
+        Payment {
+            amount: amount.to_string(),
+            token: "USDC".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fs_storage_maps_mfs_layout() {
+        let (dir, store) = temp_store();
+
+        let id = store
+            .write(&format!("{}/job-001.json", ipfs::paths::JOBS), &payment("0.10"))
+            .await
+            .unwrap();
+        assert!(dir.path().join("swarmpool/jobs/job-001.json").exists());
+
+        // Paths are readable by path and by content id
+        let by_path: Payment = store.read("/swarmpool/jobs/job-001.json").await.unwrap();
+        let by_id: Payment = store.get(&id).await.unwrap();
+        assert_eq!(by_path.amount, "0.10");
+        assert_eq!(by_id.amount, "0.10");
+        store.pin(&id).await.unwrap();
+        assert_eq!(
+            store.path_cid("/swarmpool/jobs/job-001.json").await.unwrap(),
+            Some(id)
+        );
+        assert_eq!(store.path_cid("/swarmpool/jobs/job-002.json").await.unwrap(), None);
+
+        assert_eq!(store.list(ipfs::paths::JOBS).await.unwrap(), vec!["job-001.json"]);
+        assert!(store.list(ipfs::paths::CLAIMS).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fs_storage_append_only_paths() {
+        let (_dir, store) = temp_store();
+        let path = "/swarmpool/jobs/job-001.json";
+
+        store.write(path, &payment("0.10")).await.unwrap();
+        assert!(store.write(path, &payment("0.20")).await.is_err());
+
+        // Mutable paths are replaced explicitly
+        store.replace("/swarmpool/index/state.json", &payment("1")).await.unwrap();
+        store.replace("/swarmpool/index/state.json", &payment("2")).await.unwrap();
+        let state: Payment = store.read("/swarmpool/index/state.json").await.unwrap();
+        assert_eq!(state.amount, "2");
+
+        assert!(store.read_path("/swarmpool/../etc/passwd").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ledger_records_published_snapshots() {
+        let (dir, inner) = temp_store();
+        let ledger_path = dir.path().join("ledger.jsonl");
+        let store: Box<dyn Storage> =
+            Box::new(LedgerStorage::new(inner, Ledger::at(&ledger_path)));
+
+        let job = serde_json::json!({ "type": "job", "job_id": "job-001" });
+        let cid = store.write("/swarmpool/jobs/job-001.json", &job).await.unwrap();
+        store.replace("/swarmpool/index/state.json", &payment("1")).await.unwrap();
+
+        let (entries, _) = Ledger::at(&ledger_path)
+            .query(&crate::ledger::LedgerFilter::default())
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].snapshot_type, "job");
+        assert_eq!(entries[0].local_id, "job-001");
+        assert_eq!(entries[0].cid, cid);
+        assert_eq!(entries[0].path, "/swarmpool/jobs/job-001.json");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_writes_nothing() {
+        let (dir, inner) = temp_store();
+        inner.write("/swarmpool/jobs/job-001.json", &payment("0.10")).await.unwrap();
+        let store: Box<dyn Storage> = Box::new(DryRunStorage::new(inner));
+
+        // Reads still reach the wrapped backend
+        let job: Payment = store.read("/swarmpool/jobs/job-001.json").await.unwrap();
+        assert_eq!(job.amount, "0.10");
+
+        let path = "/swarmpool/jobs/job-002.json";
+        let id = store.write(path, &payment("0.20")).await.unwrap();
+        assert!(!dir.path().join("swarmpool/jobs/job-002.json").exists());
+        assert!(store.read_path(path).await.is_err());
+
+        // The id is the CID `ipfs add` would give the published bytes
+        let bytes = serde_json::to_string_pretty(&payment("0.20")).unwrap();
+        assert_eq!(Some(id), ipfs::local_cid(bytes.as_bytes()));
+
+        store.replace("/swarmpool/index/state.json", &payment("1")).await.unwrap();
+        store.announce("/swarmpool.eth/jobs", &payment("0.20")).await.unwrap();
+        assert!(!dir.path().join("swarmpool/index").exists());
+        assert!(!dir.path().join("pubsub").exists());
+
+        let big = store.put_bytes(vec![0u8; ipfs::DEFAULT_CHUNK_SIZE + 1], "scan.dcm").await.unwrap();
+        assert!(big.starts_with("QmDryRun"));
+        assert!(!dir.path().join("objects").join(&big).exists());
+    }
+
+    #[tokio::test]
+    async fn test_sign_only_writes_the_file_and_nothing_else() {
+        let (dir, inner) = temp_store();
+        let out = dir.path().join("signed-job.json");
+        let store: Box<dyn Storage> = Box::new(SignOnlyStorage::new(inner, &out));
+
+        // Not a snapshot any schema describes: refused, nothing written
+        assert!(store.write("/swarmpool/jobs/job-000.json", &payment("0.10")).await.is_err());
+        assert!(!out.exists());
+
+        let mut job = crate::commands::submit::build_job(
+            "job-20250101000000-abcd1234".to_string(),
+            "queenbee-spine",
+            "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+            "clinic.clientswarm.eth",
+            "0.25",
+            1704067200,
+        );
+        job.sig = Some(crypto::sign_snapshot(&job, TEST_KEY).await.unwrap());
+        let path = "/swarmpool/jobs/job-20250101000000-abcd1234.json";
+        let id = store.write(path, &job).await.unwrap();
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(written, serde_json::to_value(&job).unwrap());
+        let bytes = serde_json::to_string_pretty(&written).unwrap();
+        assert_eq!(Some(id), ipfs::local_cid(bytes.as_bytes()));
+
+        // Only one snapshot per run
+        assert!(store.write("/swarmpool/jobs/job-other.json", &job).await.is_err());
+
+        store.replace("/swarmpool/index/state.json", &payment("1")).await.unwrap();
+        store.announce("/swarmpool.eth/jobs", &payment("0.20")).await.unwrap();
+        assert!(!dir.path().join("swarmpool/jobs").exists());
+        assert!(!dir.path().join("swarmpool/index").exists());
+        assert!(!dir.path().join("pubsub").exists());
+
+        // Mutable paths and content are staged for `swarm publish`, not uploaded
+        let staging = SignOnlyStorage::staging_dir(&out);
+        let state = SignOnlyStorage::staged_path(&staging, "/swarmpool/index/state.json").unwrap();
+        let state: Payment = serde_json::from_slice(&std::fs::read(state).unwrap()).unwrap();
+        assert_eq!(state.amount, "1");
+
+        let cid = store.put_bytes(b"report".to_vec(), "report.pdf").await.unwrap();
+        assert_eq!(Some(cid.clone()), ipfs::local_cid(b"report"));
+        assert_eq!(store.get_bytes(&cid).await.unwrap(), b"report");
+        assert!(SignOnlyStorage::staged_blob(&staging, &cid).unwrap().exists());
+        assert!(!dir.path().join("objects").exists());
+
+        let big = vec![0u8; ipfs::DEFAULT_CHUNK_SIZE + 1];
+        assert!(store.put_bytes(big, "scan.dcm").await.is_err());
+        let files = vec![("a.txt".to_string(), b"a".to_vec())];
+        assert!(store.put_dir(files).await.is_err());
+    }
+}
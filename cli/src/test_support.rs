@@ -0,0 +1,29 @@
+//! Fixtures shared by the unit tests
+
+use tempfile::TempDir;
+
+use crate::storage::FsStorage;
+
+/// Hardhat account #0, a published test key that never holds real funds
+pub const TEST_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+pub const TEST_ADDRESS: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+/// Hardhat account #1
+pub const OTHER_KEY: &str = "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+pub const OTHER_ADDRESS: &str = "0x70997970c51812dc3a010c7d01b50e0d17dc79c8";
+
+/// A fresh directory, removed when the guard drops
+pub fn temp_dir() -> TempDir {
+    tempfile::Builder::new()
+        .prefix("swarm-test-")
+        .tempdir()
+        .expect("failed to create temp dir")
+}
+
+/// An `FsStorage` rooted in a fresh directory; keep the guard alive for as
+/// long as the store is used
+pub fn fs_store() -> (TempDir, FsStorage) {
+    let dir = temp_dir();
+    let store = FsStorage::new(dir.path());
+    (dir, store)
+}
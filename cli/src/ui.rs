@@ -0,0 +1,184 @@
+//! Terminal output: spinners, progress bars and `--quiet`
+//!
+//! Every command shows progress through `spinner` and `progress_bar`, so the
+//! output modes are handled in one place: animated under text output, plain
+//! `swarm::progress` log events under `--log-format json`, and nothing at all
+//! under `--quiet`, which also skips detail lines printed with `say!`,
+//! leaving the result lines (IDs, CIDs), warnings and errors.
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle, TermLike};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::logging;
+
+/// Global `--quiet` switch
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Print only result lines: no banner, spinners or `say!` output
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// `println!` for progress and detail lines, skipped under `--quiet`
+#[macro_export]
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !$crate::ui::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// A status line with a spinner, finished with `done` (or `clear`)
+pub struct Spinner(ProgressBar);
+
+impl Spinner {
+    /// Stop, leaving `msg` in place of the spinner
+    pub fn done(&self, msg: impl Into<String>) {
+        self.0.finish_with_message(msg.into());
+    }
+
+    /// Stop and erase the line
+    pub fn clear(&self) {
+        self.0.finish_and_clear();
+    }
+
+    pub fn set_message(&self, msg: impl Into<String>) {
+        self.0.set_message(msg.into());
+    }
+}
+
+/// Spinner showing `msg` until finished. Under JSON logging each message
+/// (and the finish message) is logged once instead; under `--quiet` nothing
+/// is shown.
+pub fn spinner(msg: impl Into<String>) -> Spinner {
+    if is_quiet() {
+        return Spinner(ProgressBar::hidden());
+    }
+    let pb = ProgressBar::new_spinner();
+    if logging::is_json() {
+        pb.set_draw_target(log_target());
+        pb.set_style(ProgressStyle::default_spinner().template("{msg}").unwrap());
+    } else {
+        pb.set_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.cyan} {msg}")
+                .unwrap(),
+        );
+        pb.enable_steady_tick(Duration::from_millis(100));
+    }
+    pb.set_message(msg.into());
+    Spinner(pb)
+}
+
+/// Progress bar over `len` items labelled `label`. Under JSON logging the
+/// count is logged at most once a second instead; under `--quiet` nothing is
+/// shown.
+pub fn progress_bar(len: u64, label: &str) -> ProgressBar {
+    if is_quiet() {
+        return ProgressBar::hidden();
+    }
+    let pb = ProgressBar::new(len);
+    if logging::is_json() {
+        pb.set_draw_target(log_target());
+        let template = format!("{} {{pos}}/{{len}}", label);
+        pb.set_style(ProgressStyle::default_bar().template(&template).unwrap());
+    } else {
+        let template = format!("{{spinner:.cyan}} {} [{{bar:30.cyan}}] {{pos}}/{{len}}", label);
+        pb.set_style(ProgressStyle::default_bar().template(&template).unwrap());
+    }
+    pb
+}
+
+fn log_target() -> ProgressDrawTarget {
+    ProgressDrawTarget::term_like_with_hz(Box::new(LogLines::default()), 1)
+}
+
+/// A "terminal" that turns each newly drawn line into a log event and
+/// ignores cursor movement
+#[derive(Debug, Default)]
+struct LogLines {
+    last: Mutex<String>,
+}
+
+impl LogLines {
+    fn emit(&self, line: &str) {
+        let line = line.trim();
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        if line.is_empty() || *last == line {
+            return;
+        }
+        tracing::info!(target: "swarm::progress", "{}", line);
+        *last = line.to_string();
+    }
+}
+
+impl TermLike for LogLines {
+    fn width(&self) -> u16 {
+        u16::MAX
+    }
+
+    fn move_cursor_up(&self, _n: usize) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn move_cursor_down(&self, _n: usize) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn move_cursor_right(&self, _n: usize) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn move_cursor_left(&self, _n: usize) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn write_line(&self, s: &str) -> std::io::Result<()> {
+        self.emit(s);
+        Ok(())
+    }
+
+    fn write_str(&self, s: &str) -> std::io::Result<()> {
+        self.emit(s);
+        Ok(())
+    }
+
+    fn clear_line(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spinner_done_keeps_message() {
+        let pb = spinner("Fetching job from IPFS...");
+        pb.done("✓ Job fetched");
+        assert!(pb.0.is_finished());
+        assert_eq!(pb.0.message(), "✓ Job fetched");
+    }
+
+    #[test]
+    fn test_log_lines_skip_repeats() {
+        let lines = LogLines::default();
+        lines.emit("Publishing claim...");
+        lines.emit("Publishing claim...  ");
+        assert_eq!(*lines.last.lock().unwrap(), "Publishing claim...");
+        lines.emit("");
+        lines.emit("✓ Published: QmClaim");
+        assert_eq!(*lines.last.lock().unwrap(), "✓ Published: QmClaim");
+    }
+}
@@ -0,0 +1 @@
+"""Puts the merlin package on sys.path for `pytest` run from this directory."""
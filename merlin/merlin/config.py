@@ -102,5 +102,7 @@ NATO_ALPHABET = [
 
 
 def get_epoch_name(epoch_number: int) -> str:
-    """Generate epoch name from NATO alphabet"""
-    return NATO_ALPHABET[epoch_number % len(NATO_ALPHABET)]
+    """Generate epoch name from NATO alphabet, e.g. "Victor-2" after it wraps"""
+    cycle, index = divmod(epoch_number, len(NATO_ALPHABET))
+    name = NATO_ALPHABET[index]
+    return name if cycle == 0 else f"{name}-{cycle + 1}"
@@ -8,6 +8,7 @@ Keys never leave the machine.
 
 import json
 import hashlib
+from decimal import Decimal
 from typing import Any, Dict, Tuple
 from datetime import datetime, timezone
 
@@ -41,23 +42,17 @@ class MerlinSigner:
         """
         Sign a snapshot using EIP-191.
         
-        Process:
-        1. Serialize to canonical JSON (sorted keys, compact)
+        Process (identical to the Rust CLI, see docs/SIGNING.md):
+        1. Serialize to canonical JSON
         2. Hash with keccak256
-        3. Sign with EIP-191 prefix
-        4. Return hex signature
+        3. Hash the EIP-191 message over the hex digest
+        4. Sign that with EIP-191 and return the hex signature
         """
         # Remove any existing signature
         clean_data = {k: v for k, v in data.items() if k != "sig"}
         
-        # Canonical JSON
-        json_str = canonical_json(clean_data)
-        
-        # Hash
-        message_hash = keccak(text=json_str)
-        
         # Sign with EIP-191
-        message = encode_defunct(primitive=message_hash)
+        message = encode_defunct(primitive=signing_hash(clean_data))
         signed = self.account.sign_message(message)
         
         return signed.signature.hex()
@@ -70,23 +65,53 @@ class MerlinSigner:
 
 def canonical_json(data: Any) -> str:
     """
-    Serialize to canonical JSON.
+    Serialize to canonical JSON, byte for byte what the Rust CLI signs.
     
-    - Keys sorted alphabetically (recursive)
-    - Compact (no whitespace)
-    - Deterministic output
+    - Object keys sorted by code point (UTF-8 byte order), no whitespace
+    - Numbers as numbers: integers as is, floats in their shortest
+      round-trip digits without an exponent, integral floats without ".0"
+      (12.0 -> 12, 1e-07 -> 0.0000001)
+    - Strings as compact JSON with non-ASCII left unescaped
+    
+    See docs/SIGNING.md; conformance vectors live in cli/tests/vectors/.
     """
-    return json.dumps(sort_keys_recursive(data), separators=(",", ":"), sort_keys=True)
-
-
-def sort_keys_recursive(obj: Any) -> Any:
-    """Recursively sort dictionary keys"""
-    if isinstance(obj, dict):
-        return {k: sort_keys_recursive(v) for k, v in sorted(obj.items())}
-    elif isinstance(obj, list):
-        return [sort_keys_recursive(item) for item in obj]
-    else:
-        return obj
+    if isinstance(data, dict):
+        items = sorted(data.items())
+        return "{" + ",".join(
+            f"{canonical_json(k)}:{canonical_json(v)}" for k, v in items
+        ) + "}"
+    if isinstance(data, (list, tuple)):
+        return "[" + ",".join(canonical_json(item) for item in data) + "]"
+    if isinstance(data, bool) or data is None or isinstance(data, str):
+        return json.dumps(data, ensure_ascii=False)
+    if isinstance(data, int):
+        return str(data)
+    if isinstance(data, float):
+        return canonical_number(data)
+    raise TypeError(f"Cannot canonicalize {type(data).__name__}")
+
+
+def canonical_number(value: float) -> str:
+    """Shortest round-trip digits in plain notation (Rust's f64 Display)"""
+    if value != value or value in (float("inf"), float("-inf")):
+        raise ValueError(f"Cannot canonicalize {value}")
+    if value == 0:
+        return "0"
+    text = format(Decimal(repr(value)), "f")
+    if "." in text:
+        text = text.rstrip("0").rstrip(".")
+    return text
+
+
+def signing_hash(data: Dict[str, Any]) -> bytes:
+    """
+    The 32 bytes a snapshot signature covers, as in the Rust CLI:
+    keccak256 of the EIP-191 message over the hex digest of the canonical
+    JSON. Signing then applies EIP-191 once more to these bytes.
+    """
+    digest = keccak(text=canonical_json(data))
+    prefix = f"\x19Ethereum Signed Message:\n{len(digest)}".encode("utf-8")
+    return keccak(prefix + digest.hex().encode("ascii"))
 
 
 def keccak256(data: bytes) -> str:
@@ -147,12 +172,8 @@ def verify_signature(data: Dict[str, Any], signature: str, expected_address: str
         # Remove signature from data for verification
         clean_data = {k: v for k, v in data.items() if k != "sig"}
         
-        # Canonical JSON and hash
-        json_str = canonical_json(clean_data)
-        message_hash = keccak(text=json_str)
-        
         # Recover signer
-        message = encode_defunct(primitive=message_hash)
+        message = encode_defunct(primitive=signing_hash(clean_data))
         
         # Normalize signature
         if signature.startswith("0x"):